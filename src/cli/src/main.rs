@@ -3,7 +3,12 @@
 #![allow(clippy::too_many_lines)]
 #![allow(clippy::needless_pass_by_value)]
 
-use std::{fs, io, net::SocketAddr, path::PathBuf, sync::Arc};
+use std::{
+    fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use ariadne::{Color, Label, Report, ReportKind, Source};
 use clap::{ArgAction, Parser};
@@ -11,15 +16,20 @@ use color_eyre::{
     eyre::{OptionExt, eyre},
     owo_colors::OwoColorize,
 };
-use compiler::compile;
+use compiler::{
+    ImportError, compile_with_warnings,
+    format::{FormatConfig, format_qat},
+};
 use internment::ArcIntern;
 use interpreter::{
-    ActionPerformed, ExecutionState, InputRet, Interpreter, PausedState,
+    ActionPerformed, ExecutionState, InputRet, Interpreter, PausedState, SucceededSolvedGoto,
     puzzle_states::{PuzzleState, SimulatedPuzzle},
 };
 use itertools::Itertools;
+use puzzle_geometry::ksolve::{KPUZZLE_3X3, KSolveJson, PUZZLE_GEOMETRY_3X3};
 use qter_core::{
-    ByPuzzleType, File, I, Int,
+    ByPuzzleType, File, I, Int, PuzzleIdx,
+    json::{ArchitectureJson, ArchitectureRegisterJson, ProgramJson, SCHEMA_VERSION},
     table_encoding::{decode_table, encode_table},
 };
 
@@ -39,12 +49,50 @@ enum Commands {
         /// The level of execution trace to send to stderr. Can be set zero to three times.
         #[arg(short, action = ArgAction::Count)]
         trace_level: u8,
+        /// The number base to parse inputs in
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(2..=36))]
+        input_base: u32,
+        /// Strip assert-solved and assert-value instructions from the program
+        #[arg(long)]
+        release: bool,
+        /// The seed that `random` instructions draw from; runs with the same
+        /// seed produce identical transcripts
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Reject `random` instructions so that the program behaves
+        /// identically on every run
+        #[arg(long)]
+        deterministic: bool,
     },
     /// Step through a QAT or a Q program
     Debug {
         /// Which file to interpret; must be a .qat or .q file
         file: PathBuf,
     },
+    /// Compile a QAT file and emit its program, architectures, and KSolve
+    /// puzzles as JSON for external tooling
+    ExportJson {
+        /// Which file to export; must be a .qat file
+        file: PathBuf,
+    },
+    /// Format a QAT file in place
+    Fmt {
+        /// Which file to format; must be a .qat file
+        file: PathBuf,
+        /// Exit with an error instead of rewriting the file if it is not
+        /// already formatted
+        #[arg(long)]
+        check: bool,
+        /// The column to wrap long register declarations at
+        #[arg(long, default_value_t = 100)]
+        max_column: usize,
+    },
+    /// Print a human-readable description of a puzzle's geometry: its faces,
+    /// turns, orbits, facelets, and generators
+    PuzzleInfo {
+        /// The name of the puzzle to describe
+        puzzle: String,
+    },
     /// Evaluate unit tests in a QAT program
     Test {
         /// Which file to test; must be a .qat file
@@ -76,52 +124,17 @@ fn main() -> color_eyre::Result<()> {
 
     match args {
         Commands::Compile { file: _ } => todo!(),
-        Commands::Interpret { file, trace_level } => {
+        Commands::Interpret {
+            file,
+            trace_level,
+            input_base,
+            release,
+            seed,
+            deterministic,
+        } => {
             let program = match file.extension().and_then(|v| v.to_str()) {
                 Some("q") => todo!(),
-                Some("qat") => {
-                    let qat = File::from(fs::read_to_string(&file)?);
-
-                    match compile(&qat, |name| {
-                        let path = PathBuf::from(name);
-
-                        if path.ancestors().count() > 1 {
-                            // Easier not to implement relative paths and stuff
-                            return Err("Imported files must be in the same path".to_owned());
-                        }
-
-                        match fs::read_to_string(path) {
-                            Ok(s) => Ok(ArcIntern::from(s)),
-                            Err(e) => Err(e.to_string()),
-                        }
-                    }) {
-                        Ok(v) => v,
-                        Err(errs) => {
-                            for err in &errs {
-                                Report::build(ReportKind::Error, err.span().clone())
-                                    .with_config(
-                                        ariadne::Config::new()
-                                            .with_index_type(ariadne::IndexType::Byte),
-                                    )
-                                    .with_message(err.to_string())
-                                    .with_label(
-                                        Label::new(err.span().clone())
-                                            .with_message(err.reason().to_string())
-                                            .with_color(Color::Red),
-                                    )
-                                    .finish()
-                                    .eprint(Source::from(qat.inner()))
-                                    .unwrap();
-                            }
-
-                            return Err(eyre!(
-                                "Could not compile {} due to {} errors.",
-                                file.display(),
-                                errs.len()
-                            ));
-                        }
-                    }
-                }
+                Some("qat") => compile_qat_program(&file, release, deterministic)?,
                 _ => {
                     return Err(eyre!(
                         "The file {file:?} must have an extension of `.qat` or `.q`."
@@ -129,10 +142,93 @@ fn main() -> color_eyre::Result<()> {
                 }
             };
 
-            let interpreter = Interpreter::<SimulatedPuzzle>::new(Arc::new(program), ());
-            interpret(interpreter, trace_level)?;
+            let mut interpreter = Interpreter::<SimulatedPuzzle>::new(Arc::new(program), ());
+            if let Some(seed) = seed {
+                interpreter.set_seed(seed);
+            }
+            interpret(interpreter, trace_level, input_base)?;
         }
         Commands::Debug { file: _ } => todo!(),
+        Commands::ExportJson { file } => {
+            if file.extension().and_then(|v| v.to_str()) != Some("qat") {
+                return Err(eyre!("The file {file:?} must have an extension of `.qat`."));
+            }
+
+            let program = compile_qat_program(&file, false, false)?;
+
+            let architectures = (0..program.puzzles.len())
+                .map(|puzzle_idx| ArchitectureJson {
+                    schema_version: SCHEMA_VERSION,
+                    registers: program
+                        .registers_for_puzzle(PuzzleIdx(puzzle_idx))
+                        .map(|info| {
+                            let ByPuzzleType::Puzzle((_, (algorithm, facelets))) = &info.location
+                            else {
+                                unreachable!()
+                            };
+
+                            ArchitectureRegisterJson {
+                                order: info.order.to_string(),
+                                algorithm: algorithm
+                                    .move_seq_iter()
+                                    .map(|mv| (**mv).to_owned())
+                                    .collect(),
+                                signature_facelets: facelets.0.clone(),
+                            }
+                        })
+                        .collect(),
+                })
+                .collect_vec();
+
+            // The only puzzle a program can declare today is the builtin 3x3
+            let ksolves = program
+                .puzzles
+                .iter()
+                .map(|_| KSolveJson::from(&*KPUZZLE_3X3))
+                .collect_vec();
+
+            let document = serde_json::json!({
+                "schema_version": SCHEMA_VERSION,
+                "program": ProgramJson::from(&program),
+                "architectures": architectures,
+                "ksolves": ksolves,
+            });
+
+            println!("{}", serde_json::to_string_pretty(&document)?);
+        }
+        Commands::Fmt {
+            file,
+            check,
+            max_column,
+        } => {
+            if file.extension().and_then(|v| v.to_str()) != Some("qat") {
+                return Err(eyre!("The file {file:?} must have an extension of `.qat`."));
+            }
+
+            let source = fs::read_to_string(&file)?;
+
+            let config = FormatConfig {
+                max_column,
+                ..FormatConfig::default()
+            };
+            let formatted = format_qat(&source, &config);
+
+            if formatted != source {
+                if check {
+                    return Err(eyre!("{} is not formatted.", file.display()));
+                }
+
+                fs::write(&file, formatted)?;
+            }
+        }
+        Commands::PuzzleInfo { puzzle } => match &*puzzle {
+            "3x3" | "3x3x3" => print!("{}", PUZZLE_GEOMETRY_3X3.describe()),
+            _ => {
+                return Err(eyre!(
+                    "Unknown puzzle `{puzzle}`; the only supported puzzle is `3x3`."
+                ));
+            }
+        },
         Commands::Test { file: _ } => todo!(),
         #[cfg(debug_assertions)]
         Commands::Compress { input, output } => {
@@ -178,12 +274,84 @@ fn main() -> color_eyre::Result<()> {
     Ok(())
 }
 
+fn compile_qat_program(
+    file: &Path,
+    strip_asserts: bool,
+    deterministic: bool,
+) -> color_eyre::Result<qter_core::Program> {
+    let qat = File::from(fs::read_to_string(file)?);
+
+    let mut warnings = Vec::new();
+
+    let result = compile_with_warnings(
+        &qat,
+        |name| {
+            let path = PathBuf::from(name);
+
+            if path.ancestors().count() > 1 {
+                // Easier not to implement relative paths and stuff
+                return Err(ImportError::Unsupported(
+                    "Imported files must be in the same path".to_owned(),
+                ));
+            }
+
+            match fs::read_to_string(path) {
+                Ok(s) => Ok(ArcIntern::from(s)),
+                Err(e) => Err(ImportError::Io(e.to_string())),
+            }
+        },
+        strip_asserts,
+        deterministic,
+        &mut warnings,
+    );
+
+    for warning in &warnings {
+        Report::build(ReportKind::Warning, warning.span().clone())
+            .with_config(ariadne::Config::new().with_index_type(ariadne::IndexType::Byte))
+            .with_message(warning.to_string())
+            .with_label(
+                Label::new(warning.span().clone())
+                    .with_message(warning.reason().to_string())
+                    .with_color(Color::Yellow),
+            )
+            .finish()
+            .eprint(Source::from(qat.inner()))
+            .unwrap();
+    }
+
+    match result {
+        Ok(v) => Ok(v),
+        Err(errs) => {
+            for err in &errs {
+                Report::build(ReportKind::Error, err.span().clone())
+                    .with_config(ariadne::Config::new().with_index_type(ariadne::IndexType::Byte))
+                    .with_message(err.to_string())
+                    .with_label(
+                        Label::new(err.span().clone())
+                            .with_message(err.reason().to_string())
+                            .with_color(Color::Red),
+                    )
+                    .finish()
+                    .eprint(Source::from(qat.inner()))
+                    .unwrap();
+            }
+
+            Err(eyre!(
+                "Could not compile {} due to {} errors.",
+                file.display(),
+                errs.len()
+            ))
+        }
+    }
+}
+
 fn interpret<P: PuzzleState>(
     mut interpreter: Interpreter<P>,
     trace_level: u8,
+    input_base: u32,
 ) -> color_eyre::Result<()> {
     if trace_level > 0 {
-        return interpret_traced(interpreter, trace_level);
+        return interpret_traced(interpreter, trace_level, input_base);
     }
     loop {
         let paused_state = interpreter.step_until_halt();
@@ -201,7 +369,7 @@ fn interpret<P: PuzzleState>(
         }
 
         if is_input_state {
-            give_number_input(&mut interpreter)?;
+            give_number_input(&mut interpreter, input_base)?;
         } else {
             break Ok(());
         }
@@ -210,26 +378,48 @@ fn interpret<P: PuzzleState>(
 
 fn give_number_input<P: PuzzleState>(
     interpreter: &mut Interpreter<P>,
+    input_base: u32,
 ) -> color_eyre::Result<ByPuzzleType<'static, InputRet>> {
     loop {
         let mut number = String::new();
         io::stdin().read_line(&mut number)?;
-        match number.parse::<Int<I>>() {
+        match Int::<I>::from_str_radix(&number, input_base) {
             Ok(value) => match interpreter.give_input(value) {
                 Ok(input_ret) => {
                     break Ok(input_ret);
                 }
                 Err(e) => println!("{e}"),
             },
-            Err(_) => println!("Please input an integer"),
+            Err(_) => println!("Please input an integer in base {input_base}"),
         }
     }
 }
 
+/// The trace line for a `solved-goto` that jumped, e.g. `Inspect puzzle 0 - TAKEN → 12`
+fn succeeded_solved_goto_trace(succeeded: &SucceededSolvedGoto) -> String {
+    let (kind, idx) = match succeeded.source {
+        ByPuzzleType::Theoretical(idx) => ("theoretical", idx.0),
+        ByPuzzleType::Puzzle(idx) => ("puzzle", idx.0),
+    };
+
+    format!(
+        "Inspect {kind} {idx} - {} → {}",
+        "TAKEN".green(),
+        succeeded.jumped_to
+    )
+}
+
 fn interpret_traced<P: PuzzleState>(
     mut interpreter: Interpreter<P>,
     trace_level: u8,
+    input_base: u32,
 ) -> color_eyre::Result<()> {
+    let program = interpreter.program_arc();
+
+    // At `-vv` and above, adds are reported as `A: 5 -> 18` by decoding every
+    // affected register
+    interpreter.set_trace_registers(trace_level >= 2);
+
     loop {
         let program_counter = interpreter.state().program_counter() + 1;
 
@@ -286,27 +476,56 @@ fn interpret_traced<P: PuzzleState>(
                     eprintln!("Inspect puzzle {} - {}", idx.0, "NOT TAKEN".red());
                 }
             }
-            ActionPerformed::SucceededSolvedGoto(ByPuzzleType::Theoretical((_, idx))) => {
+            ActionPerformed::SucceededSolvedGoto(succeeded) => {
                 if trace_level >= 2 {
-                    eprintln!("Inspect theoretical {} - {}", idx.0, "TAKEN".green());
+                    eprintln!("{}", succeeded_solved_goto_trace(&succeeded));
                 }
             }
-            ActionPerformed::SucceededSolvedGoto(ByPuzzleType::Puzzle((_, idx, _))) => {
-                if trace_level >= 2 {
-                    eprintln!("Inspect puzzle {} - {}", idx.0, "TAKEN".green());
+            ActionPerformed::Added(ByPuzzleType::Theoretical((idx, amt, changes))) => {
+                let mut printed_any = false;
+
+                if let Some(changes) = changes {
+                    for change in changes {
+                        eprintln!(
+                            "{}: {} -> {}",
+                            program.registers[change.register_idx].name,
+                            change.before,
+                            change.after
+                        );
+                        printed_any = true;
+                    }
+                }
+
+                if !printed_any {
+                    eprintln!("Theoretical {} += {amt}", idx.0);
                 }
             }
-            ActionPerformed::Added(ByPuzzleType::Theoretical((idx, amt))) => {
-                eprintln!("Theoretical {} += {amt}", idx.0);
-            }
-            ActionPerformed::Added(ByPuzzleType::Puzzle((idx, alg))) => {
-                eprint!("Puzzle {}:", idx.0);
+            ActionPerformed::Added(ByPuzzleType::Puzzle((idx, alg, changes))) => {
+                let mut printed_any = false;
+
+                if let Some(changes) = changes {
+                    for change in changes {
+                        eprintln!(
+                            "{}: {} -> {}",
+                            program.registers[change.register_idx].name,
+                            change.before,
+                            change.after
+                        );
+                        printed_any = true;
+                    }
+                }
 
-                for move_ in alg.move_seq_iter() {
-                    eprint!(" {move_}");
+                if !printed_any {
+                    eprintln!("Puzzle {}: {alg}", idx.0);
                 }
+            }
+            ActionPerformed::RandomAdded { source, amt } => {
+                let (kind, idx) = match source {
+                    ByPuzzleType::Theoretical(idx) => ("theoretical", idx.0),
+                    ByPuzzleType::Puzzle(idx) => ("puzzle", idx.0),
+                };
 
-                eprintln!();
+                eprintln!("Random {kind} {idx} += {amt}");
             }
             ActionPerformed::Panicked => {
                 eprintln!("{}", "Panicked!".red());
@@ -326,11 +545,7 @@ fn interpret_traced<P: PuzzleState>(
                 facelets: _,
                 alg,
             } => {
-                eprint!("Repeated on puzzle {}:", puzzle_idx.0);
-
-                for move_ in alg.move_seq_iter() {
-                    eprint!(" {move_}");
-                }
+                eprint!("Repeated on puzzle {}: {alg}", puzzle_idx.0);
             }
         }
 
@@ -343,20 +558,39 @@ fn interpret_traced<P: PuzzleState>(
         }
 
         if should_give_input {
-            let input_ret = give_number_input(&mut interpreter)?;
+            let input_ret = give_number_input(&mut interpreter, input_base)?;
 
             match input_ret {
                 ByPuzzleType::Theoretical(_) => {}
                 ByPuzzleType::Puzzle((idx, alg)) => {
-                    eprint!("Puzzle {}:", idx.0);
-
-                    for move_ in alg.move_seq_iter() {
-                        eprint!(" {move_}");
-                    }
-
-                    eprintln!();
+                    eprintln!("Puzzle {}: {alg}", idx.0);
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qter_core::TheoreticalIdx;
+
+    #[test]
+    fn solved_goto_trace_prints_destination() {
+        let line = succeeded_solved_goto_trace(&SucceededSolvedGoto {
+            jumped_to: 12,
+            source: ByPuzzleType::Puzzle(PuzzleIdx(0)),
+            facelets: None,
+        });
+        assert!(line.starts_with("Inspect puzzle 0"));
+        assert!(line.ends_with("→ 12"));
+
+        let line = succeeded_solved_goto_trace(&SucceededSolvedGoto {
+            jumped_to: 3,
+            source: ByPuzzleType::Theoretical(TheoreticalIdx(1)),
+            facelets: None,
+        });
+        assert!(line.starts_with("Inspect theoretical 1"));
+        assert!(line.ends_with("→ 3"));
+    }
+}
@@ -0,0 +1,137 @@
+//! A small code generator that turns arithmetic expressions over registers
+//! into QAT loop structures, so that programs like the hand-written
+//! `multiply` and `modulus` tests don't have to be written by hand.
+
+use std::fmt::Write as _;
+
+use qter_core::{Int, U};
+
+/// A single arithmetic operation applied to the accumulator register `A`,
+/// using `B` as a scratch register that is solved before and after each
+/// operation.
+#[derive(Clone, Copy, Debug)]
+pub enum ArithmeticOp {
+    /// Add a constant to `A`
+    AddConst(Int<U>),
+    /// Multiply `A` by a constant; the product is accumulated in `B` so it is
+    /// taken modulo `B`'s order before being transferred back
+    MulConst(Int<U>),
+    /// Reduce `A` modulo a constant
+    ModConst(Int<U>),
+}
+
+/// Generates a QAT program that inputs a number into a register `A`, applies
+/// each operation in order, and halts printing the result of `A`.
+///
+/// The register orders determine the increments used by the generated loops;
+/// a decrement of `A` is emitted as `add A (a_order - 1)`. The orders must
+/// name a builtin 3x3 preset or the generated program will not compile.
+///
+/// # Panics
+///
+/// Panics if a `ModConst` constant is zero or not less than both register
+/// orders, since the decrement loops could never terminate otherwise.
+#[must_use]
+pub fn generate_arithmetic_qat(a_order: Int<U>, b_order: Int<U>, ops: &[ArithmeticOp]) -> String {
+    let dec_a = a_order - Int::<U>::one();
+    let dec_b = b_order - Int::<U>::one();
+
+    let mut qat = String::new();
+
+    writeln!(qat, ".registers {{").unwrap();
+    writeln!(qat, "    A, B ← 3x3 builtin ({a_order}, {b_order})").unwrap();
+    writeln!(qat, "}}").unwrap();
+    writeln!(qat).unwrap();
+    writeln!(qat, "input \"Enter a number:\" A").unwrap();
+
+    for (i, op) in ops.iter().enumerate() {
+        match *op {
+            ArithmeticOp::AddConst(amt) => {
+                let amt = amt % a_order;
+
+                if !amt.is_zero() {
+                    writeln!(qat, "add A {amt}").unwrap();
+                }
+            }
+            ArithmeticOp::MulConst(amt) => {
+                let amt = amt % b_order;
+
+                // Drain `A` into `B`, adding `amt` copies per decrement, then
+                // transfer the product back into `A`
+                writeln!(qat, "op{i}_multiply:").unwrap();
+                writeln!(qat, "    solved-goto A op{i}_transfer").unwrap();
+                writeln!(qat, "    add A {dec_a}").unwrap();
+                if !amt.is_zero() {
+                    writeln!(qat, "    add B {amt}").unwrap();
+                }
+                writeln!(qat, "    goto op{i}_multiply").unwrap();
+                writeln!(qat, "op{i}_transfer:").unwrap();
+                writeln!(qat, "    solved-goto B op{i}_done").unwrap();
+                writeln!(qat, "    add B {dec_b}").unwrap();
+                writeln!(qat, "    add A 1").unwrap();
+                writeln!(qat, "    goto op{i}_transfer").unwrap();
+                writeln!(qat, "op{i}_done:").unwrap();
+            }
+            ArithmeticOp::ModConst(amt) => {
+                assert!(
+                    !amt.is_zero() && amt < a_order && amt < b_order,
+                    "The modulus must be nonzero and less than both register orders"
+                );
+
+                // `B` repeatedly counts down a window of `amt` decrements of
+                // `A`; once `A` is solved, the partial window is undone by
+                // decrementing both until `B` is solved and re-adding `amt`
+                writeln!(qat, "op{i}_loop:").unwrap();
+                writeln!(qat, "    add B {amt}").unwrap();
+                writeln!(qat, "op{i}_decrement:").unwrap();
+                writeln!(qat, "    solved-goto B op{i}_loop").unwrap();
+                writeln!(qat, "    solved-goto A op{i}_fix").unwrap();
+                writeln!(qat, "    add A {dec_a}").unwrap();
+                writeln!(qat, "    add B {dec_b}").unwrap();
+                writeln!(qat, "    goto op{i}_decrement").unwrap();
+                writeln!(qat, "op{i}_fix:").unwrap();
+                writeln!(qat, "    solved-goto B op{i}_finalize").unwrap();
+                writeln!(qat, "    add A {dec_a}").unwrap();
+                writeln!(qat, "    add B {dec_b}").unwrap();
+                writeln!(qat, "    goto op{i}_fix").unwrap();
+                writeln!(qat, "op{i}_finalize:").unwrap();
+                writeln!(qat, "    add A {amt}").unwrap();
+            }
+        }
+    }
+
+    writeln!(qat, "halt \"The result is\" A").unwrap();
+
+    qat
+}
+
+#[cfg(test)]
+mod tests {
+    use qter_core::{File, Int};
+
+    use super::{ArithmeticOp, generate_arithmetic_qat};
+    use crate::compile;
+
+    #[test]
+    fn test_generated_qat_compiles() {
+        let qat = generate_arithmetic_qat(
+            Int::from(210_u64),
+            Int::from(24_u64),
+            &[
+                ArithmeticOp::AddConst(Int::from(5_u64)),
+                ArithmeticOp::MulConst(Int::from(3_u64)),
+                ArithmeticOp::ModConst(Int::from(13_u64)),
+            ],
+        );
+
+        println!("{qat}");
+
+        if let Err(errs) = compile(&File::from(qat.as_str()), |_| unreachable!(), false) {
+            for err in &errs {
+                println!("{err}; {:?}", err.span().line_and_col());
+            }
+
+            panic!();
+        }
+    }
+}
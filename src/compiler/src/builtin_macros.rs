@@ -1,10 +1,10 @@
 use chumsky::error::Rich;
 use internment::ArcIntern;
-use qter_core::{Span, WithSpan};
+use qter_core::{Int, Span, U, WithSpan};
 
 use crate::{
-    BlockID, Code, ExpansionInfo, Instruction, LabelReference, Macro, Primitive, RegisterReference,
-    Value,
+    BlockID, Code, ExpansionInfo, Instruction, LabelReference, Macro, Primitive, Puzzle,
+    RegisterReference, Value,
 };
 
 use std::collections::HashMap;
@@ -14,18 +14,9 @@ fn expect_reg(
     syntax: &ExpansionInfo,
 ) -> Result<RegisterReference, Rich<'static, char, Span>> {
     match &**reg_value {
-        Value::Ident(reg_name) => match syntax.get_register(
-            &RegisterReference::parse(WithSpan::new(
-                ArcIntern::clone(reg_name),
-                reg_value.span().to_owned(),
-            ))
-            .map_err(|e| {
-                Rich::custom(
-                    reg_value.span().clone(),
-                    format!("Could not parse the modulus as a string: {e}"),
-                )
-            })?,
-        ) {
+        Value::Ident(reg_name) => match syntax.get_register(&RegisterReference::parse(
+            WithSpan::new(ArcIntern::clone(reg_name), reg_value.span().to_owned()),
+        )?) {
             Some((reg, _)) => Ok(reg),
             None => Err(Rich::custom(
                 reg_value.span().clone(),
@@ -84,6 +75,16 @@ fn print_like(
     Ok((maybe_reg, message))
 }
 
+fn expect_message(
+    message_value: WithSpan<Value>,
+) -> Result<WithSpan<String>, Rich<'static, char, Span>> {
+    let span = message_value.span().to_owned();
+    match message_value.into_inner() {
+        Value::Ident(raw_message) => Ok(WithSpan::new((*raw_message).to_owned(), span)),
+        _ => Err(Rich::custom(span, "Expected a message")),
+    }
+}
+
 pub fn builtin_macros(
     prelude: &ArcIntern<str>,
 ) -> HashMap<(ArcIntern<str>, ArcIntern<str>), WithSpan<Macro>> {
@@ -121,6 +122,36 @@ pub fn builtin_macros(
         ),
     );
 
+    macros.insert(
+        (prelude.clone(), ArcIntern::from("random")),
+        WithSpan::new(
+            Macro::Builtin(|syntax, mut args, _| {
+                if args.len() != 2 {
+                    return Err(Rich::custom(
+                        args.span().clone(),
+                        format!("Expected two arguments, found {}", args.len()),
+                    ));
+                }
+
+                let second_arg = args.pop().unwrap();
+                let max = match *second_arg {
+                    Value::Int(int) => WithSpan::new(int, second_arg.span().to_owned()),
+                    _ => {
+                        return Err(Rich::custom(second_arg.span().clone(), "Expected a number"));
+                    }
+                };
+
+                let register = expect_reg(args.pop().as_ref().unwrap(), syntax)?;
+
+                Ok(vec![Instruction::Code(Code::Primitive(Primitive::Random {
+                    max,
+                    register,
+                }))])
+            }),
+            dummy_span.clone(),
+        ),
+    );
+
     macros.insert(
         (prelude.to_owned(), ArcIntern::from("goto")),
         WithSpan::new(
@@ -164,6 +195,109 @@ pub fn builtin_macros(
         ),
     );
 
+    macros.insert(
+        (prelude.to_owned(), ArcIntern::from("goto-table")),
+        WithSpan::new(
+            Macro::Builtin(|syntax, mut args, block_id| {
+                if args.len() < 2 {
+                    return Err(Rich::custom(
+                        args.span().clone(),
+                        format!(
+                            "Expected a register followed by a label for each of its values, found {} arguments",
+                            args.len()
+                        ),
+                    ));
+                }
+
+                let args_span = args.span().to_owned();
+
+                let mut labels = Vec::new();
+                while args.len() > 1 {
+                    labels.push(expect_label(args.pop().as_ref().unwrap(), block_id)?);
+                }
+                labels.reverse();
+
+                let reg_value = args.pop().unwrap();
+                let (register, puzzle) = match &*reg_value {
+                    Value::Ident(reg_name) => {
+                        let reference = RegisterReference::parse(WithSpan::new(
+                            ArcIntern::clone(reg_name),
+                            reg_value.span().to_owned(),
+                        ))?;
+
+                        match syntax.get_register(&reference) {
+                            Some(v) => v,
+                            None => {
+                                return Err(Rich::custom(
+                                    reg_value.span().clone(),
+                                    format!("The register {reg_name} does not exist"),
+                                ));
+                            }
+                        }
+                    }
+                    _ => {
+                        return Err(Rich::custom(
+                            reg_value.span().clone(),
+                            "Expected a register",
+                        ));
+                    }
+                };
+
+                let order = match puzzle {
+                    Puzzle::Theoretical { name: _, order } => **order,
+                    Puzzle::Real { architectures } => {
+                        let (names, architecture) = &architectures[0];
+                        let idx = names
+                            .iter()
+                            .position(|name| **name == register.reg_name)
+                            .unwrap();
+                        architecture.registers()[idx].order()
+                    }
+                };
+
+                // Dispatching is only total when every value the register can
+                // hold has a label
+                let dispatch_size = register.modulus.unwrap_or(order);
+                if Int::<U>::from(labels.len()) != dispatch_size {
+                    return Err(Rich::custom(
+                        args_span,
+                        format!(
+                            "The table has {} labels but the register holds {dispatch_size} values; every value needs a label",
+                            labels.len()
+                        ),
+                    ));
+                }
+
+                // Expand to a solved-goto ladder that decrements the register
+                // between rungs, so rung `i` is taken exactly when the
+                // register held `i`. The register is left at zero afterwards.
+                let decrement = WithSpan::new(order - Int::<U>::one(), reg_value.span().to_owned());
+
+                let mut instructions = Vec::new();
+
+                for (i, label) in labels.into_iter().enumerate() {
+                    if i > 0 {
+                        instructions.push(Instruction::Code(Code::Primitive(Primitive::Add {
+                            amt: decrement.clone(),
+                            register: RegisterReference {
+                                reg_name: ArcIntern::clone(&register.reg_name),
+                                modulus: None,
+                            },
+                        })));
+                    }
+
+                    instructions.push(Instruction::Code(Code::Primitive(Primitive::SolvedGoto {
+                        register: register.clone(),
+                        label,
+                    })));
+                }
+
+                Ok(instructions)
+            }),
+            dummy_span.clone(),
+        ),
+    );
+
     macros.insert(
         (prelude.to_owned(), ArcIntern::from("input")),
         WithSpan::new(
@@ -227,5 +361,184 @@ pub fn builtin_macros(
         ),
     );
 
+    macros.insert(
+        (prelude.to_owned(), ArcIntern::from("assert-solved")),
+        WithSpan::new(
+            Macro::Builtin(|syntax, mut args, _| {
+                if args.len() != 2 {
+                    return Err(Rich::custom(
+                        args.span().clone(),
+                        format!("Expected two arguments, found {}", args.len()),
+                    ));
+                }
+
+                let message = expect_message(args.pop().unwrap())?;
+                let register = expect_reg(args.pop().as_ref().unwrap(), syntax)?;
+                let expected = WithSpan::new(Int::<U>::zero(), message.span().to_owned());
+
+                Ok(vec![Instruction::Code(Code::Primitive(
+                    Primitive::Assert {
+                        message,
+                        expected,
+                        register,
+                    },
+                ))])
+            }),
+            dummy_span.clone(),
+        ),
+    );
+
+    macros.insert(
+        (prelude.to_owned(), ArcIntern::from("assert-value")),
+        WithSpan::new(
+            Macro::Builtin(|syntax, mut args, _| {
+                if args.len() != 3 {
+                    return Err(Rich::custom(
+                        args.span().clone(),
+                        format!("Expected three arguments, found {}", args.len()),
+                    ));
+                }
+
+                let message = expect_message(args.pop().unwrap())?;
+
+                let second_arg = args.pop().unwrap();
+                let expected = match *second_arg {
+                    Value::Int(int) => WithSpan::new(int, second_arg.span().to_owned()),
+                    _ => {
+                        return Err(Rich::custom(second_arg.span().clone(), "Expected a number"));
+                    }
+                };
+
+                let register = expect_reg(args.pop().as_ref().unwrap(), syntax)?;
+
+                Ok(vec![Instruction::Code(Code::Primitive(
+                    Primitive::Assert {
+                        message,
+                        expected,
+                        register,
+                    },
+                ))])
+            }),
+            dummy_span.clone(),
+        ),
+    );
+
     macros
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use interpreter::{Interpreter, PausedState, puzzle_states::SimulatedPuzzle};
+    use qter_core::{File, Int};
+
+    use crate::compile;
+
+    const DISPATCH: &str = "
+        .registers {
+            A ← theoretical 4
+        }
+
+        input \"Value:\" A
+        goto-table A zero one two three
+
+        zero:
+        halt \"Got zero\"
+        one:
+        halt \"Got one\"
+        two:
+        halt \"Got two\"
+        three:
+        halt \"Got three\"
+    ";
+
+    #[test]
+    fn goto_table_dispatches_to_every_label() {
+        let program = Arc::new(compile(&File::from(DISPATCH), |_| unreachable!(), false).unwrap());
+
+        for (value, message) in ["Got zero", "Got one", "Got two", "Got three"]
+            .into_iter()
+            .enumerate()
+        {
+            let mut interpreter = Interpreter::<SimulatedPuzzle>::new(Arc::clone(&program), ());
+
+            assert!(matches!(
+                interpreter.step_until_halt(),
+                PausedState::Input { .. }
+            ));
+
+            interpreter.give_input(Int::from(value)).unwrap();
+
+            assert!(matches!(
+                interpreter.step_until_halt(),
+                PausedState::Halt { .. }
+            ));
+
+            assert_eq!(
+                interpreter.state_mut().messages().back().unwrap(),
+                message,
+                "value {value} dispatched to the wrong label"
+            );
+        }
+    }
+
+    #[test]
+    fn goto_table_requires_a_label_for_every_value() {
+        let partial = DISPATCH.replace("zero one two three", "zero one two");
+
+        let errs = compile(&File::from(&*partial), |_| unreachable!(), false).unwrap_err();
+
+        assert!(
+            errs.iter()
+                .any(|err| err.to_string().contains("every value needs a label")),
+            "{errs:?}"
+        );
+    }
+
+    const ASSERTIONS: &str = "
+        .registers {
+            A ← theoretical 10
+        }
+
+        add A 3
+        assert-value A 3 \"Three\"
+        assert-solved A \"Should be solved\"
+        halt \"Done\"
+    ";
+
+    #[test]
+    fn failed_assertion_panics_with_the_decoded_value() {
+        let program =
+            Arc::new(compile(&File::from(ASSERTIONS), |_| unreachable!(), false).unwrap());
+
+        let mut interpreter = Interpreter::<SimulatedPuzzle>::new(Arc::clone(&program), ());
+
+        assert!(matches!(
+            interpreter.step_until_halt(),
+            PausedState::Panicked
+        ));
+
+        assert_eq!(
+            interpreter.state_mut().messages().back().unwrap(),
+            "Panicked: Should be solved (expected 0, actual 3)"
+        );
+    }
+
+    #[test]
+    fn stripped_assertions_compile_away() {
+        let program = Arc::new(compile(&File::from(ASSERTIONS), |_| unreachable!(), true).unwrap());
+        let unstripped = compile(&File::from(ASSERTIONS), |_| unreachable!(), false).unwrap();
+
+        assert!(program.instructions.len() < unstripped.instructions.len());
+
+        let mut interpreter = Interpreter::<SimulatedPuzzle>::new(Arc::clone(&program), ());
+
+        assert!(matches!(
+            interpreter.step_until_halt(),
+            PausedState::Halt { .. }
+        ));
+
+        assert_eq!(interpreter.state_mut().messages().back().unwrap(), "Done");
+    }
+}
@@ -0,0 +1,397 @@
+//! A formatter for QAT source code
+//!
+//! QAT's grammar is line-oriented: statements are separated by newlines, so a
+//! formatter is free to change leading whitespace, blank lines, and the
+//! spacing before a label's colon without changing what the program compiles
+//! to. The only place the grammar permits breaking a long line is around the
+//! commas inside a register architecture's parentheses, which is exactly
+//! where move sequences get long, so that is the only wrapping the formatter
+//! performs.
+//!
+//! Comments and the contents of Lua blocks are preserved verbatim.
+
+/// Settings for [`format_qat`]
+#[derive(Clone, Debug)]
+pub struct FormatConfig {
+    /// Register declarations longer than this many columns are wrapped at the
+    /// commas inside their architecture's parentheses
+    pub max_column: usize,
+    /// The amount of spaces per indentation level
+    pub indent_width: usize,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        FormatConfig {
+            max_column: 100,
+            indent_width: 4,
+        }
+    }
+}
+
+/// Formats QAT source code canonically: labels flush-left with the colon
+/// attached, instructions indented one level, block contents indented by
+/// nesting depth, runs of blank lines collapsed to one, and over-long
+/// register declarations wrapped at the commas inside their parentheses.
+///
+/// Formatting is idempotent and never changes what the source compiles to.
+#[must_use]
+pub fn format_qat(source: &str, config: &FormatConfig) -> String {
+    let mut out: Vec<String> = Vec::new();
+    // The indentation level of the line that opened each currently-open
+    // bracket, so that a closing bracket lines up under its opener
+    let mut openers: Vec<usize> = Vec::new();
+    let mut in_lua = false;
+    let mut pending_blank = false;
+
+    for (idx, raw_line) in source.lines().enumerate() {
+        let line = raw_line.trim_end();
+
+        if in_lua {
+            out.push(line.to_owned());
+            if line.trim() == "end-lua" {
+                in_lua = false;
+            }
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+
+        if trimmed.is_empty() {
+            // Collapse runs of blank lines and drop leading ones entirely
+            pending_blank = !out.is_empty();
+            continue;
+        }
+
+        if idx == 0 && trimmed.starts_with("#!") {
+            out.push(trimmed.to_owned());
+            continue;
+        }
+
+        if pending_blank {
+            out.push(String::new());
+            pending_blank = false;
+        }
+
+        let (leading_closers, opens, closes) = scan_brackets(trimmed);
+
+        let mut indent_units = openers.last().copied().map_or(0, |units| units + 1);
+        for _ in 0..leading_closers {
+            if let Some(units) = openers.pop() {
+                indent_units = units;
+            }
+        }
+
+        let content = normalize_label(trimmed).unwrap_or_else(|| trimmed.to_owned());
+
+        if openers.is_empty() && is_code(&content) {
+            // Top-level instructions sit one level in so that labels and
+            // directives stand out
+            indent_units = 1;
+        }
+        let indent = " ".repeat(indent_units * config.indent_width);
+
+        match wrap_register_decl(&content, &indent, config) {
+            Some(wrapped) => out.extend(wrapped),
+            None => out.push(format!("{indent}{content}")),
+        }
+
+        // Bracket pairs matched within the line cancel out
+        let interior_closes = closes - leading_closers;
+        for _ in 0..opens.saturating_sub(interior_closes) {
+            openers.push(indent_units);
+        }
+        for _ in 0..interior_closes.saturating_sub(opens) {
+            openers.pop();
+        }
+
+        if trimmed == ".start-lua" {
+            in_lua = true;
+        }
+    }
+
+    let mut formatted = out.join("\n");
+    formatted.push('\n');
+    formatted
+}
+
+/// Like [`format_qat`] with the default configuration
+#[must_use]
+pub fn format_qat_default(source: &str) -> String {
+    format_qat(source, &FormatConfig::default())
+}
+
+/// Whether a top-level line is an instruction or a comment, which sit one
+/// level in, as opposed to a label, directive, or closing bracket, which stay
+/// flush-left
+fn is_code(trimmed: &str) -> bool {
+    !trimmed.starts_with('.')
+        && !trimmed.starts_with('}')
+        && !trimmed.starts_with(')')
+        && normalize_label(trimmed).is_none()
+}
+
+/// Counts the brackets a line opens and closes, ignoring quoted strings and
+/// everything after a `--` comment. Also reports how many closing brackets
+/// the line starts with, since those dedent the line they are on.
+fn scan_brackets(trimmed: &str) -> (usize, usize, usize) {
+    let mut leading_closers = 0;
+    let mut opens = 0;
+    let mut closes = 0;
+    let mut at_start = true;
+    let mut in_quotes = false;
+    let mut prev_was_dash = false;
+
+    for c in trimmed.chars() {
+        if in_quotes {
+            if c == '"' {
+                in_quotes = false;
+            }
+            prev_was_dash = false;
+            continue;
+        }
+
+        match c {
+            '-' => {
+                if prev_was_dash {
+                    // The rest of the line is a comment
+                    break;
+                }
+                prev_was_dash = true;
+                at_start = false;
+                continue;
+            }
+            '"' => in_quotes = true,
+            '{' | '(' => {
+                opens += 1;
+                at_start = false;
+            }
+            '}' | ')' => {
+                closes += 1;
+                if at_start {
+                    leading_closers += 1;
+                }
+            }
+            c if c.is_whitespace() => {}
+            _ => at_start = false,
+        }
+
+        prev_was_dash = false;
+    }
+
+    (leading_closers, opens, closes)
+}
+
+/// If the line is a label, returns it with the whitespace before the colon
+/// removed
+fn normalize_label(trimmed: &str) -> Option<String> {
+    let name = trimmed.strip_suffix(':')?.trim_end();
+    let unmarked = name.strip_prefix('!').unwrap_or(name);
+
+    if unmarked.is_empty()
+        || unmarked.starts_with("--")
+        || unmarked.contains(|c: char| c.is_whitespace() || "{}.:$,←()!\"".contains(c))
+        || unmarked.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    Some(format!("{name}:"))
+}
+
+/// Wraps a register declaration whose architecture list makes the line longer
+/// than the configured column, breaking after the commas inside the
+/// parentheses. The grammar allows newlines around exactly those commas, so
+/// this is the only wrapping that cannot change the parse.
+fn wrap_register_decl(
+    content: &str,
+    indent: &str,
+    config: &FormatConfig,
+) -> Option<Vec<String>> {
+    if indent.len() + content.chars().count() <= config.max_column {
+        return None;
+    }
+
+    // Only register declarations are wrapped; quotes and comments would make
+    // splitting the line unsound
+    if (!content.contains("<-") && !content.contains('←'))
+        || content.contains('"')
+        || content.contains("--")
+    {
+        return None;
+    }
+
+    let open_idx = content.find('(')?;
+    let close_idx = content.rfind(')')?;
+    if close_idx != content.len() - 1 {
+        return None;
+    }
+
+    let head = content[..=open_idx].trim_end();
+    let body = &content[open_idx + 1..close_idx];
+
+    let mut items = Vec::new();
+    let mut item_start = 0;
+    let mut paren_depth = 0_usize;
+
+    for (idx, c) in body.char_indices() {
+        match c {
+            '(' => paren_depth += 1,
+            ')' => paren_depth = paren_depth.saturating_sub(1),
+            ',' if paren_depth == 0 => {
+                items.push(body[item_start..idx].trim());
+                item_start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+
+    let last = body[item_start..].trim();
+    if !last.is_empty() {
+        items.push(last);
+    }
+
+    if items.len() < 2 {
+        return None;
+    }
+
+    let item_indent = format!("{}{}", indent, " ".repeat(config.indent_width));
+
+    let mut lines = vec![format!("{indent}{head}")];
+    for item in items {
+        lines.push(format!("{item_indent}{item},"));
+    }
+    lines.push(format!("{indent})"));
+
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use qter_core::{File, json::ProgramJson};
+
+    use super::{FormatConfig, format_qat, format_qat_default};
+
+    const MESSY: &str = "
+
+      .registers {
+            B, A ← 3x3 builtin (24, 210)
+   }
+
+        input   \"Number to modulus:\" A
+     loop :
+        print \"A is now\" A
+     add B 13
+      decrement:
+          solved-goto B loop
+       solved-goto A fix
+          -- Remove 1 from A and B
+     add A 209
+          add B 23
+            goto decrement
+  fix:
+            halt \"Done\"
+";
+
+    const GOLDEN: &str = ".registers {
+    B, A ← 3x3 builtin (24, 210)
+}
+
+    input   \"Number to modulus:\" A
+loop:
+    print \"A is now\" A
+    add B 13
+decrement:
+    solved-goto B loop
+    solved-goto A fix
+    -- Remove 1 from A and B
+    add A 209
+    add B 23
+    goto decrement
+fix:
+    halt \"Done\"
+";
+
+    const MACROS_AND_LUA: &str = ".registers {
+    A ← theoretical 10
+}
+
+.macro bump {
+    ($r:reg) => add $r 1
+    (twice $r:reg) => {
+        again:
+        add $r 2
+        solved-goto $r again
+    }
+}
+
+.start-lua
+    function bruh()
+       print(\"unformatted lua stays put\")
+    end
+end-lua
+
+    bump A
+    halt \"Done\"
+";
+
+    #[test]
+    fn golden() {
+        assert_eq!(format_qat_default(MESSY), GOLDEN);
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        for source in [MESSY, GOLDEN, MACROS_AND_LUA] {
+            let once = format_qat_default(source);
+            let twice = format_qat_default(&once);
+            assert_eq!(once, twice);
+        }
+    }
+
+    #[test]
+    fn long_register_declarations_wrap_at_commas() {
+        let config = FormatConfig {
+            max_column: 30,
+            ..FormatConfig::default()
+        };
+
+        let source = ".registers {\n    B, A ← 3x3 builtin (24, 210)\n}\n\nhalt \"Done\"\n";
+        let formatted = format_qat(source, &config);
+
+        assert_eq!(
+            formatted,
+            ".registers {\n    B, A ← 3x3 builtin (\n        24,\n        210,\n    )\n}\n\n    halt \"Done\"\n"
+        );
+
+        // Wrapped output is stable
+        assert_eq!(format_qat(&formatted, &config), formatted);
+    }
+
+    #[test]
+    fn formatting_does_not_change_compilation_output() {
+        let config = FormatConfig {
+            max_column: 30,
+            ..FormatConfig::default()
+        };
+
+        for source in [MESSY, GOLDEN, MACROS_AND_LUA] {
+            let original = crate::compile(&File::from(source), |_| unreachable!(), false)
+                .expect("the sample should compile");
+
+            for formatted in [
+                format_qat_default(source),
+                format_qat(source, &config),
+            ] {
+                let reformatted =
+                    crate::compile(&File::from(formatted.as_str()), |_| unreachable!(), false)
+                        .expect("the formatted sample should compile");
+
+                assert_eq!(
+                    ProgramJson::from(&original),
+                    ProgramJson::from(&reformatted)
+                );
+            }
+        }
+    }
+}
@@ -5,7 +5,7 @@
     clippy::single_match_else
 )]
 
-use std::{collections::HashMap, sync::Arc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Arc};
 
 use chumsky::error::Rich;
 use internment::ArcIntern;
@@ -18,7 +18,28 @@ use strip_expanded::strip_expanded;
 
 use crate::macro_expansion::expand;
 
+/// Why a `find_import` callback could not load an import
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// The consumer does not support importing the given path
+    Unsupported(String),
+    /// The file exists but could not be read
+    Io(String),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Unsupported(reason) | ImportError::Io(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+pub mod arithmetic;
 mod builtin_macros;
+pub mod format;
 mod lua;
 mod macro_expansion;
 mod optimization;
@@ -27,18 +48,181 @@ mod strip_expanded;
 
 /// Compiles a QAT program into a Q program
 ///
+/// If `strip_asserts` is true, `assert-solved` and `assert-value` instructions
+/// are removed from the program entirely, like a release build.
+///
 /// # Errors
 ///
 /// Returns an error if the QAT program is invalid or if the macro expansion fails
 pub fn compile(
     qat: &File,
-    find_import: impl Fn(&str) -> Result<ArcIntern<str>, String> + 'static,
+    find_import: impl Fn(&str) -> Result<ArcIntern<str>, ImportError> + 'static,
+    strip_asserts: bool,
+) -> Result<Program, Vec<Rich<'static, char, Span>>> {
+    compile_with_warnings(qat, find_import, strip_asserts, false, &mut vec![])
+}
+
+/// Like [`compile`], except that `random` instructions are rejected, for
+/// callers that need the compiled program to behave identically on every run
+///
+/// # Errors
+///
+/// Returns an error if the QAT program is invalid, if the macro expansion
+/// fails, or if the program uses `random`
+pub fn compile_deterministic(
+    qat: &File,
+    find_import: impl Fn(&str) -> Result<ArcIntern<str>, ImportError> + 'static,
+    strip_asserts: bool,
+) -> Result<Program, Vec<Rich<'static, char, Span>>> {
+    compile_with_warnings(qat, find_import, strip_asserts, true, &mut vec![])
+}
+
+/// Like [`compile`], except that diagnostics that do not prevent compilation
+/// are pushed into `warnings`, and `random` instructions are rejected when
+/// `deterministic` is true
+///
+/// # Errors
+///
+/// Returns an error if the QAT program is invalid or if the macro expansion fails
+pub fn compile_with_warnings(
+    qat: &File,
+    find_import: impl Fn(&str) -> Result<ArcIntern<str>, ImportError> + 'static,
+    strip_asserts: bool,
+    deterministic: bool,
+    warnings: &mut Vec<Rich<'static, char, Span>>,
 ) -> Result<Program, Vec<Rich<'static, char, Span>>> {
     let parsed = parse(qat, find_import, false)?;
 
     let expanded = expand(parsed)?;
 
-    strip_expanded(expanded)
+    strip_expanded(expanded, strip_asserts, deterministic, warnings)
+}
+
+/// Lists the macros (builtin and user-defined, including imported ones)
+/// callable from the given QAT file, sorted by name, with a rendering of each
+/// macro's branch patterns so that an editor can offer completions. Builtin
+/// macros report no patterns.
+///
+/// # Errors
+///
+/// Returns an error if the QAT program fails to parse
+pub fn available_macros(
+    qat: &File,
+    find_import: impl Fn(&str) -> Result<ArcIntern<str>, ImportError> + 'static,
+) -> Result<Vec<(String, Vec<String>)>, Vec<Rich<'static, char, Span>>> {
+    let parsed = parse(qat, find_import, false)?;
+
+    Ok(parsed.expansion_info.available_macro_signatures(&qat.inner()))
+}
+
+/// A reusable compiler that caches parsed imports between compilations
+///
+/// Watch-mode tooling recompiles on every edit, and most edits only touch the
+/// root file. Imported files are cached keyed by a hash of their contents, so
+/// an unchanged import reuses its previous parse instead of being re-parsed.
+/// The `find_import` callback is still invoked on every compilation to load
+/// the current contents; only the parse itself is skipped.
+#[derive(Debug, Default)]
+pub struct Compiler {
+    import_cache: Rc<RefCell<parsing::ImportCache>>,
+}
+
+impl Compiler {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Like [`compile`], except that unchanged imports reuse the cached parse
+    /// from previous calls
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the QAT program is invalid or if the macro expansion fails
+    pub fn compile(
+        &mut self,
+        qat: &File,
+        find_import: impl Fn(&str) -> Result<ArcIntern<str>, ImportError> + 'static,
+        strip_asserts: bool,
+    ) -> Result<Program, Vec<Rich<'static, char, Span>>> {
+        let parsed = parsing::parse_cached(
+            qat,
+            find_import,
+            false,
+            Some(Rc::clone(&self.import_cache)),
+        )?;
+
+        let expanded = expand(parsed)?;
+
+        strip_expanded(expanded, strip_asserts, false, &mut vec![])
+    }
+
+    /// The amount of imports that were reused from the cache instead of
+    /// re-parsed, across every call to [`Compiler::compile`]
+    #[must_use]
+    pub fn import_cache_hits(&self) -> usize {
+        self.import_cache.borrow().hits()
+    }
+}
+
+/// The phase of compilation that produced a diagnostic
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompilePhase {
+    Parsing,
+    MacroExpansion,
+    Stripping,
+}
+
+/// Like [`compile`], except that every diagnostic is also handed to
+/// `on_diagnostic` as soon as the phase that produced it completes, so that
+/// tooling can report parse errors without waiting for the rest of
+/// compilation
+///
+/// # Errors
+///
+/// Returns an error if the QAT program is invalid or if the macro expansion fails
+pub fn compile_streaming(
+    qat: &File,
+    find_import: impl Fn(&str) -> Result<ArcIntern<str>, ImportError> + 'static,
+    strip_asserts: bool,
+    mut on_diagnostic: impl FnMut(CompilePhase, &Rich<'static, char, Span>),
+) -> Result<Program, Vec<Rich<'static, char, Span>>> {
+    let parsed = match parse(qat, find_import, false) {
+        Ok(v) => v,
+        Err(errs) => {
+            for err in &errs {
+                on_diagnostic(CompilePhase::Parsing, err);
+            }
+
+            return Err(errs);
+        }
+    };
+
+    let expanded = match expand(parsed) {
+        Ok(v) => v,
+        Err(errs) => {
+            for err in &errs {
+                on_diagnostic(CompilePhase::MacroExpansion, err);
+            }
+
+            return Err(errs);
+        }
+    };
+
+    let mut warnings = vec![];
+    let result = strip_expanded(expanded, strip_asserts, false, &mut warnings);
+
+    for warning in &warnings {
+        on_diagnostic(CompilePhase::Stripping, warning);
+    }
+
+    if let Err(errs) = &result {
+        for err in errs {
+            on_diagnostic(CompilePhase::Stripping, err);
+        }
+    }
+
+    result
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -63,6 +247,24 @@ struct Block {
     maybe_id: Option<BlockID>,
 }
 
+/// An integer parse failure pointing at the exact characters that failed to
+/// parse, along with what the integer was being parsed as
+#[derive(Debug)]
+struct SpannedParseIntError {
+    err: WithSpan<ParseIntError<U>>,
+    parsing_a: &'static str,
+}
+
+impl From<SpannedParseIntError> for Rich<'static, char, Span> {
+    fn from(value: SpannedParseIntError) -> Rich<'static, char, Span> {
+        let span = value.err.span().to_owned();
+        Rich::custom(
+            span,
+            format!("Could not parse the {}: {}", value.parsing_a, &*value.err),
+        )
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct RegisterReference {
     reg_name: WithSpan<ArcIntern<str>>,
@@ -70,13 +272,16 @@ struct RegisterReference {
 }
 
 impl RegisterReference {
-    fn parse(name: WithSpan<ArcIntern<str>>) -> Result<RegisterReference, ParseIntError<U>> {
+    fn parse(name: WithSpan<ArcIntern<str>>) -> Result<RegisterReference, SpannedParseIntError> {
         match Self::try_parse_mod(&name) {
             Some(Ok((s, mod_))) => Ok(RegisterReference {
                 reg_name: WithSpan::new(ArcIntern::from(s), name.span().to_owned()),
                 modulus: Some(mod_),
             }),
-            Some(Err(e)) => Err(e),
+            Some(Err((idx, e))) => Err(SpannedParseIntError {
+                err: name.span().subspan(idx + 1..name.len()).with(e),
+                parsing_a: "register modulus",
+            }),
             None => Ok(RegisterReference {
                 reg_name: name,
                 modulus: None,
@@ -84,11 +289,11 @@ impl RegisterReference {
         }
     }
 
-    fn try_parse_mod(name: &str) -> Option<Result<(&str, Int<U>), ParseIntError<U>>> {
+    fn try_parse_mod(name: &str) -> Option<Result<(&str, Int<U>), (usize, ParseIntError<U>)>> {
         let idx = name.rfind('%')?;
         let num = match name[idx + 1..].parse::<Int<U>>() {
             Ok(v) => v,
-            Err(e) => return Some(Err(e)),
+            Err(e) => return Some(Err((idx, e))),
         };
         Some(Ok((&name[0..idx], num)))
     }
@@ -100,6 +305,10 @@ enum Primitive {
         amt: WithSpan<Int<U>>,
         register: RegisterReference,
     },
+    Random {
+        max: WithSpan<Int<U>>,
+        register: RegisterReference,
+    },
     Goto {
         label: WithSpan<LabelReference>,
     },
@@ -119,6 +328,11 @@ enum Primitive {
         message: WithSpan<String>,
         register: Option<RegisterReference>,
     },
+    Assert {
+        message: WithSpan<String>,
+        expected: WithSpan<Int<U>>,
+        register: RegisterReference,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -127,6 +341,9 @@ enum Value {
     Constant(ArcIntern<str>),
     Ident(ArcIntern<str>),
     Block(Block),
+    /// A move sequence bound by `.include-alg`; usable wherever an algorithm
+    /// literal is accepted
+    Alg(Vec<ArcIntern<str>>),
 }
 
 #[derive(Clone, Debug)]
@@ -164,6 +381,17 @@ enum MacroArgTy {
     Ident,
 }
 
+impl MacroArgTy {
+    fn name(self) -> &'static str {
+        match self {
+            MacroArgTy::Int => "int",
+            MacroArgTy::Reg => "reg",
+            MacroArgTy::Block => "block",
+            MacroArgTy::Ident => "ident",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 enum MacroPatternComponent {
     Argument {
@@ -200,6 +428,21 @@ impl MacroPatternComponent {
 struct MacroPattern(Vec<WithSpan<MacroPatternComponent>>);
 
 impl MacroPattern {
+    /// Renders the pattern the way it is written in a macro branch, e.g.
+    /// `lmao $a:reg`
+    fn render(&self) -> String {
+        self.0
+            .iter()
+            .map(|component| match &**component {
+                MacroPatternComponent::Argument { name, ty } => {
+                    format!("${}:{}", &**name, ty.name())
+                }
+                MacroPatternComponent::Word(word) => (**word).to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
     /// Returns `None` if the patterns do not conflict, otherwise returns a counterexample that would match both patterns.
     pub fn conflicts_with(&self, macro_name: &str, other: &MacroPattern) -> Option<String> {
         if self.0.len() != other.0.len() {
@@ -386,6 +629,37 @@ impl ExpansionInfo {
             None => None,
         }
     }
+
+    /// The macros callable from the given file, sorted by name, each with a
+    /// rendering of every pattern its branches match. Builtin macros report no
+    /// patterns because their argument handling is not pattern-based.
+    fn available_macro_signatures(&self, source: &ArcIntern<str>) -> Vec<(String, Vec<String>)> {
+        let mut signatures = self
+            .available_macros
+            .iter()
+            .filter(|((call_site, _), _)| call_site == source)
+            .map(|((_, name), definition_source)| {
+                let patterns = match self
+                    .macros
+                    .get(&(ArcIntern::clone(definition_source), ArcIntern::clone(name)))
+                {
+                    Some(macro_def) => match &**macro_def {
+                        Macro::UserDefined { branches, .. } => branches
+                            .iter()
+                            .map(|branch| branch.pattern.render())
+                            .collect(),
+                        Macro::Builtin(_) => Vec::new(),
+                    },
+                    None => Vec::new(),
+                };
+
+                ((**name).to_owned(), patterns)
+            })
+            .collect::<Vec<_>>();
+
+        signatures.sort_unstable();
+        signatures
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -406,3 +680,151 @@ struct ExpandedCode {
     block_info: BlockInfoTracker,
     expanded_code_components: Vec<WithSpan<ExpandedCodeComponent>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use internment::ArcIntern;
+    use qter_core::File;
+
+    use crate::{CompilePhase, Compiler, compile_streaming};
+
+    #[test]
+    fn streaming_diagnostics_arrive_by_phase() {
+        let mut phases = vec![];
+
+        let result = compile_streaming(
+            &File::from(".registers {"),
+            |_| unreachable!(),
+            false,
+            |phase, _| {
+                phases.push(phase);
+            },
+        );
+
+        let errs = result.unwrap_err();
+        assert_eq!(phases.len(), errs.len());
+        assert!(!phases.is_empty());
+        assert!(phases.iter().all(|v| *v == CompilePhase::Parsing));
+
+        let code = "
+            .registers {
+                A ← theoretical 10
+            }
+
+            undefined-macro A
+        ";
+
+        let mut phases = vec![];
+
+        let result = compile_streaming(
+            &File::from(code),
+            |_| unreachable!(),
+            false,
+            |phase, _| {
+                phases.push(phase);
+            },
+        );
+
+        let errs = result.unwrap_err();
+        assert_eq!(phases.len(), errs.len());
+        assert!(!phases.is_empty());
+        assert!(phases.iter().all(|v| *v == CompilePhase::MacroExpansion));
+    }
+
+    #[test]
+    fn recompiling_reuses_cached_parses_of_unchanged_imports() {
+        let import_contents = ArcIntern::<str>::from(
+            "
+            .macro five {
+                ($r:reg) => add $r 5
+            }
+        ",
+        );
+
+        let code = "
+            .import five.qat
+
+            .registers {
+                A ← theoretical 10
+            }
+
+            five A
+            halt \"Done\" A
+        ";
+
+        let find_import = {
+            let import_contents = ArcIntern::clone(&import_contents);
+            move |name: &str| {
+                assert_eq!(name, "five.qat");
+                Ok(ArcIntern::clone(&import_contents))
+            }
+        };
+
+        let mut compiler = Compiler::new();
+
+        let first = compiler
+            .compile(&File::from(code), find_import.clone(), false)
+            .unwrap();
+        assert_eq!(compiler.import_cache_hits(), 0);
+
+        let second = compiler.compile(&File::from(code), find_import, false).unwrap();
+        assert_eq!(compiler.import_cache_hits(), 1);
+        assert_eq!(first.instructions.len(), second.instructions.len());
+
+        // An edited import hashes differently and is parsed fresh
+        compiler
+            .compile(
+                &File::from(code),
+                |_| {
+                    Ok(ArcIntern::from(
+                        "
+                    .macro five {
+                        ($r:reg) => add $r 6
+                    }
+                ",
+                    ))
+                },
+                false,
+            )
+            .unwrap();
+        assert_eq!(compiler.import_cache_hits(), 1);
+    }
+
+    #[test]
+    fn listing_available_macros_includes_builtins_and_user_macros() {
+        let code = "
+            .registers {
+                A ← theoretical 10
+            }
+
+            .macro bump {
+                ($r:reg) => add $r 1
+                (twice $r:reg) => {
+                    add $r 2
+                }
+            }
+
+            bump A
+        ";
+
+        let signatures = crate::available_macros(&File::from(code), |_| unreachable!()).unwrap();
+
+        let bump = signatures
+            .iter()
+            .find(|(name, _)| name == "bump")
+            .expect("the user-defined macro should be listed");
+        assert_eq!(bump.1, vec!["$r:reg".to_owned(), "twice $r:reg".to_owned()]);
+
+        // Builtin macros are listed too, with no pattern to render
+        for builtin in ["add", "random", "goto", "solved-goto", "halt", "print"] {
+            let (_, patterns) = signatures
+                .iter()
+                .find(|(name, _)| name == builtin)
+                .expect("every builtin macro should be listed");
+            assert!(patterns.is_empty());
+        }
+
+        // The list is sorted for a stable completion order
+        assert!(signatures.is_sorted());
+    }
+}
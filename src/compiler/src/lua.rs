@@ -1,6 +1,16 @@
 use mlua::{AnyUserData, IntoLua, Lua, UserDataMethods, UserDataRegistry, Value};
-use qter_core::{I, Int};
-
+use qter_core::{I, Int, U};
+
+/// The seed for `qter.random_below`; fixed so that compiling the same program twice expands its macros identically
+const RANDOM_BELOW_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// The Lua environment shared by every macro defined in a single file
+///
+/// A file's `.start-lua` blocks are loaded into the state exactly once, at
+/// parse time; invoking a macro only needs to look up the already-compiled
+/// function by name. The parser caches the state per file source and imports
+/// share the importee's states, so repeated invocations never re-load macro
+/// source.
 #[derive(Clone, Debug)]
 pub struct LuaMacros {
     lua_vm: Lua,
@@ -17,6 +27,48 @@ impl LuaMacros {
 
         lua_vm.globals().set("big", to_big)?;
 
+        let mod_inverse = lua_vm.create_function(|_, (c, n): (Value, Value)| {
+            let n = Self::value_to_int(n)?;
+
+            if n <= Int::<I>::zero() {
+                return Err(mlua::Error::runtime("The modulus must be positive!"));
+            }
+
+            let n = n.abs();
+            let c = Self::value_to_int(c)? % n;
+
+            Ok(c.mod_inverse(n)
+                .map(|inverse| AnyUserData::wrap(Int::<I>::from(inverse))))
+        })?;
+
+        // Xorshift, like scramble generation uses; the seed is fixed so that
+        // macro expansion is reproducible
+        let mut state = RANDOM_BELOW_SEED;
+        let random_below = lua_vm.create_function_mut(move |_, bound: Value| {
+            let bound = Self::value_to_int(bound)?;
+
+            if bound <= Int::<I>::zero() {
+                return Err(mlua::Error::runtime("The bound must be positive!"));
+            }
+
+            let mut next_word = || {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            };
+
+            Ok(AnyUserData::wrap(Int::<I>::from(Int::<U>::random_below(
+                &mut next_word,
+                bound.abs(),
+            ))))
+        })?;
+
+        let qter = lua_vm.create_table()?;
+        qter.set("mod_inverse", mod_inverse)?;
+        qter.set("random_below", random_below)?;
+        lua_vm.globals().set("qter", qter)?;
+
         Ok(LuaMacros { lua_vm })
     }
 
@@ -147,4 +199,52 @@ mod tests {
             ))
             .unwrap();
     }
+
+    #[test]
+    fn qter_arithmetic_helpers() {
+        let lua_vm = LuaMacros::new().unwrap();
+
+        lua_vm
+            .add_code(
+                "
+            function helpers()
+                assert(qter.mod_inverse(3, 7) == big(5))
+                assert(qter.mod_inverse(big(10), 17) == big(12))
+                assert(qter.mod_inverse(4, 6) == nil)
+
+                for _ = 1, 100 do
+                    local v = qter.random_below(10)
+                    assert(big(0) <= v and v < big(10))
+                end
+            end
+        ",
+            )
+            .unwrap();
+
+        lua_vm
+            .lua_vm
+            .globals()
+            .get::<Function>("helpers")
+            .unwrap()
+            .call::<()>(())
+            .unwrap();
+
+        // The sampler is seeded deterministically, so a fresh environment
+        // draws the same sequence
+        let draw = |lua_vm: &LuaMacros| {
+            lua_vm
+                .add_code("first_draw = qter.random_below(1000000)")
+                .unwrap();
+            lua_vm
+                .lua_vm
+                .globals()
+                .get::<AnyUserData>("first_draw")
+                .unwrap()
+                .borrow::<Int<I>>()
+                .map(|v| *v)
+                .unwrap()
+        };
+
+        assert_eq!(draw(&LuaMacros::new().unwrap()), draw(&LuaMacros::new().unwrap()));
+    }
 }
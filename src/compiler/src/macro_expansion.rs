@@ -3,22 +3,81 @@ use std::{cell::OnceCell, mem};
 use chumsky::error::Rich;
 use internment::ArcIntern;
 use itertools::{Either, Itertools};
-use qter_core::{Span, WithSpan};
+use qter_core::{Span, SpannedError, WithSpan};
 
 use crate::{
     BlockID, Code, ExpandedCode, ExpandedCodeComponent, ExpansionInfo, Instruction, Macro,
     ParsedSyntax, RegistersDecl, TaggedInstruction,
 };
 
-pub fn expand(mut parsed: ParsedSyntax) -> Result<ExpandedCode, Vec<Rich<'static, char, Span>>> {
+/// How deeply macros may expand into further macro calls before expansion
+/// gives up and reports a recursion error
+pub const DEFAULT_MAX_EXPANSION_DEPTH: usize = 256;
+
+/// How many frames of the expansion chain to show in a recursion error
+const TRACE_FRAMES: usize = 10;
+
+pub fn expand(parsed: ParsedSyntax) -> Result<ExpandedCode, Vec<Rich<'static, char, Span>>> {
+    expand_with_depth_limit(parsed, DEFAULT_MAX_EXPANSION_DEPTH)
+}
+
+fn depth_limit_error(
+    max_depth: usize,
+    trace: &[WithSpan<ArcIntern<str>>],
+) -> Rich<'static, char, Span> {
+    let last_frames = &trace[trace.len().saturating_sub(TRACE_FRAMES)..];
+
+    let mut error = SpannedError::new(format!(
+        "Macro expansion exceeded the depth limit of {max_depth}; the macros are recursing endlessly"
+    ));
+
+    if trace.len() > last_frames.len() {
+        error = error.with_note(format!(
+            "showing the last {} of {} expansions",
+            last_frames.len(),
+            trace.len()
+        ));
+    }
+
+    for frame in last_frames {
+        let (line, col) = frame.span().line_and_col();
+        error = error.with_note(format!("expanded `{}` at {line}:{col}", &***frame));
+    }
+
+    let fallback_span = Span::new(ArcIntern::from(""), 0, 0);
+    let span = last_frames
+        .last()
+        .map_or(fallback_span.clone(), |frame| frame.span().clone());
+
+    error.with_span(span).into_rich(&fallback_span)
+}
+
+/// Like [`expand`], but with a custom limit on how deeply macros may expand
+/// into further macro calls
+pub fn expand_with_depth_limit(
+    mut parsed: ParsedSyntax,
+    max_depth: usize,
+) -> Result<ExpandedCode, Vec<Rich<'static, char, Span>>> {
     let mut errs = Vec::new();
+    let mut trace = Vec::new();
+    let mut depth = 0;
 
+    // Every pass expands each pending macro call by exactly one level, so
+    // the pass count bounds the depth of every expansion chain
     while expand_block(
         BlockID(0),
         &mut parsed.expansion_info,
         &mut parsed.code,
         &mut errs,
-    ) {}
+        &mut trace,
+    ) {
+        depth += 1;
+
+        if depth > max_depth {
+            errs.push(depth_limit_error(max_depth, &trace));
+            break;
+        }
+    }
 
     if !errs.is_empty() {
         return Err(errs);
@@ -62,6 +121,7 @@ fn expand_block(
     expansion_info: &mut ExpansionInfo,
     code: &mut Vec<WithSpan<TaggedInstruction>>,
     errs: &mut Vec<Rich<'static, char, Span>>,
+    trace: &mut Vec<WithSpan<ArcIntern<str>>>,
 ) -> bool {
     // Will be set if anything is ever changed
     let changed = OnceCell::<()>::new();
@@ -115,8 +175,8 @@ fn expand_block(
                     vec![]
                 }
                 Instruction::Code(code) => {
-                    match expand_code(block_id, expansion_info, code, &changed) {
-                        Ok(tagged_instructions) => tagged_instructions
+                    match expand_code(block_id, expansion_info, code, &span, &changed, trace) {
+                        Ok((span, tagged_instructions)) => tagged_instructions
                             .into_iter()
                             .map(|tagged_instruction| {
                                 Ok(WithSpan::new(tagged_instruction, span.clone()))
@@ -126,7 +186,9 @@ fn expand_block(
                     }
                 }
                 Instruction::Constant(_) => todo!(),
-                Instruction::LuaCall(_) => todo!(),
+                Instruction::LuaCall(_) => {
+                    todo!("look up the compiled function in the file's cached `LuaMacros` state")
+                }
             }
         })
         .partition_map::<Vec<_>, Vec<_>, _, _, _>(|res| match res {
@@ -140,18 +202,24 @@ fn expand_block(
     changed.get().is_some()
 }
 
+/// On success, returns the span to attach to the produced instructions along
+/// with the instructions themselves; instructions produced by a macro carry
+/// the call site chained with the definition site so that diagnostics can
+/// report both
 fn expand_code(
     block_id: BlockID,
     expansion_info: &mut ExpansionInfo,
     code: Code,
+    span: &Span,
     changed: &OnceCell<()>,
-) -> Result<Vec<TaggedInstruction>, Rich<'static, char, Span>> {
+    trace: &mut Vec<WithSpan<ArcIntern<str>>>,
+) -> Result<(Span, Vec<TaggedInstruction>), Rich<'static, char, Span>> {
     let macro_call = match code {
         Code::Primitive(prim) => {
-            return Ok(vec![(
-                Instruction::Code(Code::Primitive(prim)),
-                Some(block_id),
-            )]);
+            return Ok((
+                span.clone(),
+                vec![(Instruction::Code(Code::Primitive(prim)), Some(block_id))],
+            ));
         }
         Code::Macro(mac) => mac,
     };
@@ -176,23 +244,286 @@ fn expand_code(
         ))
         .unwrap();
 
-    Ok(match &**macro_def {
-        Macro::UserDefined {
-            branches: _,
-            after: _,
-        } => todo!(),
-        Macro::Builtin(macro_fn) => macro_fn(expansion_info, macro_call.arguments, block_id)?
-            .into_iter()
-            .map(|instruction| (instruction, Some(block_id)))
-            .collect_vec(),
-    })
+    trace.push(macro_call.name.clone());
+
+    // The dummy definition spans of builtin macros registered from Rust point
+    // at nothing worth reporting
+    let expansion_span = if macro_def.span().slice().trim().is_empty() {
+        span.clone()
+    } else {
+        span.clone().with_expanded_from(macro_def.span().clone())
+    };
+
+    Ok((
+        expansion_span,
+        match &**macro_def {
+            Macro::UserDefined {
+                branches: _,
+                after: _,
+            } => todo!(),
+            Macro::Builtin(macro_fn) => macro_fn(expansion_info, macro_call.arguments, block_id)?
+                .into_iter()
+                .map(|instruction| (instruction, Some(block_id)))
+                .collect_vec(),
+        },
+    ))
 }
 
 #[cfg(test)]
 mod tests {
-    use qter_core::File;
+    use std::collections::HashMap;
+
+    use chumsky::error::Rich;
+    use internment::ArcIntern;
+    use itertools::Itertools;
+    use qter_core::{File, Int, Span, SpannedError, U, WithSpan};
+
+    use crate::{
+        BlockID, BlockInfo, BlockInfoTracker, Code, ExpansionInfo, Instruction, Macro, MacroCall,
+        ParsedSyntax, Primitive, Value,
+        macro_expansion::{expand, expand_with_depth_limit},
+        parsing::parse,
+    };
+
+    type BuiltinFn = fn(
+        &ExpansionInfo,
+        WithSpan<Vec<WithSpan<Value>>>,
+        BlockID,
+    ) -> Result<Vec<Instruction>, Rich<'static, char, Span>>;
+
+    fn call(name: &str, span: &Span, args: Vec<WithSpan<Value>>) -> Instruction {
+        Instruction::Code(Code::Macro(MacroCall {
+            name: WithSpan::new(ArcIntern::from(name), span.clone()),
+            arguments: WithSpan::new(args, span.clone()),
+        }))
+    }
+
+    fn loopy(
+        _: &ExpansionInfo,
+        arguments: WithSpan<Vec<WithSpan<Value>>>,
+        _: BlockID,
+    ) -> Result<Vec<Instruction>, Rich<'static, char, Span>> {
+        Ok(vec![call("loopy", arguments.span(), vec![])])
+    }
+
+    fn ping(
+        _: &ExpansionInfo,
+        arguments: WithSpan<Vec<WithSpan<Value>>>,
+        _: BlockID,
+    ) -> Result<Vec<Instruction>, Rich<'static, char, Span>> {
+        Ok(vec![call("pong", arguments.span(), vec![])])
+    }
+
+    fn pong(
+        _: &ExpansionInfo,
+        arguments: WithSpan<Vec<WithSpan<Value>>>,
+        _: BlockID,
+    ) -> Result<Vec<Instruction>, Rich<'static, char, Span>> {
+        Ok(vec![call("ping", arguments.span(), vec![])])
+    }
+
+    fn countdown(
+        _: &ExpansionInfo,
+        arguments: WithSpan<Vec<WithSpan<Value>>>,
+        _: BlockID,
+    ) -> Result<Vec<Instruction>, Rich<'static, char, Span>> {
+        let span = arguments.span().clone();
 
-    use crate::{macro_expansion::expand, parsing::parse};
+        let Some(Value::Int(n)) = arguments.first().map(|v| &**v) else {
+            unreachable!()
+        };
+
+        if *n == Int::<U>::from(0_u32) {
+            Ok(vec![])
+        } else {
+            Ok(vec![call(
+                "countdown",
+                &span,
+                vec![WithSpan::new(Value::Int(*n - Int::one()), span.clone())],
+            )])
+        }
+    }
+
+    /// A program whose only code is a call to `entry`, with every given
+    /// builtin macro in scope
+    fn recursive_parsed(
+        macros: &[(&str, BuiltinFn)],
+        entry: &str,
+        entry_args: Vec<WithSpan<Value>>,
+    ) -> ParsedSyntax {
+        let source = ArcIntern::<str>::from("test-macros");
+        let span = Span::new(ArcIntern::clone(&source), 0, source.len());
+
+        let mut macros_map = HashMap::new();
+        let mut available_macros = HashMap::new();
+
+        for &(name, builtin) in macros {
+            let name = ArcIntern::<str>::from(name);
+            macros_map.insert(
+                (ArcIntern::clone(&source), ArcIntern::clone(&name)),
+                WithSpan::new(Macro::Builtin(builtin), span.clone()),
+            );
+            available_macros.insert((ArcIntern::clone(&source), name), ArcIntern::clone(&source));
+        }
+
+        let mut block_info = HashMap::new();
+        block_info.insert(
+            BlockID(0),
+            BlockInfo {
+                parent_block: None,
+                child_blocks: vec![],
+                defines: vec![],
+                labels: vec![],
+            },
+        );
+
+        ParsedSyntax {
+            expansion_info: ExpansionInfo {
+                registers: None,
+                block_counter: 1,
+                block_info: BlockInfoTracker(block_info),
+                macros: macros_map,
+                available_macros,
+                lua_macros: HashMap::new(),
+            },
+            code: vec![WithSpan::new(
+                (
+                    call(entry, &span, entry_args),
+                    None,
+                ),
+                span,
+            )],
+        }
+    }
+
+    #[test]
+    fn direct_recursion_hits_depth_limit() {
+        let parsed = recursive_parsed(&[("loopy", loopy)], "loopy", vec![]);
+
+        let errs = expand_with_depth_limit(parsed, 32).unwrap_err();
+        let message = errs.iter().map(ToString::to_string).join("\n");
+
+        assert!(message.contains("depth limit of 32"));
+        assert!(message.contains("expanded `loopy`"));
+        // The trace is truncated to the last ten frames
+        assert_eq!(message.matches("expanded `").count(), 10);
+    }
+
+    #[test]
+    fn mutual_recursion_hits_depth_limit() {
+        let parsed = recursive_parsed(&[("ping", ping), ("pong", pong)], "ping", vec![]);
+
+        let errs = expand_with_depth_limit(parsed, 32).unwrap_err();
+        let message = errs.iter().map(ToString::to_string).join("\n");
+
+        assert!(message.contains("depth limit of 32"));
+        assert!(message.contains("expanded `ping`"));
+        assert!(message.contains("expanded `pong`"));
+    }
+
+    #[test]
+    fn deep_finite_recursion_expands() {
+        let source = ArcIntern::<str>::from("test-macros");
+        let span = Span::new(ArcIntern::clone(&source), 0, source.len());
+
+        let parsed = recursive_parsed(
+            &[("countdown", countdown)],
+            "countdown",
+            vec![WithSpan::new(Value::Int(Int::from(100_u32)), span)],
+        );
+
+        let expanded = expand(parsed).unwrap();
+        assert!(expanded.expanded_code_components.is_empty());
+    }
+
+    fn emit_halt(
+        _: &ExpansionInfo,
+        arguments: WithSpan<Vec<WithSpan<Value>>>,
+        _: BlockID,
+    ) -> Result<Vec<Instruction>, Rich<'static, char, Span>> {
+        Ok(vec![Instruction::Code(Code::Primitive(Primitive::Halt {
+            message: WithSpan::new("Done".to_owned(), arguments.span().clone()),
+            register: None,
+        }))])
+    }
+
+    #[test]
+    fn errors_in_expanded_code_report_the_call_and_definition_sites() {
+        let definition_source =
+            ArcIntern::<str>::from(".macro emit-halt {\n    () => halt Done\n}");
+        let definition_span = Span::new(
+            ArcIntern::clone(&definition_source),
+            0,
+            definition_source.len(),
+        );
+
+        let call_source = ArcIntern::<str>::from("\nemit-halt");
+        let call_span = Span::new(ArcIntern::clone(&call_source), 1, call_source.len());
+
+        let name = ArcIntern::<str>::from("emit-halt");
+
+        let mut macros_map = HashMap::new();
+        macros_map.insert(
+            (ArcIntern::clone(&definition_source), ArcIntern::clone(&name)),
+            WithSpan::new(Macro::Builtin(emit_halt), definition_span.clone()),
+        );
+
+        let mut available_macros = HashMap::new();
+        available_macros.insert(
+            (ArcIntern::clone(&call_source), name),
+            ArcIntern::clone(&definition_source),
+        );
+
+        let mut block_info = HashMap::new();
+        block_info.insert(
+            BlockID(0),
+            BlockInfo {
+                parent_block: None,
+                child_blocks: vec![],
+                defines: vec![],
+                labels: vec![],
+            },
+        );
+
+        let parsed = ParsedSyntax {
+            expansion_info: ExpansionInfo {
+                registers: None,
+                block_counter: 1,
+                block_info: BlockInfoTracker(block_info),
+                macros: macros_map,
+                available_macros,
+                lua_macros: HashMap::new(),
+            },
+            code: vec![WithSpan::new(
+                (call("emit-halt", &call_span, vec![]), None),
+                call_span.clone(),
+            )],
+        };
+
+        let expanded = expand(parsed).unwrap();
+        let instruction = &expanded.expanded_code_components[0];
+
+        // The instruction still points at the call site, with the definition
+        // site preserved in the expansion backtrace
+        assert_eq!(instruction.span().source(), call_source);
+        let backtrace = instruction.span().expansion_backtrace().collect_vec();
+        assert_eq!(backtrace.len(), 1);
+        assert_eq!(backtrace[0].source(), definition_source);
+
+        // A diagnostic on the expanded instruction reports both locations
+        let err = SpannedError::new("This halt is unreachable")
+            .with_span(instruction.span().clone())
+            .into_rich(instruction.span());
+
+        assert_eq!(err.span().slice(), "emit-halt");
+
+        let (line, col) = definition_span.line_and_col();
+        assert!(
+            err.to_string()
+                .contains(&format!("note: expanded from the macro at {line}:{col}")),
+            "{err}"
+        );
+    }
 
     #[test]
     fn bruh() {
@@ -8,7 +8,7 @@ use qter_core::{
 };
 
 use crate::{
-    BlockID, optimization::{OptimizingPrimitive, combinators::{PeepholeRewriter, Rewriter}, extend_from_start}, primitive_match, strip_expanded::GlobalRegs,
+    BlockID, RegisterReference, optimization::{OptimizingPrimitive, combinators::{PeepholeRewriter, Rewriter}, extend_from_start}, primitive_match, strip_expanded::GlobalRegs,
 };
 
 use super::OptimizingCodeComponent;
@@ -91,6 +91,22 @@ impl PeepholeRewriter for RemoveUselessJumps {
     }
 }
 
+/*
+Merges runs of adds into one combined add per puzzle, reordering pending adds
+past instructions that do not touch the state the adds live on. In a loop like
+
+```
+counter:
+    solved-goto A done
+    add B 2
+    add A 23
+    goto counter
+```
+
+the body collapses to one combined algorithm per puzzle per iteration, which
+the repeat-until transforms below can then pick up when everything lives on a
+single puzzle.
+*/
 #[derive(Default)]
 pub struct CoalesceAdds {
     block_id: Option<BlockID>,
@@ -127,6 +143,47 @@ impl CoalesceAdds {
             .collect()
     }
 
+    /// Pending adds commute with instructions that only read a different
+    /// state, so an instruction reading one register only forces out the
+    /// pending adds on the state it lives on. The rest get reordered past it
+    /// and may coalesce with later adds.
+    fn dump_conflicting(
+        &mut self,
+        global_regs: &GlobalRegs,
+        register: &RegisterReference,
+    ) -> Vec<WithSpan<OptimizingCodeComponent>> {
+        match global_regs.get_reg(register) {
+            ByPuzzleType::Theoretical((theoretical_idx, _)) => self
+                .theoreticals
+                .iter()
+                .position(|v| v.0 == theoretical_idx)
+                .map(|i| {
+                    self.theoreticals.remove(i).map(|(theoretical, amt)| {
+                        OptimizingCodeComponent::Instruction(
+                            Box::new(OptimizingPrimitive::AddTheoretical { theoretical, amt }),
+                            self.block_id.unwrap(),
+                        )
+                    })
+                })
+                .into_iter()
+                .collect(),
+            ByPuzzleType::Puzzle((puzzle_idx, _)) => self
+                .puzzles
+                .iter()
+                .position(|v| v.0 == puzzle_idx)
+                .map(|i| {
+                    self.puzzles.remove(i).map(|(puzzle, arch, amts)| {
+                        OptimizingCodeComponent::Instruction(
+                            Box::new(OptimizingPrimitive::AddPuzzle { puzzle, arch, amts }),
+                            self.block_id.unwrap(),
+                        )
+                    })
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
     fn merge_effects(
         effect1: &mut Vec<(usize, Option<Int<U>>, WithSpan<Int<U>>)>,
         effect2: &[(usize, Option<Int<U>>, WithSpan<Int<U>>)],
@@ -151,7 +208,7 @@ impl Rewriter for CoalesceAdds {
     fn rewrite(
         &mut self,
         component: WithSpan<OptimizingCodeComponent>,
-        _: &GlobalRegs,
+        global_regs: &GlobalRegs,
     ) -> Vec<WithSpan<OptimizingCodeComponent>> {
         let span = component.span().clone();
 
@@ -193,6 +250,41 @@ impl Rewriter for CoalesceAdds {
 
                     Vec::new()
                 }
+                OptimizingPrimitive::Input { message, register } => {
+                    let mut instrs = self.dump_conflicting(global_regs, &register);
+                    instrs.push(span.with(OptimizingCodeComponent::Instruction(
+                        Box::new(OptimizingPrimitive::Input { message, register }),
+                        block_id,
+                    )));
+                    instrs
+                }
+                OptimizingPrimitive::Assert {
+                    message,
+                    expected,
+                    register,
+                } => {
+                    let mut instrs = self.dump_conflicting(global_regs, &register);
+                    instrs.push(span.with(OptimizingCodeComponent::Instruction(
+                        Box::new(OptimizingPrimitive::Assert {
+                            message,
+                            expected,
+                            register,
+                        }),
+                        block_id,
+                    )));
+                    instrs
+                }
+                OptimizingPrimitive::Print { message, register } => {
+                    let mut instrs = match &register {
+                        Some(register) => self.dump_conflicting(global_regs, register),
+                        None => Vec::new(),
+                    };
+                    instrs.push(span.with(OptimizingCodeComponent::Instruction(
+                        Box::new(OptimizingPrimitive::Print { message, register }),
+                        block_id,
+                    )));
+                    instrs
+                }
                 primitive => {
                     let mut instrs = self.dump_state();
                     instrs.push(span.with(OptimizingCodeComponent::Instruction(
@@ -56,6 +56,10 @@ pub enum OptimizingPrimitive {
         theoretical: TheoreticalIdx,
         amt: WithSpan<Int<U>>,
     },
+    Random {
+        max: WithSpan<Int<U>>,
+        register: RegisterReference,
+    },
     Goto {
         label: WithSpan<LabelReference>,
     },
@@ -84,6 +88,11 @@ pub enum OptimizingPrimitive {
         message: WithSpan<String>,
         register: Option<RegisterReference>,
     },
+    Assert {
+        message: WithSpan<String>,
+        expected: WithSpan<Int<U>>,
+        register: RegisterReference,
+    },
 }
 
 /// Autogenerated implementation, modified to do pointer comparison for the `Arc<Architecture>`'s
@@ -116,6 +125,16 @@ impl PartialEq for OptimizingPrimitive {
                     amt: r_amt,
                 },
             ) => l_theoretical == r_theoretical && l_amt == r_amt,
+            (
+                Self::Random {
+                    max: l_max,
+                    register: l_register,
+                },
+                Self::Random {
+                    max: r_max,
+                    register: r_register,
+                },
+            ) => l_max == r_max && l_register == r_register,
             (Self::Goto { label: l_label }, Self::Goto { label: r_label }) => l_label == r_label,
             (
                 Self::SolvedGoto {
@@ -180,6 +199,18 @@ impl PartialEq for OptimizingPrimitive {
                     register: r_register,
                 },
             ) => l_message == r_message && l_register == r_register,
+            (
+                Self::Assert {
+                    message: l_message,
+                    expected: l_expected,
+                    register: l_register,
+                },
+                Self::Assert {
+                    message: r_message,
+                    expected: r_expected,
+                    register: r_register,
+                },
+            ) => l_message == r_message && l_expected == r_expected && l_register == r_register,
             _ => false,
         }
     }
@@ -4,7 +4,9 @@ use crate::{
     builtin_macros::builtin_macros, lua::LuaMacros,
 };
 use std::{
+    cell::RefCell,
     collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
     rc::Rc,
     sync::{Arc, LazyLock},
 };
@@ -19,11 +21,12 @@ use chumsky::{
 use internment::ArcIntern;
 use itertools::Itertools;
 use qter_core::{
-    Extra, File, Int, MaybeErr, Span, U, WithSpan,
-    architectures::{Architecture, puzzle_definition},
+    Extra, File, Int, MaybeErr, Span, SpannedError, U, WithSpan,
+    architectures::{Architecture, CycleGenerator, puzzle_definition},
+    table_encoding::decode_table,
 };
 
-use crate::{BlockID, Macro, ParsedSyntax, Puzzle, RegistersDecl};
+use crate::{BlockID, ImportError, Macro, ParsedSyntax, Puzzle, RegistersDecl};
 
 use super::Instruction;
 
@@ -70,27 +73,67 @@ static PRELUDE: LazyLock<ParsedSyntax> = LazyLock::new(|| {
 
 type ExtraAndSyntax = Full<
     Rich<'static, char, Span>,
-    SimpleState<(Rc<dyn Fn(&str) -> Result<ArcIntern<str>, String>>, bool)>,
+    SimpleState<(
+        Rc<dyn Fn(&str) -> Result<ArcIntern<str>, ImportError>>,
+        bool,
+        Option<Rc<RefCell<ImportCache>>>,
+    )>,
     (),
 >;
 
+/// Parsed imports cached across compilations, keyed by a hash of the imported
+/// file's contents so that editing one file never reuses a stale parse of
+/// another
+#[derive(Debug, Default)]
+pub(crate) struct ImportCache {
+    parsed: HashMap<u64, ParsedSyntax>,
+    hits: usize,
+}
+
+impl ImportCache {
+    /// The amount of imports that were reused from the cache instead of
+    /// re-parsed
+    pub(crate) fn hits(&self) -> usize {
+        self.hits
+    }
+}
+
+fn content_hash(contents: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
 pub fn parse(
     qat: &File,
-    find_import: impl Fn(&str) -> Result<ArcIntern<str>, String> + 'static,
+    find_import: impl Fn(&str) -> Result<ArcIntern<str>, ImportError> + 'static,
+    is_prelude: bool,
+) -> Result<ParsedSyntax, Vec<Rich<'static, char, Span>>> {
+    parse_cached(qat, find_import, is_prelude, None)
+}
+
+/// Like [`parse`], except that imported files whose contents hash to an entry
+/// in `import_cache` reuse the cached parse instead of being re-parsed
+pub(crate) fn parse_cached(
+    qat: &File,
+    find_import: impl Fn(&str) -> Result<ArcIntern<str>, ImportError> + 'static,
     is_prelude: bool,
+    import_cache: Option<Rc<RefCell<ImportCache>>>,
 ) -> Result<ParsedSyntax, Vec<Rich<'static, char, Span>>> {
     thread_local! {
         static PARSER: Boxed<'static, 'static, File, MaybeErr<ParsedSyntax>, ExtraAndSyntax> = parser().boxed();
     }
 
     let mut parsed_syntax_and_extras = SimpleState((
-        Rc::from(find_import) as Rc<dyn Fn(&str) -> Result<ArcIntern<str>, String>>,
+        Rc::from(find_import) as Rc<dyn Fn(&str) -> Result<ArcIntern<str>, ImportError>>,
         is_prelude,
+        import_cache,
     ));
 
     let parsed_syntax = PARSER
         .with(|parser| parser.parse_with_state(qat.clone(), &mut parsed_syntax_and_extras))
-        .into_result()?;
+        .into_result()
+        .map_err(cap_related_errors)?;
 
     Ok(match parsed_syntax {
         MaybeErr::Some(v) => v,
@@ -100,6 +143,13 @@ pub fn parse(
     })
 }
 
+/// Error recovery can emit several diagnostics rooted at the same spot; keep
+/// one error per source location so that one typo is reported once.
+fn cap_related_errors(mut errs: Vec<Rich<'static, char, Span>>) -> Vec<Rich<'static, char, Span>> {
+    errs.dedup_by(|a, b| a.span().line_and_col() == b.span().line_and_col());
+    errs
+}
+
 type ExtraAndState<S> = Full<Rich<'static, char, Span>, S, ()>;
 
 fn parser() -> impl Parser<'static, File, MaybeErr<ParsedSyntax>, ExtraAndSyntax> {
@@ -111,7 +161,7 @@ fn parser() -> impl Parser<'static, File, MaybeErr<ParsedSyntax>, ExtraAndSyntax
                 data.span().with(regs)
             })
             .or_not(),
-        statement()
+        recovered_statement()
             .with_state(())
             .separated_by(nl())
             .allow_trailing()
@@ -206,6 +256,7 @@ fn parser() -> impl Parser<'static, File, MaybeErr<ParsedSyntax>, ExtraAndSyntax
 
                     let find_import = Rc::clone(&state_ref.0);
                     let is_prelude = state_ref.1;
+                    let import_cache = state_ref.2.clone();
 
                     let import = match (find_import)(filename.slice()) {
                         Ok(v) => v,
@@ -219,20 +270,128 @@ fn parser() -> impl Parser<'static, File, MaybeErr<ParsedSyntax>, ExtraAndSyntax
                         }
                     };
 
-                    let importee =
-                        match parse(&File::from(import), move |v| (find_import)(v), is_prelude) {
-                            Ok(v) => v,
-                            Err(errs) => {
-                                for err in errs {
-                                    emitter.emit(err);
+                    let hash = content_hash(&import);
+
+                    let cached = import_cache.as_ref().and_then(|cache| {
+                        let mut cache = cache.borrow_mut();
+                        let cached = cache.parsed.get(&hash).cloned();
+                        if cached.is_some() {
+                            cache.hits += 1;
+                        }
+                        cached
+                    });
+
+                    let importee = match cached {
+                        Some(importee) => importee,
+                        None => {
+                            let importee = match parse_cached(
+                                &File::from(import),
+                                move |v| (find_import)(v),
+                                is_prelude,
+                                import_cache.clone(),
+                            ) {
+                                Ok(v) => v,
+                                Err(errs) => {
+                                    for err in errs {
+                                        emitter.emit(err);
+                                    }
+
+                                    continue;
                                 }
+                            };
 
-                                continue;
+                            if let Some(cache) = &import_cache {
+                                cache.borrow_mut().parsed.insert(hash, importee.clone());
                             }
-                        };
+
+                            importee
+                        }
+                    };
 
                     merge_files(&mut parsed_syntax, &qat, importee, data.span(), emitter);
                 }
+                Statement::IncludeAlg {
+                    name,
+                    filename,
+                    index,
+                    span,
+                } => {
+                    let find_import = Rc::clone(&data.state().0.0);
+
+                    let contents = match (find_import)(filename.slice()) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            emitter.emit(Rich::custom(
+                                filename,
+                                format!("Unable to find the algorithm file: {e}"),
+                            ));
+
+                            continue;
+                        }
+                    };
+
+                    let Some(algs) = decode_alg_file(&contents) else {
+                        emitter.emit(Rich::custom(
+                            filename,
+                            "The compressed algorithm table could not be decoded.",
+                        ));
+
+                        continue;
+                    };
+
+                    let Some(alg) = usize::try_from(*index).ok().and_then(|idx| algs.get(idx))
+                    else {
+                        emitter.emit(Rich::custom(
+                            index.span().clone(),
+                            format!(
+                                "The index is out of bounds; the file contains {} algorithms.",
+                                algs.len()
+                            ),
+                        ));
+
+                        continue;
+                    };
+
+                    // Without a real puzzle in scope there is nothing to
+                    // validate the moves against
+                    let groups = parsed_syntax
+                        .expansion_info
+                        .registers
+                        .iter()
+                        .flat_map(|regs| &regs.puzzles)
+                        .filter_map(|puzzle| match puzzle {
+                            Puzzle::Real { architectures } => Some(architectures),
+                            Puzzle::Theoretical { .. } => None,
+                        })
+                        .flatten()
+                        .map(|(_, arch)| arch.group())
+                        .collect_vec();
+
+                    if let Some(moove) = alg.iter().find(|moove| {
+                        !groups.is_empty()
+                            && groups
+                                .iter()
+                                .all(|group| group.get_generator(moove).is_none())
+                    }) {
+                        emitter.emit(Rich::custom(
+                            filename,
+                            format!(
+                                "The algorithm contains the move `{moove}`, which does not exist on the declared puzzle."
+                            ),
+                        ));
+
+                        continue;
+                    }
+
+                    let alg = alg.clone();
+                    parsed_syntax.code.push(span.clone().with((
+                        Instruction::Define(Define {
+                            name,
+                            value: DefineValue::Value(span.with(Value::Alg(alg))),
+                        }),
+                        Some(BlockID(0)),
+                    )));
+                }
             }
         }
 
@@ -335,9 +494,32 @@ fn intu<S: Inspector<'static, File> + 'static>()
     })
 }
 
-fn simple_ident<S: Inspector<'static, File> + 'static>()
--> impl Parser<'static, File, WithSpan<ArcIntern<str>>, ExtraAndState<S>> {
-    let special_char = choice((
+/// Like `intu`, except that it consumes a whole word so that a malformed
+/// integer such as `9a` reports an integer parse error spanning the exact
+/// offending characters rather than a syntax error after the digits
+fn intu_word<S: Inspector<'static, File> + 'static>(
+    parsing_a: &'static str,
+) -> impl Parser<'static, File, MaybeErr<Int<U>>, ExtraAndState<S>> {
+    group((special_char().not(), any()))
+        .repeated()
+        .at_least(1)
+        .to_span()
+        .filter(|span: &Span| span.slice().starts_with(|c: char| c.is_ascii_digit()))
+        .validate(move |span: Span, _, emitter| match span.slice().parse() {
+            Ok(v) => MaybeErr::Some(v),
+            Err(e) => {
+                emitter.emit(Rich::custom(
+                    span,
+                    format!("Could not parse the {parsing_a}: {e}"),
+                ));
+                MaybeErr::None
+            }
+        })
+}
+
+fn special_char<S: Inspector<'static, File> + 'static>()
+-> impl Parser<'static, File, (), ExtraAndState<S>> {
+    choice((
         just('{').to(()),
         just('}').to(()),
         just('.').to(()),
@@ -353,10 +535,13 @@ fn simple_ident<S: Inspector<'static, File> + 'static>()
         just('!').to(()),
         just('"').to(()),
         req_whitespace(),
-    ));
+    ))
+}
 
+fn simple_ident<S: Inspector<'static, File> + 'static>()
+-> impl Parser<'static, File, WithSpan<ArcIntern<str>>, ExtraAndState<S>> {
     Parser::map(
-        group((special_char.not(), any()))
+        group((special_char().not(), any()))
             .repeated()
             .at_least(1)
             .to_span()
@@ -397,6 +582,7 @@ fn registers() -> impl Parser<'static, File, MaybeErr<RegistersDecl>, Extra> {
         whitespace(),
         just("{"),
         register_decl()
+            .recover_with(via_parser(skip_register_decl()))
             .separated_by(nl())
             .at_least(1)
             .allow_leading()
@@ -405,11 +591,156 @@ fn registers() -> impl Parser<'static, File, MaybeErr<RegistersDecl>, Extra> {
         just("}"),
     ))
     .delimited_by(nlm(), nlm())
-    .map(|(_, (), _, puzzles, _)| puzzles.map(|puzzles| RegistersDecl { puzzles }))
+    .validate(|(_, (), _, decls, _), _, emitter| {
+        decls
+            .map(|decls| {
+                let mut puzzles = Vec::new();
+                let mut ok = true;
+
+                for (grouping, puzzle) in decls {
+                    match grouping {
+                        PuzzleGrouping::NewPuzzle => puzzles.push(puzzle.into_inner()),
+                        PuzzleGrouping::SamePuzzle => {
+                            let span = puzzle.span().clone();
+
+                            let combined = match puzzles.last() {
+                                Some(prev) => combine_same_puzzle(prev, &puzzle, &span),
+                                None => Err(Rich::custom(
+                                    span,
+                                    "There is no previous declaration for `same-puzzle` to extend.",
+                                )),
+                            };
+
+                            match combined {
+                                Ok(combined) => *puzzles.last_mut().unwrap() = combined,
+                                Err(e) => {
+                                    emitter.emit(e);
+                                    ok = false;
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if ok {
+                    MaybeErr::Some(RegistersDecl { puzzles })
+                } else {
+                    MaybeErr::None
+                }
+            })
+            .flatten()
+    })
+}
+
+/// Whether a declaration inside `.registers` describes its own physical
+/// puzzle or extends the puzzle of the declaration before it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PuzzleGrouping {
+    NewPuzzle,
+    SamePuzzle,
 }
 
-fn register_decl() -> impl Parser<'static, File, MaybeErr<Puzzle>, Extra> {
-    choice((register_decl_switchable(), register_decl_unswitchable()))
+/// Merge the registers of a `same-puzzle` declaration onto the previous
+/// declaration's puzzle by rebuilding one architecture holding every
+/// register's algorithm.
+///
+/// Rebuilding runs the shared facelet analysis over the union of the
+/// algorithms, so a combination the puzzle cannot support shows up as a
+/// register whose order shrank below what its own declaration promised.
+fn combine_same_puzzle(
+    prev: &Puzzle,
+    decl: &Puzzle,
+    span: &Span,
+) -> Result<Puzzle, Rich<'static, char, Span>> {
+    let Puzzle::Real {
+        architectures: prev_archs,
+    } = prev
+    else {
+        return Err(Rich::custom(
+            span.clone(),
+            "`same-puzzle` cannot extend a theoretical register; theoretical registers do not occupy a physical puzzle.",
+        ));
+    };
+
+    let Puzzle::Real {
+        architectures: new_archs,
+    } = decl
+    else {
+        return Err(Rich::custom(
+            span.clone(),
+            "A theoretical register cannot be declared `same-puzzle`; theoretical registers do not occupy a physical puzzle.",
+        ));
+    };
+
+    let ([(prev_names, prev_arch)], [(new_names, new_arch)]) = (&prev_archs[..], &new_archs[..])
+    else {
+        return Err(Rich::custom(
+            span.clone(),
+            "`same-puzzle` cannot be combined with switchable architectures.",
+        ));
+    };
+
+    if prev_arch.group().definition().slice() != new_arch.group().definition().slice() {
+        return Err(Rich::custom(
+            span.clone(),
+            format!(
+                "This declaration is for a {} puzzle whereas the puzzle it extends is a {} puzzle.",
+                new_arch.group().definition().slice(),
+                prev_arch.group().definition().slice(),
+            ),
+        ));
+    }
+
+    let algorithms = prev_arch
+        .registers()
+        .iter()
+        .chain(new_arch.registers())
+        .map(|register| register.algorithm().move_seq_iter().cloned().collect_vec())
+        .collect_vec();
+
+    let combined = Architecture::new(prev_arch.group_arc(), &algorithms)
+        .expect("the algorithms come from existing architectures");
+
+    let declared_orders = prev_arch
+        .registers()
+        .iter()
+        .chain(new_arch.registers())
+        .map(CycleGenerator::order);
+
+    for (register, declared) in combined.registers().iter().zip(declared_orders) {
+        if register.order() != declared {
+            return Err(Rich::custom(
+                span.clone(),
+                format!(
+                    "The registers declared on this puzzle exceed its capacity: sharing facelets between the declarations would reduce a register of order {declared} to order {}. Declare the registers on separate puzzles or pick a larger architecture.",
+                    register.order()
+                ),
+            ));
+        }
+    }
+
+    let names = prev_names.iter().chain(new_names).cloned().collect();
+
+    Ok(Puzzle::Real {
+        architectures: vec![(names, prev_arch.span().clone().with(Arc::new(combined)))],
+    })
+}
+
+fn register_decl()
+-> impl Parser<'static, File, MaybeErr<(PuzzleGrouping, WithSpan<Puzzle>)>, Extra> {
+    group((
+        choice((
+            just("same-puzzle").to(PuzzleGrouping::SamePuzzle),
+            just("new-puzzle").to(PuzzleGrouping::NewPuzzle),
+        ))
+        .then_ignore(req_whitespace())
+        .or_not()
+        .map(|grouping| grouping.unwrap_or(PuzzleGrouping::NewPuzzle)),
+        choice((register_decl_switchable(), register_decl_unswitchable())),
+    ))
+    .map_with(|(grouping, puzzle), data| {
+        puzzle.map(|puzzle| (grouping, data.span().with(puzzle)))
+    })
 }
 
 fn register_decl_unswitchable() -> impl Parser<'static, File, MaybeErr<Puzzle>, Extra> {
@@ -485,7 +816,7 @@ fn register_architecture() -> impl Parser<'static, File, MaybeErr<PuzzleUnnamed>
         group((
             just("theoretical"),
             whitespace(),
-            intu().map_with(|v, extra| v.map(|v| extra.span().with(v))),
+            intu_word("register order").map_with(|v, extra| v.map(|v| extra.span().with(v))),
         ))
         .map(|(_, (), order)| order.map(|order| PuzzleUnnamed::Theoretical { order })),
         group((
@@ -494,8 +825,8 @@ fn register_architecture() -> impl Parser<'static, File, MaybeErr<PuzzleUnnamed>
             just("builtin"),
             whitespace(),
             choice((
-                intu().map(|v| v.map(|v| vec![v])),
-                intu()
+                intu_word("register order").map(|v| v.map(|v| vec![v])),
+                intu_word("register order")
                     .separated_by(just(",").delimited_by(nlm(), nlm()))
                     .at_least(1)
                     .allow_trailing()
@@ -508,11 +839,22 @@ fn register_architecture() -> impl Parser<'static, File, MaybeErr<PuzzleUnnamed>
             |(def, (), _, (), orders), data, emitter| orders.spanspose().map(|orders| if let Some(arch) = def.get_preset(&orders) { MaybeErr::Some(PuzzleUnnamed::Real {
                 architecture: data.span().with(arch),
             }) } else {
-                emitter.emit(Rich::custom(
-                                orders.span().clone(),
-                                "There does not exist a preset architecture with the given orders.",
-                            ));
-                            MaybeErr::None
+                emitter.emit(
+                    SpannedError::new("There does not exist a preset architecture with the given orders.")
+                        .with_span(orders.span().clone())
+                        .with_note(format!(
+                            "the available presets are: {}",
+                            def.presets
+                                .iter()
+                                .map(|preset| format!(
+                                    "({})",
+                                    preset.registers().iter().map(|cycle| cycle.order()).join(", ")
+                                ))
+                                .join(", ")
+                        ))
+                        .into_rich(&data.span()),
+                );
+                MaybeErr::None
             },
         ).flatten()),
         group((
@@ -536,7 +878,15 @@ fn register_architecture() -> impl Parser<'static, File, MaybeErr<PuzzleUnnamed>
                     architecture: data.span().with(Arc::new(arch)),
                 }),
                 Err(bad_generator) => {
-                    emitter.emit(Rich::custom(bad_generator.clone(), format!("This generator does not exist in the given permutation group. The options are: {}", def.perm_group.generators().map(|(name, _)| name).join(&ArcIntern::from(", ")))));
+                    emitter.emit(
+                        SpannedError::new("This generator does not exist in the given permutation group.")
+                            .with_span(bad_generator.clone())
+                            .with_note(format!(
+                                "the options are: {}",
+                                def.perm_group.generators().map(|(name, _)| name).join(&ArcIntern::from(", "))
+                            ))
+                            .into_rich(&data.span()),
+                    );
 
                     MaybeErr::None
                 },
@@ -589,6 +939,12 @@ enum Statement {
     Instruction(WithSpan<Instruction>),
     LuaBlock(Span),
     Import(Span),
+    IncludeAlg {
+        name: WithSpan<ArcIntern<str>>,
+        filename: Span,
+        index: WithSpan<Int<U>>,
+        span: Span,
+    },
 }
 
 fn statement() -> impl Parser<'static, File, MaybeErr<Statement>, Extra> {
@@ -600,9 +956,51 @@ fn statement() -> impl Parser<'static, File, MaybeErr<Statement>, Extra> {
         instruction(block_rec).map(|instr| instr.map(Statement::Instruction)),
         lua_block().map(|v| MaybeErr::Some(Statement::LuaBlock(v))),
         import().map(|v| v.map(Statement::Import)),
+        include_alg(),
     ))
 }
 
+/// A statement that must extend to the end of its line; if it doesn't parse,
+/// report the error and skip ahead so that later statements still get checked.
+fn recovered_statement() -> impl Parser<'static, File, MaybeErr<Statement>, Extra> {
+    statement()
+        .then_ignore(
+            group((
+                whitespace(),
+                choice((just('\n').to(()), line_comment(), end())),
+            ))
+            .rewind(),
+        )
+        .recover_with(via_parser(skip_statement()))
+}
+
+/// Consumes a malformed statement without parsing it: everything up to the
+/// next newline, except that brace-delimited blocks are skipped as a whole so
+/// that one error inside e.g. a macro definition doesn't cascade into bogus
+/// errors on every line of its body.
+fn skip_statement() -> impl Parser<'static, File, MaybeErr<Statement>, Extra> {
+    let braced = recursive(|braced| {
+        choice((braced, group((one_of("{}").not(), any())).to(())))
+            .repeated()
+            .delimited_by(just('{'), just('}'))
+            .to(())
+    });
+
+    choice((braced, group((one_of("{\n").not(), any())).to(())))
+        .repeated()
+        .at_least(1)
+        .to(MaybeErr::None)
+}
+
+/// Like [`skip_statement`] but for a line inside a `.registers` block; stops
+/// before `}` so the block itself still closes.
+fn skip_register_decl() -> impl Parser<'static, File, MaybeErr<Puzzle>, Extra> {
+    group((one_of("{}\n").not(), any()))
+        .repeated()
+        .at_least(1)
+        .to(MaybeErr::None)
+}
+
 fn parse_macro(
     block_rec: BlockParser,
 ) -> impl Parser<'static, File, MaybeErr<(WithSpan<ArcIntern<str>>, WithSpan<Macro>)>, Extra> {
@@ -842,6 +1240,67 @@ fn import() -> impl Parser<'static, File, MaybeErr<Span>, Extra> {
     .map(|(_, (), span)| span)
 }
 
+fn include_alg() -> impl Parser<'static, File, MaybeErr<Statement>, Extra> {
+    group((
+        just(".include-alg"),
+        req_whitespace(),
+        ident(),
+        req_whitespace(),
+        quoted_ident(),
+        req_whitespace(),
+        just("index"),
+        req_whitespace(),
+        intu_word("algorithm index").map_with(|v, data| v.map(|v| data.span().with(v))),
+    ))
+    .map_with(|(_, (), name, (), filename, (), _, (), index), data| {
+        index.map(|index| Statement::IncludeAlg {
+            name,
+            filename: filename.span().clone(),
+            index,
+            span: data.span(),
+        })
+    })
+}
+
+/// The header marking an algorithm file as the compressed table format of
+/// [`qter_core::table_encoding`]; the compressed stream follows as hex digits
+/// so that the file stays valid UTF-8 for `find_import`
+const ALG_TABLE_MAGIC: &str = "%qat-alg-table\n";
+
+/// Reads the algorithms out of an `.include-alg` source: the compressed table
+/// format if the file starts with [`ALG_TABLE_MAGIC`], otherwise plain text
+/// with one whitespace-separated algorithm per line
+///
+/// Returns `None` if the file claims to be a compressed table but cannot be
+/// decoded
+fn decode_alg_file(contents: &str) -> Option<Vec<Vec<ArcIntern<str>>>> {
+    let Some(hex) = contents.strip_prefix(ALG_TABLE_MAGIC) else {
+        return Some(
+            contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.split_whitespace().map(ArcIntern::from).collect())
+                .collect(),
+        );
+    };
+
+    let digits = hex
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| c.to_digit(16))
+        .collect::<Option<Vec<_>>>()?;
+
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+
+    let mut bytes = digits
+        .chunks_exact(2)
+        .map(|pair| u8::try_from(pair[0] * 16 + pair[1]).unwrap());
+
+    decode_table(&mut bytes)
+}
+
 fn block(block_rec: BlockParser) -> impl Parser<'static, File, MaybeErr<Block>, Extra> + Clone {
     Rc::new(
         instruction(block_rec)
@@ -931,7 +1390,7 @@ fn merge_files(
 mod tests {
     use chumsky::Parser;
     use internment::ArcIntern;
-    use qter_core::File;
+    use qter_core::{File, Int, U, table_encoding::encode_table};
 
     use super::{ident, number, parse, registers};
 
@@ -977,6 +1436,9 @@ mod tests {
                 )
                 f ← theoretical 90
                 g, h ← 3x3 (U, D)
+                i ← 3x3 (U)
+                same-puzzle j ← 3x3 (D)
+                new-puzzle k ← 3x3 (R)
             }
         ";
 
@@ -989,6 +1451,302 @@ mod tests {
         assert!(errs.is_empty());
     }
 
+    #[test]
+    fn test_same_puzzle_merges_declarations() {
+        let code = "
+            .registers {
+                a ← 3x3 (U)
+                same-puzzle b ← 3x3 (D)
+                new-puzzle c ← 3x3 (U)
+            }
+        ";
+
+        let decl = registers()
+            .parse(File::from(code))
+            .into_result()
+            .unwrap()
+            .option()
+            .unwrap();
+
+        assert_eq!(decl.puzzles.len(), 2);
+
+        let crate::Puzzle::Real { architectures } = &decl.puzzles[0] else {
+            panic!("Expected a real puzzle");
+        };
+        let [(names, architecture)] = &architectures[..] else {
+            panic!("Expected the merged declarations to share one architecture");
+        };
+
+        assert_eq!(names.len(), 2);
+        assert_eq!(&**names[0], "a");
+        assert_eq!(&**names[1], "b");
+
+        // U and D touch disjoint facelets, so merging onto the same cube
+        // must not disturb either register's order
+        assert!(
+            architecture
+                .registers()
+                .iter()
+                .all(|register| register.order() == Int::<U>::from(4_u64))
+        );
+    }
+
+    #[test]
+    fn test_same_puzzle_over_capacity_is_an_error() {
+        let code = "
+            .registers {
+                A, B ← 3x3 builtin (90, 90)
+                same-puzzle C, D ← 3x3 builtin (90, 90)
+            }
+        ";
+
+        let errs = registers().parse(File::from(code)).into_errors();
+
+        assert!(
+            errs.iter()
+                .any(|err| err.to_string().contains("exceed its capacity")),
+            "{errs:?}"
+        );
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        let code = "
+            add 1 $
+
+            goto 3 : oops
+
+            .macro broken {
+                ( lmao $a:reg => add 1 $a
+            }
+
+            add 2 a
+        ";
+
+        let errs = parse(&File::from(code), |_| unreachable!(), false).unwrap_err();
+
+        for err in &errs {
+            println!(
+                "{err}; {:?}; `{}`",
+                err.span().line_and_col(),
+                err.span().slice()
+            );
+        }
+
+        assert_eq!(errs.len(), 3);
+    }
+
+    #[test]
+    fn test_int_error_spans() {
+        let code = "
+            .registers {
+                A, B ← 3x3 builtin (90, 9a)
+            }
+        ";
+
+        let errs = crate::compile(&File::from(code), |_| unreachable!(), false).unwrap_err();
+
+        assert_eq!(errs.len(), 1, "{errs:?}");
+        assert_eq!(errs[0].span().slice(), "9a");
+
+        let code = "
+            .registers {
+                A, B ← 3x3 builtin (90, 90)
+            }
+
+            add A%x 1
+        ";
+
+        let errs = crate::compile(&File::from(code), |_| unreachable!(), false).unwrap_err();
+
+        assert_eq!(errs.len(), 1, "{errs:?}");
+        assert_eq!(errs[0].span().slice(), "x");
+    }
+
+    #[test]
+    fn test_nonexistent_preset_is_underlined() {
+        let code = "
+            .registers {
+                A, B ← 3x3 builtin (13, 13)
+            }
+        ";
+
+        let errs = crate::compile(&File::from(code), |_| unreachable!(), false).unwrap_err();
+
+        let preset_err = errs
+            .iter()
+            .find(|err| err.to_string().contains("preset architecture"))
+            .expect("the preset failure should be reported");
+
+        // The diagnostic underlines the orders instead of floating free
+        assert_eq!(preset_err.span().slice(), "(13, 13)");
+        // And the note stack names the presets that do exist
+        assert!(
+            preset_err.to_string().contains("the available presets are:"),
+            "{preset_err}"
+        );
+    }
+
+    #[test]
+    fn import_errors_preserve_the_callback_message() {
+        let code = "
+            .import missing.qat
+
+            .registers {
+                A ← theoretical 10
+            }
+
+            halt \"Done\"
+        ";
+
+        let errs = crate::compile(
+            &File::from(code),
+            |_| Err(crate::ImportError::Io("No such file".to_owned())),
+            false,
+        )
+        .unwrap_err();
+
+        assert!(
+            errs.iter().any(|err| err
+                .to_string()
+                .contains("Unable to find import: No such file")),
+            "{errs:?}"
+        );
+    }
+
+    /// The algorithm bound by the `.include-alg` define with the given name
+    fn included_alg(parsed: &crate::ParsedSyntax, name: &str) -> Vec<ArcIntern<str>> {
+        parsed
+            .code
+            .iter()
+            .find_map(|instr| match &instr.0 {
+                crate::Instruction::Define(define) if &**define.name == name => {
+                    match &define.value {
+                        crate::DefineValue::Value(value) => match &**value {
+                            crate::Value::Alg(alg) => Some(alg.clone()),
+                            _ => None,
+                        },
+                        crate::DefineValue::LuaCall(_) => None,
+                    }
+                }
+                _ => None,
+            })
+            .expect("the directive should bind a define")
+    }
+
+    #[test]
+    fn include_alg_binds_a_define_from_a_plain_file() {
+        let code = "
+            .registers {
+                a, b ← 3x3 builtin (90, 90)
+            }
+
+            .include-alg sexy \"sexy.algs\" index 1
+        ";
+
+        let parsed = parse(
+            &File::from(code),
+            |name| {
+                assert_eq!(name, "sexy.algs");
+                Ok(ArcIntern::from("U D\n\nR U R' U'\n"))
+            },
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            included_alg(&parsed, "sexy"),
+            ["R", "U", "R'", "U'"].map(ArcIntern::from)
+        );
+    }
+
+    #[test]
+    fn include_alg_reads_the_compressed_table_format() {
+        let algs = ["U D", "R U R' U'"]
+            .map(|alg| alg.split(' ').map(ArcIntern::from).collect::<Vec<_>>());
+
+        let (encoded, _) = encode_table(&algs).unwrap();
+
+        let mut contents = super::ALG_TABLE_MAGIC.to_owned();
+        for byte in encoded {
+            contents.push_str(&format!("{byte:02x}"));
+        }
+
+        let code = "
+            .registers {
+                a, b ← 3x3 builtin (90, 90)
+            }
+
+            .include-alg sexy \"sexy.qtable\" index 1
+        ";
+
+        let contents = ArcIntern::<str>::from(contents);
+        let parsed = parse(
+            &File::from(code),
+            move |name| {
+                assert_eq!(name, "sexy.qtable");
+                Ok(ArcIntern::clone(&contents))
+            },
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(included_alg(&parsed, "sexy"), algs[1]);
+    }
+
+    #[test]
+    fn include_alg_errors_are_spanned() {
+        let registers = "
+            .registers {
+                a, b ← 3x3 builtin (90, 90)
+            }
+        ";
+
+        let code = format!("{registers}\n.include-alg oops \"missing.algs\" index 0");
+        let errs = parse(
+            &File::from(code.as_str()),
+            |_| Err(crate::ImportError::Io("No such file".to_owned())),
+            false,
+        )
+        .unwrap_err();
+        assert!(
+            errs.iter().any(|err| err
+                .to_string()
+                .contains("Unable to find the algorithm file: No such file")),
+            "{errs:?}"
+        );
+
+        let code = format!("{registers}\n.include-alg oops \"short.algs\" index 2");
+        let errs = parse(
+            &File::from(code.as_str()),
+            |_| Ok(ArcIntern::from("U D\nR U")),
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(errs.len(), 1, "{errs:?}");
+        assert_eq!(errs[0].span().slice(), "2");
+        assert!(
+            errs[0].to_string().contains("the file contains 2 algorithms"),
+            "{}",
+            errs[0]
+        );
+
+        let code = format!("{registers}\n.include-alg oops \"bad.algs\" index 0");
+        let errs = parse(
+            &File::from(code.as_str()),
+            |_| Ok(ArcIntern::from("Q U")),
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(errs.len(), 1, "{errs:?}");
+        assert_eq!(errs[0].span().slice(), "bad.algs");
+        assert!(
+            errs[0].to_string().contains("the move `Q`"),
+            "{}",
+            errs[0]
+        );
+    }
+
     #[test]
     fn bruh() {
         let code = "
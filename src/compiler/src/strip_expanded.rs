@@ -4,9 +4,9 @@ use chumsky::error::Rich;
 use internment::ArcIntern;
 use itertools::{Either, Itertools};
 use qter_core::{
-    ByPuzzleType, Facelets, Halt, Input, Instruction, Int, Print, Program, PuzzleIdx,
-    RegisterGenerator, RepeatUntil, SeparatesByPuzzleType, Span, StateIdx, TheoreticalIdx, U,
-    WithSpan,
+    Assert, ByPuzzleType, Facelets, Halt, Input, Instruction, Int, Print, Program, PuzzleIdx,
+    Random, RegisterGenerator, RegisterInfo, RepeatUntil, SeparatesByPuzzleType, Span, StateIdx,
+    TheoreticalIdx, U, WithSpan,
     architectures::{Algorithm, Architecture, CycleGeneratorSubcycle, PermutationGroup},
 };
 
@@ -18,7 +18,7 @@ use crate::{
 pub(super) struct RegisterIdx;
 
 impl SeparatesByPuzzleType for RegisterIdx {
-    type Theoretical<'s> = ();
+    type Theoretical<'s> = Option<Int<U>>;
 
     type Puzzle<'s> = (usize, Arc<Architecture>, Option<Int<U>>);
 }
@@ -27,6 +27,7 @@ pub struct GlobalRegs {
     register_table: HashMap<ArcIntern<str>, ByPuzzleType<'static, (StateIdx, RegisterIdx)>>,
     theoretical: Vec<WithSpan<Int<U>>>,
     puzzles: Vec<WithSpan<Arc<PermutationGroup>>>,
+    registers: Vec<RegisterInfo>,
 }
 
 impl GlobalRegs {
@@ -42,7 +43,7 @@ impl GlobalRegs {
 
         if let Some(mod_) = reference.modulus {
             match &mut reg {
-                ByPuzzleType::Theoretical(_) => todo!(),
+                ByPuzzleType::Theoretical((_, modulus)) => *modulus = Some(mod_),
                 ByPuzzleType::Puzzle((_, (_, _, modulus))) => *modulus = Some(mod_),
             }
         }
@@ -58,7 +59,14 @@ impl GlobalRegs {
         let reg_info = self.get_reg(register);
 
         match reg_info {
-            ByPuzzleType::Theoretical((theoretical, ())) => {
+            ByPuzzleType::Theoretical((theoretical, modulus)) => {
+                if modulus.is_some() {
+                    return Err(Rich::custom(
+                        register.reg_name.span().clone(),
+                        "Only `solved-goto` supports a modulus on a theoretical register",
+                    ));
+                }
+
                 Ok(ByPuzzleType::Theoretical((theoretical, ())))
             }
             ByPuzzleType::Puzzle((puzzle_idx, (idx, arch, modulus))) => Ok(ByPuzzleType::Puzzle((
@@ -78,8 +86,28 @@ impl GlobalRegs {
         let reg_info = self.get_reg(register);
 
         match reg_info {
-            ByPuzzleType::Theoretical((theoretical_idx, ())) => {
-                Ok(ByPuzzleType::Theoretical(theoretical_idx))
+            ByPuzzleType::Theoretical((theoretical_idx, modulus)) => {
+                let order = *self.theoretical[theoretical_idx.0];
+
+                let modulus = match modulus {
+                    Some(modulus) => {
+                        // Adds wrap around at the order, so a remainder is
+                        // only meaningful when the modulus divides it
+                        if !(order % modulus).is_zero() {
+                            return Err(Rich::custom(
+                                register.reg_name.span().clone(),
+                                format!(
+                                    "The modulus of a theoretical register must divide its order; {modulus} does not divide {order}"
+                                ),
+                            ));
+                        }
+
+                        modulus
+                    }
+                    None => order,
+                };
+
+                Ok(ByPuzzleType::Theoretical((theoretical_idx, modulus)))
             }
             ByPuzzleType::Puzzle((puzzle_idx, (idx, arch, modulus))) => Ok(ByPuzzleType::Puzzle((
                 puzzle_idx,
@@ -124,40 +152,105 @@ fn get_facelets(
 struct FaceletsInfo;
 
 impl SeparatesByPuzzleType for FaceletsInfo {
-    type Theoretical<'s> = TheoreticalIdx;
+    type Theoretical<'s> = (TheoreticalIdx, Int<U>);
 
     type Puzzle<'s> = (PuzzleIdx, Facelets);
 }
 
-pub fn strip_expanded(expanded: ExpandedCode) -> Result<Program, Vec<Rich<'static, char, Span>>> {
+pub fn strip_expanded(
+    mut expanded: ExpandedCode,
+    strip_asserts: bool,
+    deterministic: bool,
+    warnings: &mut Vec<Rich<'static, char, Span>>,
+) -> Result<Program, Vec<Rich<'static, char, Span>>> {
+    // Drop assertions before optimization so they cannot affect the emitted
+    // code in any way
+    if strip_asserts {
+        expanded.expanded_code_components.retain(|component| {
+            let ExpandedCodeComponent::Instruction(primitive, _) = &**component else {
+                return true;
+            };
+
+            !matches!(&**primitive, Primitive::Assert { .. })
+        });
+    }
+
+    // `random` makes the program behave differently from run to run, which is
+    // exactly what a deterministic build is asked to rule out
+    if deterministic {
+        let errors = expanded
+            .expanded_code_components
+            .iter()
+            .filter_map(|component| {
+                let ExpandedCodeComponent::Instruction(primitive, _) = &**component else {
+                    return None;
+                };
+
+                matches!(&**primitive, Primitive::Random { .. }).then(|| {
+                    Rich::custom(
+                        component.span().clone(),
+                        "`random` is not allowed when compiling deterministic output",
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+    }
+
     let mut global_regs = GlobalRegs {
         register_table: HashMap::new(),
         theoretical: vec![],
         puzzles: vec![],
+        registers: vec![],
     };
 
     for puzzle in &expanded.registers.puzzles {
         match puzzle {
             Puzzle::Theoretical { name, order } => {
+                let theoretical_idx = TheoreticalIdx(global_regs.theoretical.len());
+
                 global_regs.register_table.insert(
                     ArcIntern::clone(name),
-                    ByPuzzleType::Theoretical((TheoreticalIdx(global_regs.theoretical.len()), ())),
+                    ByPuzzleType::Theoretical((theoretical_idx, ())),
                 );
 
+                global_regs.registers.push(RegisterInfo {
+                    name: ArcIntern::clone(name),
+                    location: ByPuzzleType::Theoretical((theoretical_idx, ())),
+                    order: **order,
+                });
+
                 global_regs.theoretical.push(order.to_owned());
             }
             Puzzle::Real { architectures } => {
                 // TODO: Support for architecture switching
                 // Just take the first architecture
                 let (names, architecture) = &architectures[0];
+                let puzzle_idx = PuzzleIdx(global_regs.puzzles.len());
+
                 for (i, name) in names.iter().enumerate() {
                     global_regs.register_table.insert(
                         ArcIntern::clone(name),
-                        ByPuzzleType::Puzzle((
-                            PuzzleIdx(global_regs.puzzles.len()),
-                            (i, Arc::clone(architecture), None),
-                        )),
+                        ByPuzzleType::Puzzle((puzzle_idx, (i, Arc::clone(architecture), None))),
                     );
+
+                    global_regs.registers.push(RegisterInfo {
+                        name: ArcIntern::clone(name),
+                        location: ByPuzzleType::Puzzle((
+                            puzzle_idx,
+                            (
+                                Algorithm::new_from_effect(
+                                    architecture,
+                                    vec![(i, Int::<U>::one())],
+                                ),
+                                architecture.registers()[i].signature_facelets(),
+                            ),
+                        )),
+                        order: architecture.registers()[i].order(),
+                    });
                 }
 
                 global_regs.puzzles.push(WithSpan::new(
@@ -168,6 +261,57 @@ pub fn strip_expanded(expanded: ExpandedCode) -> Result<Program, Vec<Rich<'stati
         }
     }
 
+    // Reduce every add amount modulo the order of the register it targets.
+    // Amounts of at least the order are almost always bugs in macro
+    // arithmetic, so warn about them; the spans point at the macro call site
+    // because expanded instructions keep the span of the code they came from.
+    expanded.expanded_code_components.retain_mut(|component| {
+        let span = component.span().to_owned();
+
+        let ExpandedCodeComponent::Instruction(primitive, _) = &mut **component else {
+            return true;
+        };
+
+        let Primitive::Add { amt, register } = &mut **primitive else {
+            return true;
+        };
+
+        let order = match global_regs.get_reg(register) {
+            ByPuzzleType::Theoretical((theoretical, _)) => *global_regs.theoretical[theoretical.0],
+            ByPuzzleType::Puzzle((_, (idx, arch, _))) => arch.registers()[idx].order(),
+        };
+
+        if **amt < order {
+            return true;
+        }
+
+        let reduced = **amt % order;
+
+        if reduced.is_zero() {
+            warnings.push(Rich::custom(
+                span,
+                format!(
+                    "Adding {} to a register of order {order} does nothing; the instruction will be removed",
+                    **amt
+                ),
+            ));
+
+            false
+        } else {
+            warnings.push(Rich::custom(
+                span,
+                format!(
+                    "Adding {} to a register of order {order} is equivalent to adding {reduced}",
+                    **amt
+                ),
+            ));
+
+            **amt = reduced;
+
+            true
+        }
+    });
+
     let global_regs = Arc::new(global_regs);
     let global_regs_for_iter = Arc::clone(&global_regs);
 
@@ -178,7 +322,7 @@ pub fn strip_expanded(expanded: ExpandedCode) -> Result<Program, Vec<Rich<'stati
                     Box::new(match *primitive {
                         Primitive::Add { amt, register } => {
                             match global_regs_for_iter.get_reg(&register) {
-                                ByPuzzleType::Theoretical((theoretical, ())) => {
+                                ByPuzzleType::Theoretical((theoretical, _)) => {
                                     OptimizingPrimitive::AddTheoretical { theoretical, amt }
                                 }
                                 ByPuzzleType::Puzzle((puzzle, (reg_idx, arch, modulus))) => {
@@ -190,6 +334,9 @@ pub fn strip_expanded(expanded: ExpandedCode) -> Result<Program, Vec<Rich<'stati
                                 }
                             }
                         }
+                        Primitive::Random { max, register } => {
+                            OptimizingPrimitive::Random { max, register }
+                        }
                         Primitive::Goto { label } => OptimizingPrimitive::Goto { label },
                         Primitive::SolvedGoto { label, register } => {
                             OptimizingPrimitive::SolvedGoto { label, register }
@@ -203,6 +350,15 @@ pub fn strip_expanded(expanded: ExpandedCode) -> Result<Program, Vec<Rich<'stati
                         Primitive::Print { message, register } => {
                             OptimizingPrimitive::Print { message, register }
                         }
+                        Primitive::Assert {
+                            message,
+                            expected,
+                            register,
+                        } => OptimizingPrimitive::Assert {
+                            message,
+                            expected,
+                            register,
+                        },
                     }),
                     block_id,
                 )
@@ -262,6 +418,20 @@ pub fn strip_expanded(expanded: ExpandedCode) -> Result<Program, Vec<Rich<'stati
                 OptimizingPrimitive::AddTheoretical { theoretical, amt } => {
                     Instruction::PerformAlgorithm(ByPuzzleType::Theoretical((theoretical, *amt)))
                 }
+                OptimizingPrimitive::Random { max, register } => {
+                    let random = Random {
+                        max: max.into_inner(),
+                    };
+
+                    Instruction::Random(match global_regs.generator(&register)? {
+                        ByPuzzleType::Theoretical((theoretical, ())) => {
+                            ByPuzzleType::Theoretical((random, theoretical))
+                        }
+                        ByPuzzleType::Puzzle((puzzle_idx, (generator, _))) => {
+                            ByPuzzleType::Puzzle((random, puzzle_idx, generator))
+                        }
+                    })
+                }
                 OptimizingPrimitive::Goto { label } => {
                     let Some(label) = expanded.block_info.label_scope(&label) else {
                         return Err(Rich::custom(
@@ -289,8 +459,8 @@ pub fn strip_expanded(expanded: ExpandedCode) -> Result<Program, Vec<Rich<'stati
                     };
 
                     Instruction::SolvedGoto(match facelets {
-                        ByPuzzleType::Theoretical(theoretical_idx) => {
-                            ByPuzzleType::Theoretical((solved_goto, theoretical_idx))
+                        ByPuzzleType::Theoretical((theoretical_idx, modulus)) => {
+                            ByPuzzleType::Theoretical((solved_goto, theoretical_idx, modulus))
                         }
                         ByPuzzleType::Puzzle((puzzle_idx, facelets)) => {
                             ByPuzzleType::Puzzle((solved_goto, puzzle_idx, facelets))
@@ -311,16 +481,23 @@ pub fn strip_expanded(expanded: ExpandedCode) -> Result<Program, Vec<Rich<'stati
                             facelets
                         }
                     },
-                    alg: Algorithm::new_from_effect(
-                        &arch,
-                        amts.into_iter()
-                            .map(|(idx, _, amt)| (idx, amt.into_inner()))
-                            .collect(),
-                    ),
+                    alg: {
+                        let alg = Algorithm::new_from_effect(
+                            &arch,
+                            amts.into_iter()
+                                .map(|(idx, _, amt)| (idx, amt.into_inner()))
+                                .collect(),
+                        );
+                        // `repeat-until` applies the alg in a tight loop, so
+                        // pay the materialization cost at compile time
+                        alg.materialize();
+                        alg
+                    },
                 })),
                 OptimizingPrimitive::Solve { puzzle } => Instruction::Solve(match puzzle {
                     ByPuzzleType::Theoretical(idx) => ByPuzzleType::Theoretical(idx),
-                    ByPuzzleType::Puzzle(idx) => ByPuzzleType::Puzzle(idx),
+                    // The optimizer never asks for registers to be preserved
+                    ByPuzzleType::Puzzle(idx) => ByPuzzleType::Puzzle((idx, Vec::new())),
                 }),
                 OptimizingPrimitive::Input { message, register } => {
                     let input = Input {
@@ -381,6 +558,30 @@ pub fn strip_expanded(expanded: ExpandedCode) -> Result<Program, Vec<Rich<'stati
                         None => ByPuzzleType::Puzzle((print, None)),
                     })
                 }
+                OptimizingPrimitive::Assert {
+                    message,
+                    expected,
+                    register,
+                } => {
+                    let assert = Assert {
+                        message: message.into_inner(),
+                        expected: expected.into_inner(),
+                    };
+
+                    Instruction::Assert(match global_regs.generator(&register)? {
+                        ByPuzzleType::Theoretical((theoretical, ())) => {
+                            ByPuzzleType::Theoretical((assert, theoretical))
+                        }
+                        ByPuzzleType::Puzzle((puzzle_idx, (generator, solved_goto_facelets))) => {
+                            ByPuzzleType::Puzzle((
+                                assert,
+                                puzzle_idx,
+                                generator,
+                                solved_goto_facelets,
+                            ))
+                        }
+                    })
+                }
             };
 
             Ok(WithSpan::new(instruction, span))
@@ -399,6 +600,184 @@ pub fn strip_expanded(expanded: ExpandedCode) -> Result<Program, Vec<Rich<'stati
     Ok(Program {
         theoretical: global_regs.theoretical,
         puzzles: global_regs.puzzles,
+        registers: global_regs.registers,
         instructions,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use qter_core::{ByPuzzleType, File, Instruction, Int, Program, PuzzleIdx, U};
+
+    fn compile_with_warnings(code: &str) -> (Program, Vec<String>) {
+        let mut warnings = vec![];
+
+        let program = match crate::compile_with_warnings(
+            &File::from(code),
+            |_| unreachable!(),
+            false,
+            false,
+            &mut warnings,
+        ) {
+            Ok(v) => v,
+            Err(e) => panic!("{e:?}"),
+        };
+
+        (program, warnings.iter().map(ToString::to_string).collect())
+    }
+
+    #[test]
+    fn add_of_whole_order_is_removed() {
+        let (program, warnings) = compile_with_warnings(
+            "
+            .registers {
+                A ← theoretical 90
+            }
+
+            add A 90
+            halt \"Done\"
+            ",
+        );
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("does nothing"), "{}", warnings[0]);
+        assert_eq!(program.instructions.len(), 1);
+    }
+
+    #[test]
+    fn add_of_more_than_order_is_reduced() {
+        let (program, warnings) = compile_with_warnings(
+            "
+            .registers {
+                A ← theoretical 90
+            }
+
+            add A 91
+            halt \"Done\"
+            ",
+        );
+
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0].contains("equivalent to adding 1"),
+            "{}",
+            warnings[0]
+        );
+        assert_eq!(program.instructions.len(), 2);
+
+        assert!(matches!(
+            &*program.instructions[0],
+            Instruction::PerformAlgorithm(ByPuzzleType::Theoretical((_, amt))) if *amt == Int::<U>::one()
+        ));
+    }
+
+    #[test]
+    fn loop_body_adds_coalesce_to_one_algorithm_per_puzzle() {
+        use std::sync::Arc;
+
+        use interpreter::{Interpreter, PausedState, puzzle_states::SimulatedPuzzle};
+
+        let (program, warnings) = compile_with_warnings(
+            "
+            .registers {
+                A, B ← 3x3 builtin (24, 210)
+                C ← theoretical 7
+            }
+
+            add A 20
+            add C 5
+            counter:
+            solved-goto A done
+            add A 23
+            add B 2
+            add C 3
+            goto counter
+            done:
+            print \"C=\" C
+            halt \"B=\" B
+            ",
+        );
+
+        assert!(warnings.is_empty(), "{warnings:?}");
+
+        // The three adds in the loop body merge into one algorithm per
+        // puzzle, as do the two adds before the loop
+        assert_eq!(program.instructions.len(), 8);
+        assert_eq!(
+            program
+                .instructions
+                .iter()
+                .filter(|instr| matches!(&***instr, Instruction::PerformAlgorithm(_)))
+                .count(),
+            4
+        );
+
+        let mut interpreter = Interpreter::<SimulatedPuzzle>::new(Arc::new(program), ());
+
+        assert!(matches!(
+            interpreter.step_until_halt(),
+            PausedState::Halt { .. }
+        ));
+
+        // A counts down from 20, so the loop body runs twenty times
+        let messages = interpreter.state_mut().messages();
+        assert_eq!(messages[messages.len() - 2], "C= 2");
+        assert_eq!(messages[messages.len() - 1], "B= 40");
+    }
+
+    #[test]
+    fn register_info_is_exposed_by_name() {
+        let (program, warnings) = compile_with_warnings(
+            "
+            .registers {
+                B, A ← 3x3 builtin (24, 210)
+            }
+
+            halt \"Done\"
+            ",
+        );
+
+        assert!(warnings.is_empty());
+
+        let a = program.register_by_name("A").unwrap();
+        assert_eq!(a.order, Int::<U>::from(210_u32));
+        assert!(matches!(
+            a.location,
+            ByPuzzleType::Puzzle((PuzzleIdx(0), _))
+        ));
+
+        let b = program.register_by_name("B").unwrap();
+        assert_eq!(b.order, Int::<U>::from(24_u32));
+        assert!(matches!(
+            b.location,
+            ByPuzzleType::Puzzle((PuzzleIdx(0), _))
+        ));
+
+        assert!(program.register_by_name("C").is_none());
+        assert_eq!(program.registers_for_puzzle(PuzzleIdx(0)).count(), 2);
+    }
+
+    #[test]
+    fn deterministic_compilation_rejects_random() {
+        let code = "
+            .registers {
+                A ← theoretical 90
+            }
+
+            random A 89
+            halt \"Done\"
+            ";
+
+        assert!(crate::compile(&File::from(code), |_| unreachable!(), false).is_ok());
+
+        let errors = crate::compile_deterministic(&File::from(code), |_| unreachable!(), false)
+            .expect_err("`random` must be rejected in deterministic mode");
+
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0].to_string().contains("deterministic"),
+            "{}",
+            errors[0]
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! End-to-end golden tests over every checked-in QAT program in this
+//! directory. Each `<name>.qat` is compiled and interpreted with the inputs
+//! listed in `<name>.inputs` (one integer per line; the file may be absent if
+//! the program takes no input), and the message transcript is compared
+//! against `<name>.golden`. Set `QTER_REGENERATE_GOLDENS=1` to rewrite the
+//! golden files instead of comparing against them.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use compiler::{ImportError, compile};
+use interpreter::{Interpreter, PausedState, puzzle_states::SimulatedPuzzle};
+use qter_core::{File, I, Int};
+
+fn run_transcript(path: &Path) -> Vec<String> {
+    let qat = File::from(fs::read_to_string(path).unwrap());
+
+    let program = match compile(
+        &qat,
+        |_| {
+            Err(ImportError::Unsupported(
+                "Imports are not supported in golden tests".to_owned(),
+            ))
+        },
+        false,
+    ) {
+        Ok(v) => v,
+        Err(e) => panic!("Could not compile {}: {e:?}", path.display()),
+    };
+
+    let inputs_path = path.with_extension("inputs");
+    let mut inputs = if inputs_path.exists() {
+        fs::read_to_string(&inputs_path)
+            .unwrap()
+            .lines()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse::<Int<I>>().unwrap())
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    }
+    .into_iter();
+
+    let mut interpreter = Interpreter::<SimulatedPuzzle>::new(Arc::new(program), ());
+
+    loop {
+        match interpreter.step_until_halt() {
+            PausedState::Input {
+                max_input: _,
+                data: _,
+            } => {}
+            PausedState::Halt {
+                maybe_puzzle_idx_and_register: _,
+            } => break,
+            PausedState::Panicked => panic!("{} panicked", path.display()),
+        }
+
+        let input = inputs.next().unwrap_or_else(|| {
+            panic!(
+                "{} requested more inputs than {} provides",
+                path.display(),
+                inputs_path.display()
+            )
+        });
+
+        interpreter.give_input(input).unwrap();
+    }
+
+    interpreter.state_mut().messages().iter().cloned().collect()
+}
+
+#[test]
+fn golden_transcripts() {
+    let tests_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
+
+    let mut qat_paths = Vec::new();
+
+    for entry in fs::read_dir(&tests_dir).unwrap() {
+        let entry = entry.unwrap().path();
+
+        if !entry.is_dir() {
+            continue;
+        }
+
+        for file in fs::read_dir(&entry).unwrap() {
+            let file = file.unwrap().path();
+
+            if file.extension().is_some_and(|v| v == "qat") {
+                qat_paths.push(file);
+            }
+        }
+    }
+
+    qat_paths.sort();
+
+    assert!(!qat_paths.is_empty());
+
+    let regenerate = std::env::var_os("QTER_REGENERATE_GOLDENS").is_some();
+
+    for path in qat_paths {
+        let transcript = run_transcript(&path).join("\n") + "\n";
+        let golden_path = path.with_extension("golden");
+
+        if regenerate {
+            fs::write(&golden_path, transcript).unwrap();
+            continue;
+        }
+
+        let golden = fs::read_to_string(&golden_path).unwrap_or_else(|_| {
+            panic!(
+                "Missing golden transcript {}; run with QTER_REGENERATE_GOLDENS=1 to create it",
+                golden_path.display()
+            )
+        });
+
+        assert_eq!(
+            transcript,
+            golden,
+            "Transcript of {} diverged from {}; run with QTER_REGENERATE_GOLDENS=1 to update it",
+            path.display(),
+            golden_path.display()
+        );
+    }
+}
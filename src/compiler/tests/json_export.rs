@@ -0,0 +1,98 @@
+//! Validates the stable JSON export of a compiled program against the
+//! structure expected for the `simple` golden program.
+
+use std::fs;
+
+use compiler::{ImportError, compile};
+use qter_core::{File, json::ProgramJson};
+use serde_json::{Value, json};
+
+#[test]
+fn simple_program_json_structure() {
+    let qat = File::from(fs::read_to_string("tests/simple/simple.qat").unwrap());
+
+    let program = compile(
+        &qat,
+        |_| {
+            Err(ImportError::Unsupported(
+                "Imports are not supported in JSON export tests".to_owned(),
+            ))
+        },
+        false,
+    )
+    .unwrap();
+
+    let value = serde_json::to_value(ProgramJson::from(&program)).unwrap();
+
+    assert_eq!(value["schema_version"], json!(1));
+
+    assert_eq!(value["theoretical_orders"], json!([]));
+    assert_eq!(value["puzzles"].as_array().unwrap().len(), 1);
+    assert_eq!(value["puzzles"][0]["facelet_count"], json!(48));
+
+    let registers = value["registers"].as_array().unwrap();
+    assert_eq!(registers.len(), 2);
+    assert_eq!(registers[0]["name"], json!("A"));
+    assert_eq!(registers[0]["order"], json!("4"));
+    assert_eq!(
+        registers[0]["state"],
+        json!({ "type": "puzzle", "index": 0 })
+    );
+    assert_eq!(registers[0]["algorithm"], json!(["U"]));
+    assert_eq!(registers[1]["name"], json!("B"));
+    assert_eq!(registers[1]["order"], json!("4"));
+    assert_eq!(registers[1]["algorithm"], json!(["D'"]));
+
+    let kinds = value["instructions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|instruction| instruction["kind"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            json!("input"),
+            json!("input"),
+            json!("repeat-until"),
+            json!("halt")
+        ]
+    );
+
+    assert_eq!(value["instructions"][0]["message"], json!("First number:"));
+    assert_eq!(value["instructions"][0]["algorithm"], json!(["U"]));
+    assert_eq!(
+        value["instructions"][0]["state"],
+        json!({ "type": "puzzle", "index": 0 })
+    );
+
+    // The round trip back through the schema must be lossless
+    let round_tripped: ProgramJson = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&round_tripped).unwrap(), value);
+}
+
+#[test]
+fn instructions_reference_valid_indices() {
+    let qat = File::from(fs::read_to_string("tests/simple/simple.qat").unwrap());
+
+    let program = compile(
+        &qat,
+        |_| {
+            Err(ImportError::Unsupported(
+                "Imports are not supported in JSON export tests".to_owned(),
+            ))
+        },
+        false,
+    )
+    .unwrap();
+
+    let value = serde_json::to_value(ProgramJson::from(&program)).unwrap();
+    let instructions = value["instructions"].as_array().unwrap();
+
+    for instruction in instructions {
+        if let Value::Number(idx) = &instruction["instruction_idx"] {
+            let idx = usize::try_from(idx.as_u64().unwrap()).unwrap();
+            assert!(idx <= instructions.len());
+        }
+    }
+}
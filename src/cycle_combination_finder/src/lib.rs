@@ -41,10 +41,11 @@ impl fmt::Debug for PossibleOrder {
     }
 }
 
-struct Partition {
-    name: String,
-    partition: Vec<u16>,
-    order: Int<U>,
+/// The cycle lengths a register uses within one orbit
+pub struct Partition {
+    pub name: String,
+    pub partition: Vec<u16>,
+    pub order: Int<U>,
 }
 
 impl fmt::Debug for Partition {
@@ -53,9 +54,10 @@ impl fmt::Debug for Partition {
     }
 }
 
-struct Cycle {
-    order: Int<U>,
-    partitions: Vec<Partition>,
+/// One register's cycle type, with a partition per orbit of the puzzle
+pub struct Cycle {
+    pub order: Int<U>,
+    pub partitions: Vec<Partition>,
 }
 
 impl fmt::Debug for Cycle {
@@ -65,22 +67,59 @@ impl fmt::Debug for Cycle {
     }
 }
 
-struct CycleCombination {
-    used_cubie_counts: Vec<u16>,
-    order_product: Int<U>,
-    cycles: Vec<Cycle>,
-    shared_pieces: Vec<u16>,
+/// A combination of register cycle types proposed by phase1
+pub struct CycleCombination {
+    pub used_cubie_counts: Vec<u16>,
+    pub order_product: Int<U>,
+    pub cycles: Vec<Cycle>,
+    pub shared_pieces: Vec<u16>,
 }
 
+impl CycleCombination {
+    /// The order of the combined puzzle state: the LCM of every partition
+    /// order across all cycles
+    ///
+    /// `order_product` counts the value combinations the registers can
+    /// encode; because the registers share the puzzle, the state itself
+    /// cycles after `combined_order` repetitions. Equality of the two means
+    /// the registers are truly independent.
+    pub fn combined_order(&self) -> Int<U> {
+        qter_core::discrete_math::lcm_iter(
+            self.cycles
+                .iter()
+                .flat_map(|cycle| cycle.partitions.iter().map(|partition| partition.order)),
+        )
+    }
+}
+
+/// The piece-count arithmetic exceeded the range of `u16`
+///
+/// Large puzzles can propose prime powers whose piece counts don't fit in the
+/// phase1 bookkeeping; erroring beats silently wrapping around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PieceCountOverflow;
+
+impl fmt::Display for PieceCountOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("A piece count overflowed the range of u16")
+    }
+}
+
+impl std::error::Error for PieceCountOverflow {}
+
 /// return a 2D list of prime powers below n. The first index is the prime, the second is the power of that prime
-fn prime_powers_below_n(n: u16, orientable_pieces: &[u16]) -> Vec<Vec<PrimePower>> {
+fn prime_powers_below_n(
+    n: u16,
+    orientable_pieces: &[u16],
+) -> Result<Vec<Vec<PrimePower>>, PieceCountOverflow> {
     let mut primes: Vec<u16> = vec![2];
 
     // find all primes below n
     for possible_prime in (3..=n).step_by(2) {
         let mut is_prime = true;
         for p in &primes {
-            if p * p > possible_prime {
+            // a square that overflows is certainly above possible_prime
+            if p.checked_mul(*p).is_none_or(|square| square > possible_prime) {
                 break;
             }
 
@@ -116,7 +155,7 @@ fn prime_powers_below_n(n: u16, orientable_pieces: &[u16]) -> Vec<Vec<PrimePower
                     min_pieces: 0, // the minimum pieces for this prime is 0 since we can use a cycle of different prime length and orient
                 },
             ]);
-            piece_check = prime;
+            piece_check = Some(prime);
         } else {
             prime_powers.push(vec![
                 PrimePower {
@@ -128,26 +167,35 @@ fn prime_powers_below_n(n: u16, orientable_pieces: &[u16]) -> Vec<Vec<PrimePower
                     min_pieces: prime,
                 },
             ]);
-            piece_check = prime.pow(2);
+            // a square that overflows certainly doesn't fit within n pieces
+            piece_check = prime.checked_pow(2);
         }
 
-        // increase powers of this prime until one doesn't fit
-        while piece_check <= n {
+        // increase powers of this prime until one doesn't fit or overflows
+        while let Some(pieces) = piece_check
+            && pieces <= n
+        {
             prime_powers[p].push(PrimePower {
-                value: orient_multiplier * piece_check,
-                min_pieces: piece_check,
+                // the order value is real data, so overflowing it is an error
+                // rather than a power that doesn't fit
+                value: orient_multiplier
+                    .checked_mul(pieces)
+                    .ok_or(PieceCountOverflow)?,
+                min_pieces: pieces,
             });
-            piece_check *= prime;
+            piece_check = pieces.checked_mul(prime);
 
             // if the power exceeds the size of orientable orbit, remove the multiplier
-            if orient_multiplier > 1 && piece_check > orientable_pieces[prime as usize] {
-                piece_check *= orient_multiplier;
+            if orient_multiplier > 1
+                && piece_check.is_none_or(|pieces| pieces > orientable_pieces[prime as usize])
+            {
+                piece_check = piece_check.and_then(|pieces| pieces.checked_mul(orient_multiplier));
                 orient_multiplier = 1;
             }
         }
     }
 
-    prime_powers
+    Ok(prime_powers)
 }
 
 /// get a list of all possible orders to fit within a given number of pieces and partitions
@@ -155,9 +203,9 @@ fn possible_order_list(
     total_pieces: u16,
     partition_max: u16,
     orientable_pieces: &[u16],
-) -> Vec<PossibleOrder> {
+) -> Result<Vec<PossibleOrder>, PieceCountOverflow> {
     // get list of prime powers that fit within the largest partition
-    let prime_powers = prime_powers_below_n(partition_max, orientable_pieces);
+    let prime_powers = prime_powers_below_n(partition_max, orientable_pieces)?;
 
     let mut paths = vec![];
     // create a stack to handle recursive
@@ -173,7 +221,10 @@ fn possible_order_list(
     while let Some(s) = stack.pop() {
         // if all primes have been added or there's no room for the next prime, log this order
         if s.index == prime_powers.len()
-            || prime_powers[s.index][1].min_pieces + s.piece_count > total_pieces
+            || prime_powers[s.index][1]
+                .min_pieces
+                .checked_add(s.piece_count)
+                .is_none_or(|pieces| pieces > total_pieces)
         {
             let prime_powers = if s.product == Int::<U>::from(1_u16) {
                 vec![1]
@@ -197,13 +248,20 @@ fn possible_order_list(
         // try adding all powers of the current prime
         for p in &prime_powers[s.index] {
             // the new piece count will add min_pieces for the current power, plus two if parity needs handling
-            let new_piece_count = s.piece_count
-                + p.min_pieces
-                + if p.min_pieces > 0 && p.min_pieces % 2 == 0 {
-                    2
-                } else {
-                    0
-                }; // TODO this should not happen on 4x4
+            let parity = if p.min_pieces > 0 && p.min_pieces % 2 == 0 {
+                2
+            } else {
+                0
+            }; // TODO this should not happen on 4x4
+
+            // a piece count that overflows certainly doesn't fit on the puzzle
+            let Some(new_piece_count) = s
+                .piece_count
+                .checked_add(p.min_pieces)
+                .and_then(|pieces| pieces.checked_add(parity))
+            else {
+                continue;
+            };
 
             // if the new prime power fits on the puzzle, add to the stack
             if new_piece_count <= total_pieces {
@@ -227,7 +285,7 @@ fn possible_order_list(
 
     paths.sort_by(|a: &PossibleOrder, b: &PossibleOrder| b.order.partial_cmp(&a.order).unwrap());
 
-    paths
+    Ok(paths)
 }
 
 /// given some order, test if it will fit on the puzzle
@@ -363,8 +421,12 @@ fn possible_order_test(
             }
 
             // if there is room for the new cycle in this orbit, add it and push to stack
-            if new_cycle + parity + s.orbit_sums[o] + shared_pieces[orbit_orient as usize]
-                <= cycle_cubie_counts[o]
+            // (an orbit sum that overflows certainly doesn't have room)
+            if new_cycle
+                .checked_add(parity)
+                .and_then(|pieces| pieces.checked_add(s.orbit_sums[o]))
+                .and_then(|pieces| pieces.checked_add(shared_pieces[orbit_orient as usize]))
+                .is_some_and(|pieces| pieces <= cycle_cubie_counts[o])
             {
                 let mut combo_iteraton = ComboIteration {
                     register: s.register,
@@ -440,10 +502,10 @@ fn assignments_to_combo(
 
 /// this is the main function. it returns a 'near optimal' combination such that all registers have equivalent order
 /// it may not be the most optimal, since there are some assumptions made to help efficiency
-fn optimal_equivalent_combination(
+pub fn optimal_equivalent_combination(
     puzzle: &[KSolveSet],
     num_registers: u16,
-) -> Option<CycleCombination> {
+) -> Result<Option<CycleCombination>, PieceCountOverflow> {
     let mut cycle_cubie_counts: Vec<u16> = vec![0; puzzle.len()]; //the count of pieces in each orbit
     let mut orientable_pieces: Vec<u16> = vec![0; 4]; // the kth index stores the number of pieces in an orbit with orient_count k
     let mut total_cubies: u16 = 0;
@@ -453,9 +515,13 @@ fn optimal_equivalent_combination(
         let piece_count = orbit.piece_count().get();
         if orientation_count > 1 {
             orientable_pieces[orientation_count as usize] = piece_count - 1;
-            total_cubies += piece_count - 1;
+            total_cubies = total_cubies
+                .checked_add(piece_count - 1)
+                .ok_or(PieceCountOverflow)?;
         } else {
-            total_cubies += piece_count;
+            total_cubies = total_cubies
+                .checked_add(piece_count)
+                .ok_or(PieceCountOverflow)?;
         }
         cycle_cubie_counts[o] = piece_count
     }
@@ -472,7 +538,7 @@ fn optimal_equivalent_combination(
             .unwrap()
             .min(cubies_per_register),
         &orientable_pieces,
-    );
+    )?;
 
     // check the possible orders, descending, until one is found that fits
     for possible_order in possible_orders {
@@ -489,20 +555,34 @@ fn optimal_equivalent_combination(
                 .min(num_registers);
                 // each unorientable register will use 'value' pieces instead of 'prime_combo.piece_counts[v]' pieces
                 // so we need to account for that difference
-                unorientable_excess += (num_registers - orientable_registers)
-                    * (prime_power - possible_order.min_piece_counts[p]);
+                // (saturating: an excess too big for u16 certainly won't fit either)
+                unorientable_excess = unorientable_excess.saturating_add(
+                    (num_registers - orientable_registers)
+                        .saturating_mul(prime_power - possible_order.min_piece_counts[p]),
+                );
             } else if prime_power % 3 == 0 {
                 let orientable_registers = (orientable_pieces[3]
                     / 1.max(possible_order.min_piece_counts[p]))
                 .min(num_registers);
-                unorientable_excess += (num_registers - orientable_registers)
-                    * (prime_power - possible_order.min_piece_counts[p]);
+                unorientable_excess = unorientable_excess.saturating_add(
+                    (num_registers - orientable_registers)
+                        .saturating_mul(prime_power - possible_order.min_piece_counts[p]),
+                );
             }
         }
 
-        let available_pieces = total_cubies
-            - num_registers * (possible_order.min_piece_counts.iter().sum::<u16>())
-            + 2;
+        // the minimum pieces the order needs across every register; if that
+        // overflows or exceeds the puzzle, the order won't fit so we skip to
+        // the next
+        let Some(available_pieces) = possible_order
+            .min_piece_counts
+            .iter()
+            .try_fold(0_u16, |acc, &pieces| acc.checked_add(pieces))
+            .and_then(|pieces| pieces.checked_mul(num_registers))
+            .and_then(|needed| total_cubies.saturating_add(2).checked_sub(needed))
+        else {
+            continue;
+        };
         // if the excess exceeds the total number of cubies, the order won't fit so we skip to the next
         if unorientable_excess > available_pieces {
             continue;
@@ -517,17 +597,17 @@ fn optimal_equivalent_combination(
             available_pieces,
             &shared_pieces,
         ) {
-            return Some(assignments_to_combo(
+            return Ok(Some(assignments_to_combo(
                 &mut assignments,
                 &registers,
                 &cycle_cubie_counts,
                 puzzle,
                 &shared_pieces,
-            ));
+            )));
         }
     }
 
-    None
+    Ok(None)
 }
 
 fn add_order_to_registers(
@@ -572,9 +652,16 @@ fn add_order_to_registers(
             return;
         }
 
-        if possible_order.min_piece_counts.iter().sum::<u16>() > available_pieces
-            || possible_order.order > last_order
-        {
+        // a minimum piece count that overflows certainly doesn't fit
+        let Some(min_pieces) = possible_order
+            .min_piece_counts
+            .iter()
+            .try_fold(0_u16, |acc, &pieces| acc.checked_add(pieces))
+        else {
+            continue;
+        };
+
+        if min_pieces > available_pieces || possible_order.order > last_order {
             continue;
         }
 
@@ -607,7 +694,7 @@ fn add_order_to_registers(
                 possible_orders,
                 cycle_cubie_counts,
                 puzzle,
-                available_pieces - possible_order.min_piece_counts.iter().sum::<u16>(),
+                available_pieces - min_pieces,
                 cycle_combos,
                 shared_piece_options,
             );
@@ -616,7 +703,10 @@ fn add_order_to_registers(
 }
 
 // this is the main function. it returns all non-redundant combinations
-fn optimal_combinations(puzzle: &[KSolveSet], num_registers: u16) {
+fn optimal_combinations(
+    puzzle: &[KSolveSet],
+    num_registers: u16,
+) -> Result<(), PieceCountOverflow> {
     let mut cycle_cubie_counts: Vec<u16> = vec![0; puzzle.len()]; //the count of pieces in each orbit
     let mut orientable_pieces: Vec<u16> = vec![0; 4]; // the kth index stores the number of pieces in an orbit with orient_count k
 
@@ -630,14 +720,17 @@ fn optimal_combinations(puzzle: &[KSolveSet], num_registers: u16) {
         cycle_cubie_counts[o] = piece_count;
     }
 
-    let total_cubies: u16 = cycle_cubie_counts.iter().sum();
+    let total_cubies = cycle_cubie_counts
+        .iter()
+        .try_fold(0_u16, |acc, &count| acc.checked_add(count))
+        .ok_or(PieceCountOverflow)?;
 
     // get a list of all orders that would fit within a cubies_per_register amount of pieces
     let possible_orders: Vec<PossibleOrder> = possible_order_list(
         total_cubies,
         cycle_cubie_counts.iter().max().copied().unwrap(),
         &orientable_pieces,
-    );
+    )?;
 
     let mut cycle_combos: Vec<CycleCombination> = vec![];
     let shared_piece_options: Vec<Vec<u16>> = vec![
@@ -657,7 +750,7 @@ fn optimal_combinations(puzzle: &[KSolveSet], num_registers: u16) {
         &possible_orders,
         &cycle_cubie_counts,
         puzzle,
-        cycle_cubie_counts.iter().sum(),
+        total_cubies,
         &mut cycle_combos,
         &shared_piece_options,
     );
@@ -666,11 +759,14 @@ fn optimal_combinations(puzzle: &[KSolveSet], num_registers: u16) {
         //println!("Found Combo {:?}, {:?}", combo.cycles, combo.shared_pieces);
         println!("Found Combo {:?}", combo.cycles);
     }
+
+    Ok(())
 }
 
 fn main() {
     let puzzle = KPUZZLE_3X3.sets();
-    let cycle_combos: Option<CycleCombination> = optimal_equivalent_combination(puzzle, 3);
+    let cycle_combos: Option<CycleCombination> =
+        optimal_equivalent_combination(puzzle, 3).unwrap();
 
     println!(
         "Highest Equivalent Order: {}",
@@ -684,7 +780,7 @@ mod tests {
 
     #[test]
     fn test_prime_powers_below_n() {
-        let result = prime_powers_below_n(10, &[0, 0, 0, 0]);
+        let result = prime_powers_below_n(10, &[0, 0, 0, 0]).unwrap();
         assert_eq!(result.len(), 4);
         assert_eq!(result[0].len(), 4);
         assert_eq!(result[1].len(), 3);
@@ -692,12 +788,30 @@ mod tests {
         assert_eq!(result[3].len(), 2);
     }
 
+    #[test]
+    fn test_prime_powers_large_partition_max() {
+        // a partition_max this large used to wrap the sieve and the power
+        // enumeration around u16 instead of stopping
+        let result = prime_powers_below_n(u16::MAX, &[0, 0, 0, 0]).unwrap();
+
+        for powers in &result {
+            // no wraparound ever produces an out-of-order value
+            for window in powers.windows(2) {
+                assert!(window[0].value < window[1].value);
+            }
+        }
+
+        // an order value that can't be represented errors instead of wrapping
+        assert!(prime_powers_below_n(u16::MAX, &[0, 0, u16::MAX, 0]).is_err());
+    }
+
     // ... tests for each of your complicated math functions
 
     #[test]
     fn test_highest_equiv_order_3_registers_3x3() {
         let puzzle = puzzle_geometry::ksolve::KPUZZLE_3X3.sets();
-        let cycle_combos: Option<CycleCombination> = optimal_equivalent_combination(puzzle, 3);
+        let cycle_combos: Option<CycleCombination> =
+            optimal_equivalent_combination(puzzle, 3).unwrap();
         assert_eq!(
             cycle_combos.unwrap().cycles[0].order,
             Int::<U>::from(30_u16),
@@ -707,22 +821,43 @@ mod tests {
     #[test]
     fn test_highest_equiv_order_2_registers_3x3() {
         let puzzle = puzzle_geometry::ksolve::KPUZZLE_3X3.sets();
-        let cycle_combos: Option<CycleCombination> = optimal_equivalent_combination(puzzle, 2);
+        let cycle_combos: Option<CycleCombination> =
+            optimal_equivalent_combination(puzzle, 2).unwrap();
         assert_eq!(
             cycle_combos.unwrap().cycles[0].order,
             Int::<U>::from(90_u16),
         );
     }
 
+    #[test]
+    fn test_combined_order_3_registers_3x3() {
+        let puzzle = puzzle_geometry::ksolve::KPUZZLE_3X3.sets();
+        let combo = optimal_equivalent_combination(puzzle, 3)
+            .unwrap()
+            .unwrap();
+
+        let combined = combo.combined_order();
+
+        // every register's order divides the order of the combined state
+        for cycle in &combo.cycles {
+            assert!((combined % cycle.order).is_zero());
+        }
+
+        // the three registers share prime factors, so the combined state
+        // cycles long before every value combination has been seen
+        assert!(combined < combo.order_product);
+        assert!((combo.order_product % combined).is_zero());
+    }
+
     #[test]
     fn test_optimal_order_3_registers_3x3() {
         let puzzle = puzzle_geometry::ksolve::KPUZZLE_3X3.sets();
-        optimal_combinations(puzzle, 3);
+        optimal_combinations(puzzle, 3).unwrap();
     }
 
     #[test]
     fn test_optimal_order_2_registers_5X5() {
         let puzzle = puzzle_geometry::ksolve::KPUZZLE_5X5.sets();
-        optimal_combinations(puzzle, 2);
+        optimal_combinations(puzzle, 2).unwrap();
     }
 }
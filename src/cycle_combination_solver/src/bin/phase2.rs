@@ -0,0 +1,77 @@
+#![warn(clippy::pedantic)]
+
+//! The research entry point of the phase2 cycle structure solver. For now it
+//! only exposes `--god-number`, the breadth-first enumeration of the small
+//! generated puzzles we study.
+
+use cycle_combination_solver::{
+    god_number::god_number_search,
+    make_guard,
+    puzzle::{PuzzleDef, slice_puzzle::HeapPuzzle},
+};
+use puzzle_geometry::ksolve::{KPUZZLE_2X2_FIXED_CORNER, KPUZZLE_PYRAMINX_NO_TIPS, KSolve};
+use std::process::ExitCode;
+
+const DEFAULT_MEMORY_CAP_BYTES: usize = 4 << 30;
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let mut maybe_god_number_puzzle = None;
+    let mut memory_cap_bytes = DEFAULT_MEMORY_CAP_BYTES;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--god-number" => match args.next() {
+                Some(puzzle) => maybe_god_number_puzzle = Some(puzzle),
+                None => return usage(),
+            },
+            "--memory-cap-bytes" => match args.next().and_then(|bytes| bytes.parse().ok()) {
+                Some(bytes) => memory_cap_bytes = bytes,
+                None => return usage(),
+            },
+            _ => return usage(),
+        }
+    }
+
+    let Some(god_number_puzzle) = maybe_god_number_puzzle else {
+        return usage();
+    };
+    let ksolve = match god_number_puzzle.as_str() {
+        "pyraminx" => &*KPUZZLE_PYRAMINX_NO_TIPS,
+        "2x2" => &*KPUZZLE_2X2_FIXED_CORNER,
+        _ => {
+            eprintln!("Unknown puzzle `{god_number_puzzle}`; expected `pyraminx` or `2x2`");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    god_number(ksolve, memory_cap_bytes)
+}
+
+fn usage() -> ExitCode {
+    eprintln!("Usage: phase2 --god-number <pyraminx|2x2> [--memory-cap-bytes <bytes>]");
+    ExitCode::FAILURE
+}
+
+fn god_number(ksolve: &KSolve, memory_cap_bytes: usize) -> ExitCode {
+    make_guard!(guard);
+    let puzzle_def = PuzzleDef::<HeapPuzzle>::new(ksolve, guard).unwrap();
+    match god_number_search(&puzzle_def, memory_cap_bytes) {
+        Ok(report) => {
+            println!("Depth distribution of {}:", puzzle_def.name());
+            for (depth, count) in report.states_per_depth.iter().enumerate() {
+                println!("{depth:>4} {count}");
+            }
+            println!(
+                "{} states, God's number {}",
+                report.state_count(),
+                report.god_number()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! A research utility that exhaustively enumerates the state space of small
+//! puzzles by breadth-first search, reporting how many states lie at every
+//! depth and therefore the puzzle's God's number under the execution turn
+//! metric.
+
+use crate::{
+    FACT_UNTIL_19,
+    puzzle::{OrbitDef, OrbitIdentifier, PuzzleDef, PuzzleState},
+    start, success, working,
+};
+use fxhash::FxHashSet;
+use log::{debug, info};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum GodNumberSearchError {
+    #[error("The visited set outgrew the memory cap of {max_size_bytes} bytes")]
+    MemoryCapExceeded { max_size_bytes: usize },
+}
+
+/// The result of a completed breadth-first enumeration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GodNumberReport {
+    /// How many states are first reached at every depth. Index zero is the
+    /// solved state
+    pub states_per_depth: Vec<u64>,
+}
+
+impl GodNumberReport {
+    /// The total number of states reachable from the solved state
+    #[must_use]
+    pub fn state_count(&self) -> u64 {
+        self.states_per_depth.iter().sum()
+    }
+
+    /// The maximum depth of any state, i.e. the puzzle's God's number under
+    /// the execution turn metric
+    #[must_use]
+    pub fn god_number(&self) -> usize {
+        self.states_per_depth.len() - 1
+    }
+}
+
+/// The hash set backing the search. Composed `exact_hasher_orbit` values are
+/// preferred because they are perfect and small; the raw orbit bytes of the
+/// full state are a fallback for puzzles too big for that to be sound.
+enum VisitedSet {
+    Exact64(FxHashSet<u64>),
+    Exact128(FxHashSet<u128>),
+    FullState(FxHashSet<Box<[u8]>>),
+}
+
+impl VisitedSet {
+    /// Insert `state`, returning whether it was not visited before
+    fn insert<'id, P: PuzzleState<'id>>(
+        &mut self,
+        state: &P,
+        orbit_identifiers: &[P::OrbitIdentifier],
+        orbit_state_counts: &[u128],
+    ) -> bool {
+        match self {
+            VisitedSet::Exact64(visited) => visited.insert(
+                u64::try_from(exact_state_key(state, orbit_identifiers, orbit_state_counts))
+                    .unwrap(),
+            ),
+            VisitedSet::Exact128(visited) => {
+                visited.insert(exact_state_key(state, orbit_identifiers, orbit_state_counts))
+            }
+            VisitedSet::FullState(visited) => {
+                let mut state_bytes = vec![];
+                for &orbit_identifier in orbit_identifiers {
+                    let (perm_bytes, ori_bytes) = state.orbit_bytes(orbit_identifier);
+                    state_bytes.extend_from_slice(perm_bytes.as_ref());
+                    state_bytes.extend_from_slice(ori_bytes.as_ref());
+                }
+                visited.insert(state_bytes.into_boxed_slice())
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            VisitedSet::Exact64(visited) => visited.len(),
+            VisitedSet::Exact128(visited) => visited.len(),
+            VisitedSet::FullState(visited) => visited.len(),
+        }
+    }
+}
+
+/// Compose the bijective per-orbit hashes of `state` into a single mixed
+/// radix integer, where orbit `i`'s digit has radix `orbit_state_counts[i]`
+fn exact_state_key<'id, P: PuzzleState<'id>>(
+    state: &P,
+    orbit_identifiers: &[P::OrbitIdentifier],
+    orbit_state_counts: &[u128],
+) -> u128 {
+    orbit_identifiers.iter().zip(orbit_state_counts).fold(
+        0,
+        |key, (&orbit_identifier, &orbit_state_count)| {
+            key * orbit_state_count + u128::from(state.exact_hasher_orbit(orbit_identifier))
+        },
+    )
+}
+
+/// The number of states a single orbit of `orbit_def` can be in, or `None`
+/// if the count overflows
+fn orbit_state_count(orbit_def: OrbitDef) -> Option<u128> {
+    let permutation_count = *FACT_UNTIL_19.get(usize::from(orbit_def.piece_count.get()))?;
+    u128::from(orbit_def.orientation_count.get())
+        .checked_pow(u32::from(orbit_def.piece_count.get()) - 1)?
+        .checked_mul(u128::from(permutation_count))
+}
+
+/// Exhaustively enumerate the states reachable from the solved state of
+/// `puzzle_def` by breadth-first search, reporting how many states are first
+/// reached at every depth and consequently the puzzle's God's number under
+/// the execution turn metric.
+///
+/// States are deduplicated by composing `exact_hasher_orbit` across orbits
+/// whenever the puzzle's total state count fits in a `u64` or a `u128`, and
+/// by hashing the raw orbit bytes of the full state otherwise.
+///
+/// # Errors
+///
+/// If the keys of the visited set outgrow `max_size_bytes`. The cap does not
+/// account for hash set overhead or the search frontiers.
+pub fn god_number_search<'id, P: PuzzleState<'id>>(
+    puzzle_def: &PuzzleDef<'id, P>,
+    max_size_bytes: usize,
+) -> Result<GodNumberReport, GodNumberSearchError> {
+    let sorted_orbit_defs = puzzle_def.sorted_orbit_defs_ref();
+
+    let mut orbit_identifiers: Vec<P::OrbitIdentifier> =
+        Vec::with_capacity(sorted_orbit_defs.inner.len());
+    for branded_orbit_def in sorted_orbit_defs.branded_copied_iter() {
+        let orbit_identifier = match orbit_identifiers.last() {
+            None => P::OrbitIdentifier::first_orbit_identifier(branded_orbit_def),
+            Some(&prev_orbit_identifier) => {
+                prev_orbit_identifier.next_orbit_identifier(branded_orbit_def)
+            }
+        };
+        orbit_identifiers.push(orbit_identifier);
+    }
+
+    let maybe_orbit_state_counts: Option<Vec<u128>> = sorted_orbit_defs
+        .inner
+        .iter()
+        .map(|&orbit_def| orbit_state_count(orbit_def))
+        .collect();
+    let maybe_state_count = maybe_orbit_state_counts.as_ref().and_then(|counts| {
+        counts
+            .iter()
+            .try_fold(1_u128, |total, &count| total.checked_mul(count))
+    });
+    let orbit_state_counts = maybe_orbit_state_counts.unwrap_or_default();
+
+    let mut visited = match maybe_state_count {
+        Some(state_count) if u64::try_from(state_count).is_ok() => {
+            VisitedSet::Exact64(FxHashSet::default())
+        }
+        Some(_) => VisitedSet::Exact128(FxHashSet::default()),
+        None => VisitedSet::FullState(FxHashSet::default()),
+    };
+    let entry_size_bytes = match &visited {
+        VisitedSet::Exact64(_) => size_of::<u64>(),
+        VisitedSet::Exact128(_) => size_of::<u128>(),
+        VisitedSet::FullState(_) => {
+            size_of::<Box<[u8]>>()
+                + sorted_orbit_defs
+                    .inner
+                    .iter()
+                    .map(|orbit_def| 2 * usize::from(orbit_def.piece_count.get()))
+                    .sum::<usize>()
+        }
+    };
+
+    info!(
+        start!("Enumerating the state space of {}..."),
+        puzzle_def.name()
+    );
+
+    let solved = puzzle_def.new_solved_state();
+    visited.insert(&solved, &orbit_identifiers, &orbit_state_counts);
+    let mut states_per_depth = vec![1_u64];
+    let mut frontier = vec![solved.clone()];
+    let mut result = solved;
+
+    while !frontier.is_empty() {
+        let mut next_frontier = vec![];
+        let mut states_at_depth = 0_u64;
+        for state in &frontier {
+            for move_ in &puzzle_def.moves {
+                result.replace_compose(state, move_.puzzle_state(), sorted_orbit_defs);
+                if visited.insert(&result, &orbit_identifiers, &orbit_state_counts) {
+                    if visited.len() * entry_size_bytes > max_size_bytes {
+                        return Err(GodNumberSearchError::MemoryCapExceeded { max_size_bytes });
+                    }
+                    states_at_depth += 1;
+                    next_frontier.push(result.clone());
+                }
+            }
+        }
+        if next_frontier.is_empty() {
+            break;
+        }
+        states_per_depth.push(states_at_depth);
+        debug!(
+            working!("{} states at depth {}"),
+            states_at_depth,
+            states_per_depth.len() - 1
+        );
+        frontier = next_frontier;
+    }
+
+    let report = GodNumberReport { states_per_depth };
+    info!(
+        success!("Found {} states with a God's number of {}"),
+        report.state_count(),
+        report.god_number()
+    );
+    Ok(report)
+}
@@ -0,0 +1,189 @@
+//! Minimal-algorithm search for register increment operations.
+//!
+//! Each register of an architecture is incremented by performing its
+//! generating algorithm, so a shorter algorithm with the same register effect
+//! directly saves physical moves every time the register is touched. The
+//! search here asks the phase2 [`CycleStructureSolver`] for the shortest
+//! algorithms realizing the current increment's cycle structure and picks one
+//! the architecture accepts as equivalent; swapping it in is then a
+//! [`Architecture::swap_register_algorithm`] call away.
+
+use crate::{
+    pruning::PruningTables,
+    puzzle::{PuzzleDef, PuzzleState, SortedCycleStructure, slice_puzzle::HeapPuzzle},
+    puzzle_state_history::PuzzleStateHistory,
+    solver::{CycleStructureSolver, CycleStructureSolverError, SearchStrategy},
+};
+use internment::ArcIntern;
+use itertools::Itertools;
+use qter_core::architectures::{Algorithm, Architecture};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IncrementSearchError {
+    #[error("The register's move {0} is not a move of the puzzle definition")]
+    UnknownPuzzleMove(String),
+    #[error("The solver's move {0} is not a generator of the architecture's group")]
+    UnknownGenerator(String),
+    #[error(transparent)]
+    Solver(#[from] CycleStructureSolverError),
+}
+
+/// Search for an algorithm strictly shorter than the one currently
+/// incrementing `register` that has the same effect on every register of the
+/// architecture, so the caller can swap it in with
+/// [`Architecture::swap_register_algorithm`].
+///
+/// `puzzle_def` must describe the same puzzle as the architecture's group,
+/// with matching move names. The current increment's cycle structure is
+/// derived by performing its moves on the puzzle, the solver enumerates every
+/// minimum-length algorithm realizing that cycle structure below the current
+/// length, and the first one that is register-equivalent is returned. `None`
+/// means no strictly shorter equivalent algorithm was found at the solver's
+/// optimal depth.
+///
+/// `make_pruning_tables` builds the pruning tables the search runs with,
+/// since tables are generated for one specific cycle structure. The puzzle
+/// def is returned alongside the result because the solver takes ownership of
+/// it during the search.
+///
+/// # Panics
+///
+/// Panics if `register` is out of bounds for the architecture.
+#[must_use]
+pub fn find_shorter_increment<'id, H, T, F>(
+    arch: &Architecture,
+    register: usize,
+    puzzle_def: PuzzleDef<'id, HeapPuzzle<'id>>,
+    make_pruning_tables: F,
+) -> (
+    PuzzleDef<'id, HeapPuzzle<'id>>,
+    Result<Option<Algorithm>, IncrementSearchError>,
+)
+where
+    H: PuzzleStateHistory<'id, HeapPuzzle<'id>>,
+    T: PruningTables<'id, HeapPuzzle<'id>>,
+    F: FnOnce(SortedCycleStructure<'id>, &PuzzleDef<'id, HeapPuzzle<'id>>) -> T,
+{
+    let current = arch.registers()[register].algorithm();
+    let current_len = current.move_seq_iter().count();
+
+    // A one-move increment can only lose to the empty algorithm, which
+    // increments nothing
+    if current_len < 2 {
+        return (puzzle_def, Ok(None));
+    }
+
+    // Perform the current increment on the solved puzzle to read off the
+    // cycle structure the replacement must realize
+    let mut state_1 = puzzle_def.new_solved_state();
+    let mut state_2 = state_1.clone();
+    for name in current.move_seq_iter() {
+        let Some(move_) = puzzle_def.find_move(name.as_ref()) else {
+            return (
+                puzzle_def,
+                Err(IncrementSearchError::UnknownPuzzleMove(name.to_string())),
+            );
+        };
+        state_2.replace_compose(
+            &state_1,
+            move_.puzzle_state(),
+            puzzle_def.sorted_orbit_defs_ref(),
+        );
+        std::mem::swap(&mut state_1, &mut state_2);
+    }
+
+    let mut aux_mem = HeapPuzzle::new_aux_mem(puzzle_def.sorted_orbit_defs_ref());
+    let sorted_cycle_structure =
+        state_1.sorted_cycle_structure(puzzle_def.sorted_orbit_defs_ref(), &mut aux_mem);
+
+    let pruning_tables = make_pruning_tables(sorted_cycle_structure, &puzzle_def);
+    let mut solver =
+        CycleStructureSolver::new(puzzle_def, pruning_tables, SearchStrategy::AllSolutions)
+            .with_max_solution_length(current_len - 1);
+
+    let result = match solver.solve::<H>() {
+        Ok(mut solutions) => {
+            let mut found = Ok(None);
+
+            while solutions.next().is_some() {
+                let move_seq = solutions
+                    .expanded_solution()
+                    .iter()
+                    .map(|move_| ArcIntern::<str>::from(move_.name()))
+                    .collect_vec();
+
+                match Algorithm::new_from_move_seq(arch.group_arc(), move_seq) {
+                    Ok(candidate) => {
+                        // The cycle structure matches by construction; the
+                        // candidate must also move the right facelets
+                        if arch.is_equivalent_register_algorithm(register, &candidate) {
+                            found = Ok(Some(candidate));
+                            break;
+                        }
+                    }
+                    Err(bad_move) => {
+                        found = Err(IncrementSearchError::UnknownGenerator(bad_move.to_string()));
+                        break;
+                    }
+                }
+            }
+
+            found
+        }
+        // Nothing shorter realizes the cycle structure at all
+        Err(CycleStructureSolverError::MaxSolutionLengthExceeded) => Ok(None),
+        Err(e) => Err(e.into()),
+    };
+
+    (solver.into_puzzle_def_and_pruning_tables().0, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruning::ZeroTable;
+    use generativity::make_guard;
+    use puzzle_geometry::ksolve::KPUZZLE_3X3;
+    use qter_core::{Int, U, architectures::mk_puzzle_definition, discrete_math::decode};
+    use std::sync::Arc;
+
+    #[test]
+    fn finds_a_shorter_increment_with_the_same_effect() {
+        let cube_def = mk_puzzle_definition("3x3").unwrap();
+
+        // A deliberately redundant increment: `U U` is the half turn `U2`
+        let mut arch = Architecture::new(
+            Arc::clone(&cube_def.perm_group),
+            &[vec![ArcIntern::from("U"), ArcIntern::from("U")]],
+        )
+        .unwrap();
+        let original = arch.registers()[0].algorithm().clone();
+        let original_len = original.move_seq_iter().count();
+
+        make_guard!(guard);
+        let puzzle_def = PuzzleDef::<HeapPuzzle>::new(&KPUZZLE_3X3, guard).unwrap();
+
+        let (_, result) = find_shorter_increment::<Vec<HeapPuzzle>, _, _>(
+            &arch,
+            0,
+            puzzle_def,
+            |sorted_cycle_structure, _| {
+                ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap()
+            },
+        );
+
+        let shorter = result.unwrap().expect("a half turn is a single move");
+        assert!(shorter.move_seq_iter().count() < original_len);
+
+        // The found algorithm still decodes as +1 on the register
+        let facelets = arch.registers()[0].signature_facelets();
+        assert_eq!(
+            decode(shorter.permutation(), &facelets.0, &original),
+            Some(Int::<U>::one())
+        );
+
+        arch.swap_register_algorithm(0, shorter).unwrap();
+        assert_eq!(arch.registers()[0].algorithm().to_string(), "U2");
+    }
+}
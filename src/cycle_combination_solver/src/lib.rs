@@ -3,8 +3,11 @@
 #![allow(clippy::similar_names, clippy::too_many_lines)]
 
 pub(crate) mod canonical_fsm;
+pub mod god_number;
+pub mod increment;
 pub(crate) mod orbit_puzzle;
 pub(crate) mod permutator;
+pub mod phase1;
 pub mod pruning;
 pub mod puzzle;
 pub(crate) mod puzzle_state_history;
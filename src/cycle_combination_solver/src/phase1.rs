@@ -0,0 +1,272 @@
+//! Consistency checking between phase1 cycle combinations and phase2
+//! solvability.
+//!
+//! phase1 proposes cycle combinations combinatorially, so a register it
+//! proposes is not guaranteed to be physically achievable as a single
+//! algorithm. The checker here converts each register of a
+//! [`CycleCombination`] into the sorted cycle structure format the solver
+//! understands and confirms that [`CycleStructureSolver`] finds an algorithm
+//! realizing it.
+
+use crate::{
+    pruning::PruningTables,
+    puzzle::{
+        PuzzleDef, PuzzleState, SortedCycleStructure, SortedCycleStructureCreationError,
+        SortedOrbitDefsRef,
+    },
+    puzzle_state_history::PuzzleStateHistory,
+    solver::{CycleStructureSolver, CycleStructureSolverError, SearchStrategy},
+};
+use cycle_combination_finder::{Cycle, CycleCombination};
+use puzzle_geometry::ksolve::KSolveSet;
+use qter_core::{Int, U};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Phase1ConversionError {
+    #[error("The register has no partition for the orbit {0}")]
+    MissingPartition(String),
+    #[error("A cycle is longer than 255 pieces, which the solver does not support")]
+    CycleTooLong,
+    #[error(transparent)]
+    CycleStructure(#[from] SortedCycleStructureCreationError),
+}
+
+/// Convert one register of a phase1 cycle combination into the solver's
+/// cycle structure format. `puzzle` must be the same KSolve sets the
+/// combination was found on, and `sorted_orbit_defs` must belong to a
+/// `PuzzleDef` derived from them.
+///
+/// phase1 multiplies every orienting orbit's order by its orientation count,
+/// so the realized cycles in such an orbit must orient. Oriented cycles come
+/// with an invariant: the orientation deltas of the whole orbit sum to zero
+/// modulo the orientation count. A lone oriented cycle therefore needs a
+/// compensating oriented piece, and an orbit phase1 left empty needs a pair
+/// of them; these are the shared pieces phase1 sets aside.
+///
+/// # Errors
+///
+/// See `Phase1ConversionError`.
+pub fn cycle_to_sorted_cycle_structure<'id>(
+    cycle: &Cycle,
+    puzzle: &[KSolveSet],
+    sorted_orbit_defs: SortedOrbitDefsRef<'id, '_>,
+) -> Result<SortedCycleStructure<'id>, Phase1ConversionError> {
+    let mut with_sort_keys = puzzle
+        .iter()
+        .map(|set| {
+            let partition = cycle
+                .partitions
+                .iter()
+                .find(|partition| partition.name == set.name())
+                .ok_or_else(|| Phase1ConversionError::MissingPartition(set.name().to_owned()))?;
+
+            let orientation_count = set.orientation_count().get();
+            let oriented = orientation_count > 1;
+
+            let mut cycles = partition
+                .partition
+                .iter()
+                .map(|&length| {
+                    u8::try_from(length)
+                        .map(|length| (length, oriented))
+                        .map_err(|_| Phase1ConversionError::CycleTooLong)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if oriented {
+                match cycles.len() {
+                    0 => cycles.extend([(1, true), (1, true)]),
+                    1 => cycles.push((1, true)),
+                    // Two or more twisted cycles can always cancel each
+                    // other, but flips pair up, so an odd number of flipped
+                    // cycles needs one more
+                    len if orientation_count == 2 && len % 2 == 1 => cycles.push((1, true)),
+                    _ => {}
+                }
+            }
+
+            Ok((
+                (set.piece_count().get(), set.orientation_count().get()),
+                cycles,
+            ))
+        })
+        .collect::<Result<Vec<_>, Phase1ConversionError>>()?;
+
+    // Match the orbit order of `sorted_orbit_defs`, which sorts by piece
+    // count and then orientation count
+    with_sort_keys.sort_unstable_by_key(|&(sort_key, _)| sort_key);
+
+    let maybe_cycle_structure = with_sort_keys
+        .into_iter()
+        .map(|(_, cycles)| cycles)
+        .collect::<Vec<_>>();
+
+    Ok(SortedCycleStructure::new(
+        &maybe_cycle_structure,
+        sorted_orbit_defs,
+    )?)
+}
+
+#[derive(Error, Debug)]
+pub enum RealizabilityError {
+    #[error(transparent)]
+    Conversion(#[from] Phase1ConversionError),
+    #[error(transparent)]
+    Solver(#[from] CycleStructureSolverError),
+}
+
+/// The outcome of checking one register of a phase1 cycle combination
+/// against the phase2 solver
+#[derive(Debug)]
+pub struct RegisterRealizability {
+    /// The index of the register within the combination
+    pub register: usize,
+    /// The register's order as reported by phase1
+    pub order: Int<U>,
+    /// The length of the algorithm found, or why no algorithm was found
+    pub result: Result<usize, RealizabilityError>,
+}
+
+impl RegisterRealizability {
+    #[must_use]
+    pub fn is_realizable(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// Check every register of a phase1 [`CycleCombination`] against the phase2
+/// solver, reporting per register whether an algorithm realizing its cycle
+/// type exists.
+///
+/// `make_pruning_tables` builds the pruning tables each register is searched
+/// with, since tables are generated for one specific cycle structure.
+/// `max_solution_length` bounds the search so that an unrealizable register
+/// is reported instead of the search deepening forever.
+///
+/// The puzzle def is returned alongside the report because the solver takes
+/// ownership of it during each search.
+#[must_use]
+pub fn check_cycle_combination<'id, P, H, T, F>(
+    mut puzzle_def: PuzzleDef<'id, P>,
+    puzzle: &[KSolveSet],
+    cycle_combination: &CycleCombination,
+    max_solution_length: usize,
+    mut make_pruning_tables: F,
+) -> (PuzzleDef<'id, P>, Vec<RegisterRealizability>)
+where
+    P: PuzzleState<'id>,
+    H: PuzzleStateHistory<'id, P>,
+    T: PruningTables<'id, P>,
+    F: FnMut(SortedCycleStructure<'id>, &PuzzleDef<'id, P>) -> T,
+{
+    let mut report = Vec::with_capacity(cycle_combination.cycles.len());
+
+    for (register, cycle) in cycle_combination.cycles.iter().enumerate() {
+        let sorted_cycle_structure = match cycle_to_sorted_cycle_structure(
+            cycle,
+            puzzle,
+            puzzle_def.sorted_orbit_defs_ref(),
+        ) {
+            Ok(sorted_cycle_structure) => sorted_cycle_structure,
+            Err(e) => {
+                report.push(RegisterRealizability {
+                    register,
+                    order: cycle.order,
+                    result: Err(e.into()),
+                });
+                continue;
+            }
+        };
+
+        let pruning_tables = make_pruning_tables(sorted_cycle_structure, &puzzle_def);
+        let mut solver =
+            CycleStructureSolver::new(puzzle_def, pruning_tables, SearchStrategy::FirstSolution)
+                .with_max_solution_length(max_solution_length);
+
+        let result = match solver.solve::<H>() {
+            Ok(solutions) => Ok(solutions.solution_length()),
+            Err(e) => Err(e.into()),
+        };
+
+        report.push(RegisterRealizability {
+            register,
+            order: cycle.order,
+            result,
+        });
+
+        puzzle_def = solver.into_puzzle_def_and_pruning_tables().0;
+    }
+
+    (puzzle_def, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::slice_puzzle::HeapPuzzle;
+    use cycle_combination_finder::Partition;
+    use generativity::make_guard;
+    use puzzle_geometry::ksolve::KPUZZLE_3X3;
+
+    /// A synthetic order-30 register: an oriented 5-cycle of edges together
+    /// with corner orientation
+    fn order_30_edge_register() -> Cycle {
+        Cycle {
+            order: Int::<U>::from(30_u16),
+            partitions: KPUZZLE_3X3
+                .sets()
+                .iter()
+                .map(|set| {
+                    let is_edges = set.piece_count().get() == 12;
+                    Partition {
+                        name: set.name().to_owned(),
+                        partition: if is_edges { vec![5] } else { vec![] },
+                        order: Int::<U>::from(if is_edges { 10_u16 } else { 3_u16 }),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_cycle_to_sorted_cycle_structure() {
+        make_guard!(guard);
+        let cube3_def = PuzzleDef::<HeapPuzzle>::new(&KPUZZLE_3X3, guard).unwrap();
+
+        let sorted_cycle_structure = cycle_to_sorted_cycle_structure(
+            &order_30_edge_register(),
+            KPUZZLE_3X3.sets(),
+            cube3_def.sorted_orbit_defs_ref(),
+        )
+        .unwrap();
+
+        // Corners sort first (8 pieces before 12). The empty corner partition
+        // becomes a pair of compensating twisted corners, and the lone
+        // oriented edge 5-cycle gains a compensating flipped edge.
+        let expected = SortedCycleStructure::new(
+            &[vec![(1, true), (1, true)], vec![(1, true), (5, true)]],
+            cube3_def.sorted_orbit_defs_ref(),
+        )
+        .unwrap();
+        assert_eq!(sorted_cycle_structure.inner, expected.inner);
+    }
+
+    #[test]
+    fn test_missing_partition_is_reported() {
+        make_guard!(guard);
+        let cube3_def = PuzzleDef::<HeapPuzzle>::new(&KPUZZLE_3X3, guard).unwrap();
+
+        let mut cycle = order_30_edge_register();
+        cycle.partitions.pop();
+
+        assert!(matches!(
+            cycle_to_sorted_cycle_structure(
+                &cycle,
+                KPUZZLE_3X3.sets(),
+                cube3_def.sorted_orbit_defs_ref(),
+            ),
+            Err(Phase1ConversionError::MissingPartition(_))
+        ));
+    }
+}
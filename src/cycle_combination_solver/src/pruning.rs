@@ -13,9 +13,9 @@ use super::{
     puzzle::{PuzzleDef, PuzzleState},
 };
 use crate::{
-    orbit_puzzle::OrbitPuzzleState,
+    orbit_puzzle::{OrbitPuzzleState, OrbitPuzzleStateImplementor},
     permutator::pandita2,
-    puzzle::{OrbitIdentifier, SortedCycleStructure, SortedCycleStructureRef},
+    puzzle::{AuxMem, OrbitIdentifier, SortedCycleStructure, SortedCycleStructureRef},
     start, success, working,
 };
 use generativity::Id;
@@ -51,6 +51,14 @@ pub trait PruningTables<'id, P: PuzzleState<'id>> {
     /// this is not the case.
     fn admissible_heuristic(&self, puzzle_state: &P) -> u8;
 
+    /// Extend the pruning tables until `new_depth` has been traversed,
+    /// continuing their generation from where it previously stopped. The
+    /// default does nothing for tables that are already complete or do not
+    /// support extension.
+    fn extend_to_depth(&mut self, puzzle_def: &PuzzleDef<'id, P>, new_depth: u8) {
+        let _ = (puzzle_def, new_depth);
+    }
+
     /// The pruning table is expected to hold the sorted cycle structure so the
     /// instance can be tied to it and not some other foreign cycle structure.
     fn sorted_cycle_structure_ref(&self) -> SortedCycleStructureRef<'id, '_>;
@@ -86,6 +94,9 @@ pub trait StorageBackend<const EXACT: bool>: 'static {
     /// Commit the depth traversed for the pruning table, used to represent the
     /// heuristic for vacant entries.
     fn commit_depth_traversed(&mut self, depth_traversed: u8);
+
+    /// The depth committed by [`StorageBackend::commit_depth_traversed`].
+    fn depth_traversed(&self) -> u8;
 }
 
 /// A pruning table acting on a single orbit.
@@ -107,6 +118,17 @@ trait OrbitPruningTable<'id, P: PuzzleState<'id>>: 'id {
     /// is a logic error if this is not the case. Implementors are expected to
     /// have a mechanism to identify the table's target orbit.
     fn admissible_heuristic(&self, puzzle_state: &P) -> u8;
+
+    /// Continue the table's generation until `new_depth` has been traversed,
+    /// re-deriving the frontier from the recorded depths. The default does
+    /// nothing for tables that do not support extension.
+    fn extend_to_depth(
+        &mut self,
+        generate_meta: OrbitPruningTableGenerationMeta<'id, '_, P>,
+        new_depth: u8,
+    ) {
+        let _ = (generate_meta, new_depth);
+    }
 }
 
 // Not completely sure what this trait should look like; Henry change this if
@@ -126,12 +148,14 @@ struct OrbitPruningTableGenerationMeta<'id, 'a, P: PuzzleState<'id>> {
     sorted_cycle_structure_orbit: &'a [(NonZeroU8, bool)],
     orbit_identifier: P::OrbitIdentifier,
     max_size_bytes: usize,
+    max_depth: Option<u8>,
 }
 
 pub struct OrbitPruningTablesGenerateMeta<'id, 'a, P: PuzzleState<'id>> {
     puzzle_def: &'a PuzzleDef<'id, P>,
     max_size_bytes: usize,
     maybe_table_types: Option<Vec<TableTy>>,
+    max_depth: Option<u8>,
     _id: Id<'id>,
 }
 
@@ -280,10 +304,20 @@ impl<'id, 'a, P: PuzzleState<'id>> OrbitPruningTablesGenerateMeta<'id, 'a, P> {
             puzzle_def,
             max_size_bytes,
             maybe_table_types: None,
+            max_depth: None,
             _id: id,
         }
     }
 
+    /// Stop generating each table once `max_depth` has been traversed, leaving
+    /// deeper entries to saturate at that depth. The tables can be deepened
+    /// later with [`PruningTables::extend_to_depth`].
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: u8) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
     /// Create a new `OrbitPruningTablesGenerateMeta` with the given parameters
     ///
     /// # Errors
@@ -386,6 +420,7 @@ impl<'id, P: PuzzleState<'id>> PruningTables<'id, P> for OrbitPruningTables<'id,
                 sorted_cycle_structure_orbit,
                 orbit_identifier,
                 max_size_bytes,
+                max_depth: generate_metas.max_depth,
             };
 
             let (orbit_pruning_table, used_size_bytes) =
@@ -415,6 +450,34 @@ impl<'id, P: PuzzleState<'id>> PruningTables<'id, P> for OrbitPruningTables<'id,
             })
     }
 
+    fn extend_to_depth(&mut self, puzzle_def: &PuzzleDef<'id, P>, new_depth: u8) {
+        let mut maybe_orbit_identifier: Option<P::OrbitIdentifier> = None;
+        for (orbit_index, branded_orbit_def) in puzzle_def
+            .sorted_orbit_defs_ref()
+            .branded_copied_iter()
+            .enumerate()
+        {
+            maybe_orbit_identifier = Some(if orbit_index == 0 {
+                P::OrbitIdentifier::first_orbit_identifier(branded_orbit_def)
+            } else {
+                maybe_orbit_identifier
+                    .unwrap()
+                    .next_orbit_identifier(branded_orbit_def)
+            });
+
+            let generate_meta = OrbitPruningTableGenerationMeta {
+                puzzle_def,
+                sorted_cycle_structure_orbit: &self.sorted_cycle_structure.inner[orbit_index],
+                orbit_identifier: maybe_orbit_identifier.unwrap(),
+                // Extension reuses the table's existing storage
+                max_size_bytes: 0,
+                max_depth: Some(new_depth),
+            };
+
+            self.orbit_pruning_tables[orbit_index].extend_to_depth(generate_meta, new_depth);
+        }
+    }
+
     fn sorted_cycle_structure_ref(&self) -> SortedCycleStructureRef<'id, '_> {
         self.sorted_cycle_structure.as_ref()
     }
@@ -600,6 +663,10 @@ impl<const EXACT: bool> StorageBackend<EXACT> for UncompressedStorageBackend<EXA
     fn commit_depth_traversed(&mut self, depth_traversed: u8) {
         self.depth_traversed = depth_traversed;
     }
+
+    fn depth_traversed(&self) -> u8 {
+        self.depth_traversed
+    }
 }
 
 #[allow(unused)]
@@ -639,6 +706,10 @@ impl<const EXACT: bool> StorageBackend<EXACT> for NxoptStorageBackend<EXACT> {
     fn commit_depth_traversed(&mut self, depth_traversed: u8) {
         todo!();
     }
+
+    fn depth_traversed(&self) -> u8 {
+        todo!();
+    }
 }
 
 #[allow(unused)]
@@ -681,6 +752,10 @@ impl<const EXACT: bool> StorageBackend<EXACT> for TANSStorageBackend<EXACT> {
     fn commit_depth_traversed(&mut self, depth_traversed: u8) {
         todo!();
     }
+
+    fn depth_traversed(&self) -> u8 {
+        todo!();
+    }
 }
 
 #[allow(unused)]
@@ -734,29 +809,62 @@ unsafe fn knuthm(ori: &mut [u8], orientation_count: NonZeroU8) {
     *last = (*last + 2) % orientation_count.get();
 }
 
-impl<'id, P: PuzzleState<'id>, S: StorageBackend<true>> OrbitPruningTable<'id, P>
-    for ExactOrbitPruningTable<'id, S, P::OrbitIdentifier>
-{
-    fn try_generate<'a>(
-        generate_meta: OrbitPruningTableGenerationMeta<'id, 'a, P>,
-    ) -> Result<
-        (ExactOrbitPruningTable<'id, S, P::OrbitIdentifier>, usize),
-        (
-            OrbitPruningTableGenerationError,
-            OrbitPruningTableGenerationMeta<'id, 'a, P>,
-        ),
-    > {
-        let OrbitPruningTableGenerationMeta {
-            puzzle_def,
-            sorted_cycle_structure_orbit,
-            orbit_identifier,
-            max_size_bytes,
-        } = generate_meta;
-
-        let orbit_puzzle_solved = P::pick_orbit_puzzle(orbit_identifier);
+/// Collect the orbit restriction of every move that acts nontrivially on the
+/// orbit identified by `orbit_identifier`.
+fn orbit_moves_acting_on<'id, P: PuzzleState<'id>>(
+    puzzle_def: &PuzzleDef<'id, P>,
+    orbit_identifier: P::OrbitIdentifier,
+    orbit_puzzle_solved: &OrbitPuzzleStateImplementor,
+) -> Vec<OrbitPuzzleStateImplementor> {
+    let orbit_def = orbit_identifier.orbit_def();
+    let orbit_move_class_indicies = puzzle_def
+        .move_classes
+        .iter()
+        .copied()
+        .enumerate()
+        .filter_map(|(i, move_class)| {
+            let (perm, ori) = puzzle_def.moves[move_class]
+                .puzzle_state()
+                .orbit_bytes(orbit_identifier);
+            if orbit_puzzle_solved.from_orbit_transformation_unchecked(perm, ori, orbit_def)
+                == *orbit_puzzle_solved
+            {
+                None
+            } else {
+                Some(i)
+            }
+        })
+        .collect_vec();
+
+    puzzle_def
+        .moves
+        .iter()
+        .filter_map(|move_| {
+            if orbit_move_class_indicies.contains(&move_.class_index()) {
+                let (perm, ori) = move_.puzzle_state().orbit_bytes(orbit_identifier);
+                Some(orbit_puzzle_solved.from_orbit_transformation_unchecked(perm, ori, orbit_def))
+            } else {
+                None
+            }
+        })
+        .collect_vec()
+}
 
-        let orbit_def = orbit_identifier.orbit_def();
-        // TODO: make this common for all pruning tables
+impl<'id, S: StorageBackend<true>, O: OrbitIdentifier<'id>> ExactOrbitPruningTable<'id, S, O> {
+    /// Run the BFS passes from `start_depth` until the table is full or
+    /// `max_depth` has been traversed, expanding the frontier of entries
+    /// recorded at `start_depth - 1` and committing the traversed depth to the
+    /// storage backend.
+    fn traverse_depths(
+        &mut self,
+        orbit_puzzle_solved: &OrbitPuzzleStateImplementor,
+        orbit_moves: &[OrbitPuzzleStateImplementor],
+        sorted_cycle_structure_orbit: &[(NonZeroU8, bool)],
+        aux_mem: &mut AuxMem<'id>,
+        start_depth: u8,
+        max_depth: Option<u8>,
+    ) {
+        let orbit_def = self.orbit_identifier.orbit_def();
         let piece_count = orbit_def.piece_count.get();
 
         let orientation_count = u64::pow(
@@ -764,72 +872,32 @@ impl<'id, P: PuzzleState<'id>, S: StorageBackend<true>> OrbitPruningTable<'id, P
             u32::from(piece_count) - 1,
         );
         let entry_count = FACT_UNTIL_19[piece_count as usize] * orientation_count;
-        let fail = Err((
-            OrbitPruningTableGenerationError::NotBigEnough,
-            generate_meta,
-        ));
-        let initialization_meta =
-            S::initialization_meta_from_entry_count(if let Ok(v) = entry_count.try_into() {
-                v
-            } else {
-                return fail;
-            });
-        let used_size_bytes = initialization_meta.used_size_bytes();
-        if used_size_bytes > max_size_bytes {
-            return fail;
-        }
-        let mut table = ExactOrbitPruningTable {
-            storage_backend: S::initialize_from_meta(initialization_meta),
-            orbit_identifier,
-            _id: puzzle_def.id(),
-        };
-
-        let orbit_move_class_indicies = puzzle_def
-            .move_classes
-            .iter()
-            .copied()
-            .enumerate()
-            .filter_map(|(i, move_class)| {
-                let (perm, ori) = puzzle_def.moves[move_class]
-                    .puzzle_state()
-                    .orbit_bytes(orbit_identifier);
-                if orbit_puzzle_solved.from_orbit_transformation_unchecked(perm, ori, orbit_def)
-                    == orbit_puzzle_solved
-                {
-                    None
-                } else {
-                    Some(i)
-                }
-            })
-            .collect_vec();
 
-        let orbit_moves = puzzle_def
-            .moves
-            .iter()
-            .filter_map(|move_| {
-                if orbit_move_class_indicies.contains(&move_.class_index()) {
-                    let (perm, ori) = move_.puzzle_state().orbit_bytes(orbit_identifier);
-                    Some(
-                        orbit_puzzle_solved
-                            .from_orbit_transformation_unchecked(perm, ori, orbit_def),
-                    )
-                } else {
-                    None
-                }
-            })
-            .collect_vec();
+        let mut vacant_entry_count = if start_depth == 0 {
+            entry_count
+        } else {
+            let occupied_entry_count = u64::try_from(
+                (0..entry_count)
+                    .filter(|&hash| !self.storage_backend.heuristic_hash(hash).is_vacant())
+                    .count(),
+            )
+            .unwrap();
+            entry_count - occupied_entry_count
+        };
+        if vacant_entry_count == 0 {
+            return;
+        }
 
         let mut orbit_result = orbit_puzzle_solved.clone();
 
-        let mut aux_mem = P::new_aux_mem(puzzle_def.sorted_orbit_defs_ref());
-        let mut depth = 0;
-        let mut vacant_entry_count = entry_count;
-
-        // TODO: multithreading
-        // TODO: replace first few with IDDFS
         let mut perm = (0..piece_count).collect_vec().into_boxed_slice();
         let mut ori = vec![0; piece_count as usize].into_boxed_slice();
+        let mut depth = start_depth;
+        let mut depth_traversed = start_depth.saturating_sub(1);
         while let Some(depth_heuristic) = OrbitPruneHeuristic::occupied(depth) {
+            if max_depth.is_some_and(|max_depth| depth > max_depth) {
+                break;
+            }
             let depth_start = Instant::now();
             let prev_vacant_entry_count = vacant_entry_count;
             let mut exact_orbit_hash = 0;
@@ -851,7 +919,7 @@ impl<'id, P: PuzzleState<'id>, S: StorageBackend<true>> OrbitPruningTable<'id, P
                         }
                     }
                     if depth != 0
-                        && table
+                        && self
                             .storage_backend
                             .heuristic_hash(exact_orbit_hash)
                             .get_occupied()
@@ -871,8 +939,7 @@ impl<'id, P: PuzzleState<'id>, S: StorageBackend<true>> OrbitPruningTable<'id, P
                                 aux_mem.as_ref_mut(),
                             )
                         } {
-                            table
-                                .storage_backend
+                            self.storage_backend
                                 .set_heuristic_hash(exact_orbit_hash, depth_heuristic);
                             vacant_entry_count -= 1;
                         }
@@ -880,14 +947,13 @@ impl<'id, P: PuzzleState<'id>, S: StorageBackend<true>> OrbitPruningTable<'id, P
                         continue;
                     }
 
-                    for move_ in &orbit_moves {
+                    for move_ in orbit_moves {
                         unsafe {
                             orbit_result.replace_compose(&curr_state, move_, orbit_def);
                         }
                         let new_hash = unsafe { orbit_result.exact_hasher(orbit_def) };
-                        if table.storage_backend.heuristic_hash(new_hash).is_vacant() {
-                            table
-                                .storage_backend
+                        if self.storage_backend.heuristic_hash(new_hash).is_vacant() {
+                            self.storage_backend
                                 .set_heuristic_hash(new_hash, depth_heuristic);
                             vacant_entry_count -= 1;
                         }
@@ -912,15 +978,111 @@ impl<'id, P: PuzzleState<'id>, S: StorageBackend<true>> OrbitPruningTable<'id, P
                 entry_count,
                 percent
             );
+            depth_traversed = depth;
             if vacant_entry_count == 0 {
                 assert_eq!(exact_orbit_hash, entry_count);
                 break;
             }
             depth += 1;
         }
+        self.storage_backend.commit_depth_traversed(depth_traversed);
+    }
+}
+
+impl<'id, P: PuzzleState<'id>, S: StorageBackend<true>> OrbitPruningTable<'id, P>
+    for ExactOrbitPruningTable<'id, S, P::OrbitIdentifier>
+{
+    fn try_generate<'a>(
+        generate_meta: OrbitPruningTableGenerationMeta<'id, 'a, P>,
+    ) -> Result<
+        (ExactOrbitPruningTable<'id, S, P::OrbitIdentifier>, usize),
+        (
+            OrbitPruningTableGenerationError,
+            OrbitPruningTableGenerationMeta<'id, 'a, P>,
+        ),
+    > {
+        let OrbitPruningTableGenerationMeta {
+            puzzle_def,
+            sorted_cycle_structure_orbit,
+            orbit_identifier,
+            max_size_bytes,
+            max_depth,
+        } = generate_meta;
+
+        let orbit_puzzle_solved = P::pick_orbit_puzzle(orbit_identifier);
+
+        let orbit_def = orbit_identifier.orbit_def();
+        // TODO: make this common for all pruning tables
+        let piece_count = orbit_def.piece_count.get();
+
+        let orientation_count = u64::pow(
+            u64::from(orbit_def.orientation_count.get()),
+            u32::from(piece_count) - 1,
+        );
+        let entry_count = FACT_UNTIL_19[piece_count as usize] * orientation_count;
+        let fail = Err((
+            OrbitPruningTableGenerationError::NotBigEnough,
+            generate_meta,
+        ));
+        let initialization_meta =
+            S::initialization_meta_from_entry_count(if let Ok(v) = entry_count.try_into() {
+                v
+            } else {
+                return fail;
+            });
+        let used_size_bytes = initialization_meta.used_size_bytes();
+        if used_size_bytes > max_size_bytes {
+            return fail;
+        }
+        let mut table = ExactOrbitPruningTable {
+            storage_backend: S::initialize_from_meta(initialization_meta),
+            orbit_identifier,
+            _id: puzzle_def.id(),
+        };
+
+        let orbit_moves = orbit_moves_acting_on(puzzle_def, orbit_identifier, &orbit_puzzle_solved);
+
+        let mut aux_mem = P::new_aux_mem(puzzle_def.sorted_orbit_defs_ref());
+
+        // TODO: multithreading
+        // TODO: replace first few with IDDFS
+        table.traverse_depths(
+            &orbit_puzzle_solved,
+            &orbit_moves,
+            sorted_cycle_structure_orbit,
+            &mut aux_mem,
+            0,
+            max_depth,
+        );
         Ok((table, used_size_bytes))
     }
 
+    fn extend_to_depth(
+        &mut self,
+        generate_meta: OrbitPruningTableGenerationMeta<'id, '_, P>,
+        new_depth: u8,
+    ) {
+        let depth_traversed = self.storage_backend.depth_traversed();
+        if depth_traversed >= new_depth {
+            return;
+        }
+        let orbit_puzzle_solved = P::pick_orbit_puzzle(self.orbit_identifier);
+        let orbit_moves = orbit_moves_acting_on(
+            generate_meta.puzzle_def,
+            self.orbit_identifier,
+            &orbit_puzzle_solved,
+        );
+        let mut aux_mem = P::new_aux_mem(generate_meta.puzzle_def.sorted_orbit_defs_ref());
+        self.traverse_depths(
+            &orbit_puzzle_solved,
+            &orbit_moves,
+            generate_meta.sorted_cycle_structure_orbit,
+            &mut aux_mem,
+            depth_traversed + 1,
+            Some(new_depth),
+        );
+    }
+
     fn admissible_heuristic(&self, puzzle_state: &P) -> u8 {
         self.storage_backend
             .admissible_heuristic_hash(puzzle_state.exact_hasher_orbit(self.orbit_identifier))
@@ -1066,6 +1228,7 @@ mod tests {
                     .unwrap(),
             ),
             max_size_bytes: 0,
+            max_depth: None,
         };
         let (zero_orbit_table, _) = ZeroOrbitTable::try_generate(generate_meta).unwrap();
         assert_eq!(zero_orbit_table.admissible_heuristic(&solved), 0);
@@ -1202,6 +1365,52 @@ mod tests {
         }
     }
 
+    #[test_log::test]
+    fn test_extend_corner_table_to_depth() {
+        make_guard!(guard);
+        let cube3_def = PuzzleDef::<Cube3>::new(&KPUZZLE_3X3, guard).unwrap();
+        let identity_cycle_structure =
+            SortedCycleStructure::new(&[vec![], vec![]], cube3_def.sorted_orbit_defs_ref())
+                .unwrap();
+        let corners_meta = |max_depth| OrbitPruningTableGenerationMeta {
+            puzzle_def: &cube3_def,
+            sorted_cycle_structure_orbit: &identity_cycle_structure.inner[0],
+            orbit_identifier: <Cube3 as PuzzleState>::OrbitIdentifier::first_orbit_identifier(
+                cube3_def
+                    .sorted_orbit_defs_ref()
+                    .branded_copied_iter()
+                    .next()
+                    .unwrap(),
+            ),
+            max_size_bytes: 88_179_840,
+            max_depth: Some(max_depth),
+        };
+
+        let (mut extended, _) =
+            ExactOrbitPruningTable::<UncompressedStorageBackend<true>, _>::try_generate(
+                corners_meta(6),
+            )
+            .unwrap();
+        assert_eq!(extended.storage_backend.depth_traversed(), 6);
+        extended.extend_to_depth(corners_meta(8), 8);
+        assert_eq!(extended.storage_backend.depth_traversed(), 8);
+
+        let (from_scratch, _) =
+            ExactOrbitPruningTable::<UncompressedStorageBackend<true>, _>::try_generate(
+                corners_meta(8),
+            )
+            .unwrap();
+        for hash in 0..88_179_840 {
+            assert_eq!(
+                extended.storage_backend.heuristic_hash(hash).get_occupied(),
+                from_scratch
+                    .storage_backend
+                    .heuristic_hash(hash)
+                    .get_occupied()
+            );
+        }
+    }
+
     #[test_log::test]
     fn test_3x3_corners_pruning_table() {
         make_guard!(guard);
@@ -1,7 +1,10 @@
 use crate::orbit_puzzle::OrbitPuzzleStateImplementor;
 use generativity::{Guard, Id};
 use itertools::Itertools;
-use puzzle_geometry::ksolve::KSolve;
+use puzzle_geometry::{
+    PuzzleGeometry,
+    ksolve::{KSolve, KSolveSet},
+};
 use std::{fmt::Debug, hash::Hash, num::NonZeroU8};
 use thiserror::Error;
 
@@ -120,6 +123,50 @@ pub struct OrbitDef {
     pub orientation_count: NonZeroU8,
 }
 
+impl TryFrom<&KSolveSet> for OrbitDef {
+    type Error = KSolveConversionError;
+
+    fn try_from(ksolve_set: &KSolveSet) -> Result<Self, Self::Error> {
+        Ok(OrbitDef {
+            piece_count: ksolve_set
+                .piece_count()
+                .try_into()
+                .map_err(|_| KSolveConversionError::SetSizeTooBig)?,
+            // TODO: limit ori to 128 because of vectorized SlicePuzzle
+            // logic
+            orientation_count: ksolve_set.orientation_count(),
+        })
+    }
+}
+
+/// Derive the orbit defs of a puzzle straight from its geometry, without
+/// constructing a `PuzzleDef`. The orbit defs are sorted by piece count and
+/// then orientation count, the same order `PuzzleDef` uses, so the result is
+/// deterministic even though the geometry discovers its orbits in an
+/// arbitrary order.
+///
+/// # Errors
+///
+/// An orbit of the geometry could not be converted to an `OrbitDef`. See
+/// `KSolveConversionError`.
+pub fn orbit_defs_from_geometry(
+    geometry: &PuzzleGeometry,
+) -> Result<Vec<OrbitDef>, KSolveConversionError> {
+    let mut orbit_defs = geometry
+        .ksolve()
+        .sets()
+        .iter()
+        .map(OrbitDef::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    orbit_defs.sort_unstable_by_key(|orbit_def| {
+        (
+            orbit_def.piece_count.get(),
+            orbit_def.orientation_count.get(),
+        )
+    });
+    Ok(orbit_defs)
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct BrandedOrbitDef<'id> {
     pub inner: OrbitDef,
@@ -365,6 +412,16 @@ impl<'id> SortedCycleStructure<'id> {
     }
 }
 
+impl<'id> SortedCycleStructureRef<'id, '_> {
+    #[must_use]
+    pub fn to_owned(&self) -> SortedCycleStructure<'id> {
+        SortedCycleStructure {
+            inner: self.inner.to_vec(),
+            id: self._id,
+        }
+    }
+}
+
 impl<'id> SortedOrbitDefsRef<'id, '_> {
     pub fn branded_copied_iter(&self) -> impl Iterator<Item = BrandedOrbitDef<'id>> {
         self.inner.iter().copied().map(|orbit_def| BrandedOrbitDef {
@@ -478,21 +535,17 @@ impl<'id, P: PuzzleState<'id>> PuzzleDef<'id, P> {
     /// The `KSolve` definition could not be converted to a `PuzzleDef`. See
     /// `KSolveConversionError`.
     pub fn new(ksolve: &KSolve, guard: Guard<'id>) -> Result<Self, KSolveConversionError> {
+        debug_assert!(
+            ksolve.check_orientation_invariants().is_ok(),
+            "KSolve definition violates orientation invariants: {}",
+            ksolve.check_orientation_invariants().unwrap_err()
+        );
+
         let id = guard.into();
         let ksolve_orbit_defs: Vec<OrbitDef> = ksolve
             .sets()
             .iter()
-            .map(|ksolve_set| {
-                Ok(OrbitDef {
-                    piece_count: ksolve_set
-                        .piece_count()
-                        .try_into()
-                        .map_err(|_| KSolveConversionError::SetSizeTooBig)?,
-                    // TODO: limit ori to 128 because of vectorized SlicePuzzle
-                    // logic
-                    orientation_count: ksolve_set.orientation_count(),
-                })
-            })
+            .map(OrbitDef::try_from)
             .collect::<Result<_, KSolveConversionError>>()?;
 
         let mut arg_indicies = (0..ksolve_orbit_defs.len()).collect_vec();
@@ -682,7 +735,7 @@ mod tests {
         *,
     };
     use generativity::make_guard;
-    use puzzle_geometry::ksolve::KPUZZLE_3X3;
+    use puzzle_geometry::ksolve::{KPUZZLE_3X3, PUZZLE_GEOMETRY_3X3};
     use test::Bencher;
 
     type StackCube3<'id> = StackPuzzle<'id, 40>;
@@ -760,6 +813,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_orbit_defs_from_geometry_3x3() {
+        let orbit_defs = orbit_defs_from_geometry(&PUZZLE_GEOMETRY_3X3).unwrap();
+        assert_eq!(
+            orbit_defs,
+            vec![single_orbit_def(8, 3), single_orbit_def(12, 2)]
+        );
+    }
+
     #[test]
     fn test_not_enough_buffer_space() {
         make_guard!(guard);
@@ -1282,6 +1344,70 @@ mod tests {
         });
     }
 
+    fn single_orbit_def(piece_count: u8, orientation_count: u8) -> OrbitDef {
+        OrbitDef {
+            piece_count: NonZeroU8::new(piece_count).unwrap(),
+            orientation_count: NonZeroU8::new(orientation_count).unwrap(),
+        }
+    }
+
+    /// Benchmark `replace_compose` over a single synthetic orbit of the given
+    /// size so the scalar slice path can be measured as orbit sizes grow. An
+    /// orientation count of one exercises the fast path; anything larger
+    /// exercises the general path. The cube-sized variants are directly
+    /// comparable against the `bench_compose_cube3_*` SIMD benchmarks to guide
+    /// where specialized implementations for larger puzzles are worth adding.
+    pub fn bench_compose_single_orbit_helper<'id, P: PuzzleState<'id>>(
+        guard: Guard<'id>,
+        b: &mut Bencher,
+        orbit_def: OrbitDef,
+    ) {
+        let piece_count = orbit_def.piece_count.get();
+        let orientation_count = orbit_def.orientation_count.get();
+        let id = guard.into();
+        let sorted_orbit_defs = [orbit_def];
+        let sorted_orbit_defs_ref = SortedOrbitDefsRef {
+            inner: &sorted_orbit_defs,
+            id,
+        };
+
+        // Full cycles in opposite directions; composing them touches every
+        // permutation and orientation byte of the orbit each iteration.
+        let a_transformation = vec![
+            (0..piece_count)
+                .map(|i| ((i + 1) % piece_count, i % orientation_count))
+                .collect_vec(),
+        ];
+        let b_transformation = vec![
+            (0..piece_count)
+                .map(|i| {
+                    (
+                        (i + piece_count - 1) % piece_count,
+                        (piece_count - 1 - i) % orientation_count,
+                    )
+                })
+                .collect_vec(),
+        ];
+        let a_state: P = P::try_from_transformations_meta(
+            TransformationsMeta::new(&a_transformation, sorted_orbit_defs_ref).unwrap(),
+            id,
+        )
+        .unwrap();
+        let b_state: P = P::try_from_transformations_meta(
+            TransformationsMeta::new(&b_transformation, sorted_orbit_defs_ref).unwrap(),
+            id,
+        )
+        .unwrap();
+        let mut result = a_state.clone();
+        b.iter(|| {
+            test::black_box(&mut result).replace_compose(
+                test::black_box(&a_state),
+                test::black_box(&b_state),
+                sorted_orbit_defs_ref,
+            );
+        });
+    }
+
     pub fn bench_inverse_helper<'id, P: PuzzleState<'id>>(guard: Guard<'id>, b: &mut Bencher) {
         let cube3_def = PuzzleDef::<P>::new(&KPUZZLE_3X3, guard).unwrap();
         let solved = cube3_def.new_solved_state();
@@ -1531,4 +1657,102 @@ mod tests {
         make_guard!(guard);
         bench_induces_sorted_cycle_structure_average_helper::<cube3::avx2::Cube3>(guard, b);
     }
+
+    // --- Parameterized single-orbit compose benchmarks ---
+
+    #[bench]
+    fn bench_compose_orbit_8_ori_1_stack(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<StackPuzzle<16>>(guard, b, single_orbit_def(8, 1));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_8_ori_3_stack(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<StackPuzzle<16>>(guard, b, single_orbit_def(8, 3));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_8_ori_1_heap(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<HeapPuzzle>(guard, b, single_orbit_def(8, 1));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_8_ori_3_heap(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<HeapPuzzle>(guard, b, single_orbit_def(8, 3));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_12_ori_1_stack(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<StackPuzzle<24>>(guard, b, single_orbit_def(12, 1));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_12_ori_3_stack(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<StackPuzzle<24>>(guard, b, single_orbit_def(12, 3));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_12_ori_1_heap(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<HeapPuzzle>(guard, b, single_orbit_def(12, 1));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_12_ori_3_heap(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<HeapPuzzle>(guard, b, single_orbit_def(12, 3));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_24_ori_1_stack(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<StackPuzzle<48>>(guard, b, single_orbit_def(24, 1));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_24_ori_3_stack(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<StackPuzzle<48>>(guard, b, single_orbit_def(24, 3));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_24_ori_1_heap(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<HeapPuzzle>(guard, b, single_orbit_def(24, 1));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_24_ori_3_heap(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<HeapPuzzle>(guard, b, single_orbit_def(24, 3));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_48_ori_1_stack(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<StackPuzzle<96>>(guard, b, single_orbit_def(48, 1));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_48_ori_3_stack(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<StackPuzzle<96>>(guard, b, single_orbit_def(48, 3));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_48_ori_1_heap(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<HeapPuzzle>(guard, b, single_orbit_def(48, 1));
+    }
+
+    #[bench]
+    fn bench_compose_orbit_48_ori_3_heap(b: &mut Bencher) {
+        make_guard!(guard);
+        bench_compose_single_orbit_helper::<HeapPuzzle>(guard, b, single_orbit_def(48, 3));
+    }
 }
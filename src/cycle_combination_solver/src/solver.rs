@@ -1,13 +1,19 @@
 use super::{
     canonical_fsm::{CanonicalFSMState, PuzzleCanonicalFSM},
     pruning::PruningTables,
-    puzzle::{Move, PuzzleDef, PuzzleState},
+    puzzle::{Move, PuzzleDef, PuzzleState, SortedCycleStructure, SortedCycleStructureRef},
     puzzle_state_history::{PuzzleStateHistory, StackedPuzzleStateHistory},
 };
 use crate::{puzzle::AuxMem, start, success, working};
 use itertools::Itertools;
 use log::{Level, debug, info, log_enabled};
-use std::{borrow::Cow, cmp::Ordering, time::Instant, vec::IntoIter};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    mem,
+    time::{Duration, Instant},
+    vec::IntoIter,
+};
 use thiserror::Error;
 
 pub struct CycleStructureSolver<'id, P: PuzzleState<'id>, T: PruningTables<'id, P>> {
@@ -15,6 +21,7 @@ pub struct CycleStructureSolver<'id, P: PuzzleState<'id>, T: PruningTables<'id,
     pruning_tables: T,
     canonical_fsm: PuzzleCanonicalFSM<'id, P>,
     max_solution_length: Option<usize>,
+    auto_extend_tables: bool,
     search_strategy: SearchStrategy,
 }
 
@@ -27,6 +34,20 @@ struct CycleStructureSolverMutable<'id, P: PuzzleState<'id>, H: PuzzleStateHisto
     tmp: u64,
 }
 
+/// The mutable state of [`CycleStructureSolver::solve_from`]. It is much
+/// simpler than `CycleStructureSolverMutable` because the seeded search does
+/// not use the puzzle state history machinery.
+struct SeededSearchMutable<'id, P: PuzzleState<'id>> {
+    /// `state_stack[i]` is the state after the prefix and the first `i`
+    /// completion moves
+    state_stack: Vec<P>,
+    /// Move indices of the completion currently being searched
+    completion: Vec<usize>,
+    /// Move indices of every found completion
+    solutions: Vec<Vec<usize>>,
+    aux_mem: AuxMem<'id>,
+}
+
 #[derive(Error, Debug)]
 pub enum CycleStructureSolverError {
     #[error("A deep search still did not find a solution. It is unlikely that one exists")]
@@ -54,6 +75,78 @@ pub enum SearchStrategy {
     AllSolutions,
 }
 
+/// The metric a [`Solution`]'s length is measured in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Every move in the puzzle definition's move set counts as one turn.
+    /// This is the metric the solver itself searches under
+    ExecutionTurn,
+    /// Every move counts as its number of quarter turns, read from the
+    /// repetition count standard notation writes at the end of the move name
+    /// (`F` is one, `F2` is two, `R3'` is three)
+    QuarterTurn,
+}
+
+impl Metric {
+    /// The number of turns `move_name` counts for under this metric
+    fn turns(self, move_name: &str) -> usize {
+        match self {
+            Metric::ExecutionTurn => 1,
+            Metric::QuarterTurn => {
+                let unprimed = move_name.strip_suffix('\'').unwrap_or(move_name);
+                let without_repetitions =
+                    unprimed.trim_end_matches(|c: char| c.is_ascii_digit());
+                unprimed[without_repetitions.len()..].parse().unwrap_or(1)
+            }
+        }
+    }
+}
+
+/// A solution produced by [`CycleStructureSolver::solve`] in a self-describing
+/// form: the moves to perform, the length of the sequence under a chosen
+/// metric, the cycle structure the sequence realizes, and how long the search
+/// that found it took
+#[derive(Debug, Clone)]
+pub struct Solution<'id> {
+    move_names: Vec<String>,
+    metric: Metric,
+    length: usize,
+    sorted_cycle_structure: SortedCycleStructure<'id>,
+    time_taken: Duration,
+}
+
+impl<'id> Solution<'id> {
+    /// The names of the moves to perform, in order
+    #[must_use]
+    pub fn move_names(&self) -> &[String] {
+        &self.move_names
+    }
+
+    /// The metric [`Solution::length`] is measured in
+    #[must_use]
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    /// The length of the move sequence under [`Solution::metric`]
+    #[must_use]
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// The sorted cycle structure the move sequence realizes
+    #[must_use]
+    pub fn sorted_cycle_structure(&self) -> SortedCycleStructureRef<'id, '_> {
+        self.sorted_cycle_structure.as_ref()
+    }
+
+    /// How long the search that found this solution took
+    #[must_use]
+    pub fn time_taken(&self) -> Duration {
+        self.time_taken
+    }
+}
+
 impl<'id, P: PuzzleState<'id>, H: PuzzleStateHistory<'id, P>>
     CycleStructureSolverMutable<'id, P, H>
 {
@@ -81,6 +174,10 @@ pub struct SolutionsIntoIter<'id, 'a, P: PuzzleState<'id>> {
     canonical_sequence_expansion_transformation: Vec<usize>,
     /// The state of the sequence symmetry expansion
     sequence_symmetry_expansion: Option<SequenceSymmetryExpansion>,
+    /// The cycle structure the solutions realize, for [`Solution`] metadata
+    sorted_cycle_structure: SortedCycleStructure<'id>,
+    /// How long the search took, for [`Solution`] metadata
+    search_duration: Duration,
 }
 
 #[derive(Debug)]
@@ -106,6 +203,7 @@ impl<'id, P: PuzzleState<'id>, T: PruningTables<'id, P>> CycleStructureSolver<'i
             pruning_tables,
             canonical_fsm,
             max_solution_length: None,
+            auto_extend_tables: false,
             search_strategy,
         }
     }
@@ -116,6 +214,15 @@ impl<'id, P: PuzzleState<'id>, T: PruningTables<'id, P>> CycleStructureSolver<'i
         self
     }
 
+    /// Extend the pruning tables with [`PruningTables::extend_to_depth`]
+    /// whenever the iterative deepening bound passes the depth they were
+    /// generated to.
+    #[must_use]
+    pub fn with_auto_extend_tables(mut self) -> Self {
+        self.auto_extend_tables = true;
+        self
+    }
+
     pub fn into_puzzle_def_and_pruning_tables(self) -> (PuzzleDef<'id, P>, T) {
         (self.puzzle_def, self.pruning_tables)
     }
@@ -405,7 +512,7 @@ impl<'id, P: PuzzleState<'id>, T: PruningTables<'id, P>> CycleStructureSolver<'i
     /// The solver will fail if it cannot find a solution. See
     /// `CycleStructureSolverError`.
     pub fn solve<H: PuzzleStateHistory<'id, P>>(
-        &self,
+        &mut self,
     ) -> Result<SolutionsIntoIter<'id, '_, P>, CycleStructureSolverError> {
         info!(start!(
             "Beginning Cycle Combination Solver solution search..."
@@ -468,6 +575,9 @@ impl<'id, P: PuzzleState<'id>, T: PruningTables<'id, P>> CycleStructureSolver<'i
                 .puzzle_state_history
                 .resize_if_needed(usize::from(depth));
             loop {
+                if self.auto_extend_tables {
+                    self.pruning_tables.extend_to_depth(&self.puzzle_def, depth);
+                }
                 debug!(working!("Searching depth limit {}..."), depth);
                 let depth_start = Instant::now();
                 // `entry_index` must be zero here so the root level so sequence
@@ -511,11 +621,12 @@ impl<'id, P: PuzzleState<'id>, T: PruningTables<'id, P>> CycleStructureSolver<'i
             }
         }
 
+        let search_duration = start.elapsed();
         info!(
             success!("Found {} raw solutions at depth {} in {:.3}s"),
             mutable.solutions.len(),
             depth,
-            start.elapsed().as_secs_f64()
+            search_duration.as_secs_f64()
         );
         debug!("");
         let result_1 = self.puzzle_def.new_solved_state();
@@ -532,8 +643,166 @@ impl<'id, P: PuzzleState<'id>, T: PruningTables<'id, P>> CycleStructureSolver<'i
             canonical_sequence_expansion: None,
             canonical_sequence_expansion_transformation: (0..depth.into()).collect_vec(),
             sequence_symmetry_expansion: None,
+            sorted_cycle_structure: self.pruning_tables.sorted_cycle_structure_ref().to_owned(),
+            search_duration,
         })
     }
+
+    /// Complete a known partial solution. The search starts from the state
+    /// after applying `prefix` and finds the shortest move sequences that,
+    /// appended to the prefix, realize the target cycle structure. Every
+    /// reported [`Solution`] contains the prefix moves followed by a
+    /// completion, and its length counts both.
+    ///
+    /// The sequence symmetry optimization of [`CycleStructureSolver::solve`]
+    /// cannot be applied here because rotating a sequence does not preserve
+    /// its prefix, so seeded searches traverse more nodes than unseeded ones.
+    ///
+    /// # Errors
+    ///
+    /// The solver will fail if it cannot find a completion. See
+    /// `CycleStructureSolverError`.
+    pub fn solve_from(
+        &self,
+        prefix: &[Move<'id, P>],
+    ) -> Result<Vec<Solution<'id>>, CycleStructureSolverError> {
+        info!(start!("Beginning seeded solution search..."));
+        let start = Instant::now();
+        let sorted_orbit_defs = self.puzzle_def.sorted_orbit_defs_ref();
+
+        let mut state_after_prefix = self.puzzle_def.new_solved_state();
+        let mut result = state_after_prefix.clone();
+        for move_ in prefix {
+            result.replace_compose(&state_after_prefix, move_.puzzle_state(), sorted_orbit_defs);
+            mem::swap(&mut state_after_prefix, &mut result);
+        }
+
+        let mut mutable = SeededSearchMutable {
+            state_stack: vec![state_after_prefix],
+            completion: vec![],
+            solutions: vec![],
+            aux_mem: P::new_aux_mem(sorted_orbit_defs),
+        };
+
+        let mut depth = self
+            .pruning_tables
+            .admissible_heuristic(&mutable.state_stack[0]);
+        if depth == 0 {
+            if mutable.state_stack[0].induces_sorted_cycle_structure(
+                self.pruning_tables.sorted_cycle_structure_ref(),
+                sorted_orbit_defs,
+                mutable.aux_mem.as_ref_mut(),
+            ) {
+                // The prefix is already a solution on its own
+                mutable.solutions.push(vec![]);
+            } else {
+                depth = 1;
+            }
+        }
+
+        while mutable.solutions.is_empty() {
+            if depth == u8::MAX {
+                return Err(CycleStructureSolverError::SolutionDoesNotExist);
+            }
+            if let Some(max_solution_length) = self.max_solution_length
+                && prefix.len() + usize::from(depth) > max_solution_length
+            {
+                return Err(CycleStructureSolverError::MaxSolutionLengthExceeded);
+            }
+            debug!(working!("Searching completion depth limit {}..."), depth);
+            mutable
+                .state_stack
+                .resize(usize::from(depth) + 1, self.puzzle_def.new_solved_state());
+            self.search_for_completion(&mut mutable, CanonicalFSMState::default(), depth);
+            depth += 1;
+        }
+
+        let search_duration = start.elapsed();
+        info!(
+            success!("Found {} completions of the prefix in {:.3}s"),
+            mutable.solutions.len(),
+            search_duration.as_secs_f64()
+        );
+
+        let metric = Metric::ExecutionTurn;
+        Ok(mutable
+            .solutions
+            .into_iter()
+            .map(|completion| {
+                let move_names = prefix
+                    .iter()
+                    .map(|move_| move_.name().to_owned())
+                    .chain(completion.into_iter().map(|move_index| {
+                        self.puzzle_def.moves[move_index].name().to_owned()
+                    }))
+                    .collect_vec();
+                let length = move_names.iter().map(|name| metric.turns(name)).sum();
+                Solution {
+                    move_names,
+                    metric,
+                    length,
+                    sorted_cycle_structure: self
+                        .pruning_tables
+                        .sorted_cycle_structure_ref()
+                        .to_owned(),
+                    time_taken: search_duration,
+                }
+            })
+            .collect())
+    }
+
+    /// The plain IDA* recursion backing [`CycleStructureSolver::solve_from`].
+    /// Returns whether the search should terminate because a solution was
+    /// found under `SearchStrategy::FirstSolution`.
+    fn search_for_completion(
+        &self,
+        mutable: &mut SeededSearchMutable<'id, P>,
+        current_fsm_state: CanonicalFSMState,
+        permitted_cost: u8,
+    ) -> bool {
+        let stack_index = mutable.completion.len();
+        for (move_index, move_) in self.puzzle_def.moves.iter().enumerate() {
+            let move_class_index = move_.class_index();
+            // SAFETY: `move_class_index` comes from a move of the puzzle
+            // definition the FSM was created from, so it is bound
+            let next_fsm_state = unsafe {
+                self.canonical_fsm
+                    .next_state(current_fsm_state, move_class_index)
+            };
+            if next_fsm_state.is_none() {
+                continue;
+            }
+
+            let (searched, next) = mutable.state_stack.split_at_mut(stack_index + 1);
+            next[0].replace_compose(
+                &searched[stack_index],
+                move_.puzzle_state(),
+                self.puzzle_def.sorted_orbit_defs_ref(),
+            );
+
+            if permitted_cost == 1 {
+                if next[0].induces_sorted_cycle_structure(
+                    self.pruning_tables.sorted_cycle_structure_ref(),
+                    self.puzzle_def.sorted_orbit_defs_ref(),
+                    mutable.aux_mem.as_ref_mut(),
+                ) {
+                    let mut solution = mutable.completion.clone();
+                    solution.push(move_index);
+                    mutable.solutions.push(solution);
+                    if self.search_strategy == SearchStrategy::FirstSolution {
+                        return true;
+                    }
+                }
+            } else if self.pruning_tables.admissible_heuristic(&next[0]) < permitted_cost {
+                mutable.completion.push(move_index);
+                if self.search_for_completion(mutable, next_fsm_state, permitted_cost - 1) {
+                    return true;
+                }
+                mutable.completion.pop();
+            }
+        }
+        false
+    }
 }
 
 impl<'id, P: PuzzleState<'id>> Iterator for SolutionsIntoIter<'id, '_, P> {
@@ -775,6 +1044,30 @@ impl<'id, 'a, P: PuzzleState<'id>> SolutionsIntoIter<'id, 'a, P> {
     pub fn expanded_count(&self) -> usize {
         self.expanded_count
     }
+
+    /// The current expanded solution along with its metadata, with the length
+    /// measured under `metric`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is called before `.next()`
+    #[must_use]
+    pub fn solution(&self, metric: Metric) -> Solution<'id> {
+        let move_names = self
+            .expanded_solution()
+            .iter()
+            .map(|move_| move_.name().to_owned())
+            .collect_vec();
+        let length = move_names.iter().map(|name| metric.turns(name)).sum();
+
+        Solution {
+            move_names,
+            metric,
+            length,
+            sorted_cycle_structure: self.sorted_cycle_structure.clone(),
+            time_taken: self.search_duration,
+        }
+    }
 }
 
 fn pandita1(perm: &mut [usize]) -> bool {
@@ -0,0 +1,29 @@
+use cycle_combination_solver::{
+    god_number::{GodNumberSearchError, god_number_search},
+    make_guard,
+    puzzle::{PuzzleDef, slice_puzzle::HeapPuzzle},
+};
+use puzzle_geometry::ksolve::KPUZZLE_PYRAMINX_NO_TIPS;
+
+#[test_log::test]
+fn test_pyraminx_no_tips_distribution() {
+    make_guard!(guard);
+    let pyraminx_def = PuzzleDef::<HeapPuzzle>::new(&KPUZZLE_PYRAMINX_NO_TIPS, guard).unwrap();
+
+    let report = god_number_search(&pyraminx_def, 1 << 30).unwrap();
+    assert_eq!(report.state_count(), 933_120);
+    assert_eq!(report.god_number(), 11);
+}
+
+#[test_log::test]
+fn test_memory_cap_is_enforced() {
+    make_guard!(guard);
+    let pyraminx_def = PuzzleDef::<HeapPuzzle>::new(&KPUZZLE_PYRAMINX_NO_TIPS, guard).unwrap();
+
+    assert!(matches!(
+        god_number_search(&pyraminx_def, 1024),
+        Err(GodNumberSearchError::MemoryCapExceeded {
+            max_size_bytes: 1024
+        })
+    ));
+}
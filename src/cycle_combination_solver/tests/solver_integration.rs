@@ -1,13 +1,16 @@
+use cycle_combination_finder::optimal_equivalent_combination;
 use cycle_combination_solver::{
     make_guard,
+    phase1::check_cycle_combination,
     pruning::{
         OrbitPruningTables, OrbitPruningTablesGenerateMeta, PruningTables, StorageBackendTy,
         TableTy, ZeroTable,
     },
     puzzle::{
-        PuzzleDef, PuzzleState, SortedCycleStructure, cube3::Cube3, slice_puzzle::HeapPuzzle,
+        PuzzleDef, PuzzleState, SortedCycleStructure, apply_moves, cube3::Cube3,
+        slice_puzzle::HeapPuzzle,
     },
-    solver::{CycleStructureSolver, CycleStructureSolverError, SearchStrategy},
+    solver::{CycleStructureSolver, CycleStructureSolverError, Metric, SearchStrategy},
 };
 use itertools::Itertools;
 use log::{debug, trace};
@@ -20,7 +23,7 @@ fn test_identity_cycle_structure() {
     let identity_cycle_structure =
         SortedCycleStructure::new(&[vec![], vec![]], cube3_def.sorted_orbit_defs_ref()).unwrap();
 
-    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
         cube3_def,
         ZeroTable::try_generate_all(identity_cycle_structure.clone(), ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -43,7 +46,7 @@ fn test_identity_cycle_structure() {
         .unwrap(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<Cube3, _> =
+    let mut solver: CycleStructureSolver<Cube3, _> =
         CycleStructureSolver::new(cube3_def, pruning_tables, SearchStrategy::AllSolutions);
     let mut solutions = solver.solve::<[Cube3; 21]>().unwrap();
     assert_eq!(solutions.solution_length(), 0);
@@ -60,7 +63,7 @@ fn test_single_quarter_turn() {
         cube3_def.sorted_orbit_defs_ref(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
         cube3_def,
         ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -102,7 +105,7 @@ fn test_single_half_turn() {
         cube3_def.sorted_orbit_defs_ref(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
         cube3_def,
         ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -151,7 +154,7 @@ fn test_optimal_subgroup_cycle() {
         cube3_def.sorted_orbit_defs_ref(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
         cube3_def,
         ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -193,7 +196,7 @@ fn test_210_optimal_cycle() {
         cube3_def.sorted_orbit_defs_ref(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
         cube3_def,
         ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -214,6 +217,97 @@ fn test_210_optimal_cycle() {
     assert_eq!(solutions.expanded_count(), 480);
 }
 
+#[test_log::test]
+fn test_210_optimal_cycle_solution_metadata() {
+    make_guard!(guard);
+    let cube3_def = PuzzleDef::<Cube3>::new(&KPUZZLE_3X3, guard).unwrap();
+    let sorted_cycle_structure = SortedCycleStructure::new(
+        &[vec![(1, true), (5, true)], vec![(1, true), (7, true)]],
+        cube3_def.sorted_orbit_defs_ref(),
+    )
+    .unwrap();
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+        cube3_def,
+        ZeroTable::try_generate_all(sorted_cycle_structure.clone(), ()).unwrap(),
+        SearchStrategy::FirstSolution,
+    );
+
+    let mut solutions = solver.solve::<[Cube3; 21]>().unwrap();
+    solutions.next().unwrap();
+
+    let solution = solutions.solution(Metric::ExecutionTurn);
+    assert_eq!(solution.metric(), Metric::ExecutionTurn);
+    assert_eq!(solution.length(), 5);
+    assert_eq!(
+        solution.move_names(),
+        solutions
+            .expanded_solution()
+            .iter()
+            .map(|move_| move_.name())
+            .collect_vec()
+    );
+    assert_eq!(
+        solution.sorted_cycle_structure().inner,
+        sorted_cycle_structure.as_ref().inner
+    );
+    assert!(!solution.time_taken().is_zero());
+
+    // Under the quarter turn metric every half turn counts twice
+    let solution = solutions.solution(Metric::QuarterTurn);
+    let half_turns = solution
+        .move_names()
+        .iter()
+        .filter(|name| name.ends_with('2'))
+        .count();
+    assert_eq!(solution.length(), solution.move_names().len() + half_turns);
+}
+
+#[test_log::test]
+fn test_solve_from_prefix() {
+    make_guard!(guard);
+    let cube3_def = PuzzleDef::<Cube3>::new(&KPUZZLE_3X3, guard).unwrap();
+    let sorted_cycle_structure = SortedCycleStructure::new(
+        &[vec![(4, false)], vec![(4, false)]],
+        cube3_def.sorted_orbit_defs_ref(),
+    )
+    .unwrap();
+    let u_move = cube3_def.find_move("U").unwrap().clone();
+    let r_move = cube3_def.find_move("R").unwrap().clone();
+    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+        cube3_def,
+        ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
+        SearchStrategy::FirstSolution,
+    );
+
+    // A correct prefix needs no completion at all
+    let solutions = solver.solve_from(&[u_move]).unwrap();
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(solutions[0].move_names(), ["U"]);
+    assert_eq!(solutions[0].length(), 1);
+
+    // A wrong prefix still completes to a valid solution, one move longer
+    // than the optimum
+    let solutions = solver.solve_from(&[r_move]).unwrap();
+    assert_eq!(solutions.len(), 1);
+    let solution = &solutions[0];
+    assert_eq!(solution.move_names()[0], "R");
+    assert_eq!(solution.length(), 2);
+
+    let (cube3_def, _) = solver.into_puzzle_def_and_pruning_tables();
+    let mut aux_mem = Cube3::new_aux_mem(cube3_def.sorted_orbit_defs_ref());
+    let state = apply_moves(
+        &cube3_def,
+        &cube3_def.new_solved_state(),
+        &solution.move_names().join(" "),
+        1,
+    );
+    assert!(state.induces_sorted_cycle_structure(
+        solution.sorted_cycle_structure(),
+        cube3_def.sorted_orbit_defs_ref(),
+        aux_mem.as_ref_mut(),
+    ));
+}
+
 #[test_log::test]
 fn test_easy_30x30x30_optimal_cycle() {
     make_guard!(guard);
@@ -235,7 +329,7 @@ fn test_easy_30x30x30_optimal_cycle() {
     .unwrap();
     let pruning_tables =
         OrbitPruningTables::try_generate_all(sorted_cycle_structure, generate_meta).unwrap();
-    let solver: CycleStructureSolver<Cube3, _> =
+    let mut solver: CycleStructureSolver<Cube3, _> =
         CycleStructureSolver::new(cube3_def, pruning_tables, SearchStrategy::AllSolutions);
 
     let mut solutions = solver.solve::<[Cube3; 21]>().unwrap();
@@ -274,7 +368,7 @@ fn test_hard_30x30x30_optimal_cycle() {
     .unwrap();
     let pruning_tables =
         OrbitPruningTables::try_generate_all(sorted_cycle_structure, generate_meta).unwrap();
-    let solver: CycleStructureSolver<Cube3, _> =
+    let mut solver: CycleStructureSolver<Cube3, _> =
         CycleStructureSolver::new(cube3_def, pruning_tables, SearchStrategy::AllSolutions)
             .with_max_solution_length(10);
 
@@ -296,7 +390,7 @@ fn test_3c_optimal_cycle() {
         cube3_def.sorted_orbit_defs_ref(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
         cube3_def,
         ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -326,7 +420,7 @@ fn test_8c8e_optimal_cycle() {
         cube3_def.sorted_orbit_defs_ref(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
         cube3_def,
         ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -366,7 +460,7 @@ fn test_consecutive_commutative_moves() {
         cube3_def.sorted_orbit_defs_ref(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
         cube3_def,
         ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -396,7 +490,7 @@ fn test_commutative_moves_only() {
         cube3_def.sorted_orbit_defs_ref(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
         cube3_def,
         ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -426,7 +520,7 @@ fn test_sequence_symmetry_wrapping_sequence_edge_case() {
         cube3_def.sorted_orbit_defs_ref(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<Cube3, _> = CycleStructureSolver::new(
         cube3_def,
         ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -459,7 +553,7 @@ fn test_megaminx_random_order() {
         megaminx_def.sorted_orbit_defs_ref(),
     )
     .unwrap();
-    let solver: CycleStructureSolver<HeapPuzzle, _> = CycleStructureSolver::new(
+    let mut solver: CycleStructureSolver<HeapPuzzle, _> = CycleStructureSolver::new(
         megaminx_def,
         ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap(),
         SearchStrategy::AllSolutions,
@@ -480,6 +574,47 @@ fn test_megaminx_random_order() {
     assert_eq!(solutions.expanded_count(), 80856);
 }
 
+#[test_log::test]
+fn test_phase1_order_30_combo_realizable() {
+    make_guard!(guard);
+    let cube3_def = PuzzleDef::<Cube3>::new(&KPUZZLE_3X3, guard).unwrap();
+
+    let cycle_combination = optimal_equivalent_combination(KPUZZLE_3X3.sets(), 3)
+        .unwrap()
+        .unwrap();
+
+    let (_, report) = check_cycle_combination::<_, [Cube3; 21], _, _>(
+        cube3_def,
+        KPUZZLE_3X3.sets(),
+        &cycle_combination,
+        13,
+        |sorted_cycle_structure, cube3_def| {
+            let generate_meta = OrbitPruningTablesGenerateMeta::new_with_table_types(
+                cube3_def,
+                vec![
+                    TableTy::Exact(StorageBackendTy::Uncompressed),
+                    TableTy::Zero,
+                ],
+                88_179_840,
+                cube3_def.id(),
+            )
+            .unwrap();
+            OrbitPruningTables::try_generate_all(sorted_cycle_structure, generate_meta).unwrap()
+        },
+    );
+
+    assert_eq!(report.len(), 3);
+    for register in &report {
+        assert!(
+            register.is_realizable(),
+            "register {} of order {} is not realizable: {:?}",
+            register.register,
+            register.order,
+            register.result,
+        );
+    }
+}
+
 #[allow(dead_code)]
 struct OptimalCycleStructureTest {
     moves_str: &'static str,
@@ -711,7 +846,7 @@ fn test_many_optimal_cycles() {
 
         let zero_table = ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap();
 
-        let solver: CycleStructureSolver<HeapPuzzle, _> =
+        let mut solver: CycleStructureSolver<HeapPuzzle, _> =
             CycleStructureSolver::new(cube3_def, zero_table, SearchStrategy::AllSolutions);
 
         let mut solutions = solver.solve::<Vec<_>>().unwrap();
@@ -1016,7 +1151,7 @@ fn test_big_cube_optimal_cycle() {
 
         let zero_table = ZeroTable::try_generate_all(sorted_cycle_structure, ()).unwrap();
 
-        let solver: CycleStructureSolver<HeapPuzzle, _> =
+        let mut solver: CycleStructureSolver<HeapPuzzle, _> =
             CycleStructureSolver::new(cube4_def, zero_table, SearchStrategy::AllSolutions);
 
         let mut solutions = solver.solve::<Vec<_>>().unwrap();
@@ -0,0 +1,111 @@
+//! Compares the interpreter's puzzle-state backends by running the multiply
+//! program end to end under each one and reporting steps per second. Run
+//! with `cargo bench -p interpreter`.
+//!
+//! The numbers justify which backend should be the default: the packed
+//! representation trades per-facelet access for cheap composition, and this
+//! shows how that plays out over a real program rather than a microbenchmark.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use compiler::compile;
+use interpreter::{
+    ActionPerformed, Interpreter, PausedState,
+    puzzle_states::{PackedSimulatedPuzzle, PuzzleState, SimulatedPuzzle},
+};
+use qter_core::{File, I, Int, Program};
+
+/// A heavy input pair; both factors take the long way around their cycles
+const INPUTS: [i64; 2] = [29, 29];
+
+const RUNS: u32 = 10;
+
+/// Run the multiply program to completion, returning the number of
+/// instructions executed
+fn run_multiply<P: PuzzleState<InitializationArgs = ()>>(program: &Arc<Program>) -> u64 {
+    let mut interpreter = Interpreter::<P>::new(Arc::clone(program), ());
+    let mut inputs = INPUTS.into_iter();
+    let mut steps = 0;
+
+    loop {
+        let paused = match interpreter.step() {
+            ActionPerformed::Panicked => panic!("The multiply program panicked"),
+            ActionPerformed::Paused => true,
+            _ => {
+                steps += 1;
+                false
+            }
+        };
+
+        if !paused {
+            continue;
+        }
+
+        match interpreter.step_until_halt() {
+            PausedState::Input { .. } => {
+                interpreter
+                    .give_input(Int::<I>::from(inputs.next().unwrap()))
+                    .unwrap();
+            }
+            PausedState::Halt { .. } => break,
+            PausedState::Panicked => panic!("The multiply program panicked"),
+        }
+    }
+
+    steps
+}
+
+/// Time `RUNS` full executions after a warmup run, returning the total
+/// instruction count and elapsed time
+fn bench_backend<P: PuzzleState<InitializationArgs = ()>>(
+    program: &Arc<Program>,
+) -> (u64, Duration) {
+    run_multiply::<P>(program);
+
+    let start = Instant::now();
+    let mut steps = 0;
+    for _ in 0..RUNS {
+        steps += run_multiply::<P>(program);
+    }
+
+    (steps, start.elapsed())
+}
+
+fn steps_per_sec(steps: u64, elapsed: Duration) -> f64 {
+    steps as f64 / elapsed.as_secs_f64()
+}
+
+fn main() {
+    let qat = std::fs::read_to_string("../compiler/tests/multiply/multiply_transform.qat").unwrap();
+
+    let program = match compile(&File::from(qat), |_| unreachable!(), false) {
+        Ok(v) => v,
+        Err(e) => panic!("{e:?}"),
+    };
+    let program = Arc::new(program);
+
+    let (unpacked_steps, unpacked_time) = bench_backend::<SimulatedPuzzle>(&program);
+    let (packed_steps, packed_time) = bench_backend::<PackedSimulatedPuzzle>(&program);
+
+    assert_eq!(
+        unpacked_steps, packed_steps,
+        "both backends must execute the same instructions"
+    );
+
+    let unpacked_rate = steps_per_sec(unpacked_steps, unpacked_time);
+    let packed_rate = steps_per_sec(packed_steps, packed_time);
+
+    println!(
+        "SimulatedPuzzle:       {unpacked_steps} steps in {unpacked_time:?} — {unpacked_rate:.0} steps/sec"
+    );
+    println!(
+        "PackedSimulatedPuzzle: {packed_steps} steps in {packed_time:?} — {packed_rate:.0} steps/sec"
+    );
+    println!(
+        "PackedSimulatedPuzzle is {:.2}x the speed of SimulatedPuzzle",
+        packed_rate / unpacked_rate
+    );
+}
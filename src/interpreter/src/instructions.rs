@@ -1,11 +1,11 @@
 use qter_core::{
-    ByPuzzleType, Halt, Input, Int, PerformAlgorithm, Print, RepeatUntil, SeparatesByPuzzleType,
-    Solve, SolvedGoto, U, discrete_math::lcm,
+    Assert, ByPuzzleType, Halt, I, Input, Int, PerformAlgorithm, Print, Random, RepeatUntil,
+    SeparatesByPuzzleType, Solve, SolvedGoto, U, discrete_math::lcm,
 };
 
 use crate::{
-    ActionPerformed, ExecutionState, InterpreterState, PausedState, PuzzleAndRegister, PuzzleState,
-    SucceededSolvedGoto,
+    ActionPerformed, ExecutionState, InterpreterState, PausedState, ProgressHint,
+    PuzzleAndRegister, PuzzleState, SucceededSolvedGoto,
 };
 
 pub fn do_instr<'a, Instr: PuzzleInstructionImpl, P: PuzzleState>(
@@ -35,15 +35,16 @@ impl PuzzleInstructionImpl for SolvedGoto {
         instr: &'a Self::Theoretical<'static>,
         state: &mut InterpreterState<P>,
     ) -> ActionPerformed<'a> {
-        if Int::is_zero(&state.puzzle_states.theoretical_state(instr.1).value()) {
+        let value = state.puzzle_states.theoretical_state(instr.1).value();
+
+        if Int::is_zero(&(value % instr.2)) {
             state.program_counter = instr.0.instruction_idx;
 
-            ActionPerformed::SucceededSolvedGoto(ByPuzzleType::Theoretical((
-                SucceededSolvedGoto {
-                    jumped_to: instr.0.instruction_idx,
-                },
-                instr.1,
-            )))
+            ActionPerformed::SucceededSolvedGoto(SucceededSolvedGoto {
+                jumped_to: instr.0.instruction_idx,
+                source: ByPuzzleType::Theoretical(instr.1),
+                facelets: None,
+            })
         } else {
             state.program_counter += 1;
 
@@ -60,13 +61,11 @@ impl PuzzleInstructionImpl for SolvedGoto {
         if puzzle.facelets_solved(&instr.2.0) {
             state.program_counter = instr.0.instruction_idx;
 
-            ActionPerformed::SucceededSolvedGoto(ByPuzzleType::Puzzle((
-                SucceededSolvedGoto {
-                    jumped_to: instr.0.instruction_idx,
-                },
-                instr.1,
-                &instr.2,
-            )))
+            ActionPerformed::SucceededSolvedGoto(SucceededSolvedGoto {
+                jumped_to: instr.0.instruction_idx,
+                source: ByPuzzleType::Puzzle(instr.1),
+                facelets: Some(&instr.2),
+            })
         } else {
             state.program_counter += 1;
 
@@ -83,9 +82,7 @@ fn input_impl<'a, P: PuzzleState>(
 ) -> ActionPerformed<'a> {
     let max_input = order - Int::<U>::one();
     state.execution_state = ExecutionState::Paused(PausedState::Input { max_input, data });
-    state
-        .messages
-        .push_back(format!("{message} (max input {max_input})"));
+    state.emit_message(format!("{message} (max input {max_input})"));
 
     ActionPerformed::Paused
 }
@@ -143,7 +140,7 @@ fn perform_halt<'a, P: PuzzleState>(
 
         instr.message.clone()
     };
-    state.messages.push_back(full_message);
+    state.emit_message(full_message);
 
     ActionPerformed::Paused
 }
@@ -205,7 +202,7 @@ fn perform_print<'a, P: PuzzleState>(
         }
         None => instr.message.clone(),
     };
-    state.messages.push_back(full_message);
+    state.emit_message(full_message);
     state.program_counter += 1;
 
     ActionPerformed::None
@@ -249,6 +246,51 @@ impl PuzzleInstructionImpl for Print {
     }
 }
 
+fn perform_assert<'a, P: PuzzleState>(
+    actual: Int<U>,
+    instr: &'a Assert,
+    state: &mut InterpreterState<P>,
+) -> ActionPerformed<'a> {
+    if actual == instr.expected {
+        state.execution_state = ExecutionState::Running;
+        state.program_counter += 1;
+
+        ActionPerformed::None
+    } else {
+        state.panic(&format!(
+            "{} (expected {}, actual {actual})",
+            instr.message, instr.expected
+        ))
+    }
+}
+
+impl PuzzleInstructionImpl for Assert {
+    fn perform_theoretical<'a, P: PuzzleState>(
+        instr: &'a Self::Theoretical<'static>,
+        state: &mut InterpreterState<P>,
+    ) -> ActionPerformed<'a> {
+        perform_assert(
+            state.puzzle_states.theoretical_state(instr.1).value(),
+            &instr.0,
+            state,
+        )
+    }
+
+    fn perform_puzzle<'a, P: PuzzleState>(
+        instr: &'a Self::Puzzle<'static>,
+        state: &mut InterpreterState<P>,
+    ) -> ActionPerformed<'a> {
+        let puzzle = state.puzzle_states.puzzle_state_mut(instr.1);
+
+        // `print` decodes the register and restores the puzzle to the state it
+        // started in, which is exactly what an assertion needs
+        match puzzle.print(&instr.3.0, &instr.2) {
+            Some(actual) => perform_assert(actual, &instr.0, state),
+            None => state.panic("The register specified is not decodable!"),
+        }
+    }
+}
+
 impl PuzzleInstructionImpl for PerformAlgorithm {
     fn perform_theoretical<'a, P: PuzzleState>(
         instr: &'a Self::Theoretical<'static>,
@@ -263,7 +305,7 @@ impl PuzzleInstructionImpl for PerformAlgorithm {
 
         state.program_counter += 1;
 
-        ActionPerformed::Added(ByPuzzleType::Theoretical((instr.0, instr.1)))
+        ActionPerformed::Added(ByPuzzleType::Theoretical((instr.0, instr.1, None)))
     }
 
     fn perform_puzzle<'a, P: PuzzleState>(
@@ -278,7 +320,59 @@ impl PuzzleInstructionImpl for PerformAlgorithm {
 
         state.program_counter += 1;
 
-        ActionPerformed::Added(ByPuzzleType::Puzzle((instr.0, &instr.1)))
+        ActionPerformed::Added(ByPuzzleType::Puzzle((instr.0, &instr.1, None)))
+    }
+}
+
+impl PuzzleInstructionImpl for Random {
+    fn perform_theoretical<'a, P: PuzzleState>(
+        instr: &'a Self::Theoretical<'static>,
+        state: &mut InterpreterState<P>,
+    ) -> ActionPerformed<'a> {
+        let Some(amt) = state.random_up_to(instr.0.max) else {
+            return state.panic("The `random` bound does not fit in 64 bits!");
+        };
+
+        state.execution_state = ExecutionState::Running;
+
+        state
+            .puzzle_states
+            .theoretical_state_mut(instr.1)
+            .add_to(amt);
+
+        state.program_counter += 1;
+
+        ActionPerformed::RandomAdded {
+            source: ByPuzzleType::Theoretical(instr.1),
+            amt,
+        }
+    }
+
+    fn perform_puzzle<'a, P: PuzzleState>(
+        instr: &'a Self::Puzzle<'static>,
+        state: &mut InterpreterState<P>,
+    ) -> ActionPerformed<'a> {
+        let Some(amt) = state.random_up_to(instr.0.max) else {
+            return state.panic("The `random` bound does not fit in 64 bits!");
+        };
+
+        state.execution_state = ExecutionState::Running;
+
+        // Exponentiating the register's generator keeps this a single composed
+        // algorithm on the robot path
+        let mut algorithm = instr.2.to_owned();
+        algorithm.exponentiate(Int::<I>::from(amt));
+        state
+            .puzzle_states
+            .puzzle_state_mut(instr.1)
+            .compose_into(&algorithm);
+
+        state.program_counter += 1;
+
+        ActionPerformed::RandomAdded {
+            source: ByPuzzleType::Puzzle(instr.1),
+            amt,
+        }
     }
 }
 
@@ -298,11 +392,34 @@ impl PuzzleInstructionImpl for Solve {
         instr: &'a Self::Puzzle<'static>,
         state: &mut InterpreterState<P>,
     ) -> ActionPerformed<'a> {
-        state.puzzle_states.puzzle_state_mut(*instr).solve();
+        let (puzzle_idx, preserved) = instr;
+
+        // Decode the preserved registers before solving; `print` restores the
+        // puzzle to the state it started in
+        let values = {
+            let puzzle = state.puzzle_states.puzzle_state_mut(*puzzle_idx);
+            preserved
+                .iter()
+                .map(|(algorithm, facelets)| puzzle.print(&facelets.0, algorithm))
+                .collect::<Option<Vec<_>>>()
+        };
+
+        let Some(values) = values else {
+            return state.panic("A register to preserve is not decodable!");
+        };
+
+        let puzzle = state.puzzle_states.puzzle_state_mut(*puzzle_idx);
+        puzzle.solve();
+
+        for ((algorithm, _), value) in preserved.iter().zip(values) {
+            let mut restore = algorithm.to_owned();
+            restore.exponentiate(Int::<I>::from(value));
+            puzzle.compose_into(&restore);
+        }
 
         state.program_counter += 1;
 
-        ActionPerformed::Solved(ByPuzzleType::Puzzle(*instr))
+        ActionPerformed::Solved(ByPuzzleType::Puzzle(*puzzle_idx))
     }
 }
 
@@ -318,10 +435,32 @@ impl PuzzleInstructionImpl for RepeatUntil {
         instr: &'a Self::Puzzle<'static>,
         state: &mut InterpreterState<P>,
     ) -> ActionPerformed<'a> {
-        state
-            .puzzle_states
+        let loop_iterations_max = instr
+            .facelets
+            .0
+            .iter()
+            .map(|facelet| instr.alg.chromatic_orders_by_facelets()[*facelet])
+            .fold(Int::<U>::one(), lcm);
+
+        state.progress = Some(ProgressHint {
+            current_instruction: state.program_counter,
+            loop_iterations_done: Int::zero(),
+            loop_iterations_max,
+        });
+
+        let InterpreterState {
+            puzzle_states,
+            progress,
+            ..
+        } = state;
+
+        puzzle_states
             .puzzle_state_mut(instr.puzzle_idx)
-            .repeat_until(&instr.facelets.0, &instr.alg);
+            .repeat_until_with_progress(&instr.facelets.0, &instr.alg, &mut |done| {
+                if let Some(hint) = progress.as_mut() {
+                    hint.loop_iterations_done = done;
+                }
+            });
 
         state.program_counter += 1;
 
@@ -1,7 +1,9 @@
 use std::{
-    io::{self, BufRead, BufReader, Write}, net::TcpStream, sync::Arc
+    collections::HashMap, io::{self, BufRead, BufReader, Write}, net::TcpStream, sync::Arc
 };
 
+use internment::ArcIntern;
+
 use log::trace;
 use qter_core::{
     I, Int, Program, PuzzleIdx, TheoreticalIdx, U,
@@ -72,6 +74,19 @@ pub trait PuzzleState {
     /// Returns None if the facelets cannot be solved by repeating the algorithm.
     fn repeat_until(&mut self, facelets: &[usize], generator: &Algorithm) -> Option<()>;
 
+    /// Like [`PuzzleState::repeat_until`], except that the number of repetitions performed so far is reported through `progress` as the loop runs.
+    ///
+    /// Implementations that perform the repetitions one at a time should override this to report each one; the default performs the whole loop without reporting.
+    fn repeat_until_with_progress(
+        &mut self,
+        facelets: &[usize],
+        generator: &Algorithm,
+        progress: &mut dyn FnMut(Int<U>),
+    ) -> Option<()> {
+        let _ = progress;
+        self.repeat_until(facelets, generator)
+    }
+
     /// Bring the puzzle to the solved state
     fn solve(&mut self);
 }
@@ -117,23 +132,52 @@ impl<R: RobotLike> RobotLikeDyn for R {
 pub struct RobotState<R: RobotLike> {
     robot: R,
     perm_group: Arc<PermutationGroup>,
+    /// Algorithms batched across consecutive instructions so that the robot
+    /// sees them as one uninterrupted move sequence and can collapse moves
+    /// across instruction boundaries. The batch is flushed before anything
+    /// that needs an accurate picture of the puzzle.
+    pending: Option<Algorithm>,
+}
+
+impl<R: RobotLike> RobotState<R> {
+    /// Send any batched algorithms to the robot
+    fn flush_pending(&mut self) {
+        if let Some(alg) = self.pending.take() {
+            self.robot.compose_into(&alg);
+        }
+    }
 }
 
 impl<R: RobotLike> PuzzleState for RobotState<R> {
     type InitializationArgs = R::InitializationArgs;
 
     fn compose_into(&mut self, alg: &Algorithm) {
-        self.robot.compose_into(alg);
+        match &mut self.pending {
+            Some(pending) => pending.compose_into(alg),
+            None => self.pending = Some(alg.to_owned()),
+        }
+
+        // If the batch cancels itself out, the robot never needs to see it
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|pending| pending.permutation() == &self.perm_group.identity())
+        {
+            self.pending = None;
+        }
     }
 
     fn initialize(perm_group: Arc<PermutationGroup>, args: Self::InitializationArgs) -> Self {
         RobotState {
             perm_group: Arc::clone(&perm_group),
             robot: R::initialize(perm_group, args),
+            pending: None,
         }
     }
 
     fn facelets_solved(&mut self, facelets: &[usize]) -> bool {
+        self.flush_pending();
+
         let state = self.robot.take_picture();
 
         for &facelet in facelets {
@@ -149,6 +193,7 @@ impl<R: RobotLike> PuzzleState for RobotState<R> {
     }
 
     fn print(&mut self, facelets: &[usize], generator: &Algorithm) -> Option<Int<U>> {
+        self.flush_pending();
         let before = self.robot.take_picture().to_owned();
 
         let c = self.halt(facelets, generator)?;
@@ -157,6 +202,7 @@ impl<R: RobotLike> PuzzleState for RobotState<R> {
         exponentiated.exponentiate(c.into());
 
         self.compose_into(&exponentiated);
+        self.flush_pending();
 
         if &before != self.robot.take_picture() {
             eprintln!("Printing did not return the cube to the original state!");
@@ -191,11 +237,44 @@ impl<R: RobotLike> PuzzleState for RobotState<R> {
     }
 
     fn repeat_until(&mut self, facelets: &[usize], generator: &Algorithm) -> Option<()> {
+        self.repeat_until_with_progress(facelets, generator, &mut |_| ())
+    }
+
+    fn repeat_until_with_progress(
+        &mut self,
+        facelets: &[usize],
+        generator: &Algorithm,
+        progress: &mut dyn FnMut(Int<U>),
+    ) -> Option<()> {
         // Halting has the same behavior as repeat_until
-        self.halt(facelets, generator).map(|_| ())
+        let mut generator = generator.to_owned();
+        generator.exponentiate(-Int::<U>::one());
+
+        let mut sum = Int::<U>::zero();
+
+        let chromatic_orders = generator.chromatic_orders_by_facelets();
+        let order = lcm_iter(facelets.iter().map(|&i| chromatic_orders[i]));
+
+        while !self.facelets_solved(facelets) {
+            sum += Int::<U>::one();
+
+            if sum >= order {
+                eprintln!(
+                    "Decoding failure! Performed as many cycles as the size of the register."
+                );
+                return None;
+            }
+
+            self.compose_into(&generator);
+            progress(sum);
+        }
+
+        Some(())
     }
 
     fn solve(&mut self) {
+        // Anything batched is about to be undone anyways
+        self.pending = None;
         self.robot.solve();
     }
 }
@@ -278,6 +357,123 @@ impl RobotLike for SimulatedPuzzle {
     }
 }
 
+/// A drop-in alternative to [`SimulatedPuzzle`] that stores the puzzle state
+/// as a flat byte permutation, in the style of the solver's packed orbit
+/// representations.
+///
+/// Each algorithm's permutation is packed into bytes the first time it is
+/// performed and cached by its move sequence, so the tight loops of
+/// repeat-heavy programs compose `u8`s in place instead of walking a
+/// `Vec<usize>` permutation per instruction. Decoding reconstructs a
+/// [`Permutation`] on demand, which only happens at the rare `print`, `halt`,
+/// and `repeat-until` boundaries.
+pub struct PackedSimulatedPuzzle {
+    perm_group: Arc<PermutationGroup>,
+    /// `state[facelet]` is the facelet it permutes to, like
+    /// [`Permutation::mapping`]
+    state: Box<[u8]>,
+    /// The color of each facelet in the solved state, as indices into the
+    /// group's color list
+    solved_colors: Box<[u8]>,
+    packed_algorithms: HashMap<Vec<ArcIntern<str>>, Box<[u8]>>,
+}
+
+impl PackedSimulatedPuzzle {
+    fn pack(permutation: &Permutation, facelet_count: usize) -> Box<[u8]> {
+        (0..facelet_count)
+            .map(|facelet| {
+                u8::try_from(*permutation.mapping().get(facelet).unwrap_or(&facelet)).unwrap()
+            })
+            .collect()
+    }
+
+    /// Get the state underlying the puzzle as a permutation
+    pub fn unpacked_state(&self) -> Permutation {
+        Permutation::from_mapping(
+            self.state
+                .iter()
+                .map(|&facelet| usize::from(facelet))
+                .collect(),
+        )
+    }
+}
+
+impl PuzzleState for PackedSimulatedPuzzle {
+    type InitializationArgs = ();
+
+    fn initialize(perm_group: Arc<PermutationGroup>, (): ()) -> Self {
+        assert!(
+            perm_group.facelet_count() <= usize::from(u8::MAX) + 1,
+            "The puzzle has too many facelets to pack into bytes"
+        );
+
+        let mut color_indices = Vec::<ArcIntern<str>>::new();
+        let solved_colors = perm_group
+            .facelet_colors()
+            .iter()
+            .map(|color| {
+                let idx = match color_indices.iter().position(|c| c == color) {
+                    Some(idx) => idx,
+                    None => {
+                        color_indices.push(ArcIntern::clone(color));
+                        color_indices.len() - 1
+                    }
+                };
+
+                u8::try_from(idx).unwrap()
+            })
+            .collect();
+
+        PackedSimulatedPuzzle {
+            state: (0..perm_group.facelet_count())
+                .map(|facelet| u8::try_from(facelet).unwrap())
+                .collect(),
+            solved_colors,
+            packed_algorithms: HashMap::new(),
+            perm_group,
+        }
+    }
+
+    fn compose_into(&mut self, alg: &Algorithm) {
+        let facelet_count = self.perm_group.facelet_count();
+
+        let packed = self
+            .packed_algorithms
+            .entry(alg.move_seq_iter().cloned().collect())
+            .or_insert_with(|| Self::pack(alg.permutation(), facelet_count));
+
+        // `state[i]` only depends on itself, so composing in place is fine
+        for facelet in &mut self.state {
+            *facelet = packed[usize::from(*facelet)];
+        }
+    }
+
+    fn facelets_solved(&mut self, facelets: &[usize]) -> bool {
+        facelets.iter().all(|&facelet| {
+            self.solved_colors[usize::from(self.state[facelet])] == self.solved_colors[facelet]
+        })
+    }
+
+    fn print(&mut self, facelets: &[usize], generator: &Algorithm) -> Option<Int<U>> {
+        decode(&self.unpacked_state(), facelets, generator)
+    }
+
+    fn solve(&mut self) {
+        for (facelet, slot) in self.state.iter_mut().enumerate() {
+            *slot = u8::try_from(facelet).unwrap();
+        }
+    }
+
+    fn repeat_until(&mut self, facelets: &[usize], generator: &Algorithm) -> Option<()> {
+        let mut generator = generator.to_owned();
+        generator.exponentiate(-Int::<U>::one());
+        let v = decode(&self.unpacked_state(), facelets, &generator)?;
+        generator.exponentiate(-v);
+        <Self as PuzzleState>::compose_into(self, &generator);
+        Some(())
+    }
+}
+
 /// A collection of the states of every puzzle and theoretical register
 pub struct PuzzleStates<P: PuzzleState> {
     theoretical_states: Vec<TheoreticalState>,
@@ -415,15 +611,7 @@ impl<C: Connection> RobotLike for RemoteRobot<C> {
     fn compose_into(&mut self, alg: &Algorithm) {
         self.current_state = None;
         let writer = self.conn.writer();
-        writeln!(
-            writer,
-            "{}",
-            alg.move_seq_iter()
-                .map(|v| &**v)
-                .collect::<Vec<_>>()
-                .join(" ")
-        )
-        .unwrap();
+        writeln!(writer, "{alg}").unwrap();
         writer.flush().unwrap();
     }
 
@@ -516,11 +704,21 @@ pub fn run_robot_server<C: Connection, R: RobotLike>(
 
 #[cfg(test)]
 mod tests {
-    use std::{io::{self, BufReader, Read, Write}, sync::{Arc, atomic::{AtomicUsize, Ordering}}};
-
-    use qter_core::architectures::{Algorithm, Permutation, PermutationGroup, mk_puzzle_definition};
-
-    use crate::puzzle_states::{RemoteRobot, RobotLike, run_robot_server};
+    use std::{io::{self, BufReader, Read, Write}, sync::{Arc, Mutex, atomic::{AtomicUsize, Ordering}}};
+
+    use compiler::compile;
+    use qter_core::{
+        ByPuzzleType, File, I, Int, Program, U,
+        architectures::{Algorithm, Permutation, PermutationGroup, mk_puzzle_definition},
+    };
+
+    use crate::{
+        Interpreter, PausedState,
+        puzzle_states::{
+            PackedSimulatedPuzzle, PuzzleState, RemoteRobot, RobotLike, RobotState,
+            SimulatedPuzzle, run_robot_server,
+        },
+    };
 
     #[test]
     fn remote_robot() {
@@ -598,4 +796,173 @@ mod tests {
 
         assert_eq!(out, "1 0\n");
     }
+
+    /// Wraps a simulated puzzle, recording the move count of every algorithm
+    /// the robot is asked to perform
+    struct CountingRobot {
+        puzzle: SimulatedPuzzle,
+        batches: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl RobotLike for CountingRobot {
+        type InitializationArgs = Arc<Mutex<Vec<usize>>>;
+
+        fn initialize(
+            perm_group: Arc<PermutationGroup>,
+            batches: Self::InitializationArgs,
+        ) -> Self {
+            CountingRobot {
+                puzzle: <SimulatedPuzzle as RobotLike>::initialize(perm_group, ()),
+                batches,
+            }
+        }
+
+        fn compose_into(&mut self, alg: &Algorithm) {
+            self.batches
+                .lock()
+                .unwrap()
+                .push(alg.move_seq_iter().count());
+            <SimulatedPuzzle as RobotLike>::compose_into(&mut self.puzzle, alg);
+        }
+
+        fn take_picture(&mut self) -> &Permutation {
+            <SimulatedPuzzle as RobotLike>::take_picture(&mut self.puzzle)
+        }
+
+        fn solve(&mut self) {
+            <SimulatedPuzzle as RobotLike>::solve(&mut self.puzzle);
+        }
+    }
+
+    #[test]
+    fn packed_state_matches_permutation_composition() {
+        let cube3 = Arc::clone(&mk_puzzle_definition("3x3").unwrap().perm_group);
+
+        let mut packed = PackedSimulatedPuzzle::initialize(Arc::clone(&cube3), ());
+        let mut reference = SimulatedPuzzle::initialize(Arc::clone(&cube3), ());
+
+        for alg_str in ["R U R' F2", "D2 L'", "B", "R U R' F2"] {
+            let alg = Algorithm::parse_from_string(Arc::clone(&cube3), alg_str).unwrap();
+
+            PuzzleState::compose_into(&mut packed, &alg);
+            PuzzleState::compose_into(&mut reference, &alg);
+
+            assert_eq!(&packed.unpacked_state(), reference.puzzle_state());
+
+            for facelet in 0..cube3.facelet_count() {
+                assert_eq!(
+                    packed.facelets_solved(&[facelet]),
+                    reference.facelets_solved(&[facelet]),
+                );
+            }
+        }
+
+        packed.solve();
+        assert_eq!(packed.unpacked_state(), cube3.identity());
+    }
+
+    fn multiply_transcript<P: PuzzleState<InitializationArgs = ()>>(
+        program: &Arc<Program>,
+    ) -> Vec<String> {
+        let mut interpreter = Interpreter::<P>::new(Arc::clone(program), ());
+        let mut inputs = [Int::<I>::from(7_i64), Int::from(9_i64)].into_iter();
+
+        loop {
+            match interpreter.step_until_halt() {
+                PausedState::Input { .. } => {
+                    interpreter.give_input(inputs.next().unwrap()).unwrap();
+                }
+                PausedState::Halt { .. } => break,
+                PausedState::Panicked => panic!("The multiply program panicked"),
+            }
+        }
+
+        interpreter.state_mut().messages().iter().cloned().collect()
+    }
+
+    #[test]
+    fn packed_and_unpacked_multiply_transcripts_match() {
+        let qat =
+            std::fs::read_to_string("../compiler/tests/multiply/multiply_transform.qat").unwrap();
+
+        let program = match compile(&File::from(qat), |_| unreachable!(), false) {
+            Ok(v) => v,
+            Err(e) => panic!("{e:?}"),
+        };
+        let program = Arc::new(program);
+
+        let unpacked = multiply_transcript::<SimulatedPuzzle>(&program);
+        let packed = multiply_transcript::<PackedSimulatedPuzzle>(&program);
+
+        assert_eq!(unpacked, packed);
+        assert_eq!(packed.last().unwrap(), "(X * Y) mod 30 = 3");
+    }
+
+    #[test]
+    fn robot_state_batches_moves() {
+        let code = "
+            .registers {
+                A, B ← 3x3 builtin (90, 90)
+            }
+
+            input \"A:\" A
+            add A 1
+            print \"A is\" A
+            halt \"Done\"
+        ";
+
+        let program = match compile(&File::from(code), |_| unreachable!(), false) {
+            Ok(v) => v,
+            Err(e) => panic!("{e:?}"),
+        };
+        let program = Arc::new(program);
+
+        let generator_len = match &program.register_by_name("A").unwrap().location {
+            ByPuzzleType::Puzzle((_, (generator, _))) => generator.move_seq_iter().count(),
+            ByPuzzleType::Theoretical(_) => unreachable!(),
+        };
+
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let mut interpreter = Interpreter::<RobotState<CountingRobot>>::new(
+            Arc::clone(&program),
+            Arc::clone(&batches),
+        );
+
+        assert!(matches!(
+            interpreter.step_until_halt(),
+            PausedState::Input { .. }
+        ));
+        interpreter.give_input(Int::from(5_u64)).unwrap();
+        assert!(matches!(
+            interpreter.step_until_halt(),
+            PausedState::Halt { .. }
+        ));
+
+        assert_eq!(interpreter.state_mut().messages()[1], "A is 6");
+
+        // The input and the add reached the robot as a single batch rather
+        // than one algorithm per instruction
+        assert_eq!(batches.lock().unwrap()[0], 6 * generator_len);
+    }
+
+    #[test]
+    fn robot_state_never_sends_a_cancelled_batch() {
+        let cube3 = Arc::clone(&mk_puzzle_definition("3x3").unwrap().perm_group);
+
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let mut state =
+            RobotState::<CountingRobot>::initialize(Arc::clone(&cube3), Arc::clone(&batches));
+
+        let alg = Algorithm::parse_from_string(Arc::clone(&cube3), "R U R' F2").unwrap();
+        let mut inverse = alg.clone();
+        inverse.exponentiate(-Int::<U>::one());
+
+        PuzzleState::compose_into(&mut state, &alg);
+        PuzzleState::compose_into(&mut state, &inverse);
+
+        // Taking a picture flushes the batch, but the batch undid itself so
+        // there is nothing to perform
+        assert!(state.facelets_solved(&[0]));
+        assert!(batches.lock().unwrap().is_empty());
+    }
 }
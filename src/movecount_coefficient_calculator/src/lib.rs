@@ -1,10 +1,176 @@
 #![warn(clippy::pedantic)]
-#![allow(unused_variables, dead_code)]
+#![allow(dead_code, clippy::too_many_lines)]
 
 //! A Rust port of the [Movecount Coefficient Calculator](https://trangium.github.io/MovecountCoefficient/)
 //! adapted with permission.
 
-// Very blatantly copy pasted from a single pass of AI transpilation
+use serde::{Deserialize, Serialize};
+
+/// Why a move sequence could not be scored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlgSpeedError {
+    /// A move in the sequence is not recognized
+    InvalidMove(String),
+    /// A wide move appears in the sequence but `allow_wide` is off
+    WideMoveDisallowed(String),
+    /// The sequence contains no moves at all
+    EmptySequence,
+    /// No grip configuration can perform the sequence without running a wrist
+    /// out of rotation on its very first move
+    Infeasible,
+}
+
+impl core::fmt::Display for AlgSpeedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlgSpeedError::InvalidMove(move_str) => write!(f, "Invalid move: {move_str}"),
+            AlgSpeedError::WideMoveDisallowed(move_str) => write!(
+                f,
+                "Wide move {move_str} is not allowed; enable `allow_wide` to score wide moves"
+            ),
+            AlgSpeedError::EmptySequence => write!(f, "The sequence contains no moves"),
+            AlgSpeedError::Infeasible => {
+                write!(f, "No feasible grip configuration exists for this sequence")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AlgSpeedError {}
+
+/// A tuning value that cannot be used for scoring
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidConfigValue {
+    /// The name of the offending field
+    pub field: &'static str,
+}
+
+impl core::fmt::Display for InvalidConfigValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid value for `{}`: tuning values must be non-negative finite numbers",
+            self.field
+        )
+    }
+}
+
+impl std::error::Error for InvalidConfigValue {}
+
+/// Why a speed profile file could not be loaded
+#[derive(Debug)]
+pub enum SpeedProfileError {
+    /// The file is not valid TOML for a speed profile
+    Toml(toml::de::Error),
+    /// A tuning value in the profile is out of range
+    InvalidValue(InvalidConfigValue),
+}
+
+impl core::fmt::Display for SpeedProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpeedProfileError::Toml(e) => write!(f, "Invalid speed profile: {e}"),
+            SpeedProfileError::InvalidValue(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SpeedProfileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SpeedProfileError::Toml(e) => Some(e),
+            SpeedProfileError::InvalidValue(e) => Some(e),
+        }
+    }
+}
+
+/// A single move in standard cube notation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    /// The letter of the face (`R`), slice (`M`), or rotation (`x`) being
+    /// turned, normalized to uppercase for faces and slices and lowercase
+    /// for rotations
+    pub face: char,
+    /// How many layers turn together: 1 for a face move, 2 for `Rw`/`r`/`2R`,
+    /// 3 for `3Rw`, …
+    pub width: u8,
+    /// How many quarter turns: 1, or 2 for a double turn
+    pub amount: u8,
+    /// Whether the turn is counterclockwise
+    pub prime: bool,
+}
+
+/// Parse a single move in standard notation
+///
+/// Accepts face moves (`R`, `U2`, `F'`), slices (`M`, `s2`), rotations
+/// (`x`, `y'`), and wide moves in each of the common notations: lowercase
+/// (`r`), `w` suffixed (`Rw2`), and layer-count prefixed (`2U'`, `3Rw`).
+///
+/// # Errors
+///
+/// Returns [`AlgSpeedError::InvalidMove`] if `s` is not a recognized move.
+pub fn parse_move(s: &str) -> Result<Move, AlgSpeedError> {
+    let invalid = || AlgSpeedError::InvalidMove(s.to_owned());
+
+    let (rest, prime) = match s.strip_suffix('\'') {
+        Some(rest) => (rest, true),
+        None => (s, false),
+    };
+    let (rest, amount) = match rest.strip_suffix('2') {
+        Some(rest) => (rest, 2),
+        None => (rest, 1),
+    };
+    let (rest, wide_suffix) = match rest.strip_suffix('w') {
+        Some(rest) => (rest, true),
+        None => (rest, false),
+    };
+
+    let digit_count = rest.chars().take_while(char::is_ascii_digit).count();
+    let (prefix, face_str) = rest.split_at(digit_count);
+    let prefix_width = if prefix.is_empty() {
+        None
+    } else {
+        Some(prefix.parse::<u8>().map_err(|_| invalid())?)
+    };
+
+    let mut chars = face_str.chars();
+    let (Some(letter), None) = (chars.next(), chars.next()) else {
+        return Err(invalid());
+    };
+
+    let (face, mut width) = match letter {
+        'R' | 'U' | 'F' | 'D' | 'L' | 'B' => (letter, 1),
+        'r' | 'u' | 'f' | 'd' | 'l' | 'b' => (letter.to_ascii_uppercase(), 2),
+        'M' | 'S' | 'E' | 'm' | 's' | 'e' => (letter.to_ascii_uppercase(), 1),
+        'x' | 'y' | 'z' | 'X' | 'Y' | 'Z' => (letter.to_ascii_lowercase(), 1),
+        _ => return Err(invalid()),
+    };
+
+    let is_face = matches!(face, 'R' | 'U' | 'F' | 'D' | 'L' | 'B');
+
+    // `w` marks a wide move and only faces have layers to widen
+    if wide_suffix {
+        if !is_face {
+            return Err(invalid());
+        }
+        width = width.max(2);
+    }
+
+    // A layer-count prefix like `2U` or `3Rw` names how many layers turn
+    if let Some(prefix_width) = prefix_width {
+        if !is_face || prefix_width < 2 {
+            return Err(invalid());
+        }
+        width = prefix_width;
+    }
+
+    Ok(Move {
+        face,
+        width,
+        amount,
+        prime,
+    })
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Location {
@@ -71,12 +237,63 @@ impl HandState {
                 .max(self.middle.last_move_time.max(self.ring.last_move_time)),
         )
     }
+
+    /// Put every finger back on its home position, as happens during a
+    /// rotation where the whole hand comes off the puzzle
+    fn reset_grip(&mut self) {
+        self.wrist = 0;
+        for finger in [
+            &mut self.thumb,
+            &mut self.index,
+            &mut self.middle,
+            &mut self.ring,
+        ] {
+            finger.location = Location::Home;
+        }
+    }
 }
 
-#[derive(Debug)]
+/// Which hand performs a move, or that the hands come off the puzzle first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+    /// The move is preceded by a regrip — either one the search inserted or a
+    /// rotation, which takes both hands off the puzzle
+    Regrip,
+}
+
+impl Hand {
+    fn other(self) -> Hand {
+        match self {
+            Hand::Left => Hand::Right,
+            Hand::Right => Hand::Left,
+            // Only a real hand can block the other one
+            Hand::Regrip => unreachable!(),
+        }
+    }
+}
+
+/// Which part of the hand performs a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FingerRole {
+    Thumb,
+    Index,
+    Middle,
+    Ring,
+    Wrist,
+}
+
+/// The grip configurations the regrip search tries at the start of the
+/// sequence and again after every inserted regrip
+const GRIP_CONFIGURATIONS: [(i8, i8); 5] = [(0, 0), (0, -1), (0, 1), (-1, 0), (1, 0)];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AlgSpeedConfig {
     ignore_errors: bool,
     ignore_auf: bool,
+    allow_wide: bool,
     wrist_mult: f64,
     push_mult: f64,
     ring_mult: f64,
@@ -94,6 +311,7 @@ impl Default for AlgSpeedConfig {
         Self {
             ignore_errors: false,
             ignore_auf: false,
+            allow_wide: false,
             wrist_mult: 0.8,
             push_mult: 1.3,
             ring_mult: 1.4,
@@ -108,6 +326,132 @@ impl Default for AlgSpeedConfig {
     }
 }
 
+impl AlgSpeedConfig {
+    /// Start building a config from the default tuning values
+    ///
+    /// ```
+    /// use movecount_coefficient_calculator::AlgSpeedConfig;
+    ///
+    /// let config = AlgSpeedConfig::builder()
+    ///     .wrist_mult(0.7)
+    ///     .rotation(4.0)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[must_use]
+    pub fn builder() -> AlgSpeedConfigBuilder {
+        AlgSpeedConfigBuilder {
+            config: AlgSpeedConfig::default(),
+        }
+    }
+
+    /// Load a speed profile from TOML; fields absent from the profile keep
+    /// their default values
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TOML doesn't parse or a tuning value is
+    /// negative or not finite.
+    pub fn from_toml(profile: &str) -> Result<Self, SpeedProfileError> {
+        let config: AlgSpeedConfig = toml::from_str(profile).map_err(SpeedProfileError::Toml)?;
+        config.validate().map_err(SpeedProfileError::InvalidValue)?;
+        Ok(config)
+    }
+
+    /// Serialize the config as a TOML speed profile
+    ///
+    /// # Errors
+    ///
+    /// Serializing a config never fails in practice; the error is toml's.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+
+    fn validate(&self) -> Result<(), InvalidConfigValue> {
+        for (field, value) in [
+            ("wrist_mult", self.wrist_mult),
+            ("push_mult", self.push_mult),
+            ("ring_mult", self.ring_mult),
+            ("destabilize", self.destabilize),
+            ("add_regrip", self.add_regrip),
+            ("double", self.double),
+            ("seslice_mult", self.seslice_mult),
+            ("over_work_mult", self.over_work_mult),
+            ("move_block", self.move_block),
+            ("rotation", self.rotation),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(InvalidConfigValue { field });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! builder_setters {
+    ($($field:ident),* $(,)?) => {
+        $(
+            #[must_use]
+            pub fn $field(mut self, $field: f64) -> Self {
+                self.config.$field = $field;
+                self
+            }
+        )*
+    };
+}
+
+/// Builder for [`AlgSpeedConfig`], exposing the same tuning knobs as the
+/// sliders of the original web tool
+#[derive(Debug, Clone)]
+pub struct AlgSpeedConfigBuilder {
+    config: AlgSpeedConfig,
+}
+
+impl AlgSpeedConfigBuilder {
+    builder_setters!(
+        wrist_mult,
+        push_mult,
+        ring_mult,
+        destabilize,
+        add_regrip,
+        double,
+        seslice_mult,
+        over_work_mult,
+        move_block,
+        rotation,
+    );
+
+    #[must_use]
+    pub fn ignore_errors(mut self, ignore_errors: bool) -> Self {
+        self.config.ignore_errors = ignore_errors;
+        self
+    }
+
+    #[must_use]
+    pub fn ignore_auf(mut self, ignore_auf: bool) -> Self {
+        self.config.ignore_auf = ignore_auf;
+        self
+    }
+
+    #[must_use]
+    pub fn allow_wide(mut self, allow_wide: bool) -> Self {
+        self.config.allow_wide = allow_wide;
+        self
+    }
+
+    /// Validate the tuning values and produce the config
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first field whose value is negative or not
+    /// finite.
+    pub fn build(self) -> Result<AlgSpeedConfig, InvalidConfigValue> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
 pub struct AlgSpeed {
     config: AlgSpeedConfig,
 }
@@ -132,19 +476,88 @@ impl AlgSpeed {
         }
     }
 
-    fn process_sequence(&self, sequence: &str) -> Result<f64, String> {
+    /// Score a space separated move sequence; lower means the sequence is
+    /// faster to perform for an average solver
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sequence is empty, contains an unrecognized
+    /// move, or contains a wide move while `allow_wide` is off, unless the
+    /// config asks for errors to be ignored.
+    pub fn score(&self, alg: &str) -> Result<f64, AlgSpeedError> {
+        if alg.split_whitespace().next().is_none() {
+            return Err(AlgSpeedError::EmptySequence);
+        }
+
+        self.score_moves(&self.effective_moves(alg))
+    }
+
+    /// Score a move sequence with the default [`AlgSpeedConfig`]
+    ///
+    /// # Errors
+    ///
+    /// See [`AlgSpeed::score`].
+    pub fn score_default(alg: &str) -> Result<f64, AlgSpeedError> {
+        AlgSpeed::new(AlgSpeedConfig::default()).score(alg)
+    }
+
+    /// The movecount coefficient of the sequence; the same number as
+    /// [`AlgSpeed::score`], under the name the original web tool uses
+    ///
+    /// Unlike a raw move count, the coefficient weighs every move by how
+    /// awkward it is to perform: regrips, rotations, and overworked fingers
+    /// all push it up, so a long sequence of comfortable moves can score
+    /// below a shorter awkward one.
+    ///
+    /// # Errors
+    ///
+    /// See [`AlgSpeed::score`].
+    pub fn coefficient(&self, sequence: &str) -> Result<f64, AlgSpeedError> {
+        self.score(sequence)
+    }
+
+    /// The coefficient divided by the number of moves actually scored, after
+    /// AUF and unknown moves are filtered out; useful for comparing sequences
+    /// of different lengths
+    ///
+    /// # Errors
+    ///
+    /// See [`AlgSpeed::score`]; a sequence whose moves are all filtered out
+    /// counts as empty.
+    pub fn coefficient_per_move(&self, sequence: &str) -> Result<f64, AlgSpeedError> {
+        let moves = self.effective_moves(sequence);
+
+        if moves.is_empty() {
+            return Err(AlgSpeedError::EmptySequence);
+        }
+
+        #[expect(clippy::cast_precision_loss)]
+        let move_count = moves.len() as f64;
+
+        Ok(self.score_moves(&moves)? / move_count)
+    }
+
+    /// Parse a move and apply the config's move policy: wide moves are only
+    /// accepted when `allow_wide` is on
+    fn check_move(&self, move_str: &str) -> Result<Move, AlgSpeedError> {
+        let move_ = parse_move(move_str)?;
+
+        if move_.width > 1 && !self.config.allow_wide {
+            return Err(AlgSpeedError::WideMoveDisallowed(move_str.to_owned()));
+        }
+
+        Ok(move_)
+    }
+
+    /// The moves that actually get scored: whitespace split, with unknown and
+    /// disallowed moves dropped under `ignore_errors` and leading/trailing
+    /// AUF trimmed under `ignore_auf`
+    fn effective_moves(&self, sequence: &str) -> Vec<String> {
         let split_seq: Vec<&str> = sequence.split_whitespace().collect();
         let true_split_seq: Vec<String> = if self.config.ignore_errors {
             split_seq
                 .into_iter()
-                .filter(|&move_str| {
-                    let valid_moves = [
-                        "r", "r2", "r'", "u", "u'", "u2", "f", "f2", "f'", "d", "d2", "d'", "l",
-                        "l2", "l'", "b", "b2", "b'", "m", "m2", "m'", "s", "s2", "s'", "e", "e2",
-                        "e'", "x", "x'", "x2", "y", "y'", "y2", "z", "z'", "z2",
-                    ];
-                    valid_moves.contains(&move_str.to_lowercase().as_str())
-                })
+                .filter(|&move_str| self.check_move(move_str).is_ok())
                 .map(String::from)
                 .collect()
         } else {
@@ -184,59 +597,468 @@ impl AlgSpeed {
             }
         }
 
-        let initial_tests = vec![
-            self.test_sequence(&final_seq, 0, 0, 0.0),
-            self.test_sequence(&final_seq, 0, -1, 1.0 + self.config.add_regrip),
-            self.test_sequence(&final_seq, 0, 1, 1.0 + self.config.add_regrip),
-            self.test_sequence(&final_seq, -1, 0, 1.0 + self.config.add_regrip),
-            self.test_sequence(&final_seq, 1, 0, 1.0 + self.config.add_regrip),
-        ];
+        final_seq
+    }
+
+    /// Each move of the sequence paired with the incremental cost it added to
+    /// the speed in the best-found grip configuration; the deltas sum to the
+    /// scalar [`AlgSpeed::coefficient`]
+    ///
+    /// The setup cost a move triggers — a pre-rotated starting grip or a
+    /// regrip forced by the moves before it — is attributed to that move.
+    ///
+    /// # Errors
+    ///
+    /// See [`AlgSpeed::score`].
+    pub fn coefficient_breakdown(
+        &self,
+        sequence: &str,
+    ) -> Result<Vec<(String, f64)>, AlgSpeedError> {
+        if sequence.split_whitespace().next().is_none() {
+            return Err(AlgSpeedError::EmptySequence);
+        }
+
+        let moves = self.effective_moves(sequence);
+        let (_, segments) = self.find_best_speed(&moves, self.initial_tests(&moves))?;
+
+        let mut breakdown = Vec::with_capacity(moves.len());
+        let mut prev = 0.0;
+
+        for (idx, segment) in segments.iter().enumerate() {
+            let end = segments.get(idx + 1).map_or(moves.len(), |next| next.start);
+
+            let mut trace = Vec::new();
+            self.test_sequence(
+                &moves[segment.start..end],
+                segment.l_grip,
+                segment.r_grip,
+                segment.initial_speed,
+                Some(&mut trace),
+                None,
+            )?;
+
+            for (move_str, speed) in moves[segment.start..end].iter().zip(trace) {
+                breakdown.push((move_str.clone(), speed - prev));
+                prev = speed;
+            }
+        }
+
+        Ok(breakdown)
+    }
+
+    /// Which hand performs each move of the sequence in the best-found grip
+    /// configuration
+    ///
+    /// A move that the hands come off the puzzle for — the first move after a
+    /// regrip the search inserted, or a rotation — reports [`Hand::Regrip`]
+    /// instead of the hand, at exactly the indices where the regrips were
+    /// inserted.
+    ///
+    /// # Errors
+    ///
+    /// See [`AlgSpeed::score`].
+    pub fn fingering(&self, sequence: &str) -> Result<Vec<Hand>, AlgSpeedError> {
+        if sequence.split_whitespace().next().is_none() {
+            return Err(AlgSpeedError::EmptySequence);
+        }
+
+        let moves = self.effective_moves(sequence);
+        let (_, segments) = self.find_best_speed(&moves, self.initial_tests(&moves))?;
+
+        let mut hands = Vec::with_capacity(moves.len());
+
+        for (idx, segment) in segments.iter().enumerate() {
+            let end = segments.get(idx + 1).map_or(moves.len(), |next| next.start);
+
+            let mut segment_hands = Vec::new();
+            self.test_sequence(
+                &moves[segment.start..end],
+                segment.l_grip,
+                segment.r_grip,
+                segment.initial_speed,
+                None,
+                Some(&mut segment_hands),
+            )?;
+
+            // The first move of every later segment is where the hands come
+            // back onto the puzzle
+            if idx > 0 {
+                segment_hands[0] = Hand::Regrip;
+            }
+
+            hands.extend(segment_hands);
+        }
+
+        Ok(hands)
+    }
+
+    fn score_moves(&self, final_seq: &[String]) -> Result<f64, AlgSpeedError> {
+        self.find_best_speed(final_seq, self.initial_tests(final_seq))
+            .map(|(speed, _)| speed)
+    }
 
-        self.find_best_speed(initial_tests, &final_seq)
+    /// The grip candidates every search starts from
+    fn initial_tests(&self, final_seq: &[String]) -> Vec<GripTest> {
+        GRIP_CONFIGURATIONS
+            .into_iter()
+            .map(|(l_grip, r_grip)| {
+                // The hands start off the puzzle, so a neutral grip is free
+                // while a pre-rotated wrist costs a regrip
+                let initial_speed = if (l_grip, r_grip) == (0, 0) {
+                    0.0
+                } else {
+                    1.0 + self.config.add_regrip
+                };
+
+                GripTest {
+                    l_grip,
+                    r_grip,
+                    initial_speed,
+                    start: 0,
+                    history: Vec::new(),
+                    result: self.test_sequence(final_seq, l_grip, r_grip, initial_speed, None, None),
+                }
+            })
+            .collect()
     }
 
+    /// Simulate performing the sequence with the given starting wrist grips,
+    /// accumulating the cost of every move into the returned speed
+    ///
+    /// When a `trace` is supplied it receives the accumulated speed after
+    /// each move, which [`AlgSpeed::coefficient_breakdown`] turns into
+    /// per-move deltas. When `hands` is supplied it receives the hand that
+    /// performed each move, which [`AlgSpeed::fingering`] reports.
     fn test_sequence(
         &self,
         sequence: &[String],
         l_grip: i8,
         r_grip: i8,
         initial_speed: f64,
-    ) -> TestResult {
-        let left = HandState::new(l_grip);
-        let right = HandState::new(r_grip);
-        let speed = initial_speed;
-        let grip = 1;
-        let ud_grip = -1;
-        // let mut prev_speed = None;
-        // let mut first_move_speed = None;
+        mut trace: Option<&mut Vec<f64>>,
+        mut hands: Option<&mut Vec<Hand>>,
+    ) -> Result<TestResult, AlgSpeedError> {
+        let mut left = HandState::new(l_grip);
+        let mut right = HandState::new(r_grip);
+        let mut speed = initial_speed;
+        // The hand that performed the previous move; the other hand can start
+        // its move while this one finishes, discounted by `move_block`
+        let mut prev_hand: Option<Hand> = None;
+        let mut first_regrip = -1_i32;
 
         for (i, move_str) in sequence.iter().enumerate() {
-            // Process move logic here...
-            // This would be a very large match statement handling all possible moves
-            // Similar to the JavaScript switch statement but in Rust style
+            let move_ = self.check_move(move_str)?;
+            let prime = move_.prime;
+            let double = move_.amount == 2;
+
+            let turn_mult = if double { self.config.double } else { 1.0 };
+
+            // Rotations take the whole puzzle out of both grips
+            if matches!(move_.face, 'x' | 'y' | 'z') {
+                speed += self.config.rotation * turn_mult;
+                left.reset_grip();
+                right.reset_grip();
+                prev_hand = None;
+
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.push(speed);
+                }
+                if let Some(hands) = hands.as_deref_mut() {
+                    hands.push(Hand::Regrip);
+                }
+                continue;
+            }
+
+            // A wide move is the face move performed while dragging extra
+            // layers along, which destabilizes the grip
+            let wide = move_.width > 1;
+
+            let (hand, role, location, mut cost) = match (move_.face, prime) {
+                ('R', _) => (
+                    Hand::Right,
+                    FingerRole::Wrist,
+                    Location::RDown,
+                    self.config.wrist_mult,
+                ),
+                ('L', _) => (
+                    Hand::Left,
+                    FingerRole::Wrist,
+                    Location::Top,
+                    self.config.wrist_mult,
+                ),
+                ('U', false) => (Hand::Right, FingerRole::Index, Location::UFlick, 1.0),
+                ('U', true) => (Hand::Left, FingerRole::Index, Location::UFlick, 1.0),
+                ('D', false) => (
+                    Hand::Right,
+                    FingerRole::Ring,
+                    Location::DFlick,
+                    self.config.ring_mult,
+                ),
+                ('D', true) => (
+                    Hand::Left,
+                    FingerRole::Ring,
+                    Location::DFlick,
+                    self.config.ring_mult,
+                ),
+                ('F', false) => (
+                    Hand::Right,
+                    FingerRole::Thumb,
+                    Location::FFlick,
+                    self.config.push_mult,
+                ),
+                ('F', true) => (
+                    Hand::Left,
+                    FingerRole::Thumb,
+                    Location::FFlick,
+                    self.config.push_mult,
+                ),
+                // The back face is an awkward reach no matter which hand
+                ('B', false) => (
+                    Hand::Right,
+                    FingerRole::Middle,
+                    Location::Bottom,
+                    self.config.push_mult + self.config.destabilize,
+                ),
+                ('B', true) => (
+                    Hand::Left,
+                    FingerRole::Middle,
+                    Location::Bottom,
+                    self.config.push_mult + self.config.destabilize,
+                ),
+                ('M', _) => (
+                    Hand::Right,
+                    FingerRole::Ring,
+                    Location::MFlick,
+                    self.config.seslice_mult,
+                ),
+                ('S', _) => (
+                    Hand::Right,
+                    FingerRole::Thumb,
+                    Location::SFlick,
+                    self.config.seslice_mult * self.config.push_mult,
+                ),
+                ('E', _) => (
+                    Hand::Right,
+                    FingerRole::Ring,
+                    Location::EFlick,
+                    self.config.seslice_mult * self.config.ring_mult,
+                ),
+                // `parse_move` only produces faces, slices, and rotations,
+                // and rotations were handled above
+                _ => unreachable!(),
+            };
+
+            cost *= turn_mult;
+
+            if wide {
+                cost += self.config.destabilize;
+            }
+
+            if prev_hand == Some(hand.other()) {
+                cost *= self.config.move_block;
+            }
+
+            let hand_state = match hand {
+                Hand::Left => &mut left,
+                Hand::Right => &mut right,
+            };
+
+            // Finger moves are less stable while the wrist is rotated away
+            // from home
+            if role != FingerRole::Wrist && hand_state.wrist != 0 {
+                cost += self.config.destabilize * f64::from(hand_state.wrist.abs());
+            }
+
+            speed += cost;
+
+            if role == FingerRole::Wrist {
+                let mut delta: i8 = if prime { -1 } else { 1 };
+                if hand == Hand::Left {
+                    delta = -delta;
+                }
+                if double {
+                    delta *= 2;
+                }
+
+                hand_state.wrist += delta;
+
+                // The wrist can't turn further; the hand has to come off and
+                // regrip
+                if hand_state.wrist.abs() > 1 {
+                    speed += self.config.add_regrip;
+                    hand_state.wrist = 0;
+
+                    if first_regrip < 0 {
+                        first_regrip = i32::try_from(i).unwrap_or(i32::MAX);
+                    }
+                }
+
+                hand_state.thumb.location = location;
+                hand_state.thumb.last_move_time = speed;
+            } else {
+                let finger = match role {
+                    FingerRole::Thumb => &mut hand_state.thumb,
+                    FingerRole::Index => &mut hand_state.index,
+                    FingerRole::Middle => &mut hand_state.middle,
+                    FingerRole::Ring => &mut hand_state.ring,
+                    FingerRole::Wrist => unreachable!(),
+                };
+
+                // Reusing a finger that hasn't made it back to where this
+                // move needs it overworks the finger
+                speed += self.calc_overwork(finger, location, self.config.over_work_mult, speed);
+
+                finger.location = location;
+                finger.last_move_time = speed;
+            }
+
+            prev_hand = Some(hand);
+
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.push(speed);
+            }
+            if let Some(hands) = hands.as_deref_mut() {
+                hands.push(hand);
+            }
         }
 
-        TestResult {
-            move_index: -1,
+        Ok(TestResult {
+            move_index: first_regrip,
             speed,
-            left_wrist: l_grip,
-            right_wrist: r_grip,
+            left_wrist: left.wrist,
+            right_wrist: right.wrist,
             left_time: left.max_finger_time(),
             right_time: right.max_finger_time(),
-        }
+        })
     }
 
+    /// Search for the fastest way to perform the sequence, starting from the
+    /// grip candidates produced by [`AlgSpeed::score_moves`]
+    ///
+    /// Whenever a candidate reports a forced regrip, the sequence is split
+    /// right before the move that forced it and the remainder is re-run from
+    /// every grip configuration. The lowest speed among candidates that finish
+    /// without a forced regrip wins, returned along with the segments of its
+    /// configuration.
     fn find_best_speed(
         &self,
-        initial_tests: Vec<TestResult>,
         sequence: &[String],
-    ) -> Result<f64, String> {
-        // Implementation of the speed finding algorithm
-        // This would replace the while(true) loop from JavaScript
-        Ok(0.0) // Placeholder
+        initial_tests: Vec<GripTest>,
+    ) -> Result<(f64, Vec<Segment>), AlgSpeedError> {
+        let mut pending = initial_tests;
+        let mut best = f64::INFINITY;
+        let mut best_segments = Vec::new();
+
+        while let Some(test) = pending.pop() {
+            let result = test.result?;
+
+            // Nothing downstream of this candidate can beat the best speed
+            // found so far
+            if test.initial_speed >= best {
+                continue;
+            }
+
+            let segment = Segment {
+                start: test.start,
+                l_grip: test.l_grip,
+                r_grip: test.r_grip,
+                initial_speed: test.initial_speed,
+            };
+
+            if result.move_index < 0 {
+                let total = result.speed.max(result.left_time).max(result.right_time);
+                if total < best {
+                    best = total;
+                    best_segments = test.history.clone();
+                    best_segments.push(segment);
+                }
+                continue;
+            }
+
+            // A wrist ran out of rotation; unless the overflowing move is the
+            // very first one tested, try coming off the puzzle right before
+            // it instead
+            let split = test.start + usize::try_from(result.move_index).unwrap();
+            if split == test.start {
+                continue;
+            }
+
+            let prefix = self.test_sequence(
+                &sequence[test.start..split],
+                test.l_grip,
+                test.r_grip,
+                test.initial_speed,
+                None,
+                None,
+            )?;
+            let regripped =
+                prefix.speed.max(prefix.left_time).max(prefix.right_time) + self.config.add_regrip;
+
+            for (l_grip, r_grip) in GRIP_CONFIGURATIONS {
+                // Pre-rotating a wrist during the regrip costs extra, just as
+                // it does at the start of the sequence
+                let initial_speed = if (l_grip, r_grip) == (0, 0) {
+                    regripped
+                } else {
+                    regripped + 1.0
+                };
+
+                let mut history = test.history.clone();
+                history.push(segment);
+
+                pending.push(GripTest {
+                    l_grip,
+                    r_grip,
+                    initial_speed,
+                    start: split,
+                    history,
+                    result: self.test_sequence(
+                        &sequence[split..],
+                        l_grip,
+                        r_grip,
+                        initial_speed,
+                        None,
+                        None,
+                    ),
+                });
+            }
+        }
+
+        if best.is_finite() {
+            Ok((best, best_segments))
+        } else {
+            Err(AlgSpeedError::Infeasible)
+        }
     }
 }
 
+/// One grip configuration tried during the regrip search, along with the
+/// simulation it produced
+#[derive(Debug)]
+struct GripTest {
+    l_grip: i8,
+    r_grip: i8,
+    /// Speed accumulated before the tested part of the sequence begins
+    initial_speed: f64,
+    /// Index into the full sequence where the tested part begins
+    start: usize,
+    /// The completed segments whose regrips led to this candidate
+    history: Vec<Segment>,
+    result: Result<TestResult, AlgSpeedError>,
+}
+
+/// One contiguous run of moves between regrips in a grip configuration
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    /// Index into the full sequence where the run begins
+    start: usize,
+    l_grip: i8,
+    r_grip: i8,
+    /// Speed accumulated before the run begins, including the cost of the
+    /// regrip that started it
+    initial_speed: f64,
+}
+
 #[derive(Debug)]
 struct TestResult {
     move_index: i32,
@@ -246,3 +1068,337 @@ struct TestResult {
     left_time: f64,
     right_time: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coefficient(sequence: &str) -> f64 {
+        AlgSpeed::score_default(sequence).unwrap()
+    }
+
+    #[test]
+    fn sexy_move_has_a_nonzero_coefficient() {
+        let sexy = coefficient("R U R' U'");
+        assert!(sexy > 0.0);
+        assert!(sexy.is_finite());
+        // Scoring must be deterministic
+        assert!((sexy - coefficient("R U R' U'")).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn longer_sequences_cost_more() {
+        // The T perm begins with the sexy move, and every extra move has a
+        // positive cost
+        assert!(coefficient("R U R' U' R' F R2 U' R' U' R U R' F'") > coefficient("R U R' U'"));
+        // The sune
+        assert!(coefficient("R U R' U R U2 R'") > coefficient("R U R'"));
+    }
+
+    #[test]
+    fn slice_heavy_sequences_pay_the_slice_multiplier() {
+        assert!(coefficient("M") > coefficient("R"));
+        // The slice heavy H perm
+        assert!(coefficient("M2 U M2 U2 M2 U M2") > 0.0);
+    }
+
+    #[test]
+    fn rotations_are_expensive() {
+        assert!(coefficient("x") > coefficient("R"));
+    }
+
+    #[test]
+    fn feasible_alg_scores_as_its_single_pass() {
+        let alg = AlgSpeed::new(AlgSpeedConfig::default());
+        let seq: Vec<String> = "R U R' U'".split_whitespace().map(String::from).collect();
+
+        let single = alg.test_sequence(&seq, 0, 0, 0.0, None, None).unwrap();
+        assert_eq!(single.move_index, -1);
+        let total = single.speed.max(single.left_time).max(single.right_time);
+
+        // The neutral grip never regrips, so the search settles on its single
+        // pass
+        assert!((alg.score("R U R' U'").unwrap() - total).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn forced_regrip_costs_more_than_the_naive_pass() {
+        let alg = AlgSpeed::new(AlgSpeedConfig::default());
+        let seq: Vec<String> = "R2 R2".split_whitespace().map(String::from).collect();
+
+        // Two double turns overflow the wrist from any starting grip, so
+        // every candidate has to pay for a regrip somewhere
+        let naive = alg.test_sequence(&seq, 0, 0, 0.0, None, None).unwrap();
+        assert!(naive.move_index >= 0);
+        let naive_total = naive.speed.max(naive.left_time).max(naive.right_time);
+
+        assert!(alg.score("R2 R2").unwrap() > naive_total);
+    }
+
+    #[test]
+    fn invalid_moves_are_rejected_or_ignored() {
+        assert!(matches!(
+            AlgSpeed::score_default("R Q"),
+            Err(AlgSpeedError::InvalidMove(move_str)) if move_str == "Q"
+        ));
+
+        // With `ignore_errors` the unknown move is dropped instead
+        let ignoring = AlgSpeed::new(AlgSpeedConfig {
+            ignore_errors: true,
+            ..AlgSpeedConfig::default()
+        });
+        assert!((ignoring.score("R Q U").unwrap() - coefficient("R U")).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn empty_sequences_are_rejected() {
+        assert_eq!(
+            AlgSpeed::score_default(""),
+            Err(AlgSpeedError::EmptySequence)
+        );
+        assert_eq!(
+            AlgSpeed::score_default("   "),
+            Err(AlgSpeedError::EmptySequence)
+        );
+    }
+
+    #[test]
+    fn moves_parse_in_standard_notations() {
+        assert_eq!(
+            parse_move("Rw2"),
+            Ok(Move {
+                face: 'R',
+                width: 2,
+                amount: 2,
+                prime: false
+            })
+        );
+        assert_eq!(
+            parse_move("2U'"),
+            Ok(Move {
+                face: 'U',
+                width: 2,
+                amount: 1,
+                prime: true
+            })
+        );
+        assert_eq!(
+            parse_move("3Rw"),
+            Ok(Move {
+                face: 'R',
+                width: 3,
+                amount: 1,
+                prime: false
+            })
+        );
+        assert_eq!(
+            parse_move("r"),
+            Ok(Move {
+                face: 'R',
+                width: 2,
+                amount: 1,
+                prime: false
+            })
+        );
+        assert_eq!(
+            parse_move("x'"),
+            Ok(Move {
+                face: 'x',
+                width: 1,
+                amount: 1,
+                prime: true
+            })
+        );
+
+        for garbage in ["Q3", "Rx", "2x", "Mw", "", "R22", "w"] {
+            assert!(
+                matches!(
+                    parse_move(garbage),
+                    Err(AlgSpeedError::InvalidMove(move_str)) if move_str == garbage
+                ),
+                "{garbage:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn wide_notations_score_like_their_lowercase_equivalent() {
+        let wide = AlgSpeed::new(AlgSpeedConfig::builder().allow_wide(true).build().unwrap());
+        let coefficient = |sequence: &str| wide.score(sequence).unwrap();
+
+        assert!((coefficient("Rw U") - coefficient("r U")).abs() < f64::EPSILON);
+        assert!((coefficient("2U'") - coefficient("u'")).abs() < f64::EPSILON);
+
+        // dragging the extra layer along destabilizes the grip
+        assert!(coefficient("Rw") > coefficient("R"));
+    }
+
+    #[test]
+    fn wide_moves_require_allow_wide() {
+        // `allow_wide` defaults off, so a wide move is an error
+        assert!(matches!(
+            AlgSpeed::score_default("Rw U Rw'"),
+            Err(AlgSpeedError::WideMoveDisallowed(move_str)) if move_str == "Rw"
+        ));
+
+        let wide = AlgSpeed::new(AlgSpeedConfig::builder().allow_wide(true).build().unwrap());
+        assert!(wide.score("Rw U Rw'").unwrap() > 0.0);
+
+        // With `ignore_errors` the disallowed wide moves are dropped instead
+        let ignoring = AlgSpeed::new(
+            AlgSpeedConfig::builder().ignore_errors(true).build().unwrap(),
+        );
+        assert!((ignoring.score("Rw U Rw'").unwrap() - coefficient("U")).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn coefficient_per_move_divides_by_effective_moves() {
+        let alg = AlgSpeed::new(AlgSpeedConfig::default());
+
+        assert!(
+            (alg.coefficient("R U R' U'").unwrap() - coefficient("R U R' U'")).abs()
+                < f64::EPSILON
+        );
+        assert!(
+            (alg.coefficient_per_move("R U R' U'").unwrap() - coefficient("R U R' U'") / 4.0)
+                .abs()
+                < f64::EPSILON
+        );
+
+        // AUF moves are trimmed before the move count is taken
+        let ignoring_auf = AlgSpeed::new(
+            AlgSpeedConfig::builder().ignore_auf(true).build().unwrap(),
+        );
+        assert!(
+            (ignoring_auf.coefficient_per_move("U R U R' U'").unwrap()
+                - ignoring_auf.coefficient("R U R'").unwrap() / 3.0)
+                .abs()
+                < f64::EPSILON
+        );
+
+        // a sequence that trims to nothing cannot be scored per move
+        assert_eq!(
+            ignoring_auf.coefficient_per_move("U"),
+            Err(AlgSpeedError::EmptySequence)
+        );
+    }
+
+    #[test]
+    fn breakdown_deltas_sum_to_the_coefficient() {
+        let alg = AlgSpeed::new(AlgSpeedConfig::default());
+
+        let breakdown = alg.coefficient_breakdown("R U R' U'").unwrap();
+        let tokens: Vec<&str> = breakdown.iter().map(|(move_str, _)| &**move_str).collect();
+        assert_eq!(tokens, ["R", "U", "R'", "U'"]);
+
+        // The deltas telescope back to the scalar, up to rounding
+        let sum: f64 = breakdown.iter().map(|&(_, delta)| delta).sum();
+        assert!((sum - alg.coefficient("R U R' U'").unwrap()).abs() < 1e-9);
+
+        // A forced regrip's cost lands on the move that forced it, and the
+        // sum invariant holds across the regrip
+        let breakdown = alg.coefficient_breakdown("R2 R2").unwrap();
+        assert_eq!(breakdown.len(), 2);
+        assert!(breakdown.iter().all(|&(_, delta)| delta > 0.0));
+        let sum: f64 = breakdown.iter().map(|&(_, delta)| delta).sum();
+        assert!((sum - alg.coefficient("R2 R2").unwrap()).abs() < 1e-9);
+
+        assert_eq!(
+            alg.coefficient_breakdown("  "),
+            Err(AlgSpeedError::EmptySequence)
+        );
+    }
+
+    #[test]
+    fn fingering_reports_hands_and_regrips() {
+        let alg = AlgSpeed::new(AlgSpeedConfig::default());
+
+        // Plain `U` turns are all right-index flicks
+        assert_eq!(
+            alg.fingering("U U U").unwrap(),
+            [Hand::Right, Hand::Right, Hand::Right]
+        );
+
+        // The sexy move alternates wrists and index fingers
+        assert_eq!(
+            alg.fingering("R U R' U'").unwrap(),
+            [Hand::Right, Hand::Right, Hand::Right, Hand::Left]
+        );
+
+        // A rotation takes both hands off the puzzle
+        assert_eq!(alg.fingering("x R").unwrap(), [Hand::Regrip, Hand::Right]);
+
+        // Two double wrist turns force a regrip, and it lands at the index
+        // where `find_best_speed` split the sequence
+        assert_eq!(
+            alg.fingering("R2 R2").unwrap(),
+            [Hand::Right, Hand::Regrip]
+        );
+
+        assert_eq!(alg.fingering(" "), Err(AlgSpeedError::EmptySequence));
+    }
+
+    #[test]
+    fn tuning_a_multiplier_changes_the_coefficient() {
+        let slow_rotations = AlgSpeedConfig::builder().rotation(10.0).build().unwrap();
+        assert!(AlgSpeed::new(slow_rotations).score("x R U").unwrap() > coefficient("x R U"));
+
+        let light_wrist = AlgSpeedConfig::builder().wrist_mult(0.4).build().unwrap();
+        assert!(AlgSpeed::new(light_wrist).score("R U R' U'").unwrap() < coefficient("R U R' U'"));
+    }
+
+    #[test]
+    fn builder_rejects_negative_and_nan_values() {
+        assert_eq!(
+            AlgSpeedConfig::builder().wrist_mult(-0.1).build().unwrap_err(),
+            InvalidConfigValue {
+                field: "wrist_mult"
+            }
+        );
+        assert_eq!(
+            AlgSpeedConfig::builder().rotation(f64::NAN).build().unwrap_err(),
+            InvalidConfigValue { field: "rotation" }
+        );
+
+        assert!(AlgSpeedConfig::builder().build().is_ok());
+    }
+
+    #[test]
+    fn speed_profiles_load_from_toml() {
+        // a partial profile only overrides the fields it names
+        let config = AlgSpeedConfig::from_toml("wrist_mult = 0.4").unwrap();
+        assert!(AlgSpeed::new(config).score("R").unwrap() < coefficient("R"));
+
+        let round_tripped =
+            AlgSpeedConfig::from_toml(&AlgSpeedConfig::default().to_toml().unwrap()).unwrap();
+        assert!(
+            (AlgSpeed::new(round_tripped).score("R U R' U'").unwrap()
+                - coefficient("R U R' U'"))
+            .abs()
+                < f64::EPSILON
+        );
+
+        assert!(matches!(
+            AlgSpeedConfig::from_toml("wrist_mult = \"fast\""),
+            Err(SpeedProfileError::Toml(_))
+        ));
+        assert!(matches!(
+            AlgSpeedConfig::from_toml("double = -1.0"),
+            Err(SpeedProfileError::InvalidValue(InvalidConfigValue {
+                field: "double"
+            }))
+        ));
+    }
+
+    #[test]
+    fn prime_and_double_variants_parse() {
+        for face in [
+            "R", "U", "F", "D", "L", "B", "M", "S", "E", "x", "y", "z", "r", "u", "f",
+        ] {
+            for suffix in ["", "'", "2"] {
+                let score = AlgSpeed::score_default(&format!("{face}{suffix}")).unwrap();
+                assert!(score > 0.0, "{face}{suffix}");
+            }
+        }
+    }
+}
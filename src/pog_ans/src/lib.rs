@@ -348,9 +348,176 @@ pub fn ans_decode<S: State, FSM: CodingFSM<S>>(
     Some(output)
 }
 
+/// Spread `range_left` over the nonzero entries of `ranges` evenly, leaving
+/// every nonzero entry at least one
+pub fn rest_unweighted(ranges: &mut [u16], mut range_left: usize) {
+    for v in ranges.iter_mut() {
+        if *v != 0 {
+            *v = 1;
+        }
+    }
+
+    let mut amt_to_set = ranges.iter().filter(|v| **v != 0).count();
+
+    for dist_spot in ranges.iter_mut() {
+        if *dist_spot == 0 {
+            continue;
+        }
+
+        let range_to_take = ((range_left + amt_to_set) / amt_to_set).saturating_sub(1);
+        range_left -= range_to_take;
+        *dist_spot += range_to_take as u16;
+        amt_to_set -= 1;
+    }
+}
+
+/// Spread `range_left` over the nonzero entries of `ranges` proportionally to
+/// `distribution`, leaving every nonzero entry at least one
+pub fn rest_weighted(ranges: &mut [u16], mut range_left: usize, distribution: &[u32]) {
+    let mut total_weight = 0;
+    let mut amt_to_set = 0;
+
+    ranges.iter_mut().enumerate().for_each(|(i, v)| {
+        if *v != 0 {
+            *v = 1;
+
+            total_weight += distribution[i] as usize;
+            amt_to_set += 1;
+        }
+    });
+
+    let mut order = (0..ranges.len()).collect::<Vec<_>>();
+    order.sort_unstable_by_key(|&i| distribution[i]);
+
+    for i in order {
+        let dist_spot = &mut ranges[i];
+
+        if *dist_spot == 0 {
+            continue;
+        }
+
+        let range_available = range_left + amt_to_set;
+
+        let range_to_take =
+            (range_available * distribution[i] as usize / total_weight).saturating_sub(1);
+        range_left -= range_to_take;
+        *dist_spot += range_to_take as u16;
+        total_weight -= distribution[i] as usize;
+        amt_to_set -= 1;
+    }
+}
+
+/// Which face and axis each move of an alphabet turns.
+///
+/// Moves that turn the same face share a `faces` entry and moves on parallel
+/// layers share an `axes` entry. The metadata comes from a puzzle's geometry,
+/// but it is plain data so that this crate doesn't have to know about any
+/// particular puzzle.
+#[derive(Clone, Debug)]
+pub struct MoveRelations {
+    pub faces: Vec<usize>,
+    pub axes: Vec<usize>,
+}
+
+impl MoveRelations {
+    /// Whether `next` may follow `prev` in a canonical move sequence
+    #[must_use]
+    pub fn allows(&self, prev: usize, next: usize) -> bool {
+        // The same face twice always collapses into fewer moves...
+        if self.faces[prev] == self.faces[next] {
+            return false;
+        }
+
+        // ...and moves on parallel layers commute, so canonical sequences
+        // order them by face
+        !(self.axes[prev] == self.axes[next] && self.faces[next] < self.faces[prev])
+    }
+}
+
+#[derive(Debug)]
+struct MoveSequenceData {
+    relations: MoveRelations,
+    frequencies: Vec<u32>,
+}
+
+/// A [`CodingFSM`] for move sequences over a configurable alphabet,
+/// predicting each move from the one before it.
+///
+/// Pairs that [`MoveRelations::allows`] rules out get a range of zero
+/// (structural zeros); every other move gets a range proportional to its
+/// frequency, never less than one so that the coder can always represent it.
+#[derive(Clone, Debug)]
+pub struct MoveSequenceModel {
+    data: Rc<MoveSequenceData>,
+    prev: Option<usize>,
+}
+
+// Only cache based off of the values used by the range generator
+impl PartialEq for MoveSequenceModel {
+    fn eq(&self, other: &Self) -> bool {
+        self.prev == other.prev
+    }
+}
+
+impl Eq for MoveSequenceModel {}
+
+impl Hash for MoveSequenceModel {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.prev.hash(state);
+    }
+}
+
+impl MoveSequenceModel {
+    /// # Panics
+    ///
+    /// Panics if the relation tables and `frequencies` disagree on the size
+    /// of the alphabet
+    #[must_use]
+    pub fn new(relations: MoveRelations, frequencies: Vec<u32>) -> Self {
+        assert_eq!(relations.faces.len(), relations.axes.len());
+        assert_eq!(relations.faces.len(), frequencies.len());
+
+        MoveSequenceModel {
+            data: Rc::new(MoveSequenceData {
+                relations,
+                frequencies,
+            }),
+            prev: None,
+        }
+    }
+}
+
+impl CodingFSM<u16> for MoveSequenceModel {
+    fn symbol_count(&self) -> usize {
+        self.data.frequencies.len()
+    }
+
+    fn found_symbol(&mut self, symbol: usize) {
+        self.prev = Some(symbol);
+    }
+
+    fn predict_next_symbol(&self, out: &mut [u16]) {
+        let mut range_left = 1 << u8::BITS;
+
+        for (sym, spot) in out.iter_mut().enumerate() {
+            if self
+                .prev
+                .is_none_or(|prev| self.data.relations.allows(prev, sym))
+            {
+                *spot = 1;
+                range_left -= 1;
+            } else {
+                *spot = 0;
+            }
+        }
+
+        rest_weighted(out, range_left, &self.data.frequencies);
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{Cache, CodingFSM, ans_decode, ans_encode};
+    use crate::{Cache, CodingFSM, MoveRelations, MoveSequenceModel, ans_decode, ans_encode};
 
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
     struct Fsm {
@@ -426,4 +593,64 @@ mod tests {
         .unwrap();
         assert_eq!(decoded, v);
     }
+
+    /// 18 moves: three powers of each of six faces; opposite faces share an
+    /// axis
+    fn cube_relations() -> MoveRelations {
+        MoveRelations {
+            faces: (0..18).map(|i| i / 3).collect(),
+            axes: (0..18).map(|i| i / 6).collect(),
+        }
+    }
+
+    #[test]
+    fn test_move_model_structural_zeros() {
+        let mut model = MoveSequenceModel::new(cube_relations(), vec![1; 18]);
+
+        // Predict what may follow a move of face 1
+        model.found_symbol(3);
+        let mut ranges = [0u16; 18];
+        model.predict_next_symbol(&mut ranges);
+
+        for (sym, range) in ranges.iter().enumerate() {
+            // Face 1 itself is banned and face 0 shares its axis but is
+            // ordered before it; everything else must stay representable
+            if sym / 3 <= 1 {
+                assert_eq!(*range, 0, "symbol {sym}");
+            } else {
+                assert_ne!(*range, 0, "symbol {sym}");
+            }
+        }
+
+        assert!(ranges.iter().copied().map(usize::from).sum::<usize>() <= 1 << u8::BITS);
+    }
+
+    #[test]
+    fn test_move_model_roundtrip() {
+        let relations = cube_relations();
+
+        // A canonical sequence; a disallowed pair would hit the zero-range
+        // assert in the coder
+        let mut symbols = vec![0_usize];
+        for step in 1..40 {
+            let prev = *symbols.last().unwrap();
+            let next = (0..18)
+                .map(|i| (i + step * 7) % 18)
+                .find(|&sym| relations.allows(prev, sym))
+                .unwrap();
+            symbols.push(next);
+        }
+
+        let model = MoveSequenceModel::new(relations, (1..=18).collect());
+
+        let mut encoded = Vec::new();
+        ans_encode(&mut encoded, &symbols, Cache::new(model.clone()));
+        let decoded = ans_decode(
+            &mut encoded.iter().copied(),
+            Some(symbols.len()),
+            Cache::new(model),
+        )
+        .unwrap();
+        assert_eq!(decoded, symbols);
+    }
 }
@@ -34,12 +34,10 @@ pub struct PlaneCut {
 
 impl CutSurface for PlaneCut {
     fn region(&self, point: Point) -> Option<ArcIntern<str>> {
-        match self
-            .normal
-            .clone()
-            .dot(point.0 - self.spot.clone())
-            .cmp_zero()
-        {
+        let mut diff = point.0;
+        diff -= &self.spot;
+
+        match self.normal.dot_ref(&diff).cmp_zero() {
             std::cmp::Ordering::Less => None,
             std::cmp::Ordering::Equal => {
                 panic!("Argument to region should not be exactly on the boundary")
@@ -49,15 +47,20 @@ impl CutSurface for PlaneCut {
     }
 
     fn on_boundary(&self, point: Point) -> bool {
-        self.normal
-            .clone()
-            .dot(point.0 - self.spot.clone())
-            .is_zero()
+        let mut diff = point.0;
+        diff -= &self.spot;
+
+        self.normal.dot_ref(&diff).is_zero()
     }
 
     fn boundaries_between(&self, a: Point, b: Point) -> Vec<Point> {
-        let a_dot = self.normal.clone().dot(a.0.clone() - self.spot.clone());
-        let b_dot = self.normal.clone().dot(b.0.clone() - self.spot.clone());
+        let mut a_diff = a.0.clone();
+        a_diff -= &self.spot;
+        let a_dot = self.normal.dot_ref(&a_diff);
+
+        let mut b_diff = b.0.clone();
+        b_diff -= &self.spot;
+        let b_dot = self.normal.dot_ref(&b_diff);
 
         if a_dot.cmp_zero() == b_dot.cmp_zero() {
             return vec![];
@@ -156,11 +159,18 @@ pub(crate) fn do_cut<S: CutSurface + ?Sized>(
             .map(|(a, b)| {
                 let middle = Point(a.0.clone() / &Num::from(2) + (b.0.clone() / &Num::from(2)));
 
+                let mut a_2d = Vector::zero();
+                let mut shifted = a.0.clone();
+                shifted -= &subspace_info.offset;
+                subspace_info.make_2d.mul_vec_into(&shifted, &mut a_2d);
+
+                let mut b_2d = Vector::zero();
+                shifted = b.0.clone();
+                shifted -= &subspace_info.offset;
+                subspace_info.make_2d.mul_vec_into(&shifted, &mut b_2d);
+
                 (
-                    (
-                        &subspace_info.make_2d * &(a.0.clone() - subspace_info.offset.clone()),
-                        &subspace_info.make_2d * &(b.0.clone() - subspace_info.offset.clone()),
-                    ),
+                    (a_2d, b_2d),
                     if surface.on_boundary(middle.clone()) {
                         None
                     } else {
@@ -174,6 +184,7 @@ pub(crate) fn do_cut<S: CutSurface + ?Sized>(
     let mut faces = Vec::new();
 
     let ninety_deg = Matrix::new([[0, 1], [-1, 0]]);
+    let mut rotated = Vector::zero();
 
     while edges.len() >= 3 {
         // Merge collinear edges
@@ -181,11 +192,15 @@ pub(crate) fn do_cut<S: CutSurface + ?Sized>(
         while i < edges.len() && edges.len() > 1 {
             let a = edges.prev().unwrap();
             let b = edges.spot().unwrap();
-            if a.1 == b.1
-                && (&ninety_deg * &(a.0.1.clone() - a.0.0.clone()))
-                    .dot(b.0.1.clone() - a.0.0.clone())
-                    .is_zero()
-            {
+            if a.1 == b.1 && {
+                let mut leg_a = a.0.1.clone();
+                leg_a -= &a.0.0;
+                let mut leg_b = b.0.1.clone();
+                leg_b -= &a.0.0;
+                ninety_deg.mul_vec_into(&leg_a, &mut rotated);
+
+                rotated.dot_ref(&leg_b).is_zero()
+            } {
                 edges.prev_mut().unwrap().0.1 = b.0.1.clone();
                 edges.remove_spot();
                 continue;
@@ -1,12 +1,17 @@
 use internment::ArcIntern;
+use itertools::Itertools;
+use log::warn;
 use qter_core::Span;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     num::{NonZeroU8, NonZeroU16},
     sync::{Arc, LazyLock},
 };
 use thiserror::Error;
 
-use crate::{PuzzleGeometryDefinition, knife::{CutSurface, PlaneCut}, num::{Num, Vector}, shapes::{CUBE, DODECAHEDRON}};
+use crate::{MoveRelation, PuzzleGeometry, PuzzleGeometryDefinition, knife::{CutSurface, PlaneCut}, num::{Num, Vector}, shapes::{CUBE, DODECAHEDRON, TETRAHEDRON}};
 
 /// A representation of a puzzle in the `KSolve` format. We choose to remain
 /// consistent with `KSolve` format and terminology because it is the
@@ -18,6 +23,12 @@ pub struct KSolve {
     pub(crate) sets: Vec<KSolveSet>,
     pub(crate) moves: Vec<KSolveMove>,
     pub(crate) symmetries: Vec<KSolveMove>,
+    /// The solved state of the puzzle, when it isn't the identity labeling.
+    /// The standard `KSolve` format allows a solved-state definition per set,
+    /// which is needed for imported definitions whose pieces aren't labeled
+    /// in solved order or that have indistinguishable pieces.
+    pub(crate) solved_state: Option<KSolveTransformation>,
+    pub(crate) move_relations: Option<HashMap<ArcIntern<str>, MoveRelation>>,
 }
 
 /// A piece orbit of a `KSolve` puzzle, or "Set" to remain consistent with the
@@ -65,11 +76,37 @@ impl KSolve {
         &self.symmetries
     }
 
+    /// Get how every move relates to its base move and its inverse, if the
+    /// puzzle was generated with that metadata
+    #[must_use]
+    pub fn move_relations(&self) -> Option<&HashMap<ArcIntern<str>, MoveRelation>> {
+        self.move_relations.as_ref()
+    }
+
     /// Get the solved state of the puzzle
+    ///
+    /// This is the identity labeling unless the puzzle defines an explicit
+    /// solved state.
+    #[must_use]
+    pub fn solved(&self) -> KSolveTransformation {
+        match &self.solved_state {
+            Some(solved_state) => solved_state.clone(),
+            None => self.identity_transformation(),
+        }
+    }
+
+    /// Get the explicitly defined solved state of the puzzle, or `None` if
+    /// the solved state is the identity labeling
+    #[must_use]
+    pub fn solved_state(&self) -> Option<&KSolveTransformation> {
+        self.solved_state.as_ref()
+    }
+
+    /// Get the identity transformation of the puzzle
     #[must_use]
     // Should not panic
     #[allow(clippy::missing_panics_doc)]
-    pub fn solved(&self) -> KSolveTransformation {
+    pub fn identity_transformation(&self) -> KSolveTransformation {
         self.sets
             .iter()
             .map(|ksolve_set| {
@@ -81,8 +118,78 @@ impl KSolve {
             .collect()
     }
 
+    /// Verify the orientation conventions that the rest of qter relies on.
+    ///
+    /// The orientation numbers assigned by puzzle geometry are unspecified,
+    /// but they always satisfy two invariants that phase1's orientation
+    /// assumptions and the solver's orientation arithmetic depend on:
+    ///
+    /// * For every move and every set, the orientation deltas sum to zero
+    ///   modulo the set's orientation count. A turn cannot twist a single
+    ///   piece in isolation.
+    /// * A move that permutes a set trivially must carry all-zero orientation
+    ///   deltas on that set; otherwise repeating it would never return the
+    ///   set to solved.
+    ///
+    /// # Errors
+    ///
+    /// Returns a report naming the offending move and set if either invariant
+    /// is violated.
+    pub fn check_orientation_invariants(&self) -> Result<(), OrientationInvariantError> {
+        for ksolve_move in self.moves.iter().chain(self.symmetries.iter()) {
+            for (transformation, ksolve_set) in ksolve_move.transformation.iter().zip(&self.sets) {
+                let orientation_count = ksolve_set.orientation_count.get();
+
+                let delta_sum = transformation
+                    .iter()
+                    .map(|&(_, orientation_delta)| u32::from(orientation_delta))
+                    .sum::<u32>();
+
+                if delta_sum % u32::from(orientation_count) != 0 {
+                    return Err(OrientationInvariantError::NonZeroOrientationSum {
+                        move_name: ksolve_move.name.clone(),
+                        set_name: ksolve_set.name.clone(),
+                        delta_sum,
+                        orientation_count,
+                    });
+                }
+
+                let is_identity = transformation
+                    .iter()
+                    .enumerate()
+                    .all(|(i, &(perm, _))| perm.get() as usize == i + 1);
+
+                if is_identity
+                    && let Some((piece, _)) = transformation
+                        .iter()
+                        .find_position(|&&(_, orientation_delta)| orientation_delta != 0)
+                {
+                    return Err(OrientationInvariantError::IdentityMoveWithOrientation {
+                        move_name: ksolve_move.name.clone(),
+                        set_name: ksolve_set.name.clone(),
+                        piece: piece as u16 + 1,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     #[must_use]
     pub fn with_moves(self, moves: &[&str]) -> Self {
+        // A relation that mentions a removed move is dangling, so only keep
+        // the relations that stay entirely within the retained moves
+        let move_relations = self.move_relations.map(|relations| {
+            relations
+                .into_iter()
+                .filter(|(name, relation)| {
+                    moves.contains(&&**name)
+                        && moves.contains(&&*relation.base)
+                        && moves.contains(&&*relation.inverse)
+                })
+                .collect()
+        });
         let moves = self
             .moves
             .into_iter()
@@ -93,10 +200,352 @@ impl KSolve {
             sets: self.sets,
             moves,
             symmetries: self.symmetries,
+            solved_state: self.solved_state,
+            move_relations,
+        }
+    }
+
+    /// Composes two transformations into the transformation equivalent to
+    /// applying `first` and then `second`
+    #[must_use]
+    pub fn compose_transformations(
+        &self,
+        first: &KSolveTransformation,
+        second: &KSolveTransformation,
+    ) -> KSolveTransformation {
+        first
+            .iter()
+            .zip(second)
+            .zip(&self.sets)
+            .map(|((first, second), ksolve_set)| {
+                second
+                    .iter()
+                    .map(|&(perm, orientation_delta)| {
+                        let (prev_perm, prev_orientation_delta) =
+                            first[usize::from(perm.get()) - 1];
+
+                        (
+                            prev_perm,
+                            (prev_orientation_delta + orientation_delta)
+                                % ksolve_set.orientation_count.get(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Re-expresses each move of `solution` as a product of moves from
+    /// `target_moves`, so that e.g. a solution containing half turns can be
+    /// executed on hardware that only supports quarter turns. Each move is
+    /// replaced by the fewest repetitions of a single target move with the
+    /// same transformation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first move of the solution that is not a
+    /// power of any target move.
+    pub fn rebase_solution<'a>(
+        &self,
+        solution: &[&KSolveMove],
+        target_moves: &'a [KSolveMove],
+    ) -> Result<Vec<&'a KSolveMove>, RebaseError> {
+        let identity = self.identity_transformation();
+
+        let mut rebased = Vec::new();
+
+        for &ksolve_move in solution {
+            let mut best: Option<(usize, &'a KSolveMove)> = None;
+
+            for target in target_moves {
+                let mut power = target.transformation.clone();
+                let mut count = 1;
+
+                loop {
+                    if power == ksolve_move.transformation {
+                        if best.is_none_or(|(best_count, _)| count < best_count) {
+                            best = Some((count, target));
+                        }
+                        break;
+                    }
+
+                    if power == identity {
+                        break;
+                    }
+
+                    power = self.compose_transformations(&power, &target.transformation);
+                    count += 1;
+                }
+            }
+
+            match best {
+                Some((count, target)) => {
+                    rebased.extend(std::iter::repeat_n(target, count));
+                }
+                None => return Err(RebaseError(ksolve_move.name.clone())),
+            }
+        }
+
+        Ok(rebased)
+    }
+
+    /// Serialize the puzzle into the `ksolve` text format that ksolve and
+    /// twsearch accept: a `Name` header, a `Set` line per set, the solved
+    /// state, and a `Move` block per move. The permutation vectors are
+    /// 1-indexed, following the text format convention, and every set's
+    /// permutation line is followed by its orientation line.
+    // Writing to a `String` cannot fail
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn to_def_string(&self) -> String {
+        use std::fmt::Write as _;
+
+        fn write_transformation(
+            out: &mut String,
+            sets: &[KSolveSet],
+            transformation: &KSolveTransformation,
+        ) {
+            for (ksolve_set, perm_and_ori) in sets.iter().zip(transformation) {
+                writeln!(out, "{}", ksolve_set.name).unwrap();
+                writeln!(
+                    out,
+                    "{}",
+                    perm_and_ori.iter().map(|&(perm, _)| perm).join(" ")
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "{}",
+                    perm_and_ori.iter().map(|&(_, orientation)| orientation).join(" ")
+                )
+                .unwrap();
+            }
+        }
+
+        let mut out = String::new();
+
+        writeln!(out, "Name {}", self.name).unwrap();
+        writeln!(out).unwrap();
+
+        for ksolve_set in &self.sets {
+            writeln!(
+                out,
+                "Set {} {} {}",
+                ksolve_set.name, ksolve_set.piece_count, ksolve_set.orientation_count
+            )
+            .unwrap();
+        }
+
+        writeln!(out).unwrap();
+        writeln!(out, "Solved").unwrap();
+        write_transformation(&mut out, &self.sets, &self.solved());
+        writeln!(out, "End").unwrap();
+
+        for ksolve_move in &self.moves {
+            writeln!(out).unwrap();
+            writeln!(out, "Move {}", ksolve_move.name).unwrap();
+            write_transformation(&mut out, &self.sets, &ksolve_move.transformation);
+            writeln!(out, "End").unwrap();
         }
+
+        out
+    }
+
+    /// Parse a puzzle from the `ksolve` text format, the inverse of
+    /// [`KSolve::to_def_string`]. This accepts externally authored definition
+    /// files: `#` comments, omitted orientation lines (which default to all
+    /// zeros), and sets left out of a `Solved` or `Move` block (which default
+    /// to the identity) are all allowed. Sections that qter has no use for,
+    /// such as `ForbiddenPairs`, are skipped with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the definition is structurally malformed, or if
+    /// the transformations it declares are invalid: permutation vectors must
+    /// be 1-indexed bijections (solved states may repeat labels to mark
+    /// pieces as indistinguishable) and orientation values must be below the
+    /// set's orientation count.
+    pub fn parse_def(input: &str) -> Result<KSolve, KSolveParseError> {
+        fn identity_of(sets: &[KSolveSet]) -> KSolveTransformation {
+            sets.iter()
+                .map(|ksolve_set| {
+                    (1..=ksolve_set.piece_count.get())
+                        .map(|i| (i.try_into().unwrap(), 0))
+                        .collect()
+                })
+                .collect()
+        }
+
+        fn parse_transformation_block(
+            lines: &mut std::iter::Peekable<impl Iterator<Item = (usize, &str)>>,
+            sets: &[KSolveSet],
+            section: &str,
+        ) -> Result<KSolveTransformation, KSolveParseError> {
+            let mut transformation = identity_of(sets);
+
+            loop {
+                let Some((line_number, line)) = lines.next() else {
+                    return Err(KSolveParseError::UnterminatedSection(section.to_owned()));
+                };
+
+                if line == "End" {
+                    return Ok(transformation);
+                }
+
+                let Some(set_idx) = sets.iter().position(|ksolve_set| ksolve_set.name == line)
+                else {
+                    return Err(KSolveParseError::UnknownSet {
+                        line: line_number,
+                        name: line.to_owned(),
+                    });
+                };
+
+                let Some((perm_line_number, perm_line)) = lines.next() else {
+                    return Err(KSolveParseError::UnterminatedSection(section.to_owned()));
+                };
+                let permutation = perm_line
+                    .split_whitespace()
+                    .map(|token| token.parse::<NonZeroU16>().ok())
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(KSolveParseError::Malformed {
+                        line: perm_line_number,
+                        expected: "1-indexed piece numbers",
+                    })?;
+
+                // The orientation line is optional; a line of exactly as many
+                // numbers as the permutation line is taken to be one
+                let orientations = match lines.peek() {
+                    Some(&(_, next))
+                        if next.split_whitespace().count() == permutation.len()
+                            && next
+                                .split_whitespace()
+                                .all(|token| token.parse::<u8>().is_ok()) =>
+                    {
+                        lines
+                            .next()
+                            .unwrap()
+                            .1
+                            .split_whitespace()
+                            .map(|token| token.parse::<u8>().unwrap())
+                            .collect()
+                    }
+                    _ => vec![0; permutation.len()],
+                };
+
+                transformation[set_idx] = permutation.into_iter().zip(orientations).collect();
+            }
+        }
+
+        // Strip comments and blank lines up front, keeping line numbers for
+        // error reporting
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or("").trim()))
+            .filter(|&(_, line)| !line.is_empty())
+            .peekable();
+
+        let mut name = None;
+        let mut sets: Vec<KSolveSet> = Vec::new();
+        let mut moves = Vec::new();
+        let mut solved_state = None;
+
+        while let Some((line_number, line)) = lines.next() {
+            let (keyword, rest) = match line.split_once(char::is_whitespace) {
+                Some((keyword, rest)) => (keyword, rest.trim()),
+                None => (line, ""),
+            };
+
+            match keyword {
+                "Name" => {
+                    if rest.is_empty() {
+                        return Err(KSolveParseError::Malformed {
+                            line: line_number,
+                            expected: "Name <name>",
+                        });
+                    }
+                    name = Some(rest.to_owned());
+                }
+                "Set" => {
+                    let malformed = KSolveParseError::Malformed {
+                        line: line_number,
+                        expected: "Set <name> <piece count> <orientation count>",
+                    };
+                    let mut tokens = rest.split_whitespace();
+                    let (Some(set_name), Some(piece_count), Some(orientation_count), None) =
+                        (tokens.next(), tokens.next(), tokens.next(), tokens.next())
+                    else {
+                        return Err(malformed);
+                    };
+                    let (Ok(piece_count), Ok(orientation_count)) = (
+                        piece_count.parse::<NonZeroU16>(),
+                        orientation_count.parse::<NonZeroU8>(),
+                    ) else {
+                        return Err(malformed);
+                    };
+                    sets.push(KSolveSet {
+                        name: set_name.to_owned(),
+                        piece_count,
+                        orientation_count,
+                    });
+                }
+                "Solved" => {
+                    let transformation = parse_transformation_block(&mut lines, &sets, "Solved")?;
+                    // An identity solved state carries no information; leave
+                    // it implicit so round-trips compare equal
+                    solved_state = (transformation != identity_of(&sets)).then_some(transformation);
+                }
+                "Move" => {
+                    if rest.is_empty() {
+                        return Err(KSolveParseError::Malformed {
+                            line: line_number,
+                            expected: "Move <name>",
+                        });
+                    }
+                    let transformation = parse_transformation_block(&mut lines, &sets, rest)?;
+                    moves.push(KSolveMove {
+                        transformation,
+                        name: rest.to_owned(),
+                    });
+                }
+                _ => {
+                    warn!("Ignoring unknown section `{keyword}` in a ksolve definition");
+                    // A bare keyword opens a block ended by `End`; a keyword
+                    // with arguments is a single-line directive
+                    if rest.is_empty() {
+                        for (_, skipped) in lines.by_ref() {
+                            if skipped == "End" {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let name = name.ok_or(KSolveParseError::MissingName)?;
+
+        if sets.is_empty() {
+            return Err(KSolveParseError::NoSets);
+        }
+
+        Ok(KSolve::try_from(KSolveFields {
+            name,
+            sets,
+            moves,
+            symmetries: Vec::new(),
+            solved_state,
+            move_relations: None,
+        })?)
     }
 }
 
+/// Produced by [`KSolve::rebase_solution`] when a solution move is not
+/// expressible in the target move basis
+#[derive(Error, Debug)]
+#[error("The move {0} cannot be expressed as a product of the target moves")]
+pub struct RebaseError(pub String);
+
 impl KSolveSet {
     /// Get the name of the set
     #[must_use]
@@ -146,12 +595,152 @@ impl KSolveMove {
     }
 }
 
+/// A piece orbit of a [`KSolveJson`] puzzle
+#[cfg(feature = "json")]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KSolveSetJson {
+    pub name: String,
+    pub piece_count: u16,
+    pub orientation_count: u8,
+}
+
+/// A transformation of a [`KSolveJson`] puzzle
+///
+/// The permutation vectors are 1-indexed, consistent with the `KSolve` text
+/// format, unless the document's `zero_indexed` flag is set.
+#[cfg(feature = "json")]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KSolveMoveJson {
+    pub name: String,
+    pub transformation: Vec<Vec<(u16, u8)>>,
+}
+
+/// A serializable snapshot of a [`KSolve`] following the stable JSON schema
+/// of [`qter_core::json`]
+///
+/// Move relations are not part of the document since they are generation
+/// metadata rather than part of the puzzle itself.
+#[cfg(feature = "json")]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KSolveJson {
+    /// See [`qter_core::json::SCHEMA_VERSION`]
+    pub schema_version: u32,
+    pub name: String,
+    pub sets: Vec<KSolveSetJson>,
+    pub moves: Vec<KSolveMoveJson>,
+    pub symmetries: Vec<KSolveMoveJson>,
+    /// The explicitly defined solved state, or `null` when the solved state
+    /// is the identity labeling. Indexed like move transformations.
+    pub solved_state: Option<Vec<Vec<(u16, u8)>>>,
+    /// Whether the transformations and solved state are 0-indexed instead of
+    /// the 1-indexed `KSolve` text format convention
+    #[serde(default)]
+    pub zero_indexed: bool,
+}
+
+#[cfg(feature = "json")]
+fn ksolve_move_json(ksolve_move: &KSolveMove, zero_indexed: bool) -> KSolveMoveJson {
+    let offset = u16::from(zero_indexed);
+
+    KSolveMoveJson {
+        name: ksolve_move.name.clone(),
+        transformation: ksolve_move
+            .transformation
+            .iter()
+            .map(|perm_and_ori| {
+                perm_and_ori
+                    .iter()
+                    .map(|&(p, o)| (p.get() - offset, o))
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+#[cfg(feature = "json")]
+impl KSolveJson {
+    /// Export a [`KSolve`], emitting the transformations and solved state
+    /// 0-indexed when `zero_indexed` is set
+    #[must_use]
+    pub fn new(ksolve: &KSolve, zero_indexed: bool) -> KSolveJson {
+        let offset = u16::from(zero_indexed);
+
+        KSolveJson {
+            schema_version: qter_core::json::SCHEMA_VERSION,
+            name: ksolve.name.clone(),
+            sets: ksolve
+                .sets
+                .iter()
+                .map(|set| KSolveSetJson {
+                    name: set.name.clone(),
+                    piece_count: set.piece_count.get(),
+                    orientation_count: set.orientation_count.get(),
+                })
+                .collect(),
+            moves: ksolve
+                .moves
+                .iter()
+                .map(|ksolve_move| ksolve_move_json(ksolve_move, zero_indexed))
+                .collect(),
+            symmetries: ksolve
+                .symmetries
+                .iter()
+                .map(|ksolve_move| ksolve_move_json(ksolve_move, zero_indexed))
+                .collect(),
+            solved_state: ksolve.solved_state.as_ref().map(|solved_state| {
+                solved_state
+                    .iter()
+                    .map(|perm_and_ori| {
+                        perm_and_ori
+                            .iter()
+                            .map(|&(p, o)| (p.get() - offset, o))
+                            .collect()
+                    })
+                    .collect()
+            }),
+            zero_indexed,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&KSolve> for KSolveJson {
+    fn from(ksolve: &KSolve) -> Self {
+        KSolveJson::new(ksolve, false)
+    }
+}
+
 /// A possibly invalid `KSolve` puzzle representation
 pub(crate) struct KSolveFields {
     name: String,
     sets: Vec<KSolveSet>,
     moves: Vec<KSolveMove>,
     symmetries: Vec<KSolveMove>,
+    solved_state: Option<KSolveTransformation>,
+    move_relations: Option<HashMap<ArcIntern<str>, MoveRelation>>,
+}
+
+/// A violated orientation invariant found by
+/// [`KSolve::check_orientation_invariants`]
+#[derive(Error, Debug)]
+pub enum OrientationInvariantError {
+    #[error(
+        "The orientation deltas of move {move_name} over set {set_name} sum to {delta_sum}, which is not divisible by the set's orientation count {orientation_count}"
+    )]
+    NonZeroOrientationSum {
+        move_name: String,
+        set_name: String,
+        delta_sum: u32,
+        orientation_count: u8,
+    },
+    #[error(
+        "Move {move_name} permutes set {set_name} trivially but carries a nonzero orientation delta on piece {piece}"
+    )]
+    IdentityMoveWithOrientation {
+        move_name: String,
+        set_name: String,
+        piece: u16,
+    },
 }
 
 #[derive(Error, Debug)]
@@ -225,15 +814,77 @@ impl TryFrom<KSolveFields> for KSolve {
             }
         }
 
+        if let Some(solved_state) = &ksolve_fields.solved_state {
+            let actual_set_count = solved_state.len();
+
+            if actual_set_count != expected_set_count {
+                return Err(KSolveConstructionError::InvalidSetCount(
+                    expected_set_count,
+                    actual_set_count,
+                ));
+            }
+
+            for (state, orbit_def) in solved_state.iter().zip(&ksolve_fields.sets) {
+                let expected_piece_count = orbit_def.piece_count.get();
+
+                if state.len() != expected_piece_count as usize {
+                    return Err(KSolveConstructionError::InvalidPieceCount(
+                        expected_piece_count,
+                        state.len(),
+                    ));
+                }
+
+                let max_orientation_delta = orbit_def.orientation_count.get() - 1;
+
+                // Unlike a move, a solved state may repeat labels to mark
+                // pieces as indistinguishable, so there is no bijection check
+                for &(perm, orientation_delta) in state {
+                    if orientation_delta > max_orientation_delta {
+                        return Err(KSolveConstructionError::InvalidOrientationDelta(
+                            max_orientation_delta,
+                            orientation_delta,
+                        ));
+                    }
+
+                    if perm.get() > expected_piece_count {
+                        return Err(KSolveConstructionError::PermutationOutOfRange(
+                            expected_piece_count,
+                            perm.get(),
+                        ));
+                    }
+                }
+            }
+        }
+
         Ok(KSolve {
             name: ksolve_fields.name,
             sets: ksolve_fields.sets,
             moves: ksolve_fields.moves,
             symmetries: ksolve_fields.symmetries,
+            solved_state: ksolve_fields.solved_state,
+            move_relations: ksolve_fields.move_relations,
         })
     }
 }
 
+/// Produced by [`KSolve::parse_def`] when a definition file cannot be
+/// understood
+#[derive(Error, Debug)]
+pub enum KSolveParseError {
+    #[error("The definition is missing its `Name` header")]
+    MissingName,
+    #[error("The definition declares no sets")]
+    NoSets,
+    #[error("Line {line}: expected `{expected}`")]
+    Malformed { line: usize, expected: &'static str },
+    #[error("Line {line}: {name} is not a declared set")]
+    UnknownSet { line: usize, name: String },
+    #[error("The `{0}` section is missing its `End`")]
+    UnterminatedSection(String),
+    #[error(transparent)]
+    Construction(#[from] KSolveConstructionError),
+}
+
 #[allow(clippy::needless_pass_by_value)]
 fn nonzero_perm(transformation: Vec<Vec<(u16, u8)>>) -> KSolveTransformation {
     transformation
@@ -278,52 +929,130 @@ pub static KPUZZLE_5X5: LazyLock<KSolve> = LazyLock::new(|| KSolve {
     ],
     moves: vec![],
     symmetries: vec![],
+    solved_state: None,
+    move_relations: None,
 });
 
 // This is here for testing. This should be replaced with a puzzle geometry
 // string in the future.
-pub static KPUZZLE_3X3: LazyLock<KSolve> = LazyLock::new(|| {
-    KSolve::clone(
-        &*PuzzleGeometryDefinition {
-            polyhedron: CUBE.to_owned(),
-            cut_surfaces: vec![
-                Arc::from(PlaneCut {
-                    spot: Vector::new_ratios([[(1, 3), (0, 1), (0, 1)]]),
-                    normal: Vector::new([[1, 0, 0]]),
-                    name: ArcIntern::from("R"),
-                }),
-                Arc::from(PlaneCut {
-                    spot: Vector::new_ratios([[(-1, 3), (0, 1), (0, 1)]]),
-                    normal: Vector::new([[-1, 0, 0]]),
-                    name: ArcIntern::from("L"),
-                }),
-                Arc::from(PlaneCut {
-                    spot: Vector::new_ratios([[(0, 1), (1, 3), (0, 1)]]),
-                    normal: Vector::new([[0, 1, 0]]),
-                    name: ArcIntern::from("U"),
-                }),
-                Arc::from(PlaneCut {
-                    spot: Vector::new_ratios([[(0, 1), (-1, 3), (0, 1)]]),
-                    normal: Vector::new([[0, -1, 0]]),
-                    name: ArcIntern::from("D"),
-                }),
-                Arc::from(PlaneCut {
-                    spot: Vector::new_ratios([[(0, 1), (0, 1), (1, 3)]]),
-                    normal: Vector::new([[0, 0, 1]]),
-                    name: ArcIntern::from("F"),
-                }),
-                Arc::from(PlaneCut {
-                    spot: Vector::new_ratios([[(0, 1), (0, 1), (-1, 3)]]),
-                    normal: Vector::new([[0, 0, -1]]),
-                    name: ArcIntern::from("B"),
-                }),
-            ],
-            definition: Span::new(ArcIntern::from("3x3"), 0, 3),
-        }
-        .geometry()
-        .unwrap()
-        .ksolve(),
-    )
+pub static KPUZZLE_3X3: LazyLock<KSolve> =
+    LazyLock::new(|| KSolve::clone(&*PUZZLE_GEOMETRY_3X3.ksolve()));
+
+/// The geometry that `KPUZZLE_3X3` is derived from
+pub static PUZZLE_GEOMETRY_3X3: LazyLock<PuzzleGeometry> = LazyLock::new(|| {
+    PuzzleGeometryDefinition {
+        polyhedron: CUBE.to_owned(),
+        cut_surfaces: vec![
+            Arc::from(PlaneCut {
+                spot: Vector::new_ratios([[(1, 3), (0, 1), (0, 1)]]),
+                normal: Vector::new([[1, 0, 0]]),
+                name: ArcIntern::from("R"),
+            }),
+            Arc::from(PlaneCut {
+                spot: Vector::new_ratios([[(-1, 3), (0, 1), (0, 1)]]),
+                normal: Vector::new([[-1, 0, 0]]),
+                name: ArcIntern::from("L"),
+            }),
+            Arc::from(PlaneCut {
+                spot: Vector::new_ratios([[(0, 1), (1, 3), (0, 1)]]),
+                normal: Vector::new([[0, 1, 0]]),
+                name: ArcIntern::from("U"),
+            }),
+            Arc::from(PlaneCut {
+                spot: Vector::new_ratios([[(0, 1), (-1, 3), (0, 1)]]),
+                normal: Vector::new([[0, -1, 0]]),
+                name: ArcIntern::from("D"),
+            }),
+            Arc::from(PlaneCut {
+                spot: Vector::new_ratios([[(0, 1), (0, 1), (1, 3)]]),
+                normal: Vector::new([[0, 0, 1]]),
+                name: ArcIntern::from("F"),
+            }),
+            Arc::from(PlaneCut {
+                spot: Vector::new_ratios([[(0, 1), (0, 1), (-1, 3)]]),
+                normal: Vector::new([[0, 0, -1]]),
+                name: ArcIntern::from("B"),
+            }),
+        ],
+        definition: Span::new(ArcIntern::from("3x3"), 0, 3),
+    }
+    .geometry()
+    .unwrap()
+});
+
+/// The pyraminx restricted to its deep vertex turns. The tip cuts are still
+/// part of the geometry, but without the tip moves every tip stays slaved to
+/// the layer beneath it, so this is the "pyraminx without tips" puzzle
+pub static KPUZZLE_PYRAMINX_NO_TIPS: LazyLock<KSolve> = LazyLock::new(|| {
+    KSolve::clone(&*PUZZLE_GEOMETRY_PYRAMINX.ksolve())
+        .with_moves(&["A", "A'", "B", "B'", "C", "C'", "D", "D'"])
+});
+
+/// The geometry that `KPUZZLE_PYRAMINX_NO_TIPS` is derived from: a full
+/// pyraminx whose vertex cuts are named `A` through `D` and whose tip cuts
+/// are named `E` through `H`
+pub static PUZZLE_GEOMETRY_PYRAMINX: LazyLock<PuzzleGeometry> = LazyLock::new(|| {
+    let vertices = [
+        TETRAHEDRON.0[0].points[0].clone().0,
+        TETRAHEDRON.0[3].points[0].clone().0,
+        TETRAHEDRON.0[3].points[1].clone().0,
+        TETRAHEDRON.0[3].points[2].clone().0,
+    ];
+
+    let vertex_cuts = vertices.iter().zip(["A", "B", "C", "D"]).map(
+        |(vertex, name)| {
+            Arc::from(PlaneCut {
+                spot: vertex.clone() / &Num::from(9),
+                normal: vertex.clone(),
+                name: ArcIntern::from(name),
+            }) as Arc<dyn CutSurface>
+        },
+    );
+    let tip_cuts = vertices.iter().zip(["E", "F", "G", "H"]).map(
+        |(vertex, name)| {
+            Arc::from(PlaneCut {
+                spot: (vertex.clone() / &Num::from(9)) * &Num::from(5),
+                normal: vertex.clone(),
+                name: ArcIntern::from(name),
+            }) as Arc<dyn CutSurface>
+        },
+    );
+
+    PuzzleGeometryDefinition {
+        polyhedron: TETRAHEDRON.to_owned(),
+        cut_surfaces: vertex_cuts.chain(tip_cuts).collect(),
+        definition: Span::new(ArcIntern::from("pyraminx"), 0, "pyraminx".len()),
+    }
+    .geometry()
+    .unwrap()
+});
+
+/// A 2x2x2 generated by only the `R`, `U`, and `F` half-cube turns, which
+/// keeps the DBL corner fixed in place
+pub static KPUZZLE_2X2_FIXED_CORNER: LazyLock<KSolve> = LazyLock::new(|| {
+    let definition = PuzzleGeometryDefinition {
+        polyhedron: CUBE.to_owned(),
+        cut_surfaces: vec![
+            Arc::from(PlaneCut {
+                spot: Vector::new([[0, 0, 0]]),
+                normal: Vector::new([[1, 0, 0]]),
+                name: ArcIntern::from("R"),
+            }),
+            Arc::from(PlaneCut {
+                spot: Vector::new([[0, 0, 0]]),
+                normal: Vector::new([[0, 1, 0]]),
+                name: ArcIntern::from("U"),
+            }),
+            Arc::from(PlaneCut {
+                spot: Vector::new([[0, 0, 0]]),
+                normal: Vector::new([[0, 0, 1]]),
+                name: ArcIntern::from("F"),
+            }),
+        ],
+        definition: Span::new(ArcIntern::from("2x2"), 0, 3),
+    };
+
+    KSolve::clone(&*definition.geometry().unwrap().ksolve())
 });
 
 pub static KPUZZLE_MEGAMINX: LazyLock<KSolve> = LazyLock::new(|| {
@@ -1516,6 +2245,8 @@ pub static KPUZZLE_4X4: LazyLock<KSolve> = LazyLock::new(|| KSolve {
     ],
     // later
     symmetries: vec![],
+    solved_state: None,
+    move_relations: None,
 });
 
 #[cfg(test)]
@@ -1581,6 +2312,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_orientation_invariants_3x3() {
+        assert!(KPUZZLE_3X3.check_orientation_invariants().is_ok());
+    }
+
+    #[test]
+    fn test_orientation_invariants_corrupted() {
+        let mut corrupted = KSolve::clone(&KPUZZLE_3X3);
+
+        // Twist a single piece of the first set of the "B" move in isolation
+        let ksolve_move = &mut corrupted.moves[0];
+        assert_eq!(ksolve_move.name(), "B");
+        ksolve_move.transformation[0][0].1 += 1;
+
+        match corrupted.check_orientation_invariants() {
+            Err(OrientationInvariantError::NonZeroOrientationSum { move_name, .. }) => {
+                assert_eq!(move_name, "B");
+            }
+            other => panic!("expected NonZeroOrientationSum, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_orientation_invariants_identity_move() {
+        let ksolve_fields = KSolveFields {
+            name: "do nothing".to_owned(),
+            sets: vec![KSolveSet {
+                name: "with style".to_owned(),
+                piece_count: 3.try_into().unwrap(),
+                orientation_count: 3.try_into().unwrap(),
+            }],
+            moves: vec![KSolveMove {
+                name: "Z".to_owned(),
+                transformation: nonzero_perm(vec![vec![(1, 1), (2, 1), (3, 1)]]),
+            }],
+            symmetries: vec![],
+            solved_state: None,
+            move_relations: None,
+        };
+
+        let ksolve = KSolve::try_from(ksolve_fields).unwrap();
+
+        match ksolve.check_orientation_invariants() {
+            Err(OrientationInvariantError::IdentityMoveWithOrientation {
+                move_name, piece, ..
+            }) => {
+                assert_eq!(move_name, "Z");
+                assert_eq!(piece, 1);
+            }
+            other => panic!("expected IdentityMoveWithOrientation, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_valid_construction() {
         let ksolve_fields = KSolveFields {
@@ -1605,6 +2389,8 @@ mod tests {
                 ]),
             }],
             symmetries: vec![],
+            solved_state: None,
+            move_relations: None,
         };
 
         let ksolve = KSolve::try_from(ksolve_fields).unwrap();
@@ -1630,11 +2416,292 @@ mod tests {
                 ]),
             }],
             symmetries: vec![],
+            solved_state: None,
+            move_relations: None,
         };
 
         assert_eq!(ksolve, expected);
     }
 
+    #[test]
+    fn test_custom_solved_state_round_trips() {
+        let ksolve_fields = KSolveFields {
+            name: "scrambled".to_owned(),
+            sets: vec![KSolveSet {
+                name: "pieces".to_owned(),
+                piece_count: 3.try_into().unwrap(),
+                orientation_count: 2.try_into().unwrap(),
+            }],
+            moves: vec![KSolveMove {
+                name: "X".to_owned(),
+                transformation: nonzero_perm(vec![vec![(2, 0), (3, 0), (1, 0)]]),
+            }],
+            symmetries: vec![],
+            // Labels may repeat to mark pieces as indistinguishable
+            solved_state: Some(nonzero_perm(vec![vec![(3, 1), (1, 0), (1, 1)]])),
+            move_relations: None,
+        };
+
+        let ksolve = KSolve::try_from(ksolve_fields).unwrap();
+
+        let expected = nonzero_perm(vec![vec![(3, 1), (1, 0), (1, 1)]]);
+        assert_eq!(ksolve.solved_state(), Some(&expected));
+        assert_eq!(ksolve.solved(), expected);
+        // The identity transformation must not be affected by the custom
+        // solved state
+        assert_eq!(
+            ksolve.identity_transformation(),
+            nonzero_perm(vec![vec![(1, 0), (2, 0), (3, 0)]])
+        );
+    }
+
+    #[test]
+    fn test_def_string() {
+        let ksolve_fields = KSolveFields {
+            name: "example".to_owned(),
+            sets: vec![
+                KSolveSet {
+                    name: "spinny".to_owned(),
+                    piece_count: 3.try_into().unwrap(),
+                    orientation_count: 2.try_into().unwrap(),
+                },
+                KSolveSet {
+                    name: "swappy".to_owned(),
+                    piece_count: 2.try_into().unwrap(),
+                    orientation_count: 1.try_into().unwrap(),
+                },
+            ],
+            moves: vec![KSolveMove {
+                name: "X".to_owned(),
+                transformation: nonzero_perm(vec![
+                    vec![(2, 1), (3, 1), (1, 0)],
+                    vec![(2, 0), (1, 0)],
+                ]),
+            }],
+            symmetries: vec![],
+            solved_state: None,
+            move_relations: None,
+        };
+
+        let ksolve = KSolve::try_from(ksolve_fields).unwrap();
+
+        assert_eq!(
+            ksolve.to_def_string(),
+            "Name example\n\
+             \n\
+             Set spinny 3 2\n\
+             Set swappy 2 1\n\
+             \n\
+             Solved\n\
+             spinny\n\
+             1 2 3\n\
+             0 0 0\n\
+             swappy\n\
+             1 2\n\
+             0 0\n\
+             End\n\
+             \n\
+             Move X\n\
+             spinny\n\
+             2 3 1\n\
+             1 1 0\n\
+             swappy\n\
+             2 1\n\
+             0 0\n\
+             End\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_def_2x2() {
+        let def = "\
+            # A hand-written 2x2\n\
+            Name 2x2\n\
+            \n\
+            Set CORNERS 8 3\n\
+            \n\
+            Solved\n\
+            CORNERS\n\
+            1 2 3 4 5 6 7 8\n\
+            End\n\
+            \n\
+            MoveLimit 10\n\
+            \n\
+            Move U\n\
+            CORNERS\n\
+            2 3 4 1 5 6 7 8\n\
+            0 0 0 0 0 0 0 0\n\
+            End\n\
+            \n\
+            ForbiddenPairs\n\
+            U U\n\
+            End\n\
+            \n\
+            Move R\n\
+            CORNERS\n\
+            4 2 3 8 5 1 7 6\n\
+            2 0 0 1 0 1 0 2\n\
+            End\n";
+
+        let ksolve = KSolve::parse_def(def).unwrap();
+
+        assert_eq!(ksolve.name(), "2x2");
+        assert_eq!(ksolve.sets().len(), 1);
+        assert_eq!(ksolve.sets()[0].name(), "CORNERS");
+        assert_eq!(ksolve.sets()[0].piece_count().get(), 8);
+        assert_eq!(ksolve.sets()[0].orientation_count().get(), 3);
+        // The identity solved state is left implicit
+        assert_eq!(ksolve.solved_state(), None);
+
+        let move_names = ksolve
+            .moves()
+            .iter()
+            .map(KSolveMove::name)
+            .collect::<Vec<_>>();
+        assert_eq!(move_names, ["U", "R"]);
+        assert_eq!(
+            ksolve.moves()[0].transformation(),
+            &nonzero_perm(vec![vec![
+                (2, 0),
+                (3, 0),
+                (4, 0),
+                (1, 0),
+                (5, 0),
+                (6, 0),
+                (7, 0),
+                (8, 0),
+            ]])
+        );
+
+        // The serialization and the parser are inverses
+        assert_eq!(KSolve::parse_def(&ksolve.to_def_string()).unwrap(), ksolve);
+    }
+
+    #[test]
+    fn test_parse_def_duplicate_piece_index() {
+        let def = "\
+            Name broken\n\
+            \n\
+            Set CORNERS 8 3\n\
+            \n\
+            Move U\n\
+            CORNERS\n\
+            1 1 3 4 5 6 7 8\n\
+            0 0 0 0 0 0 0 0\n\
+            End\n";
+
+        assert!(matches!(
+            KSolve::parse_def(def),
+            Err(KSolveParseError::Construction(
+                KSolveConstructionError::InvalidMove(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_solved_state() {
+        let ksolve_fields = KSolveFields {
+            name: "twisted".to_owned(),
+            sets: vec![KSolveSet {
+                name: "pieces".to_owned(),
+                piece_count: 3.try_into().unwrap(),
+                orientation_count: 2.try_into().unwrap(),
+            }],
+            moves: vec![],
+            symmetries: vec![],
+            solved_state: Some(nonzero_perm(vec![vec![(1, 0), (2, 2), (3, 0)]])),
+            move_relations: None,
+        };
+
+        assert!(matches!(
+            KSolve::try_from(ksolve_fields),
+            Err(KSolveConstructionError::InvalidOrientationDelta(1, 2))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_solved_state_round_trips_through_json() {
+        let ksolve_fields = KSolveFields {
+            name: "scrambled".to_owned(),
+            sets: vec![KSolveSet {
+                name: "pieces".to_owned(),
+                piece_count: 3.try_into().unwrap(),
+                orientation_count: 2.try_into().unwrap(),
+            }],
+            moves: vec![KSolveMove {
+                name: "X".to_owned(),
+                transformation: nonzero_perm(vec![vec![(2, 0), (3, 0), (1, 0)]]),
+            }],
+            symmetries: vec![],
+            solved_state: Some(nonzero_perm(vec![vec![(3, 1), (1, 0), (1, 1)]])),
+            move_relations: None,
+        };
+
+        let ksolve = KSolve::try_from(ksolve_fields).unwrap();
+
+        let exported = crate::ksolve::KSolveJson::from(&ksolve);
+        assert_eq!(
+            exported.solved_state,
+            Some(vec![vec![(3, 1), (1, 0), (1, 1)]])
+        );
+
+        let serialized = serde_json::to_string(&exported).unwrap();
+        let deserialized: crate::ksolve::KSolveJson = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, exported);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_zero_indexed_json_export_of_u() {
+        let kpuzzle_3x3 = &*KPUZZLE_3X3;
+        let u_move = kpuzzle_3x3
+            .moves
+            .iter()
+            .find(|ksolve_move| ksolve_move.name() == "U")
+            .unwrap();
+
+        let exported = crate::ksolve::KSolveJson::new(kpuzzle_3x3, true);
+        assert!(exported.zero_indexed);
+
+        let exported_u = exported
+            .moves
+            .iter()
+            .find(|ksolve_move| ksolve_move.name == "U")
+            .unwrap();
+
+        let expected = u_move
+            .transformation()
+            .iter()
+            .map(|perm_and_ori| {
+                perm_and_ori
+                    .iter()
+                    .map(|&(p, o)| (p.get() - 1, o))
+                    .collect::<Vec<(u16, u8)>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(exported_u.transformation, expected);
+        assert_eq!(
+            exported_u.transformation,
+            u_move.zero_indexed_transformation()
+        );
+
+        // Genuinely 0-indexed: the smallest mapped-to piece is 0
+        assert_eq!(
+            exported_u
+                .transformation
+                .iter()
+                .flatten()
+                .map(|&(p, _)| p)
+                .min(),
+            Some(0)
+        );
+
+        // The `From` conversion still emits the 1-indexed text format convention
+        assert!(!crate::ksolve::KSolveJson::from(kpuzzle_3x3).zero_indexed);
+    }
+
     #[test]
     fn test_invalid_set_count() {
         let ksolve_fields = KSolveFields {
@@ -1656,6 +2723,8 @@ mod tests {
                 transformation: nonzero_perm(vec![vec![(1, 0), (2, 0), (3, 0)]]),
             }],
             symmetries: vec![],
+            solved_state: None,
+            move_relations: None,
         };
 
         assert!(matches!(
@@ -1685,6 +2754,8 @@ mod tests {
                 transformation: nonzero_perm(vec![vec![(1, 0), (2, 0), (3, 0), (4, 0)], vec![]]),
             }],
             symmetries: vec![],
+            solved_state: None,
+            move_relations: None,
         };
 
         assert!(matches!(
@@ -1717,6 +2788,8 @@ mod tests {
                 ]),
             }],
             symmetries: vec![],
+            solved_state: None,
+            move_relations: None,
         };
 
         assert!(matches!(
@@ -1749,6 +2822,8 @@ mod tests {
                 ]),
             }],
             symmetries: vec![],
+            solved_state: None,
+            move_relations: None,
         };
 
         assert!(matches!(
@@ -1781,6 +2856,8 @@ mod tests {
                 ]),
             }],
             symmetries: vec![],
+            solved_state: None,
+            move_relations: None,
         };
 
         assert!(matches!(
@@ -1788,4 +2865,42 @@ mod tests {
             Err(KSolveConstructionError::InvalidMove(_))
         ));
     }
+
+    #[test]
+    fn test_rebase_half_turns() {
+        let kpuzzle_3x3 = &*KPUZZLE_3X3;
+
+        let half_turn = kpuzzle_3x3
+            .moves()
+            .iter()
+            .find(|m| m.name() == "R2")
+            .unwrap();
+
+        let quarter_turn_basis =
+            KSolve::clone(kpuzzle_3x3).with_moves(&["R", "L", "U", "D", "F", "B"]);
+
+        let rebased = kpuzzle_3x3
+            .rebase_solution(&[half_turn], quarter_turn_basis.moves())
+            .unwrap();
+
+        assert_eq!(
+            rebased.iter().map(|m| m.name()).collect::<Vec<_>>(),
+            ["R", "R"]
+        );
+
+        // The rebased solution must compose back into the original
+        // transformation
+        let mut composed = rebased[0].transformation().clone();
+        for ksolve_move in &rebased[1..] {
+            composed = kpuzzle_3x3.compose_transformations(&composed, ksolve_move.transformation());
+        }
+        assert_eq!(&composed, half_turn.transformation());
+
+        // A move that is not a power of any target move reports its name
+        let u_only = KSolve::clone(kpuzzle_3x3).with_moves(&["U"]);
+        let err = kpuzzle_3x3
+            .rebase_solution(&[half_turn], u_only.moves())
+            .unwrap_err();
+        assert_eq!(err.0, "R2");
+    }
 }
@@ -1,4 +1,5 @@
 use std::{
+    array,
     cmp::Ordering,
     iter::Sum,
     mem::{self, MaybeUninit},
@@ -340,6 +341,18 @@ impl<const N: usize> Vector<N> {
             .sum::<Num>()
     }
 
+    /// Like [`Vector::dot`], but without consuming either vector
+    #[must_use]
+    pub fn dot_ref(&self, other: &Vector<N>) -> Num {
+        let [v1] = &self.0;
+        let [v2] = &other.0;
+
+        v1.iter()
+            .zip(v2)
+            .map(|(a, b)| a.clone() * b.clone())
+            .sum::<Num>()
+    }
+
     #[must_use]
     pub fn proj_onto(self, other: Vector<N>) -> Vector<N> {
         let dot = self.dot(other.clone());
@@ -426,6 +439,22 @@ impl<const O: usize, const I: usize> Matrix<O, I> {
         Matrix(new_data.map(|v| v.map(|v| unsafe { v.assume_init() })))
     }
 
+    /// Multiply by a vector, writing the product into `out` instead of
+    /// building a new vector
+    pub fn mul_vec_into(&self, vec: &Vector<I>, out: &mut Vector<O>) {
+        let [vec_col] = &vec.0;
+        let [out_col] = &mut out.0;
+
+        for (j, out_elem) in out_col.iter_mut().enumerate() {
+            *out_elem = self
+                .0
+                .iter()
+                .zip(vec_col)
+                .map(|(col, v)| col[j].clone() * v.clone())
+                .sum::<Num>();
+        }
+    }
+
     #[must_use]
     pub fn into_inner(self) -> [[Num; O]; I] {
         self.0
@@ -457,6 +486,16 @@ impl<const O: usize, const I: usize> AddAssign<Matrix<O, I>> for Matrix<O, I> {
     }
 }
 
+impl<const O: usize, const I: usize> AddAssign<&Matrix<O, I>> for Matrix<O, I> {
+    fn add_assign(&mut self, rhs: &Matrix<O, I>) {
+        self.0.iter_mut().zip(&rhs.0).for_each(|(lhs, rhs)| {
+            lhs.iter_mut().zip(rhs).for_each(|(lhs, rhs)| {
+                *lhs += rhs.clone();
+            });
+        });
+    }
+}
+
 impl<const O: usize, const I: usize> Add<Matrix<O, I>> for Matrix<O, I> {
     type Output = Self;
 
@@ -476,6 +515,16 @@ impl<const O: usize, const I: usize> SubAssign<Matrix<O, I>> for Matrix<O, I> {
     }
 }
 
+impl<const O: usize, const I: usize> SubAssign<&Matrix<O, I>> for Matrix<O, I> {
+    fn sub_assign(&mut self, rhs: &Matrix<O, I>) {
+        self.0.iter_mut().zip(&rhs.0).for_each(|(lhs, rhs)| {
+            lhs.iter_mut().zip(rhs).for_each(|(lhs, rhs)| {
+                *lhs -= rhs.clone();
+            });
+        });
+    }
+}
+
 impl<const O: usize, const I: usize> Sub<Matrix<O, I>> for Matrix<O, I> {
     type Output = Self;
 
@@ -555,6 +604,86 @@ impl<const O: usize, const I: usize> Sum for Matrix<O, I> {
     }
 }
 
+impl<const N: usize> Matrix<N, N> {
+    #[must_use]
+    pub fn identity() -> Self {
+        Matrix::new(array::from_fn(|i| array::from_fn(|j| i32::from(i == j))))
+    }
+
+    /// The sum of the diagonal entries
+    #[must_use]
+    pub fn trace(&self) -> Num {
+        (0..N).map(|i| self.0[i][i].clone()).sum::<Num>()
+    }
+
+    /// The smallest positive power of the matrix that equals the identity, or
+    /// `None` if no power up to `cap` does
+    #[must_use]
+    pub fn order(&self, cap: usize) -> Option<usize> {
+        let identity = Matrix::identity();
+        let mut current = self.clone();
+
+        for power in 1..=cap {
+            if current == identity {
+                return Some(power);
+            }
+
+            current = &current * self;
+        }
+
+        None
+    }
+}
+
+/// The axis that a rotation matrix rotates about, normalized and with its sign
+/// chosen so that the first nonzero coordinate is positive. Returns `None` for
+/// the identity, which has no axis.
+///
+/// The sign canonicalization means that a rotation and its inverse report the
+/// same axis, making the result suitable for grouping rotations by axis.
+#[must_use]
+pub fn rotation_axis(matrix: &Matrix<3, 3>) -> Option<Vector<3>> {
+    if *matrix == Matrix::identity() {
+        return None;
+    }
+
+    let m = matrix.inner();
+
+    // https://en.wikipedia.org/wiki/Rotation_matrix#Determining_the_axis
+    let mut axis = Vector::new([[
+        m[1][2].clone() - m[2][1].clone(),
+        m[2][0].clone() - m[0][2].clone(),
+        m[0][1].clone() - m[1][0].clone(),
+    ]]);
+
+    if axis.is_zero() {
+        // A 180° rotation about a unit axis `a` is `2aaᵀ - I`, so every
+        // nonzero column of `M + I = 2aaᵀ` is parallel to the axis
+        let mut shifted = matrix.clone();
+        shifted += Matrix::identity();
+
+        axis = shifted
+            .into_inner()
+            .into_iter()
+            .map(|column| Vector::new([column]))
+            .find(|column| !column.is_zero())?;
+    }
+
+    axis.normalize_in_place();
+
+    let flip = axis
+        .vec_inner()
+        .iter()
+        .find(|coordinate| !coordinate.is_zero())
+        .is_some_and(|coordinate| coordinate.cmp_zero().is_lt());
+
+    if flip {
+        axis = -axis;
+    }
+
+    Some(axis)
+}
+
 #[must_use]
 pub fn rotate_to(from: Matrix<3, 2>, to: Matrix<3, 2>) -> Matrix<3, 3> {
     // Let A be the matrix we want to return, F be `from`, and T be `to` (after orthonormalization and adding the third column)
@@ -617,7 +746,12 @@ pub fn rotation_about(axis: Vector<3>, x_axis: Vector<2>) -> Matrix<3, 3> {
 #[cfg(test)]
 mod tests {
     use algebraics::prelude::*;
-    use std::cmp::Ordering;
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+        cmp::Ordering,
+        mem,
+    };
 
     use algebraics::RealAlgebraicNumber;
 
@@ -628,6 +762,36 @@ mod tests {
 
     use super::Matrix;
 
+    /// Counts allocations per thread so that allocation counts are not
+    /// polluted by other tests running in parallel
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATION_COUNT: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            // `try_with` because allocation may happen while the thread local
+            // is being torn down
+            let _ = ALLOCATION_COUNT.try_with(|count| count.set(count.get() + 1));
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations_during(f: impl FnOnce()) -> usize {
+        let before = ALLOCATION_COUNT.with(Cell::get);
+        f();
+        ALLOCATION_COUNT.with(Cell::get) - before
+    }
+
     #[test]
     fn num_ops() {
         assert_eq!(Num::from(1) + Num::from(2), Num::from(3));
@@ -685,6 +849,16 @@ mod tests {
             Vector::new([[1, 1, 0]]).dot(Vector::new([[0, 2, 1]])),
             Num::from(2)
         );
+        assert_eq!(
+            Vector::new([[1, 1, 0]]).dot_ref(&Vector::new([[0, 2, 1]])),
+            Num::from(2)
+        );
+
+        let mut vec = Vector::new([[1, 2, 3]]);
+        vec += &Vector::new([[2, 4, 6]]);
+        assert_eq!(vec, Vector::new([[3, 6, 9]]));
+        vec -= &Vector::new([[1, 2, 3]]);
+        assert_eq!(vec, Vector::new([[2, 4, 6]]));
 
         assert_eq!(
             Vector::new([[1, 2, 0]]).proj_onto(Vector::new([[0, 100, 0]])),
@@ -716,6 +890,11 @@ mod tests {
             Vector::new([[1, 3, 2]])
         );
 
+        let mut out = Vector::zero();
+        Matrix::new([[1, 0, 0], [0, 0, 1], [0, 1, 0]])
+            .mul_vec_into(&Vector::new([[1, 2, 3]]), &mut out);
+        assert_eq!(out, Vector::new([[1, 3, 2]]));
+
         assert_eq!(
             &Matrix::new([[5, 2, 9], [3, 9, 0], [2, 4, 3]])
                 * &Matrix::new([[9, 3, 4], [2, 5, 1], [6, 2, 1]]),
@@ -760,6 +939,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn in_place_ops_allocate_less() {
+        // The point transformation performed for every sticker of every turn
+        // while building a puzzle's permutation group, written with owned
+        // operators the way `calc_permutation_group` used to and with the
+        // in-place operators the way it does now. Exact arithmetic is used so
+        // that every `Num` clone allocates.
+        let turn = Matrix::new_ratios([
+            [(0, 1), (0, 1), (1, 1)],
+            [(1, 1), (0, 1), (0, 1)],
+            [(0, 1), (1, 1), (0, 1)],
+        ]);
+        let center = Vector::new_ratios([[(1, 3), (1, 3), (1, 3)]]);
+        let point = Vector::new_ratios([[(1, 1), (1, 2), (1, 4)]]);
+
+        let mut point_owned = point.clone();
+        let owned = allocations_during(|| {
+            for _ in 0..120 {
+                point_owned = &turn * &(point_owned.clone() - center.clone()) + center.clone();
+            }
+        });
+
+        let mut point_in_place = point.clone();
+        let mut rotated = Vector::zero();
+        let in_place = allocations_during(|| {
+            for _ in 0..120 {
+                point_in_place -= &center;
+                turn.mul_vec_into(&point_in_place, &mut rotated);
+                rotated += &center;
+                mem::swap(&mut point_in_place, &mut rotated);
+            }
+        });
+
+        assert_eq!(point_owned, point_in_place);
+        assert!(
+            in_place < owned,
+            "in-place: {in_place} allocations, owned: {owned} allocations"
+        );
+    }
+
     #[test]
     fn test_float_approx() {
         assert_eq!(3., approx_float(RealAlgebraicNumber::from(3)));
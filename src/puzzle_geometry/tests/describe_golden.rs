@@ -0,0 +1,36 @@
+//! Golden test for the human-readable geometry report. The 3x3 report is
+//! compared against `goldens/3x3.txt`; set `QTER_REGENERATE_GOLDENS=1` to
+//! rewrite the golden file instead of comparing against it.
+
+use std::{fs, path::PathBuf};
+
+use puzzle_geometry::ksolve::PUZZLE_GEOMETRY_3X3;
+
+#[test]
+fn describe_3x3_matches_golden() {
+    let description = PUZZLE_GEOMETRY_3X3.describe();
+
+    let golden_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("goldens")
+        .join("3x3.txt");
+
+    if std::env::var_os("QTER_REGENERATE_GOLDENS").is_some() {
+        fs::write(&golden_path, description).unwrap();
+        return;
+    }
+
+    let golden = fs::read_to_string(&golden_path).unwrap_or_else(|_| {
+        panic!(
+            "Missing golden description {}; run with QTER_REGENERATE_GOLDENS=1 to create it",
+            golden_path.display()
+        )
+    });
+
+    assert_eq!(
+        description,
+        golden,
+        "The 3x3 description diverged from {}; run with QTER_REGENERATE_GOLDENS=1 to update it",
+        golden_path.display()
+    );
+}
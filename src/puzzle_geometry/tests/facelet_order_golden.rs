@@ -0,0 +1,96 @@
+//! Golden tests pinning the facelet numbering. Every facelet index is mapped
+//! to its face color and sticker centroid and compared against
+//! `goldens/3x3-facelets.txt` and `goldens/pyraminx-facelets.txt`; set
+//! `QTER_REGENERATE_GOLDENS=1` to rewrite the golden files instead of
+//! comparing against them.
+//!
+//! Facelet indices are baked into preset architectures and their optimized
+//! tables, so a diff here is not a harmless formatting change: it means the
+//! numbering moved and `qter_core::architectures::FACELET_ORDER_VERSION`
+//! must be bumped along with regenerating the goldens. See
+//! [`PuzzleGeometry::ordering_version`].
+
+use std::{fs, path::PathBuf};
+
+use puzzle_geometry::{
+    Face, PuzzleGeometry,
+    ksolve::{PUZZLE_GEOMETRY_3X3, PUZZLE_GEOMETRY_PYRAMINX},
+};
+use qter_core::architectures::FACELET_ORDER_VERSION;
+
+/// The centroid of a sticker, snapped to the printed precision so that float
+/// noise and negative zero cannot flip a digit of the golden
+fn centroid(face: &Face) -> [f64; 3] {
+    let mut sum = [0.0; 3];
+    for point in &face.points {
+        let [x, y, z] = point.approx_coordinates();
+        sum[0] += x;
+        sum[1] += y;
+        sum[2] += z;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let count = face.points.len() as f64;
+    sum.map(|v| ((v / count) * 1e6).round() / 1e6 + 0.)
+}
+
+fn facelet_table(geometry: &PuzzleGeometry) -> String {
+    let group = geometry.permutation_group();
+
+    let mut table = String::new();
+    for (facelet, (face, _)) in geometry.non_fixed_stickers().iter().enumerate() {
+        assert_eq!(
+            group.facelet_colors()[facelet],
+            face.color,
+            "the permutation group's colors must match the sticker list"
+        );
+
+        let [x, y, z] = centroid(face);
+        table.push_str(&format!(
+            "{facelet}: {} ({x:.6}, {y:.6}, {z:.6})\n",
+            face.color
+        ));
+    }
+
+    table
+}
+
+fn check_against_golden(geometry: &PuzzleGeometry, golden_name: &str) {
+    assert_eq!(PuzzleGeometry::ordering_version(), FACELET_ORDER_VERSION);
+
+    let table = facelet_table(geometry);
+
+    let golden_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("goldens")
+        .join(golden_name);
+
+    if std::env::var_os("QTER_REGENERATE_GOLDENS").is_some() {
+        fs::write(&golden_path, table).unwrap();
+        return;
+    }
+
+    let golden = fs::read_to_string(&golden_path).unwrap_or_else(|_| {
+        panic!(
+            "Missing golden facelet table {}; run with QTER_REGENERATE_GOLDENS=1 to create it",
+            golden_path.display()
+        )
+    });
+
+    assert_eq!(
+        table,
+        golden,
+        "The facelet numbering diverged from {}; bump FACELET_ORDER_VERSION, re-derive everything authored against the old numbering, and run with QTER_REGENERATE_GOLDENS=1 to update it",
+        golden_path.display()
+    );
+}
+
+#[test]
+fn facelet_order_of_3x3_matches_golden() {
+    check_against_golden(&PUZZLE_GEOMETRY_3X3, "3x3-facelets.txt");
+}
+
+#[test]
+fn facelet_order_of_pyraminx_matches_golden() {
+    check_against_golden(&PUZZLE_GEOMETRY_PYRAMINX, "pyraminx-facelets.txt");
+}
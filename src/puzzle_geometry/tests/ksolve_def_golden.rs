@@ -0,0 +1,39 @@
+//! Golden test for the `ksolve` text-format serialization. The 3x3 dump is
+//! compared against `goldens/3x3.def`; set `QTER_REGENERATE_GOLDENS=1` to
+//! rewrite the golden file instead of comparing against it.
+//!
+//! The set names in the golden are the ones puzzle geometry derives from the
+//! sticker counts of its orbits.
+
+use std::{fs, path::PathBuf};
+
+use puzzle_geometry::ksolve::PUZZLE_GEOMETRY_3X3;
+
+#[test]
+fn def_of_3x3_matches_golden() {
+    let def = PUZZLE_GEOMETRY_3X3.ksolve().to_def_string();
+
+    let golden_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("goldens")
+        .join("3x3.def");
+
+    if std::env::var_os("QTER_REGENERATE_GOLDENS").is_some() {
+        fs::write(&golden_path, def).unwrap();
+        return;
+    }
+
+    let golden = fs::read_to_string(&golden_path).unwrap_or_else(|_| {
+        panic!(
+            "Missing golden definition {}; run with QTER_REGENERATE_GOLDENS=1 to create it",
+            golden_path.display()
+        )
+    });
+
+    assert_eq!(
+        def,
+        golden,
+        "The 3x3 definition diverged from {}; run with QTER_REGENERATE_GOLDENS=1 to update it",
+        golden_path.display()
+    );
+}
@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     fmt::Debug,
     sync::{Arc, OnceLock},
 };
@@ -14,6 +14,7 @@ use crate::{
     discrete_math::{
         decode, lcm, lcm_iter, length_of_substring_that_this_string_is_n_repeated_copies_of,
     },
+    schreier_sims::StabilizerChain,
     shared_facelet_detection::algorithms_to_cycle_generators,
     table_encoding,
 };
@@ -25,6 +26,19 @@ pub(crate) const OPTIMIZED_TABLES: [&[u8]; 4] = [
     include_bytes!("../puzzles/90-90.bin"),
 ];
 
+/// The version of the facelet numbering that puzzle geometry assigns.
+///
+/// Facelet indices are load-bearing across the whole system: preset
+/// architectures, the optimized decoding tables, and the visualizer all bake
+/// them in. Any change to how puzzle geometry orders faces or sorts the
+/// stickers within a face silently renumbers every facelet, so the numbering
+/// is pinned by a golden test in `puzzle_geometry` and versioned here. If the
+/// ordering ever must change, bump this constant and re-derive everything
+/// authored against the old numbering; [`PuzzleDefinition::get_preset`]
+/// asserts that the presets it hands out were authored against the current
+/// version.
+pub const FACELET_ORDER_VERSION: u32 = 1;
+
 /// The definition of a puzzle parsed from the custom format
 #[derive(Debug)]
 pub struct PuzzleDefinition {
@@ -32,6 +46,8 @@ pub struct PuzzleDefinition {
     pub perm_group: Arc<PermutationGroup>,
     /// A list of preset architectures
     pub presets: Vec<Arc<Architecture>>,
+    /// The [`FACELET_ORDER_VERSION`] that the presets were authored against
+    pub facelet_order_version: u32,
 }
 
 impl PuzzleDefinition {
@@ -83,8 +99,18 @@ impl PuzzleDefinition {
     }
 
     /// Find a preset with the specified cycle orders
+    ///
+    /// Panics if the presets were authored against a different facelet
+    /// numbering than the current [`FACELET_ORDER_VERSION`], because their
+    /// algorithms would cycle the wrong facelets.
     #[must_use]
     pub fn get_preset(&self, orders: &[Int<U>]) -> Option<Arc<Architecture>> {
+        assert_eq!(
+            self.facelet_order_version, FACELET_ORDER_VERSION,
+            "The presets were authored against facelet ordering version {} but the current ordering is version {}; re-derive them against the new numbering",
+            self.facelet_order_version, FACELET_ORDER_VERSION
+        );
+
         for preset in &self.presets {
             if preset.registers.len() != orders.len() {
                 continue;
@@ -232,6 +258,159 @@ impl PermutationGroup {
                 ArcIntern::clone(self.generator_inverses.get(generator_move).unwrap());
         }
     }
+
+    /// Whether `permutation` is a state that some product of the group's
+    /// generators can actually reach
+    ///
+    /// A permutation of the facelets is not necessarily an element of the
+    /// group; on a 3×3 for example, a single twisted corner permutes three
+    /// facelets but no move sequence produces it. This builds a stabilizer
+    /// chain on every call, so cache the answer rather than calling it in a
+    /// loop.
+    #[must_use]
+    pub fn is_reachable(self: &Arc<Self>, permutation: &Permutation) -> bool {
+        StabilizerChain::new(self).is_member(permutation.clone())
+    }
+
+    /// Search for a facelet relabeling under which this group's generators
+    /// become exactly `other`'s generators of the same names.
+    ///
+    /// Returns a vector mapping each of this group's facelets to the
+    /// corresponding facelet of `other`, or `None` if no such relabeling
+    /// exists. Two puzzle constructions that number their facelets
+    /// differently are the same puzzle exactly when a relabeling is found,
+    /// which makes this useful for validating refactors of the geometry
+    /// pipeline. Facelet colors are not compared since equivalent
+    /// constructions may name their colors differently.
+    #[must_use]
+    pub fn is_isomorphic_labeling(&self, other: &PermutationGroup) -> Option<Vec<usize>> {
+        let facelet_count = self.facelet_count();
+        if facelet_count != other.facelet_count() {
+            return None;
+        }
+
+        let names = self.generators.keys().sorted().collect_vec();
+        if names != other.generators.keys().sorted().collect_vec() {
+            return None;
+        }
+
+        let gens = names
+            .iter()
+            .map(|&name| {
+                (
+                    self.generators[name].mapping(),
+                    other.generators[name].mapping(),
+                )
+            })
+            .collect_vec();
+
+        // The length of the cycle each facelet sits in, per generator; a
+        // facelet can only relabel to one with an identical signature
+        let signatures = |which: fn(&(&[usize], &[usize])) -> &[usize]| {
+            (0..facelet_count)
+                .map(|facelet| {
+                    gens.iter()
+                        .map(|gen| {
+                            let mapping = which(gen);
+
+                            let mut len = 1;
+                            let mut spot = mapping[facelet];
+                            while spot != facelet {
+                                spot = mapping[spot];
+                                len += 1;
+                            }
+
+                            len
+                        })
+                        .collect_vec()
+                })
+                .collect_vec()
+        };
+
+        let self_signatures = signatures(|&(mapping, _)| mapping);
+        let other_signatures = signatures(|&(_, mapping)| mapping);
+
+        /// Record σ(i) = j and propagate σ(g(i)) = g(σ(i)) for every
+        /// generator, pushing every new assignment onto `trail` so it can be
+        /// undone on backtrack
+        fn assign(
+            i: usize,
+            j: usize,
+            gens: &[(&[usize], &[usize])],
+            mapping: &mut [usize],
+            used: &mut [bool],
+            trail: &mut Vec<usize>,
+        ) -> bool {
+            if mapping[i] != usize::MAX {
+                return mapping[i] == j;
+            }
+
+            if used[j] {
+                return false;
+            }
+
+            mapping[i] = j;
+            used[j] = true;
+            trail.push(i);
+
+            gens.iter()
+                .all(|&(s, o)| assign(s[i], o[j], gens, mapping, used, trail))
+        }
+
+        fn search(
+            next: usize,
+            gens: &[(&[usize], &[usize])],
+            self_signatures: &[Vec<usize>],
+            other_signatures: &[Vec<usize>],
+            mapping: &mut [usize],
+            used: &mut [bool],
+        ) -> bool {
+            let Some(i) = (next..mapping.len()).find(|&i| mapping[i] == usize::MAX) else {
+                return true;
+            };
+
+            for j in 0..mapping.len() {
+                if used[j] || self_signatures[i] != other_signatures[j] {
+                    continue;
+                }
+
+                let mut trail = Vec::new();
+
+                if assign(i, j, gens, mapping, used, &mut trail)
+                    && search(
+                        i + 1,
+                        gens,
+                        self_signatures,
+                        other_signatures,
+                        mapping,
+                        used,
+                    )
+                {
+                    return true;
+                }
+
+                for &undone in &trail {
+                    used[mapping[undone]] = false;
+                    mapping[undone] = usize::MAX;
+                }
+            }
+
+            false
+        }
+
+        let mut mapping = vec![usize::MAX; facelet_count];
+        let mut used = vec![false; facelet_count];
+
+        search(
+            0,
+            &gens,
+            &self_signatures,
+            &other_signatures,
+            &mut mapping,
+            &mut used,
+        )
+        .then_some(mapping)
+    }
 }
 
 /// An element of a permutation group
@@ -464,7 +643,10 @@ impl CycleGeneratorSubcycle {
 #[derive(Clone)]
 pub struct Algorithm {
     perm_group: Arc<PermutationGroup>,
-    permutation: Permutation,
+    /// Materialized lazily from `move_seq` so that a big program doesn't pay
+    /// for a full facelet mapping per instruction until the instruction is
+    /// actually executed; see [`Algorithm::materialize`]
+    permutation: OnceLock<Permutation>,
     move_seq: Vec<ArcIntern<str>>,
     chromatic_orders: OnceLock<Vec<Int<U>>>,
     repeat: Int<U>,
@@ -517,15 +699,16 @@ impl Algorithm {
         perm_group: Arc<PermutationGroup>,
         move_seq: Vec<ArcIntern<str>>,
     ) -> Result<Algorithm, ArcIntern<str>> {
-        let mut permutation = perm_group.identity();
-
-        perm_group
-            .compose_generators_into(&mut permutation, move_seq.iter())
-            .map_err(ArcIntern::clone)?;
+        if let Some(bad_move) = move_seq
+            .iter()
+            .find(|moove| perm_group.get_generator(moove).is_none())
+        {
+            return Err(ArcIntern::clone(bad_move));
+        }
 
         Ok(Algorithm {
             perm_group,
-            permutation,
+            permutation: OnceLock::new(),
             move_seq,
             chromatic_orders: OnceLock::new(),
             repeat: Int::<U>::one(),
@@ -538,20 +721,17 @@ impl Algorithm {
     ///
     /// If the string cannot be parsed as an algorithm, this code will return `None`
     pub fn parse_from_string(perm_group: Arc<PermutationGroup>, string: &str) -> Option<Algorithm> {
-        let mut permutation = perm_group.identity();
-
         let mut move_seq = Vec::new();
 
         for moove in string.split(' ').filter(|s| !s.is_empty()) {
-            let (interned, perm) = perm_group.generators().find(|v| v.0 == moove)?;
+            let (interned, _) = perm_group.generators().find(|v| v.0 == moove)?;
 
             move_seq.push(interned);
-            permutation.compose_into(perm);
         }
 
         Some(Algorithm {
             perm_group,
-            permutation,
+            permutation: OnceLock::new(),
             move_seq,
             chromatic_orders: OnceLock::new(),
             repeat: Int::<U>::one(),
@@ -561,10 +741,9 @@ impl Algorithm {
     /// Create a new algorithm that is the identity permutation (does nothing).
     #[must_use]
     pub fn identity(perm_group: Arc<PermutationGroup>) -> Algorithm {
-        let identity = perm_group.identity();
         Algorithm {
             perm_group,
-            permutation: identity,
+            permutation: OnceLock::new(),
             move_seq: Vec::new(),
             chromatic_orders: OnceLock::new(),
             repeat: Int::<U>::one(),
@@ -577,25 +756,49 @@ impl Algorithm {
             self.repeat = Int::<U>::one();
         }
         self.move_seq.extend(other.move_seq_iter().cloned());
-        self.permutation.compose_into(&other.permutation);
+        // Stay lazy unless our permutation was already materialized, in which
+        // case recomposing the whole move word later would waste the work
+        if let Some(permutation) = self.permutation.get_mut() {
+            permutation.compose_into(other.permutation());
+        }
         self.chromatic_orders = OnceLock::new();
     }
 
-    /// Get the underlying permutation of the `Algorithm` instance
+    /// Get the underlying permutation of the `Algorithm` instance,
+    /// materializing it from the move sequence on first use
     pub fn permutation(&self) -> &Permutation {
-        &self.permutation
+        self.permutation.get_or_init(|| {
+            let mut permutation = self.perm_group.identity();
+
+            self.perm_group
+                .compose_generators_into(&mut permutation, self.move_seq_iter())
+                .expect("every move was validated on construction");
+
+            permutation
+        })
+    }
+
+    /// Force the lazy permutation to materialize now, so hot paths like
+    /// `repeat-until` don't pay the composition cost during execution
+    pub fn materialize(&self) {
+        self.permutation();
     }
 
     /// Find the result of applying the algorithm to the identity `exponent` times.
     ///
     /// This calculates the value in O(1) time with respect to `exponent`.
     pub fn exponentiate(&mut self, exponent: Int<I>) {
+        // Materialize before touching the move word: exponentiating the
+        // permutation directly is O(1) in `exponent` while recomposing the
+        // repeated move word would not be
+        self.materialize();
+
         if exponent.signum() == -1 {
             self.perm_group.invert_generator_moves(&mut self.move_seq);
         }
 
         self.repeat *= exponent.abs();
-        self.permutation.exponentiate(exponent);
+        self.permutation.get_mut().unwrap().exponentiate(exponent);
     }
 
     /// Returns a move sequence that when composed, give the same result as applying `.permutation()`
@@ -606,6 +809,141 @@ impl Algorithm {
             .take(self.move_seq.len() * self.repeat.try_into().unwrap_or(usize::MAX))
     }
 
+    /// Returns the shortest known move sequence with the same permutation as this algorithm.
+    ///
+    /// Sequences are searched breadth-first in lexicographic generator order, so any two equivalent algorithms within the search bound canonicalize to the same sequence. If no sequence within the bound exists, the algorithm's own moves are returned unchanged.
+    #[must_use]
+    pub fn canonical_moves(&self) -> Vec<ArcIntern<str>> {
+        /// The breadth-first search visits `generators^len` sequences, so keep the bound small
+        const MAX_CANONICAL_SEARCH_LEN: usize = 3;
+
+        let generators = self
+            .perm_group
+            .generators()
+            .sorted_by(|a, b| a.0.cmp(&b.0))
+            .collect_vec();
+
+        let mut seen = HashSet::new();
+        seen.insert(self.perm_group.identity().mapping().to_vec());
+
+        let mut queue = VecDeque::new();
+        queue.push_back((self.perm_group.identity(), Vec::new()));
+
+        while let Some((perm, word)) = queue.pop_front() {
+            if perm == *self.permutation() {
+                return word;
+            }
+
+            if word.len() >= MAX_CANONICAL_SEARCH_LEN {
+                continue;
+            }
+
+            for (name, generator) in &generators {
+                let mut next_perm = perm.clone();
+                next_perm.compose_into(generator);
+
+                if seen.insert(next_perm.mapping().to_vec()) {
+                    let mut next_word = word.clone();
+                    next_word.push(ArcIntern::clone(name));
+                    queue.push_back((next_perm, next_word));
+                }
+            }
+        }
+
+        self.move_seq_iter().cloned().collect()
+    }
+
+    /// Rewrite every move to the canonical name for its turn: a power of a base move is spelled with the smaller of its forward and inverse powers, preferring the forward spelling on ties, and whole turns are dropped entirely (e.g. `U3` on a degree four puzzle becomes `U'` and `U4` disappears).
+    ///
+    /// Moves whose names don't follow the `U`/`U2`/`U'` convention, or whose canonical spelling isn't a generator of the group with the same permutation, are left unchanged. The permutation of the algorithm is unaffected.
+    pub fn normalize(&mut self) {
+        let normalized = self
+            .move_seq
+            .iter()
+            .filter_map(|moove| self.canonical_move_name(moove))
+            .collect();
+
+        self.move_seq = normalized;
+    }
+
+    /// The canonical name for `moove` following [`Algorithm::normalize`], or `None` if the move is a whole number of full turns
+    fn canonical_move_name(&self, moove: &ArcIntern<str>) -> Option<ArcIntern<str>> {
+        let unchanged = Some(ArcIntern::clone(moove));
+
+        let (without_prime, prime) = match moove.strip_suffix('\'') {
+            Some(rest) => (rest, true),
+            None => (&**moove, false),
+        };
+
+        let base = without_prime.trim_end_matches(|c: char| c.is_ascii_digit());
+
+        let power = if base.len() == without_prime.len() {
+            1
+        } else {
+            match without_prime[base.len()..].parse::<usize>() {
+                Ok(v) => v,
+                Err(_) => return unchanged,
+            }
+        };
+
+        let (Some(original), Some(base_perm)) = (
+            self.perm_group.get_generator(moove),
+            self.perm_group.get_generator(base),
+        ) else {
+            return unchanged;
+        };
+
+        // The order of the base turn
+        let Ok(symm) = usize::try_from(lcm_iter(
+            base_perm
+                .cycles()
+                .iter()
+                .map(|cycle| Int::<U>::from(cycle.len())),
+        )) else {
+            return unchanged;
+        };
+
+        let forward = if prime {
+            (symm - power % symm) % symm
+        } else {
+            power % symm
+        };
+
+        if forward == 0 {
+            // A whole number of full turns does nothing
+            return if *original == self.perm_group.identity() {
+                None
+            } else {
+                unchanged
+            };
+        }
+
+        let inverse = symm - forward;
+
+        let canonical = if forward <= inverse {
+            if forward == 1 {
+                ArcIntern::from(base)
+            } else {
+                ArcIntern::from(format!("{base}{forward}"))
+            }
+        } else if inverse == 1 {
+            ArcIntern::from(format!("{base}'"))
+        } else {
+            ArcIntern::from(format!("{base}{inverse}'"))
+        };
+
+        if canonical == *moove {
+            return Some(canonical);
+        }
+
+        // Only rename if the group really defines the canonical spelling as
+        // the same turn
+        match self.perm_group.get_generator(&canonical) {
+            Some(canonical_perm) if canonical_perm == original => Some(canonical),
+            _ => unchanged,
+        }
+    }
+
     /// Return the permutation group that this alg operates on
     pub fn group(&self) -> &PermutationGroup {
         &self.perm_group
@@ -640,17 +978,17 @@ impl Algorithm {
     }
 }
 
+/// Algorithms are compared by their permutation effect rather than by their move sequences, so e.g. `R R R` and `R'` are equal
 impl PartialEq for Algorithm {
     fn eq(&self, other: &Self) -> bool {
-        self.move_seq_iter()
-            .zip(other.move_seq_iter())
-            .all(|(a, b)| a == b)
+        self.permutation() == other.permutation()
     }
 }
 
 impl Eq for Algorithm {}
 
-impl Debug for Algorithm {
+/// Algorithms display as their moves separated by single spaces, so the output can be given back to [`Algorithm::parse_from_string`] to produce an equal algorithm
+impl core::fmt::Display for Algorithm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for (i, generator) in self.move_seq_iter().enumerate() {
             if i != 0 {
@@ -659,8 +997,13 @@ impl Debug for Algorithm {
             f.write_str(generator)?;
         }
 
-        f.write_str(" — ")?;
-        self.permutation().fmt(f)
+        Ok(())
+    }
+}
+
+impl Debug for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self} — {}", self.permutation())
     }
 }
 
@@ -1005,6 +1348,48 @@ impl Architecture {
     pub fn shared_facelets(&self) -> &[usize] {
         &self.shared_facelets
     }
+
+    /// Whether `algorithm` performs on every register exactly like the algorithm currently generating `register`
+    ///
+    /// The two must act identically on every facelet the architecture decodes from: the facelets of every register's unshared cycles along with the shared facelets. Facelets outside of every register carry no register state, so the algorithms are free to differ there — that freedom is what allows a shorter equivalent algorithm to exist at all.
+    #[must_use]
+    pub fn is_equivalent_register_algorithm(&self, register: usize, algorithm: &Algorithm) -> bool {
+        let current = self.registers[register].algorithm().permutation();
+        let candidate = algorithm.permutation();
+
+        self.registers
+            .iter()
+            .flat_map(|register| {
+                register
+                    .unshared_cycles()
+                    .iter()
+                    .flat_map(|cycle| cycle.facelet_cycle().iter())
+            })
+            .chain(self.shared_facelets.iter())
+            .all(|&facelet| candidate.mapping()[facelet] == current.mapping()[facelet])
+    }
+
+    /// Swap the algorithm generating `register` for an equivalent one, typically a shorter one found by a solver. The register's cycles and order are unaffected because the replacement acts identically on them.
+    ///
+    /// # Errors
+    ///
+    /// If [`Architecture::is_equivalent_register_algorithm`] rejects the algorithm, the architecture is left untouched and the algorithm is handed back.
+    pub fn swap_register_algorithm(
+        &mut self,
+        register: usize,
+        algorithm: Algorithm,
+    ) -> Result<(), Algorithm> {
+        if !self.is_equivalent_register_algorithm(register, &algorithm) {
+            return Err(algorithm);
+        }
+
+        self.registers[register].algorithm = algorithm;
+        // The decoding table bakes in the register algorithms' move
+        // sequences, so it must be rebuilt around the new one
+        self.decoded_table = OnceLock::new();
+
+        Ok(())
+    }
 }
 
 /// Get a puzzle definition by name
@@ -1152,6 +1537,10 @@ pub fn puzzle_definition() -> impl Parser<'static, File, Arc<PuzzleDefinition>,
             Arc::new(PuzzleDefinition {
                 perm_group: group,
                 presets: presets.into(),
+                // The literal marks the numbering the tables above were
+                // derived from; bumping `FACELET_ORDER_VERSION` alone makes
+                // `get_preset` refuse them until they are re-derived
+                facelet_order_version: 1,
             })
         })
         .memoized()
@@ -1166,14 +1555,14 @@ pub fn mk_puzzle_definition(def: &str) -> Option<Arc<PuzzleDefinition>> {
 #[cfg(test)]
 mod tests {
 
-    use std::sync::Arc;
+    use std::{collections::HashMap, sync::Arc};
 
     use internment::ArcIntern;
     use itertools::Itertools;
 
-    use crate::{I, Int, U, architectures::mk_puzzle_definition};
+    use crate::{I, Int, Span, U, architectures::mk_puzzle_definition};
 
-    use super::Architecture;
+    use super::{Algorithm, Architecture, Permutation, PermutationGroup};
 
     #[test]
     fn three_by_three() {
@@ -1213,6 +1602,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn swap_register_algorithm_validates_effect() {
+        let cube_def = mk_puzzle_definition("3x3").unwrap();
+
+        let mut arch = Architecture::new(
+            Arc::clone(&cube_def.perm_group),
+            &[vec![ArcIntern::from("U"), ArcIntern::from("U")]],
+        )
+        .unwrap();
+        let order = arch.registers()[0].order();
+
+        let u2 = Algorithm::parse_from_string(Arc::clone(&cube_def.perm_group), "U2").unwrap();
+        let f2 = Algorithm::parse_from_string(Arc::clone(&cube_def.perm_group), "F2").unwrap();
+
+        // `F2` has the same cycle structure as `U U` but moves the wrong
+        // facelets
+        assert!(!arch.is_equivalent_register_algorithm(0, &f2));
+        assert!(arch.swap_register_algorithm(0, f2).is_err());
+
+        assert!(arch.is_equivalent_register_algorithm(0, &u2));
+        arch.swap_register_algorithm(0, u2).unwrap();
+        assert_eq!(arch.registers()[0].algorithm().to_string(), "U2");
+        assert_eq!(arch.registers()[0].order(), order);
+    }
+
     #[test]
     fn exponentiation() {
         let cube_def = mk_puzzle_definition("3x3").unwrap();
@@ -1242,4 +1656,223 @@ mod tests {
 
         assert_eq!(exp_perm, repeat_compose_perm);
     }
+
+    #[test]
+    fn algorithm_equality_by_effect() {
+        let cube_def = mk_puzzle_definition("3x3").unwrap();
+
+        let triple_r =
+            Algorithm::parse_from_string(Arc::clone(&cube_def.perm_group), "R R R").unwrap();
+        let r_prime = Algorithm::parse_from_string(Arc::clone(&cube_def.perm_group), "R'").unwrap();
+        let u = Algorithm::parse_from_string(Arc::clone(&cube_def.perm_group), "U").unwrap();
+
+        assert_eq!(triple_r, r_prime);
+        assert_ne!(triple_r, u);
+
+        assert_eq!(triple_r.canonical_moves(), vec![ArcIntern::from("R'")]);
+        assert_eq!(r_prime.canonical_moves(), vec![ArcIntern::from("R'")]);
+
+        let sexy_move =
+            Algorithm::parse_from_string(Arc::clone(&cube_def.perm_group), "R U R' U'").unwrap();
+        assert_eq!(sexy_move.canonical_moves().len(), 4);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let cube_def = mk_puzzle_definition("3x3").unwrap();
+
+        let generators = cube_def
+            .perm_group
+            .generators()
+            .map(|(name, _)| name)
+            .sorted()
+            .collect_vec();
+
+        // Xorshift; the tests must be deterministic
+        let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..50 {
+            let len = usize::try_from(next() % 20).unwrap();
+
+            let word = (0..len)
+                .map(|_| {
+                    let idx = next() % u64::try_from(generators.len()).unwrap();
+                    ArcIntern::clone(&generators[usize::try_from(idx).unwrap()])
+                })
+                .collect_vec();
+
+            let alg = Algorithm::new_from_move_seq(Arc::clone(&cube_def.perm_group), word.clone())
+                .unwrap();
+
+            let displayed = alg.to_string();
+            assert_eq!(displayed, word.iter().join(" "));
+
+            let reparsed =
+                Algorithm::parse_from_string(Arc::clone(&cube_def.perm_group), &displayed).unwrap();
+            assert_eq!(reparsed, alg);
+            assert_eq!(reparsed.to_string(), displayed);
+        }
+    }
+
+    #[test]
+    fn normalize_canonicalizes_turn_names() {
+        // A cyclic group of order four where every power of the base turn is a
+        // generator, including the redundant spellings `U3` and `U4`
+        let base = Permutation::from_cycles(vec![vec![0, 1, 2, 3]]);
+
+        let mut generators = HashMap::new();
+        for (name, power) in [("U", 1_i64), ("U2", 2), ("U3", 3), ("U'", 3), ("U4", 4)] {
+            let mut perm = base.clone();
+            perm.exponentiate(Int::<I>::from(power));
+            generators.insert(ArcIntern::from(name), perm);
+        }
+
+        let group = Arc::new(PermutationGroup::new(
+            (0..4).map(|i| ArcIntern::from(format!("{i}"))).collect(),
+            generators,
+            Span::from_static("cyclic4"),
+        ));
+
+        let mut alg = Algorithm::parse_from_string(Arc::clone(&group), "U3 U2 U U3").unwrap();
+        let before = alg.permutation().clone();
+        alg.normalize();
+        assert_eq!(alg.to_string(), "U' U2 U U'");
+        assert_eq!(*alg.permutation(), before);
+
+        let mut alg = Algorithm::parse_from_string(Arc::clone(&group), "U U4 U'").unwrap();
+        alg.normalize();
+        assert_eq!(alg.to_string(), "U U'");
+    }
+
+    #[test]
+    fn isomorphic_labeling_of_relabeled_group() {
+        let cube_def = mk_puzzle_definition("3x3").unwrap();
+        let group = &cube_def.perm_group;
+
+        // Relabel every facelet by reversal
+        let facelet_count = group.facelet_count();
+        let relabel = |i: usize| facelet_count - 1 - i;
+
+        let generators = group
+            .generators()
+            .map(|(name, perm)| {
+                let mut mapping = vec![0; facelet_count];
+                for (from, &to) in perm.mapping().iter().enumerate() {
+                    mapping[relabel(from)] = relabel(to);
+                }
+
+                (name, Permutation::from_mapping(mapping))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let relabeled = PermutationGroup::new(
+            group.facelet_colors().iter().rev().cloned().collect(),
+            generators,
+            Span::from_static("relabeled"),
+        );
+
+        let labeling = group.is_isomorphic_labeling(&relabeled).unwrap();
+
+        // The labeling must conjugate every generator onto its counterpart
+        for (name, perm) in group.generators() {
+            let other = relabeled.get_generator(&name).unwrap();
+
+            for (from, &to) in perm.mapping().iter().enumerate() {
+                assert_eq!(other.mapping()[labeling[from]], labeling[to]);
+            }
+        }
+    }
+
+    #[test]
+    fn non_isomorphic_groups_have_no_labeling() {
+        let mut gens_a = HashMap::new();
+        gens_a.insert(
+            ArcIntern::from("U"),
+            Permutation::from_cycles(vec![vec![0, 1, 2, 3]]),
+        );
+        let mut inverse = Permutation::from_cycles(vec![vec![0, 1, 2, 3]]);
+        inverse.exponentiate(Int::from(-1));
+        gens_a.insert(ArcIntern::from("U'"), inverse);
+
+        let a = PermutationGroup::new(
+            (0..4).map(|i| ArcIntern::from(format!("{i}"))).collect(),
+            gens_a,
+            Span::from_static("four cycle"),
+        );
+
+        let mut gens_b = HashMap::new();
+        gens_b.insert(
+            ArcIntern::from("U"),
+            Permutation::from_cycles(vec![vec![0, 1], vec![2, 3]]),
+        );
+        gens_b.insert(
+            ArcIntern::from("U'"),
+            Permutation::from_cycles(vec![vec![0, 1], vec![2, 3]]),
+        );
+
+        let b = PermutationGroup::new(
+            (0..4).map(|i| ArcIntern::from(format!("{i}"))).collect(),
+            gens_b,
+            Span::from_static("double swap"),
+        );
+
+        assert!(a.is_isomorphic_labeling(&b).is_none());
+    }
+
+    #[test]
+    fn lazy_permutation_is_cached_after_first_use() {
+        let cube_def = mk_puzzle_definition("3x3").unwrap();
+
+        let alg =
+            Algorithm::parse_from_string(Arc::clone(&cube_def.perm_group), "R U R' U'").unwrap();
+
+        // A hot path like `repeat-until` must hit the cached permutation on
+        // every application after the first rather than recomposing the word
+        assert!(core::ptr::eq(alg.permutation(), alg.permutation()));
+
+        // Materializing lazily must match composing the generators eagerly
+        let mut eager = cube_def.perm_group.identity();
+        cube_def
+            .perm_group
+            .compose_generators_into(&mut eager, ["R", "U", "R'", "U'"].iter())
+            .unwrap();
+        assert_eq!(*alg.permutation(), eager);
+    }
+
+    #[test]
+    fn lazy_algorithms_behave_like_eager_ones() {
+        let cube_def = mk_puzzle_definition("3x3").unwrap();
+
+        // Compose and exponentiate algorithms whose permutations were never
+        // materialized, then compare against the same operations performed
+        // directly on permutations
+        let mut alg = Algorithm::parse_from_string(Arc::clone(&cube_def.perm_group), "R U").unwrap();
+        let other = Algorithm::parse_from_string(Arc::clone(&cube_def.perm_group), "F2 D'").unwrap();
+        alg.compose_into(&other);
+        alg.exponentiate(Int::from(-3));
+
+        let mut eager = cube_def.perm_group.identity();
+        cube_def
+            .perm_group
+            .compose_generators_into(&mut eager, ["R", "U", "F2", "D'"].iter())
+            .unwrap();
+        eager.exponentiate(Int::from(-3));
+
+        assert_eq!(*alg.permutation(), eager);
+
+        // Invalid moves must still be rejected at construction time
+        assert_eq!(
+            Algorithm::new_from_move_seq(
+                Arc::clone(&cube_def.perm_group),
+                vec![ArcIntern::from("R"), ArcIntern::from("Q")],
+            ),
+            Err(ArcIntern::from("Q"))
+        );
+    }
 }
@@ -0,0 +1,450 @@
+//! A stable JSON representation of qter data for external tooling
+//!
+//! Web frontends and analysis scripts can't consume qter's Rust types
+//! directly, so this module flattens them into plain data structures that
+//! serialize to a documented schema. Every top-level document carries
+//! [`SCHEMA_VERSION`], which will be bumped whenever the structure of a
+//! document changes incompatibly.
+//!
+//! Register orders and other big integers are serialized as decimal strings
+//! because they can exceed the range a JSON number can represent exactly.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ByPuzzleType, Instruction, Program,
+    architectures::{Algorithm, Architecture},
+};
+
+/// The version of the JSON schema emitted by this module
+pub const SCHEMA_VERSION: u32 = 1;
+
+fn moves_of(algorithm: &Algorithm) -> Vec<String> {
+    algorithm
+        .move_seq_iter()
+        .map(|move_| (**move_).to_owned())
+        .collect()
+}
+
+/// Which state a register or instruction operates on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum StateJson {
+    /// A theoretical register, by index into `theoretical_orders`
+    Theoretical { index: usize },
+    /// A real puzzle, by index into `puzzles`
+    Puzzle { index: usize },
+}
+
+/// A puzzle declared by a program
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzleJson {
+    /// The amount of facelets on the puzzle
+    pub facelet_count: usize,
+    /// The names of the puzzle's generators, sorted for a canonical order
+    pub generators: Vec<String>,
+}
+
+/// A register declared by a program
+///
+/// `algorithm` and `facelets` are `null` for theoretical registers, which
+/// have no generator to decode.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisterJson {
+    /// The name the register was declared with
+    pub name: String,
+    /// The amount of values the register can hold, as a decimal string
+    pub order: String,
+    /// Which state the register lives in
+    pub state: StateJson,
+    /// The move words of the register's generator algorithm
+    pub algorithm: Option<Vec<String>>,
+    /// The facelets needed to decode the register's value
+    pub facelets: Option<Vec<usize>>,
+}
+
+/// A register whose value a `solve` instruction preserves
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreservedRegisterJson {
+    /// The move words of the register's generator algorithm
+    pub algorithm: Vec<String>,
+    /// The facelets needed to decode the register's value
+    pub facelets: Vec<usize>,
+}
+
+/// A single qter instruction
+///
+/// The `kind` tag matches the QAT primitive the instruction came from.
+/// Fields that only apply to one side of the theoretical/puzzle split are
+/// `null` on the other side.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum InstructionJson {
+    Goto {
+        instruction_idx: usize,
+    },
+    SolvedGoto {
+        instruction_idx: usize,
+        state: StateJson,
+        facelets: Option<Vec<usize>>,
+        /// The modulus a theoretical register's value is checked against, as
+        /// a decimal string; `null` for puzzles, whose facelets encode it
+        #[serde(default)]
+        modulus: Option<String>,
+    },
+    Input {
+        message: String,
+        state: StateJson,
+        algorithm: Option<Vec<String>>,
+        facelets: Option<Vec<usize>>,
+    },
+    Halt {
+        message: String,
+        state: Option<StateJson>,
+        algorithm: Option<Vec<String>>,
+        facelets: Option<Vec<usize>>,
+    },
+    Print {
+        message: String,
+        state: Option<StateJson>,
+        algorithm: Option<Vec<String>>,
+        facelets: Option<Vec<usize>>,
+    },
+    Add {
+        state: StateJson,
+        /// The amount added to a theoretical register, as a decimal string
+        amount: Option<String>,
+        algorithm: Option<Vec<String>>,
+    },
+    Random {
+        /// The largest amount the instruction may add, as a decimal string
+        max: String,
+        state: StateJson,
+        algorithm: Option<Vec<String>>,
+    },
+    Solve {
+        state: StateJson,
+        /// The registers whose values survive the solve; always empty for
+        /// theoretical states
+        #[serde(default)]
+        preserved: Vec<PreservedRegisterJson>,
+    },
+    RepeatUntil {
+        puzzle_index: usize,
+        facelets: Vec<usize>,
+        algorithm: Vec<String>,
+    },
+    Assert {
+        message: String,
+        /// The value the register must decode to, as a decimal string
+        expected: String,
+        state: StateJson,
+        algorithm: Option<Vec<String>>,
+        facelets: Option<Vec<usize>>,
+    },
+}
+
+/// A compiled qter program
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramJson {
+    /// See [`SCHEMA_VERSION`]
+    pub schema_version: u32,
+    /// The orders of the theoretical registers, as decimal strings
+    pub theoretical_orders: Vec<String>,
+    /// The puzzles used by the program
+    pub puzzles: Vec<PuzzleJson>,
+    /// The declared registers, in declaration order
+    pub registers: Vec<RegisterJson>,
+    /// The program itself; `instruction_idx` fields index into this list
+    pub instructions: Vec<InstructionJson>,
+}
+
+impl From<&Program> for ProgramJson {
+    fn from(program: &Program) -> Self {
+        let puzzles = program
+            .puzzles
+            .iter()
+            .map(|group| {
+                let mut generators = group
+                    .generators()
+                    .map(|(name, _)| (*name).to_owned())
+                    .collect::<Vec<_>>();
+                generators.sort_unstable();
+
+                PuzzleJson {
+                    facelet_count: group.facelet_count(),
+                    generators,
+                }
+            })
+            .collect();
+
+        let registers = program
+            .registers
+            .iter()
+            .map(|info| {
+                let (state, algorithm, facelets) = match &info.location {
+                    ByPuzzleType::Theoretical((idx, ())) => {
+                        (StateJson::Theoretical { index: idx.0 }, None, None)
+                    }
+                    ByPuzzleType::Puzzle((idx, (algorithm, facelets))) => (
+                        StateJson::Puzzle { index: idx.0 },
+                        Some(moves_of(algorithm)),
+                        Some(facelets.0.clone()),
+                    ),
+                };
+
+                RegisterJson {
+                    name: (*info.name).to_owned(),
+                    order: info.order.to_string(),
+                    state,
+                    algorithm,
+                    facelets,
+                }
+            })
+            .collect();
+
+        ProgramJson {
+            schema_version: SCHEMA_VERSION,
+            theoretical_orders: program
+                .theoretical
+                .iter()
+                .map(|order| order.to_string())
+                .collect(),
+            puzzles,
+            registers,
+            instructions: program
+                .instructions
+                .iter()
+                .map(|instruction| instruction_json(instruction))
+                .collect(),
+        }
+    }
+}
+
+fn instruction_json(instruction: &Instruction) -> InstructionJson {
+    match instruction {
+        Instruction::Goto { instruction_idx } => InstructionJson::Goto {
+            instruction_idx: *instruction_idx,
+        },
+        Instruction::SolvedGoto(ByPuzzleType::Theoretical((solved_goto, idx, modulus))) => {
+            InstructionJson::SolvedGoto {
+                instruction_idx: solved_goto.instruction_idx,
+                state: StateJson::Theoretical { index: idx.0 },
+                facelets: None,
+                modulus: Some(modulus.to_string()),
+            }
+        }
+        Instruction::SolvedGoto(ByPuzzleType::Puzzle((solved_goto, idx, facelets))) => {
+            InstructionJson::SolvedGoto {
+                instruction_idx: solved_goto.instruction_idx,
+                state: StateJson::Puzzle { index: idx.0 },
+                facelets: Some(facelets.0.clone()),
+                modulus: None,
+            }
+        }
+        Instruction::Input(ByPuzzleType::Theoretical((input, idx))) => InstructionJson::Input {
+            message: input.message.clone(),
+            state: StateJson::Theoretical { index: idx.0 },
+            algorithm: None,
+            facelets: None,
+        },
+        Instruction::Input(ByPuzzleType::Puzzle((input, idx, algorithm, facelets))) => {
+            InstructionJson::Input {
+                message: input.message.clone(),
+                state: StateJson::Puzzle { index: idx.0 },
+                algorithm: Some(moves_of(algorithm)),
+                facelets: Some(facelets.0.clone()),
+            }
+        }
+        Instruction::Halt(ByPuzzleType::Theoretical((halt, idx))) => InstructionJson::Halt {
+            message: halt.message.clone(),
+            state: idx.map(|idx| StateJson::Theoretical { index: idx.0 }),
+            algorithm: None,
+            facelets: None,
+        },
+        Instruction::Halt(ByPuzzleType::Puzzle((halt, register))) => InstructionJson::Halt {
+            message: halt.message.clone(),
+            state: register
+                .as_ref()
+                .map(|(idx, _, _)| StateJson::Puzzle { index: idx.0 }),
+            algorithm: register
+                .as_ref()
+                .map(|(_, algorithm, _)| moves_of(algorithm)),
+            facelets: register.as_ref().map(|(_, _, facelets)| facelets.0.clone()),
+        },
+        Instruction::Print(ByPuzzleType::Theoretical((print, idx))) => InstructionJson::Print {
+            message: print.message.clone(),
+            state: idx.map(|idx| StateJson::Theoretical { index: idx.0 }),
+            algorithm: None,
+            facelets: None,
+        },
+        Instruction::Print(ByPuzzleType::Puzzle((print, register))) => InstructionJson::Print {
+            message: print.message.clone(),
+            state: register
+                .as_ref()
+                .map(|(idx, _, _)| StateJson::Puzzle { index: idx.0 }),
+            algorithm: register
+                .as_ref()
+                .map(|(_, algorithm, _)| moves_of(algorithm)),
+            facelets: register.as_ref().map(|(_, _, facelets)| facelets.0.clone()),
+        },
+        Instruction::PerformAlgorithm(ByPuzzleType::Theoretical((idx, amount))) => {
+            InstructionJson::Add {
+                state: StateJson::Theoretical { index: idx.0 },
+                amount: Some(amount.to_string()),
+                algorithm: None,
+            }
+        }
+        Instruction::PerformAlgorithm(ByPuzzleType::Puzzle((idx, algorithm))) => {
+            InstructionJson::Add {
+                state: StateJson::Puzzle { index: idx.0 },
+                amount: None,
+                algorithm: Some(moves_of(algorithm)),
+            }
+        }
+        Instruction::Random(ByPuzzleType::Theoretical((random, idx))) => InstructionJson::Random {
+            max: random.max.to_string(),
+            state: StateJson::Theoretical { index: idx.0 },
+            algorithm: None,
+        },
+        Instruction::Random(ByPuzzleType::Puzzle((random, idx, algorithm))) => {
+            InstructionJson::Random {
+                max: random.max.to_string(),
+                state: StateJson::Puzzle { index: idx.0 },
+                algorithm: Some(moves_of(algorithm)),
+            }
+        }
+        Instruction::Solve(ByPuzzleType::Theoretical(idx)) => InstructionJson::Solve {
+            state: StateJson::Theoretical { index: idx.0 },
+            preserved: Vec::new(),
+        },
+        Instruction::Solve(ByPuzzleType::Puzzle((idx, preserved))) => InstructionJson::Solve {
+            state: StateJson::Puzzle { index: idx.0 },
+            preserved: preserved
+                .iter()
+                .map(|(algorithm, facelets)| PreservedRegisterJson {
+                    algorithm: moves_of(algorithm),
+                    facelets: facelets.0.clone(),
+                })
+                .collect(),
+        },
+        Instruction::RepeatUntil(ByPuzzleType::Theoretical(infallible)) => match *infallible {},
+        Instruction::RepeatUntil(ByPuzzleType::Puzzle(repeat_until)) => {
+            InstructionJson::RepeatUntil {
+                puzzle_index: repeat_until.puzzle_idx.0,
+                facelets: repeat_until.facelets.0.clone(),
+                algorithm: moves_of(&repeat_until.alg),
+            }
+        }
+        Instruction::Assert(ByPuzzleType::Theoretical((assert, idx))) => InstructionJson::Assert {
+            message: assert.message.clone(),
+            expected: assert.expected.to_string(),
+            state: StateJson::Theoretical { index: idx.0 },
+            algorithm: None,
+            facelets: None,
+        },
+        Instruction::Assert(ByPuzzleType::Puzzle((assert, idx, algorithm, facelets))) => {
+            InstructionJson::Assert {
+                message: assert.message.clone(),
+                expected: assert.expected.to_string(),
+                state: StateJson::Puzzle { index: idx.0 },
+                algorithm: Some(moves_of(algorithm)),
+                facelets: Some(facelets.0.clone()),
+            }
+        }
+    }
+}
+
+/// A register of an [`ArchitectureJson`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchitectureRegisterJson {
+    /// The amount of values the register can hold, as a decimal string
+    pub order: String,
+    /// The move words of the register's generator algorithm
+    pub algorithm: Vec<String>,
+    /// The facelets that allow decoding the register and determining whether
+    /// it is solved
+    pub signature_facelets: Vec<usize>,
+}
+
+/// An architecture: a set of registers coexisting on one puzzle
+///
+/// The underlying permutation group is not part of the document; consumers
+/// that need to reconstruct an [`Architecture`] must pair the algorithms with
+/// the puzzle they were defined on.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchitectureJson {
+    /// See [`SCHEMA_VERSION`]
+    pub schema_version: u32,
+    /// The registers of the architecture, in order
+    pub registers: Vec<ArchitectureRegisterJson>,
+}
+
+impl From<&Architecture> for ArchitectureJson {
+    fn from(architecture: &Architecture) -> Self {
+        ArchitectureJson {
+            schema_version: SCHEMA_VERSION,
+            registers: architecture
+                .registers()
+                .iter()
+                .map(|register| ArchitectureRegisterJson {
+                    order: register.order().to_string(),
+                    algorithm: moves_of(register.algorithm()),
+                    signature_facelets: register.signature_facelets().0,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        Int, U,
+        architectures::{Architecture, mk_puzzle_definition},
+    };
+
+    use super::{ArchitectureJson, SCHEMA_VERSION};
+
+    #[test]
+    fn architecture_round_trips_through_json() {
+        let cube_def = mk_puzzle_definition("3x3").unwrap();
+
+        let architecture = cube_def
+            .get_preset(&[Int::<U>::from(90_u64), Int::<U>::from(90_u64)])
+            .unwrap();
+
+        let exported = ArchitectureJson::from(&*architecture);
+        assert_eq!(exported.schema_version, SCHEMA_VERSION);
+
+        let serialized = serde_json::to_string(&exported).unwrap();
+        let deserialized: ArchitectureJson = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, exported);
+
+        // Pairing the deserialized algorithms with the original puzzle must
+        // reconstruct the same registers
+        let reconstructed = Architecture::new(
+            Arc::clone(&cube_def.perm_group),
+            &deserialized
+                .registers
+                .iter()
+                .map(|register| register.algorithm.clone())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        for (original, reconstructed) in architecture
+            .registers()
+            .iter()
+            .zip(reconstructed.registers())
+        {
+            assert_eq!(original.order(), reconstructed.order());
+            assert_eq!(
+                original.algorithm().permutation(),
+                reconstructed.algorithm().permutation()
+            );
+        }
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(clippy::pedantic)]
 #![allow(
     clippy::too_many_lines,
@@ -5,15 +6,50 @@
     clippy::missing_panics_doc
 )]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod architectures;
+#[cfg(feature = "std")]
+pub mod parallelism;
+#[cfg(feature = "std")]
+pub mod scramble;
+#[cfg(feature = "std")]
 mod shared_facelet_detection;
+#[cfg(feature = "std")]
 pub mod table_encoding;
 
+#[cfg(feature = "std")]
 mod span;
+#[cfg(feature = "std")]
 pub use span::*;
 
+#[cfg(feature = "std")]
 mod runtime;
+#[cfg(feature = "std")]
 pub use runtime::*;
 
 mod math;
 pub use math::*;
+
+#[cfg(feature = "json")]
+pub mod json;
+
+/// The most commonly used qter types, re-exported so consumers can glob
+/// import them instead of maintaining a long import list
+///
+/// ```
+/// use qter_core::prelude::*;
+///
+/// let three = Int::<U>::from(3_u32);
+/// assert_eq!(three + Int::one(), Int::from(4_u32));
+/// ```
+#[cfg(feature = "std")]
+pub mod prelude {
+    pub use crate::{
+        ByPuzzleType, Facelets, File, I, Instruction, Int, Program, PuzzleIdx, RegisterInfo,
+        SeparatesByPuzzleType, Span, StateIdx, TheoreticalIdx, U, WithSpan,
+        architectures::{Algorithm, Architecture, Permutation, PermutationGroup},
+        scramble::Scramble,
+    };
+}
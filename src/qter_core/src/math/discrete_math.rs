@@ -1,7 +1,8 @@
-use crate::{
-    I, Int, U,
-    architectures::{Algorithm, Permutation},
-};
+use alloc::vec;
+
+#[cfg(feature = "std")]
+use crate::architectures::{Algorithm, Permutation};
+use crate::{I, Int, U};
 
 /// Calculate the GCD of two numbers
 #[must_use]
@@ -59,6 +60,27 @@ pub fn extended_euclid(mut a: Int<U>, mut b: Int<U>) -> ((Int<I>, Int<I>), Int<U
     }
 }
 
+/// Calculate the modular inverse of `c` modulo `n`, i.e. the value `c⁻¹` such that `c·c⁻¹ ≡ 1 (mod n)`.
+///
+/// The inverse exists exactly when `gcd(c, n) = 1`; otherwise this returns `None`. Multiplying a register by the inverse of a constant is how divide-by-constant programs are generated.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+#[must_use]
+pub fn mod_inverse(c: Int<U>, n: Int<U>) -> Option<Int<U>> {
+    assert!(!n.is_zero());
+
+    let ((c_coefficient, _), gcd) = extended_euclid(c, n);
+
+    if gcd != Int::<U>::one() {
+        return None;
+    }
+
+    // Bézout gives `c·x + n·y = 1`, so `x mod n` is the inverse
+    Some(c_coefficient % n)
+}
+
 // Implementation based on https://math.stackexchange.com/questions/1644677/what-to-do-if-the-modulus-is-not-coprime-in-the-chinese-remainder-theorem
 /// Calculate the chinese remainder theorem over a list of tuples of remainders with moduli. The return value is bounded by the LCM of the moduli.
 ///
@@ -143,6 +165,7 @@ pub fn length_of_substring_that_this_string_is_n_repeated_copies_of<'a>(
 /// Decode the permutation using the register generator and the given facelets.
 ///
 /// In general, an arbitrary scramble cannot be decoded. If this is the case, the function will return `None`.
+#[cfg(feature = "std")]
 pub fn decode(
     permutation: &Permutation,
     facelets: &[usize],
@@ -186,7 +209,7 @@ mod tests {
         architectures::{Algorithm, mk_puzzle_definition},
         discrete_math::{
             decode, extended_euclid, gcd, lcm,
-            length_of_substring_that_this_string_is_n_repeated_copies_of,
+            length_of_substring_that_this_string_is_n_repeated_copies_of, mod_inverse,
         },
     };
 
@@ -215,6 +238,33 @@ mod tests {
         assert_eq!(lcm_int(4, 6), 12);
     }
 
+    #[test]
+    fn modular_inverses() {
+        let mod_inverse_int =
+            |c: u64, n: u64| mod_inverse(Int::from(c), Int::from(n)).map(|v| v.to_u64());
+
+        assert_eq!(mod_inverse_int(3, 7), Some(5));
+        assert_eq!(mod_inverse_int(1, 2), Some(1));
+        assert_eq!(mod_inverse_int(10, 17), Some(12));
+        // Everything is congruent to zero mod one
+        assert_eq!(mod_inverse_int(5, 1), Some(0));
+
+        assert_eq!(mod_inverse_int(4, 6), None);
+        assert_eq!(mod_inverse_int(0, 5), None);
+
+        // Every residue coprime to the modulus must invert, and nothing else
+        for n in [2_u64, 9, 24, 90, 210] {
+            for c in 1..n {
+                match mod_inverse(Int::from(c), Int::from(n)) {
+                    Some(inverse) => {
+                        assert_eq!((Int::<U>::from(c) * inverse) % Int::from(n), Int::<U>::one());
+                    }
+                    None => assert_ne!(gcd(Int::from(c), Int::from(n)), Int::<U>::one()),
+                }
+            }
+        }
+    }
+
     fn crt_int(v: impl IntoIterator<Item = (u64, u64)>) -> Option<u64> {
         chinese_remainder_theorem(
             v.into_iter()
@@ -1,5 +1,6 @@
 pub mod discrete_math;
 mod numbers;
+#[cfg(feature = "std")]
 pub mod schreier_sims;
 pub mod union_find;
 
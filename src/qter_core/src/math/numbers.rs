@@ -1,5 +1,9 @@
 //! The point of this module is to define a generic number type so that we can try out different number types without refactoring. I'm most interested in arbitrary size integers so that we can represent arbitrarily large orders (megaminx) but that would come with a performance penalty since we lose the Copy implementation.
-use std::{
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+};
+use core::{
     cmp::Ordering,
     fmt::{Debug, Display},
     iter::{Product, Sum},
@@ -84,6 +88,38 @@ impl<Signed> Int<Signed> {
 }
 
 impl Int<I> {
+    /// Parses an integer in the given radix, where digits above nine are represented by the letters `a` through `z`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not a valid integer in the given radix
+    ///
+    /// # Panics
+    ///
+    /// Panics if the radix is not in the range 2 to 36
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<Int<I>, ParseIntError<I>> {
+        Ok(Self::from_inner(
+            I512::from_str_radix(s.trim(), radix).map_err(map_err(s))?,
+        ))
+    }
+
+    /// Create an `Int` from the closest integer to the given float
+    ///
+    /// Returns `None` if the value is `NaN`, infinite, or too large to represent.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn from_f64_rounded(value: f64) -> Option<Int<I>> {
+        if !value.is_finite() {
+            return None;
+        }
+
+        let rounded = value.round();
+
+        I512::from_str_radix(&format!("{rounded:.0}"), 10)
+            .ok()
+            .map(Int::from_inner)
+    }
+
     #[must_use]
     pub fn signum(&self) -> i8 {
         self.value.signum().as_()
@@ -98,6 +134,72 @@ impl Int<I> {
     }
 }
 
+impl Int<U> {
+    /// Create an `Int` from the closest integer to the given float
+    ///
+    /// Returns `None` if the value is negative, `NaN`, infinite, or too large to represent.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn from_f64_rounded(value: f64) -> Option<Int<U>> {
+        let int = Int::<I>::from_f64_rounded(value)?;
+
+        if int.signum() == -1 {
+            return None;
+        }
+
+        Some(Int::from_inner(int.value))
+    }
+
+    /// Calculate the modular inverse of this value modulo `n`, i.e. the value `v` such that `self·v ≡ 1 (mod n)`
+    ///
+    /// The inverse exists exactly when `gcd(self, n) = 1`; otherwise this returns `None`. See [`mod_inverse`](super::discrete_math::mod_inverse).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    #[must_use]
+    pub fn mod_inverse(self, n: Int<U>) -> Option<Int<U>> {
+        super::discrete_math::mod_inverse(self, n)
+    }
+
+    /// Draw a uniformly random value in the range `[0, bound)`
+    ///
+    /// `next_word` is the source of randomness and must return uniformly random 64 bit words; a seeded xorshift works fine. Candidates of `bound`'s bit length are drawn and anything at or above `bound` is thrown away, so the result is exactly uniform rather than carrying the bias of reducing a fixed-width value modulo `bound`. Each candidate is accepted with probability at least one half, so the expected number of words consumed is small.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is zero.
+    #[must_use]
+    pub fn random_below(next_word: &mut impl FnMut() -> u64, bound: Int<U>) -> Int<U> {
+        assert!(!bound.is_zero());
+
+        // Enough bits to represent `bound - 1`; every value a candidate can
+        // take is a possible return value except the slice rejected at the top
+        let bits = (bound.value - I512::ONE).unsigned_abs().bits();
+        let whole_words = bits / 64;
+        let leftover_bits = bits % 64;
+
+        loop {
+            let mut candidate = U512::ZERO;
+
+            for _ in 0..whole_words {
+                candidate = (candidate << 64u32) | U512::from(next_word());
+            }
+
+            if leftover_bits > 0 {
+                candidate =
+                    (candidate << leftover_bits) | U512::from(next_word() >> (64 - leftover_bits));
+            }
+
+            let candidate = candidate.cast_signed();
+
+            if candidate < bound.value {
+                return Int::from_inner(candidate);
+            }
+        }
+    }
+}
+
 impl<Signed> Clone for Int<Signed> {
     fn clone(&self) -> Self {
         *self
@@ -107,13 +209,13 @@ impl<Signed> Clone for Int<Signed> {
 impl<Signed> Copy for Int<Signed> {}
 
 impl<Signed> Debug for Int<Signed> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{} {}", core::any::type_name::<Signed>(), self)
     }
 }
 
 impl<Signed> Display for Int<Signed> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         Display::fmt(&self.value, f)
     }
 }
@@ -126,13 +228,13 @@ pub struct NumberOutOfRange<Signed> {
 }
 
 impl<Signed> Debug for NumberOutOfRange<Signed> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{self}")
     }
 }
 
 impl<Signed> Display for NumberOutOfRange<Signed> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "The number {} is out of range for values of type {} that must be between {} and {}.",
@@ -143,25 +245,38 @@ impl<Signed> Display for NumberOutOfRange<Signed> {
 
 pub struct ParseIntError<Signed> {
     err: bnum::errors::ParseIntError,
+    offending: String,
     phantom: PhantomData<Signed>,
 }
 
-fn map_err<Signed>(err: bnum::errors::ParseIntError) -> ParseIntError<Signed> {
-    ParseIntError {
+impl<Signed> ParseIntError<Signed> {
+    /// The text that failed to parse as an integer
+    pub fn offending(&self) -> &str {
+        &self.offending
+    }
+}
+
+fn map_err<Signed>(s: &str) -> impl Fn(bnum::errors::ParseIntError) -> ParseIntError<Signed> + '_ {
+    move |err| ParseIntError {
         err,
+        offending: s.trim().to_owned(),
         phantom: PhantomData,
     }
 }
 
 impl<Signed> Debug for ParseIntError<Signed> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         Debug::fmt(&self.err, f)
     }
 }
 
 impl<Signed> Display for ParseIntError<Signed> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Display::fmt(&self.err, f)
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "`{}` is not a valid integer; {}",
+            self.offending, self.err
+        )
     }
 }
 
@@ -169,7 +284,7 @@ impl FromStr for Int<I> {
     type Err = ParseIntError<I>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self::from_inner(s.trim().parse().map_err(map_err)?))
+        Ok(Self::from_inner(s.trim().parse().map_err(map_err(s))?))
     }
 }
 
@@ -177,8 +292,8 @@ impl FromStr for Int<U> {
     type Err = ParseIntError<U>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let num: U512 = s.trim().parse().map_err(map_err)?;
-        let num: I512 = num.to_string().parse().map_err(map_err)?;
+        let num: U512 = s.trim().parse().map_err(map_err(s))?;
+        let num: I512 = num.to_string().parse().map_err(map_err(s))?;
 
         Ok(Self::from_inner(num))
     }
@@ -368,7 +483,7 @@ impl<SignedA, SignedB> PartialEq<Int<SignedA>> for Int<SignedB> {
 impl<Signed> Eq for Int<Signed> {}
 
 impl<Signed> core::hash::Hash for Int<Signed> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.value.hash(state);
     }
 }
@@ -443,3 +558,113 @@ impl Product for Int<I> {
         accumulator
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{I, Int, U};
+
+    #[test]
+    fn from_f64_rounded_exact() {
+        assert_eq!(Int::<U>::from_f64_rounded(12.0).unwrap().to_u64(), 12);
+        assert_eq!(Int::<I>::from_f64_rounded(-12.0).unwrap().to_i64(), -12);
+    }
+
+    #[test]
+    fn from_f64_rounded_rounds_to_nearest() {
+        assert_eq!(Int::<U>::from_f64_rounded(2.4).unwrap().to_u64(), 2);
+        assert_eq!(Int::<U>::from_f64_rounded(2.5).unwrap().to_u64(), 3);
+        assert_eq!(Int::<I>::from_f64_rounded(-2.6).unwrap().to_i64(), -3);
+        assert_eq!(Int::<U>::from_f64_rounded(-0.4).unwrap().to_u64(), 0);
+    }
+
+    // Xorshift; the tests must be deterministic
+    fn xorshift(mut state: u64) -> impl FnMut() -> u64 {
+        move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        }
+    }
+
+    #[test]
+    fn mod_inverse_of_coprime_values() {
+        let inv = |c: u64, n: u64| {
+            Int::<U>::from(c)
+                .mod_inverse(Int::from(n))
+                .map(|v| v.to_u64())
+        };
+
+        assert_eq!(inv(3, 7), Some(5));
+        assert_eq!(inv(10, 17), Some(12));
+        assert_eq!(inv(1, 2), Some(1));
+    }
+
+    #[test]
+    fn mod_inverse_of_non_coprime_values() {
+        let inv = |c: u64, n: u64| Int::<U>::from(c).mod_inverse(Int::from(n));
+
+        assert_eq!(inv(4, 6), None);
+        assert_eq!(inv(0, 5), None);
+    }
+
+    #[test]
+    fn random_below_is_in_range_and_covers_every_residue() {
+        let mut next_word = xorshift(0x9E37_79B9_7F4A_7C15);
+        let bound = Int::<U>::from(10_u64);
+
+        let mut counts = [0_u32; 10];
+
+        for _ in 0..1000 {
+            let value = Int::<U>::random_below(&mut next_word, bound);
+            assert!(value < bound);
+            counts[usize::try_from(value).unwrap()] += 1;
+        }
+
+        // A bucket of 1000 uniform draws over ten residues lands outside this
+        // band with vanishing probability, and the seed is fixed anyway
+        for (residue, &count) in counts.iter().enumerate() {
+            assert!(
+                (50..=200).contains(&count),
+                "residue {residue} appeared {count} times"
+            );
+        }
+    }
+
+    #[test]
+    fn random_below_handles_bounds_wider_than_a_word() {
+        let mut next_word = xorshift(1);
+        let bound = Int::<U>::from(u64::MAX) * Int::<U>::from(16_u64);
+
+        let mut saw_wide_value = false;
+
+        for _ in 0..64 {
+            let value = Int::<U>::random_below(&mut next_word, bound);
+            assert!(value < bound);
+
+            if value > Int::<U>::from(u64::MAX) {
+                saw_wide_value = true;
+            }
+        }
+
+        assert!(saw_wide_value);
+    }
+
+    #[test]
+    fn random_below_one_is_always_zero() {
+        let mut next_word = xorshift(5);
+
+        for _ in 0..10 {
+            assert!(Int::<U>::random_below(&mut next_word, Int::one()).is_zero());
+        }
+    }
+
+    #[test]
+    fn from_f64_rounded_rejects_invalid() {
+        assert!(Int::<U>::from_f64_rounded(-1.0).is_none());
+        assert!(Int::<U>::from_f64_rounded(f64::NAN).is_none());
+        assert!(Int::<U>::from_f64_rounded(f64::INFINITY).is_none());
+        assert!(Int::<I>::from_f64_rounded(f64::NEG_INFINITY).is_none());
+        assert!(Int::<U>::from_f64_rounded(1e300).is_none());
+    }
+}
@@ -1,4 +1,5 @@
-use std::{
+use alloc::{boxed::Box, vec::Vec};
+use core::{
     cell::{Cell, Ref, RefCell},
     mem,
 };
@@ -0,0 +1,108 @@
+//! A workspace-wide cap on how many worker threads parallel operations may
+//! use.
+//!
+//! The robot runs on a resource-constrained Pi where the motor threads have
+//! real-time deadlines, so parallel paths such as batch solving must not
+//! default to saturating every core. Callers either pass a thread count
+//! explicitly or take [`max_threads`], which honors the cap installed through
+//! [`set_max_threads`].
+
+use std::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread::{self, available_parallelism},
+};
+
+/// Zero means no cap was installed
+static MAX_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Cap the number of worker threads that parallel operations may use
+pub fn set_max_threads(max_threads: NonZeroUsize) {
+    MAX_THREADS.store(max_threads.get(), Ordering::Relaxed);
+}
+
+/// The number of worker threads parallel operations may use; every available
+/// core, bounded by the cap installed through [`set_max_threads`] if any
+#[must_use]
+pub fn max_threads() -> NonZeroUsize {
+    let available = available_parallelism().unwrap_or(NonZeroUsize::MIN);
+
+    match NonZeroUsize::new(MAX_THREADS.load(Ordering::Relaxed)) {
+        Some(cap) => cap.min(available),
+        None => available,
+    }
+}
+
+/// Map `map` over `items` using at most `max_threads` worker threads,
+/// returning the results in the order of `items`.
+///
+/// A thread count of one runs sequentially on the calling thread, which
+/// parallel features should treat as their deterministic reference behavior.
+pub fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    max_threads: NonZeroUsize,
+    map: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let thread_count = max_threads.get().min(items.len());
+
+    if thread_count <= 1 {
+        return items.iter().map(map).collect();
+    }
+
+    let map = &map;
+    let chunk_size = items.len().div_ceil(thread_count);
+    let mut results = Vec::with_capacity(items.len());
+
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(map).collect::<Vec<R>>()))
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            results.extend(handle.join().unwrap());
+        }
+    });
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn limit_of_one_is_sequential_and_deterministic() {
+        let items = (0..100_u32).collect::<Vec<_>>();
+        let in_flight = AtomicUsize::new(0);
+
+        let results = parallel_map(&items, NonZeroUsize::MIN, |&item| {
+            let previously_in_flight = in_flight.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(previously_in_flight, 0);
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            item * 2
+        });
+
+        assert_eq!(results, (0..200).step_by(2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn results_stay_in_input_order() {
+        let items = (0..1000_u32).collect::<Vec<_>>();
+
+        let results = parallel_map(&items, NonZeroUsize::new(4).unwrap(), |&item| item + 1);
+
+        assert_eq!(results, (1..=1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn more_threads_than_items_is_fine() {
+        assert_eq!(
+            parallel_map(&[5_u32], NonZeroUsize::new(8).unwrap(), |&item| item),
+            vec![5]
+        );
+        assert!(parallel_map(&[], NonZeroUsize::new(8).unwrap(), |&item: &u32| item).is_empty());
+    }
+}
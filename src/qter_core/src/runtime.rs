@@ -1,5 +1,7 @@
 use crate::architectures::{Algorithm, PermutationGroup};
+use crate::discrete_math::decode;
 use crate::{Int, U, WithSpan};
+use internment::ArcIntern;
 use std::convert::Infallible;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -108,15 +110,21 @@ pub enum Instruction {
     PerformAlgorithm(ByPuzzleType<'static, PerformAlgorithm>),
     Solve(ByPuzzleType<'static, Solve>),
     RepeatUntil(ByPuzzleType<'static, RepeatUntil>),
+    Assert(ByPuzzleType<'static, Assert>),
+    Random(ByPuzzleType<'static, Random>),
 }
 
+/// Jump when a register's value is zero modulo some amount. The puzzle
+/// variant encodes the modulus in its choice of facelets; the theoretical
+/// variant carries the modulus directly, defaulting to the register's order
+/// so the check degenerates to `value == 0`.
 #[derive(Clone, Debug)]
 pub struct SolvedGoto {
     pub instruction_idx: usize,
 }
 
 impl SeparatesByPuzzleType for SolvedGoto {
-    type Theoretical<'s> = (Self, TheoreticalIdx);
+    type Theoretical<'s> = (Self, TheoreticalIdx, Int<U>);
 
     type Puzzle<'s> = (Self, PuzzleIdx, Facelets);
 }
@@ -162,12 +170,46 @@ impl SeparatesByPuzzleType for PerformAlgorithm {
     type Puzzle<'s> = (PuzzleIdx, Algorithm);
 }
 
+/// Reset a puzzle to the solved state, optionally preserving designated
+/// registers. Each preserved register is carried as its generator algorithm
+/// and signature facelets; the interpreter decodes the register's value
+/// before solving and re-adds it afterwards.
 pub struct Solve;
 
 impl SeparatesByPuzzleType for Solve {
     type Theoretical<'s> = TheoreticalIdx;
 
-    type Puzzle<'s> = PuzzleIdx;
+    type Puzzle<'s> = (PuzzleIdx, Vec<(Algorithm, Facelets)>);
+}
+
+/// An executable assertion that a register decodes to an expected value,
+/// panicking the interpreter when violated. Compiles away entirely when
+/// assertions are stripped.
+#[derive(Clone, Debug)]
+pub struct Assert {
+    pub message: String,
+    pub expected: Int<U>,
+}
+
+impl SeparatesByPuzzleType for Assert {
+    type Theoretical<'s> = (Self, TheoreticalIdx);
+
+    type Puzzle<'s> = (Self, PuzzleIdx, Algorithm, Facelets);
+}
+
+/// Add a uniformly random amount in `[0, max]` to a register, drawn from the
+/// interpreter's seeded PRNG. The puzzle variant carries the register's
+/// generator; the interpreter exponentiates it by the drawn amount, so a real
+/// puzzle (or the robot behind it) sees one composed algorithm.
+#[derive(Clone, Debug)]
+pub struct Random {
+    pub max: Int<U>,
+}
+
+impl SeparatesByPuzzleType for Random {
+    type Theoretical<'s> = (Self, TheoreticalIdx);
+
+    type Puzzle<'s> = (Self, PuzzleIdx, Algorithm);
 }
 
 #[derive(Clone, Debug)]
@@ -183,6 +225,36 @@ impl SeparatesByPuzzleType for RepeatUntil {
     type Puzzle<'s> = Self;
 }
 
+/// Metadata about a declared register, allowing frontends to report registers
+/// by the names the programmer declared rather than by the indices carried in
+/// the instructions
+#[derive(Debug)]
+pub struct RegisterInfo {
+    /// The name the register was declared with
+    pub name: ArcIntern<str>,
+    /// Which state the register lives in, along with the generator and
+    /// facelets needed to decode its value out of a real puzzle
+    pub location: ByPuzzleType<'static, (StateIdx, RegisterGenerator)>,
+    /// The amount of values the register can hold
+    pub order: Int<U>,
+}
+
+impl RegisterInfo {
+    /// Find the amount that performing the algorithm adds to this register.
+    ///
+    /// Returns `None` for theoretical registers and for algorithms whose
+    /// effect on the register cannot be decoded.
+    #[must_use]
+    pub fn amount_added_by(&self, alg: &Algorithm) -> Option<Int<U>> {
+        match &self.location {
+            ByPuzzleType::Theoretical(_) => None,
+            ByPuzzleType::Puzzle((_, (generator, facelets))) => {
+                decode(alg.permutation(), &facelets.0, generator)
+            }
+        }
+    }
+}
+
 /// A qter program
 #[derive(Debug)]
 pub struct Program {
@@ -190,6 +262,26 @@ pub struct Program {
     pub theoretical: Vec<WithSpan<Int<U>>>,
     /// A list of puzzles to be used for registers
     pub puzzles: Vec<WithSpan<Arc<PermutationGroup>>>,
+    /// The declared registers, in declaration order
+    pub registers: Vec<RegisterInfo>,
     /// The program itself
     pub instructions: Vec<WithSpan<Instruction>>,
 }
+
+impl Program {
+    /// Find the metadata of the register declared with the given name
+    #[must_use]
+    pub fn register_by_name(&self, name: &str) -> Option<&RegisterInfo> {
+        self.registers.iter().find(|info| &*info.name == name)
+    }
+
+    /// Iterate over the metadata of every register declared on the given puzzle
+    pub fn registers_for_puzzle(
+        &self,
+        puzzle_idx: PuzzleIdx,
+    ) -> impl Iterator<Item = &RegisterInfo> {
+        self.registers.iter().filter(move |info| {
+            matches!(&info.location, ByPuzzleType::Puzzle((idx, _)) if *idx == puzzle_idx)
+        })
+    }
+}
@@ -0,0 +1,234 @@
+//! WCA-style random-move scramble generation and parsing for arbitrary
+//! permutation groups
+//!
+//! A scramble draws every move uniformly from the group's generators,
+//! rejecting moves that are redundant with the run of pairwise commuting
+//! moves directly before them, per the WCA scrambling rules. Random-state
+//! scrambling additionally requires a solver for the puzzle and is out of
+//! scope for `qter_core`; tooling with a solver can wrap the solving
+//! algorithm's inverse in a [`Scramble`] through [`From<Algorithm>`].
+
+use std::{
+    hash::{BuildHasher, Hasher, RandomState},
+    sync::Arc,
+};
+
+use internment::ArcIntern;
+use itertools::Itertools;
+
+use crate::architectures::{Algorithm, PermutationGroup};
+
+/// A scrambling sequence for a permutation group
+#[derive(Clone, PartialEq, Eq)]
+pub struct Scramble {
+    algorithm: Algorithm,
+}
+
+fn disjoint(a: &[usize], b: &[usize]) -> bool {
+    a.iter().all(|facelet| b.binary_search(facelet).is_err())
+}
+
+impl Scramble {
+    /// Generate a random-move scramble of `length` moves, seeded from
+    /// ambient entropy
+    #[must_use]
+    pub fn random_moves(perm_group: Arc<PermutationGroup>, length: usize) -> Scramble {
+        let seed = RandomState::new().build_hasher().finish();
+        Scramble::random_moves_with_seed(perm_group, length, seed)
+    }
+
+    /// Generate a random-move scramble of `length` moves from the given seed
+    ///
+    /// Moves are drawn uniformly from the group's generators. Following the
+    /// WCA scrambling rules, a move is rejected while a move of the same face
+    /// is still in the run of pairwise commuting moves directly before it, so
+    /// sequences like `U U'` and `U D U` never appear. Two generators count
+    /// as turning the same face when they move exactly the same facelets, and
+    /// as commuting when the facelets they move are disjoint.
+    ///
+    /// The same seed always produces the same scramble, so logging the seed
+    /// is enough to reproduce a scramble. If every generator commutes with
+    /// every other one, the scramble may come out shorter than `length`
+    /// because at some point no non-redundant move is left.
+    #[must_use]
+    pub fn random_moves_with_seed(
+        perm_group: Arc<PermutationGroup>,
+        length: usize,
+        seed: u64,
+    ) -> Scramble {
+        // Group the generators by the set of facelets they move; a turn, its
+        // inverse, and its powers all move the same facelets and therefore
+        // share a face.
+        let mut faces = Vec::<Vec<usize>>::new();
+        let generators = perm_group
+            .generators()
+            .map(|(name, perm)| {
+                let support = perm
+                    .mapping()
+                    .iter()
+                    .enumerate()
+                    .filter(|&(from, &to)| from != to)
+                    .map(|(from, _)| from)
+                    .collect_vec();
+
+                let face = faces.iter().position(|v| *v == support).unwrap_or_else(|| {
+                    faces.push(support);
+                    faces.len() - 1
+                });
+
+                (name, face)
+            })
+            // Sorted so that a seed's outcome does not depend on the
+            // generator map's iteration order
+            .sorted()
+            .collect_vec();
+
+        // Xorshift; zero is its fixed point so nudge it to an arbitrary
+        // nonzero state
+        let mut state = if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed };
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let mut commuting_run = Vec::<usize>::new();
+        let mut move_seq = Vec::with_capacity(length);
+
+        while move_seq.len() < length {
+            if commuting_run.len() == faces.len() {
+                // Every face commutes with every other one and each may
+                // appear at most once per run, so no legal move is left
+                break;
+            }
+
+            let idx = next() % u64::try_from(generators.len()).unwrap();
+            let (name, face) = &generators[usize::try_from(idx).unwrap()];
+
+            if commuting_run.contains(face) {
+                continue;
+            }
+
+            if !commuting_run
+                .iter()
+                .all(|&prev| disjoint(&faces[prev], &faces[*face]))
+            {
+                commuting_run.clear();
+            }
+            commuting_run.push(*face);
+
+            move_seq.push(ArcIntern::clone(name));
+        }
+
+        Scramble {
+            algorithm: Algorithm::new_from_move_seq(perm_group, move_seq)
+                .expect("every move is a generator of the group"),
+        }
+    }
+
+    /// Parse a scramble from a space separated sequence of moves
+    ///
+    /// If the string cannot be parsed as an algorithm, this code will return `None`
+    #[must_use]
+    pub fn parse_from_string(perm_group: Arc<PermutationGroup>, string: &str) -> Option<Scramble> {
+        Algorithm::parse_from_string(perm_group, string).map(Scramble::from)
+    }
+
+    /// The scramble's move sequence as an [`Algorithm`]
+    #[must_use]
+    pub fn algorithm(&self) -> &Algorithm {
+        &self.algorithm
+    }
+
+    #[must_use]
+    pub fn into_algorithm(self) -> Algorithm {
+        self.algorithm
+    }
+}
+
+impl From<Algorithm> for Scramble {
+    fn from(algorithm: Algorithm) -> Scramble {
+        Scramble { algorithm }
+    }
+}
+
+/// Scrambles display exactly like the algorithm they wrap, so the output can
+/// be given back to [`Scramble::parse_from_string`] to produce an equal
+/// scramble
+impl core::fmt::Display for Scramble {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        core::fmt::Display::fmt(&self.algorithm, f)
+    }
+}
+
+impl core::fmt::Debug for Scramble {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        core::fmt::Debug::fmt(&self.algorithm, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::architectures::mk_puzzle_definition;
+
+    #[test]
+    fn scramble_round_trips_through_display() {
+        let cube_def = mk_puzzle_definition("3x3").unwrap();
+
+        for seed in 0..20 {
+            let scramble =
+                Scramble::random_moves_with_seed(Arc::clone(&cube_def.perm_group), 25, seed);
+            assert_eq!(scramble.algorithm().move_seq_iter().count(), 25);
+
+            let again =
+                Scramble::random_moves_with_seed(Arc::clone(&cube_def.perm_group), 25, seed);
+            assert_eq!(again.to_string(), scramble.to_string());
+
+            let displayed = scramble.to_string();
+            let reparsed =
+                Scramble::parse_from_string(Arc::clone(&cube_def.perm_group), &displayed).unwrap();
+            assert_eq!(reparsed, scramble);
+            assert_eq!(reparsed.to_string(), displayed);
+        }
+    }
+
+    #[test]
+    fn random_moves_are_never_redundant() {
+        let cube_def = mk_puzzle_definition("3x3").unwrap();
+
+        // Recover each move's face as the set of facelets it moves
+        let support = |name: &ArcIntern<str>| {
+            cube_def
+                .perm_group
+                .get_generator(name)
+                .unwrap()
+                .mapping()
+                .iter()
+                .enumerate()
+                .filter(|&(from, &to)| from != to)
+                .map(|(from, _)| from)
+                .collect_vec()
+        };
+
+        for seed in 0..10 {
+            let scramble =
+                Scramble::random_moves_with_seed(Arc::clone(&cube_def.perm_group), 100, seed);
+
+            let mut commuting_run = Vec::<Vec<usize>>::new();
+            for moove in scramble.algorithm().move_seq_iter() {
+                let face = support(moove);
+                assert!(
+                    !commuting_run.contains(&face),
+                    "redundant move {moove} in {scramble}"
+                );
+
+                if !commuting_run.iter().all(|prev| disjoint(prev, &face)) {
+                    commuting_run.clear();
+                }
+                commuting_run.push(face);
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     ops::{Deref, DerefMut},
-    sync::OnceLock,
+    sync::{Arc, OnceLock},
 };
 
 use chumsky::{container::Container, extra::Full, input::ValueInput, prelude::*};
@@ -69,6 +69,9 @@ pub struct Span {
     start: usize,
     end: usize,
     line_and_col: OnceLock<(usize, usize)>,
+    /// The chain of macro expansions that produced this code, innermost
+    /// first; `None` for code that was written directly
+    expanded_from: Option<Arc<Span>>,
 }
 
 impl Span {
@@ -88,6 +91,7 @@ impl Span {
             start,
             end,
             line_and_col: OnceLock::new(),
+            expanded_from: None,
         }
     }
 
@@ -141,6 +145,22 @@ impl Span {
         self
     }
 
+    /// Narrows the span to the given range of byte offsets within the span
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range extends past the end of the span or if its start is greater than its end
+    #[must_use]
+    pub fn subspan(&self, range: std::ops::Range<usize>) -> Span {
+        assert!(self.start + range.end <= self.end);
+
+        Span::new(
+            self.source.clone(),
+            self.start + range.start,
+            self.start + range.end,
+        )
+    }
+
     pub fn source(&self) -> ArcIntern<str> {
         self.source.clone()
     }
@@ -159,7 +179,31 @@ impl Span {
             start: self.start.min(other.start),
             end: self.end.max(other.end),
             line_and_col: OnceLock::new(),
+            expanded_from: self.expanded_from,
+        }
+    }
+
+    /// Records that this code only exists because of the expansion of the
+    /// macro at `origin`; any chain this span already carries is pushed
+    /// behind `origin` so that the innermost expansion comes first
+    #[must_use]
+    pub fn with_expanded_from(mut self, mut origin: Span) -> Span {
+        if origin.expanded_from.is_none() {
+            origin.expanded_from = self.expanded_from.take();
         }
+
+        self.expanded_from = Some(Arc::new(origin));
+        self
+    }
+
+    /// The macro that this code was expanded from, if any
+    pub fn expanded_from(&self) -> Option<&Span> {
+        self.expanded_from.as_deref()
+    }
+
+    /// The chain of macro expansions that produced this code, innermost first
+    pub fn expansion_backtrace(&self) -> impl Iterator<Item = &Span> {
+        core::iter::successors(self.expanded_from(), |origin| origin.expanded_from())
     }
 
     pub fn with<T>(self, v: T) -> WithSpan<T> {
@@ -294,6 +338,137 @@ impl<T: core::hash::Hash> core::hash::Hash for WithSpan<T> {
     }
 }
 
+/// An error paired with the span it originated from and a stack of
+/// contextual notes, so that every crate reports errors the same way instead
+/// of inventing its own pairing of error and location.
+///
+/// The span is optional because some errors (e.g. IO failures during imports)
+/// have no meaningful location; conversions take a fallback span so that the
+/// diagnostic still points somewhere sensible.
+#[derive(Debug, Clone)]
+pub struct SpannedError<E> {
+    error: E,
+    span: Option<Span>,
+    notes: Vec<String>,
+}
+
+impl<E> SpannedError<E> {
+    pub fn new(error: E) -> SpannedError<E> {
+        SpannedError {
+            error,
+            span: None,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Attach the span the error originated from
+    #[must_use]
+    pub fn with_span(mut self, span: Span) -> SpannedError<E> {
+        self.span = Some(span);
+        self
+    }
+
+    /// Push a contextual note onto the note stack; notes are rendered after
+    /// the error message in the order they were pushed
+    #[must_use]
+    pub fn with_note(mut self, note: impl Into<String>) -> SpannedError<E> {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    pub fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    pub fn map<V>(self, f: impl FnOnce(E) -> V) -> SpannedError<V> {
+        SpannedError {
+            error: f(self.error),
+            span: self.span,
+            notes: self.notes,
+        }
+    }
+}
+
+impl<E: core::fmt::Display> SpannedError<E> {
+    /// Convert into the chumsky error type that `compile` reports, pointing
+    /// at `fallback_span` if the error carries no span of its own
+    #[must_use]
+    pub fn into_rich(self, fallback_span: &Span) -> Rich<'static, char, Span> {
+        let span = self.span.unwrap_or_else(|| fallback_span.clone());
+
+        let mut message = self.error.to_string();
+        for note in &self.notes {
+            message.push_str("\nnote: ");
+            message.push_str(note);
+        }
+
+        for origin in span.expansion_backtrace() {
+            let (line, col) = origin.line_and_col();
+            message.push_str(&format!("\nnote: expanded from the macro at {line}:{col}"));
+        }
+
+        Rich::custom(span, message)
+    }
+
+    /// Build an ariadne report with the error's span underlined and each note
+    /// attached, pointing at `fallback_span` if the error carries no span of
+    /// its own
+    #[must_use]
+    pub fn report(&self, fallback_span: &Span) -> ariadne::Report<'static, Span> {
+        let span = self.span.clone().unwrap_or_else(|| fallback_span.clone());
+
+        let mut builder = ariadne::Report::build(ariadne::ReportKind::Error, span.clone())
+            .with_config(ariadne::Config::new().with_index_type(ariadne::IndexType::Byte))
+            .with_message(self.error.to_string())
+            .with_label(ariadne::Label::new(span.clone()).with_message(self.error.to_string()));
+
+        for note in &self.notes {
+            builder = builder.with_note(note.clone());
+        }
+
+        for origin in span.expansion_backtrace() {
+            if origin.source() == span.source() {
+                builder = builder.with_label(
+                    ariadne::Label::new(origin.clone()).with_message("expanded from the macro here"),
+                );
+            } else {
+                let (line, col) = origin.line_and_col();
+                builder = builder
+                    .with_note(format!("expanded from the macro at {line}:{col} in another file"));
+            }
+        }
+
+        builder.finish()
+    }
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for SpannedError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        core::fmt::Display::fmt(&self.error, f)?;
+        for note in &self.notes {
+            write!(f, "\nnote: {note}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: core::fmt::Display + core::fmt::Debug> std::error::Error for SpannedError<E> {}
+
+impl<E> From<WithSpan<E>> for SpannedError<E> {
+    fn from(value: WithSpan<E>) -> Self {
+        let span = value.span().clone();
+        SpannedError::new(value.into_inner()).with_span(span)
+    }
+}
+
 pub enum MaybeErr<T> {
     Some(T),
     None,
@@ -6,7 +6,7 @@ use std::{
 
 use internment::ArcIntern;
 use itertools::Itertools;
-use pog_ans::{Cache, CodingFSM, TakeFrom, ans_decode, ans_encode};
+use pog_ans::{Cache, CodingFSM, TakeFrom, ans_decode, ans_encode, rest_unweighted, rest_weighted};
 
 #[derive(Debug)]
 struct TableStats {
@@ -171,60 +171,6 @@ pub fn encode_table(algs: &[Vec<ArcIntern<str>>]) -> Option<(Vec<u8>, usize)> {
     Some((stream, after - before))
 }
 
-fn rest_unweighted(ranges: &mut [u16], mut range_left: usize) {
-    for v in ranges.iter_mut() {
-        if *v != 0 {
-            *v = 1;
-        }
-    }
-
-    let mut amt_to_set = ranges.iter().filter(|v| **v != 0).count();
-
-    for dist_spot in ranges.iter_mut() {
-        if *dist_spot == 0 {
-            continue;
-        }
-
-        let range_to_take = ((range_left + amt_to_set) / amt_to_set).saturating_sub(1);
-        range_left -= range_to_take;
-        *dist_spot += range_to_take as u16;
-        amt_to_set -= 1;
-    }
-}
-
-fn rest_weighted(ranges: &mut [u16], mut range_left: usize, distribution: &[u32]) {
-    let mut total_weight = 0;
-    let mut amt_to_set = 0;
-
-    ranges.iter_mut().enumerate().for_each(|(i, v)| {
-        if *v != 0 {
-            *v = 1;
-
-            total_weight += distribution[i] as usize;
-            amt_to_set += 1;
-        }
-    });
-
-    for (i, dist_spot) in ranges
-        .iter_mut()
-        .enumerate()
-        .sorted_unstable_by_key(|(i, _)| distribution[*i])
-    {
-        if *dist_spot == 0 {
-            continue;
-        }
-
-        let range_available = range_left + amt_to_set;
-
-        let range_to_take =
-            (range_available * distribution[i] as usize / total_weight).saturating_sub(1);
-        range_left -= range_to_take;
-        *dist_spot += range_to_take as u16;
-        total_weight -= distribution[i] as usize;
-        amt_to_set -= 1;
-    }
-}
-
 /// Decodes a table and returns None if it can't be decoded
 pub fn decode_table(data: &mut impl Iterator<Item = u8>) -> Option<Vec<Vec<ArcIntern<str>>>> {
     let symbol_count = u32::take_from(data)?;
@@ -540,6 +486,88 @@ mod tests {
         // panic!()
     }
 
+    #[test]
+    fn shared_move_model_on_canonical_sequences() {
+        use pog_ans::{Cache, MoveRelations, MoveSequenceModel, ans_decode, ans_encode};
+
+        let moves = [
+            "U", "U'", "U2", "D", "D'", "D2", "L", "L'", "L2", "R", "R'", "R2", "F", "F'", "F2",
+            "B", "B'", "B2",
+        ];
+
+        // Three powers of each of six faces; opposite faces share an axis
+        let relations = MoveRelations {
+            faces: (0..18).map(|i| i / 3).collect(),
+            axes: (0..18).map(|i| i / 6).collect(),
+        };
+
+        // Deterministically generate a corpus of canonical move sequences
+        let mut state: u32 = 0x2545_F491;
+        let mut rand = move || {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            usize::try_from(state >> 16).unwrap()
+        };
+
+        let mut frequencies = vec![0_u32; moves.len()];
+        let mut symbol_algs = Vec::new();
+
+        for _ in 0..60 {
+            let len = 10 + rand() % 10;
+            let mut alg: Vec<usize> = Vec::new();
+
+            while alg.len() < len {
+                let candidate = rand() % moves.len();
+
+                if alg
+                    .last()
+                    .is_none_or(|&prev| relations.allows(prev, candidate))
+                {
+                    frequencies[candidate] += 1;
+                    alg.push(candidate);
+                }
+            }
+
+            symbol_algs.push(alg);
+        }
+
+        let algs = symbol_algs
+            .iter()
+            .map(|alg| {
+                alg.iter()
+                    .map(|&sym| ArcIntern::from(moves[sym]))
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        let (_, table_size) = encode_table(&algs).unwrap();
+
+        let symbols = symbol_algs.iter().flatten().copied().collect_vec();
+        let model = MoveSequenceModel::new(relations, frequencies);
+
+        let mut stream = Vec::new();
+        ans_encode(&mut stream, &symbols, Cache::new(model.clone()));
+
+        // The shared model's structural zeros are directional, which the
+        // dynamic pair list can't express, and the flat stream doesn't pay
+        // for alg boundaries, so it should never lose to the table encoding
+        // on canonical sequences
+        assert!(
+            stream.len() <= table_size,
+            "shared model: {} bytes, table encoding: {table_size} bytes",
+            stream.len()
+        );
+
+        // None of the structural zeros tripped the coder, and the stream
+        // still decodes
+        let decoded = ans_decode(
+            &mut stream.iter().copied(),
+            Some(symbols.len()),
+            Cache::new(model),
+        )
+        .unwrap();
+        assert_eq!(decoded, symbols);
+    }
+
     #[test]
     fn extensive_table_encoding_test() {
         // All the OLL PLL algs
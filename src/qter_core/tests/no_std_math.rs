@@ -0,0 +1,59 @@
+//! Exercises the `no_std` surface of `qter_core` — everything used here must
+//! be available without the `std` feature, so this file opts out of `std`
+//! itself to catch accidental dependencies on it at compile time. The test
+//! harness still links `std`, which is why `#[test]` works.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use qter_core::{
+    I, Int, U,
+    discrete_math::{
+        chinese_remainder_theorem, extended_euclid, gcd, lcm, lcm_iter,
+        length_of_substring_that_this_string_is_n_repeated_copies_of,
+    },
+};
+
+#[test]
+fn int_arithmetic() {
+    let three = Int::<U>::from(3_u64);
+    let four = Int::<U>::from(4_u64);
+
+    assert_eq!(three + four, Int::from(7_u64));
+    assert_eq!(three * four, Int::from(12_u64));
+    assert_eq!(-Int::<I>::one(), Int::from(-1_i64));
+    assert!(Int::<U>::zero().is_zero());
+
+    let parsed: Int<U> = "360".parse().unwrap();
+    assert_eq!(parsed, Int::from(360_u64));
+}
+
+#[test]
+fn pure_discrete_math() {
+    let int = Int::<U>::from;
+
+    assert_eq!(gcd(int(12), int(18)), int(6));
+    assert_eq!(lcm(int(4), int(6)), int(12));
+    assert_eq!(lcm_iter([int(2), int(3), int(5)].into_iter()), int(30));
+
+    let ((x, y), z) = extended_euclid(int(240), int(46));
+    assert_eq!(Int::<U>::from(240_u64) * x + Int::<U>::from(46_u64) * y, z);
+    assert_eq!(z, int(2));
+
+    assert_eq!(
+        chinese_remainder_theorem(
+            [(2_u64, 3_u64), (1, 2)]
+                .into_iter()
+                .map(|(a, b)| Some((Int::from(a), Int::from(b))))
+        ),
+        Some(int(5))
+    );
+
+    let colors: Vec<&str> = ["a", "b", "a", "b"].into_iter().collect();
+    assert_eq!(
+        length_of_substring_that_this_string_is_n_repeated_copies_of(colors.into_iter()),
+        2
+    );
+}
@@ -1,10 +1,16 @@
-use std::{fmt::Debug, ops::Index, str::FromStr};
+use std::{
+    fmt::Debug,
+    num::NonZeroUsize,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
 
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use super::uart::{NodeAddress, UartId};
+use crate::solve::SolveBackend;
 
 /// Global robot configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,8 +21,64 @@ pub struct RobotConfig {
     pub microstep_resolution: Microsteps,
     pub priority: Priority,
     pub wait_between_moves: f64,
+
+    /// Seconds to let the motors settle between consecutive moves, avoiding
+    /// missed steps at high speed. Zero performs moves back to back.
+    #[serde(default)]
+    pub settle_delay: f64,
+
     pub compensation: u32,
     pub float: bool,
+
+    /// The most worker threads the solver and other parallel paths may use.
+    /// Unset uses every core; set it to leave cores free for the real-time
+    /// motor threads.
+    #[serde(default)]
+    pub max_threads: Option<NonZeroUsize>,
+
+    /// Current profiles for the motor drivers
+    #[serde(default)]
+    pub currents: CurrentProfiles,
+
+    /// The solver backend `solve` instructions run through
+    #[serde(default)]
+    pub solve_backend: SolveBackend,
+}
+
+/// Motor current profiles, in the TMC2209's current scale where 0 is the
+/// minimum and 31 is the maximum current.
+///
+/// Holding full current at all times heats the motors for no benefit while
+/// the robot waits (e.g. for the interpreter to decode a register), so the
+/// motor thread drops to `idle` after `idle_timeout` seconds without moves
+/// and restores `run` before the next move starts. Transitions are only ever
+/// written between moves, never while a motor is stepping.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurrentProfiles {
+    /// The current used while moves are being performed
+    pub run: u8,
+    /// The hold current written after `idle_timeout` passes without a move.
+    /// Setting it equal to `run` disables idle transitions entirely.
+    pub idle: u8,
+    /// The current for retrying a move that stalled, giving the retry extra
+    /// torque to push through whatever caused the stall
+    pub recovery: u8,
+    /// Seconds without moves before the idle current is written
+    pub idle_timeout: f64,
+}
+
+impl Default for CurrentProfiles {
+    fn default() -> Self {
+        // Full current at all times, matching the behavior of configurations
+        // written before current profiles existed
+        CurrentProfiles {
+            run: 31,
+            idle: 31,
+            recovery: 31,
+            idle_timeout: 5.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +90,11 @@ pub struct MotorConfig {
 
     pub pos_compensation: Option<u32>,
     pub neg_compensation: Option<u32>,
+
+    /// Steps of trim applied per 100 quarter turns, correcting a face that
+    /// consistently under- or over-rotates. Measured by `qter-robot calibrate`.
+    #[serde(default)]
+    pub step_trim: i8,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
@@ -83,6 +150,12 @@ impl Index<Face> for Motors {
     }
 }
 
+impl IndexMut<Face> for Motors {
+    fn index_mut(&mut self, index: Face) -> &mut Self::Output {
+        &mut self.0[index as usize]
+    }
+}
+
 impl Debug for Motors {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         MotorsRepr::from(self.clone()).fmt(f)
@@ -186,7 +259,7 @@ impl Microsteps {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
 pub enum Priority {
     /// Leave the priority as whatever the OS decides it to be
     Default,
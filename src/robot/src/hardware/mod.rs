@@ -6,7 +6,11 @@ use std::{
     fmt::Display,
     iter::from_fn,
     ops::Add,
-    sync::mpsc::{self, RecvTimeoutError},
+    sync::{
+        Arc, Condvar, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError},
+    },
     thread,
     time::{Duration, Instant},
 };
@@ -18,10 +22,10 @@ use thread_priority::{
 
 use crate::hardware::{
     config::{Face, Priority, RobotConfig},
-    motor::Motor,
+    motor::{Motor, motion_limits, trapezoid_profile_inv},
     uart::{
-        UartBus, UartId,
-        regs::{GConf, IholdIrun, NodeConf},
+        UartBus, UartId, UartNode,
+        regs::{DrvStatus, GConf, IholdIrun, NodeConf},
     },
 };
 
@@ -35,29 +39,221 @@ pub const FULLSTEPS_PER_QUARTER: u32 = FULLSTEPS_PER_REVOLUTION / 4;
 enum MotorMessage {
     QueueMove((Face, Dir)),
     PrevMovesDone(Unparker),
+    /// Swap in a reloaded configuration; see [`RobotHandle::reload_config`]
+    Reconfigure(Box<RobotConfig>),
+}
+
+/// Lets [`RobotHandle::pause`] stop the motor thread from pulling further
+/// messages without losing the queue
+#[derive(Default)]
+struct PauseFlag {
+    paused: Mutex<bool>,
+    unpaused: Condvar,
+}
+
+impl PauseFlag {
+    /// Block until the flag is cleared, returning immediately if it isn't set
+    fn wait_if_paused(&self) {
+        let mut paused = self.paused.lock().unwrap();
+        while *paused {
+            paused = self.unpaused.wait(paused).unwrap();
+        }
+    }
+}
+
+/// Progress reports sent by the motor thread while it configures the drivers,
+/// so that [`RobotHandle::init`] doesn't return before the motors are safe to
+/// step.
+enum InitProgress {
+    Configuring { face: Face, uart_bus: UartId },
+    Ready,
+}
+
+/// How long to wait for each driver to finish being configured before
+/// declaring initialization failed.
+const DRIVER_INIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum RobotInitError {
+    /// A driver did not respond within [`DRIVER_INIT_TIMEOUT`] while being
+    /// configured
+    DriverTimeout { face: Face, uart_bus: UartId },
+    /// The motor thread died before reporting that the drivers were configured
+    MotorThreadDied,
+}
+
+impl Display for RobotInitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RobotInitError::DriverTimeout { face, uart_bus } => write!(
+                f,
+                "Timed out waiting for the {face:?} motor's driver on {uart_bus:?} to respond during initialization"
+            ),
+            RobotInitError::MotorThreadDied => {
+                f.write_str("The motor thread died before the drivers were configured")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RobotInitError {}
+
+/// A reloaded numeric setting that the robot cannot run with; see
+/// [`RobotHandle::reload_config`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigReloadError {
+    /// The name of the offending field
+    pub field: &'static str,
+}
+
+impl Display for ConfigReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid value for `{}` in the reloaded configuration",
+            self.field
+        )
+    }
+}
+
+impl std::error::Error for ConfigReloadError {}
+
+/// Check that a reloaded configuration's numeric settings are usable, naming
+/// the first one that isn't
+fn validate_reload(config: &RobotConfig) -> Result<(), ConfigReloadError> {
+    // (field, value, whether zero is also unusable)
+    let numeric = [
+        (
+            "revolutions_per_second",
+            config.revolutions_per_second,
+            true,
+        ),
+        ("max_acceleration", config.max_acceleration, true),
+        ("wait_between_moves", config.wait_between_moves, false),
+        ("settle_delay", config.settle_delay, false),
+        ("currents.idle_timeout", config.currents.idle_timeout, false),
+    ];
+
+    for (field, value, must_be_positive) in numeric {
+        let minimum_ok = if must_be_positive {
+            value > 0.0
+        } else {
+            value >= 0.0
+        };
+        if !value.is_finite() || !minimum_ok {
+            return Err(ConfigReloadError { field });
+        }
+    }
+
+    for (field, value) in [
+        ("currents.run", config.currents.run),
+        ("currents.idle", config.currents.idle),
+        ("currents.recovery", config.currents.recovery),
+    ] {
+        // The TMC2209's current scale tops out at 31
+        if value > 31 {
+            return Err(ConfigReloadError { field });
+        }
+    }
+
+    Ok(())
+}
+
+/// Merge a reloaded configuration over the running one.
+///
+/// Non-structural settings (speeds, delays, currents, compensation, step
+/// trim) are taken from `new`. Structural settings were applied to hardware
+/// or the OS at startup — pins and UART addresses, the drivers' microstep
+/// resolution, the motor thread's priority, freewheel mode, and the thread
+/// cap — so they keep their running values; the names of the ones that
+/// differed are returned alongside the merged configuration.
+fn merge_reload(running: &RobotConfig, mut new: RobotConfig) -> (RobotConfig, Vec<&'static str>) {
+    let mut needs_restart = Vec::new();
+
+    for face in Face::ALL {
+        let old_motor = &running.motors[face];
+        let motor = &mut new.motors[face];
+
+        if motor.step_pin != old_motor.step_pin
+            || motor.dir_pin != old_motor.dir_pin
+            || motor.uart_bus != old_motor.uart_bus
+            || motor.uart_address != old_motor.uart_address
+        {
+            if !needs_restart.contains(&"motor pins and addresses") {
+                needs_restart.push("motor pins and addresses");
+            }
+            motor.step_pin = old_motor.step_pin;
+            motor.dir_pin = old_motor.dir_pin;
+            motor.uart_bus = old_motor.uart_bus;
+            motor.uart_address = old_motor.uart_address;
+        }
+    }
+
+    if new.microstep_resolution.value() != running.microstep_resolution.value() {
+        needs_restart.push("microstep_resolution");
+        new.microstep_resolution = running.microstep_resolution;
+    }
+
+    if new.priority != running.priority {
+        needs_restart.push("priority");
+        new.priority = running.priority;
+    }
+
+    if new.float != running.float {
+        needs_restart.push("float");
+        new.float = running.float;
+    }
+
+    if new.max_threads != running.max_threads {
+        needs_restart.push("max_threads");
+        new.max_threads = running.max_threads;
+    }
+
+    (new, needs_restart)
 }
 
 pub struct RobotHandle {
     motor_thread_handle: mpsc::Sender<MotorMessage>,
     config: RobotConfig,
+    pending: Arc<AtomicUsize>,
+    pause: Arc<PauseFlag>,
+    /// Whether any move was queued since the last completed
+    /// [`RobotHandle::await_moves`], letting it skip the round trip to the
+    /// motor thread when there is nothing to wait for
+    queued_since_await: AtomicBool,
 }
 
 impl RobotHandle {
-    /// Initialize the robot such that it is ready for use
-    pub fn init(robot_config: RobotConfig) -> RobotHandle {
-        uart_init(&robot_config);
-
+    /// Initialize the robot such that it is ready for use, blocking until the
+    /// motor thread has finished configuring every driver so that no move can
+    /// be queued before the drivers have correct settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the face and bus of the first driver that did
+    /// not respond in time.
+    pub fn init(robot_config: RobotConfig) -> Result<RobotHandle, RobotInitError> {
         let (tx, rx) = mpsc::channel();
+        let (progress_tx, progress_rx) = mpsc::channel();
+        let pending = Arc::new(AtomicUsize::new(0));
+        let pause = Arc::new(PauseFlag::default());
 
         {
             let robot_config = robot_config.clone();
-            thread::spawn(move || motor_thread(rx, robot_config));
+            let pending = Arc::clone(&pending);
+            let pause = Arc::clone(&pause);
+            thread::spawn(move || motor_thread(rx, pending, pause, &progress_tx, robot_config));
         }
 
-        RobotHandle {
+        await_driver_configuration(&progress_rx, DRIVER_INIT_TIMEOUT)?;
+
+        Ok(RobotHandle {
             motor_thread_handle: tx,
             config: robot_config,
-        }
+            pending,
+            pause,
+            queued_since_await: AtomicBool::new(false),
+        })
     }
 
     pub fn config(&self) -> &RobotConfig {
@@ -66,37 +262,79 @@ impl RobotHandle {
 
     pub fn loop_face_turn(&mut self, face: Face) {
         loop {
-            self.motor_thread_handle
-                .send(MotorMessage::QueueMove((face, Dir::Normal)))
-                .unwrap();
+            self.queue_move((face, Dir::Normal));
             self.await_moves();
         }
     }
 
+    fn queue_move(&self, move_: (Face, Dir)) {
+        self.queued_since_await.store(true, Ordering::Relaxed);
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        self.motor_thread_handle
+            .send(MotorMessage::QueueMove(move_))
+            .unwrap();
+    }
+
+    /// The number of queued moves that the motor thread has not yet started
+    /// performing
+    #[must_use]
+    pub fn pending_moves(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// Pause the motor thread once the move it is currently performing
+    /// finishes, keeping the queued moves intact
+    pub fn pause(&self) {
+        *self.pause.paused.lock().unwrap() = true;
+    }
+
+    /// Continue performing queued moves after [`RobotHandle::pause`]
+    pub fn resume(&self) {
+        *self.pause.paused.lock().unwrap() = false;
+        self.pause.unpaused.notify_all();
+    }
+
     /// Queue a sequence of moves to be performed by the robot
     pub fn queue_move_seq(&mut self, alg: &Algorithm) {
+        // An identity algorithm performs no moves (e.g. adding a register's
+        // order to it); skip it so that a following `await_moves` doesn't
+        // round-trip to the motor thread for nothing
+        if alg.move_seq_iter().next().is_none() {
+            return;
+        }
+
         for move_ in alg.move_seq_iter() {
-            let mut move_ = &**move_;
-            let dir = if let Some(rest) = move_.strip_suffix('\'') {
-                move_ = rest;
-                Dir::Prime
-            } else if let Some(rest) = move_.strip_suffix('2') {
-                move_ = rest;
-                Dir::Double
-            } else {
-                Dir::Normal
-            };
+            self.queue_move(parse_move(move_));
+        }
+    }
 
-            let face: Face = move_.parse().expect("invalid move: {move_}");
+    /// Roughly how long the robot will take to physically perform `alg`; see
+    /// [`estimate_duration`]
+    #[must_use]
+    pub fn estimate_duration(&self, alg: &Algorithm) -> Duration {
+        estimate_duration(alg, &self.config)
+    }
 
-            self.motor_thread_handle
-                .send(MotorMessage::QueueMove((face, dir)))
-                .unwrap();
+    /// Perform quarter turns of the given face one at a time, clockwise if
+    /// `qturns` is positive. Each turn is awaited individually so that none of
+    /// them collapse together into fewer physical turns.
+    pub fn turn_face_quarters(&mut self, face: Face, qturns: i32) {
+        let dir = if qturns < 0 { Dir::Prime } else { Dir::Normal };
+
+        for _ in 0..qturns.unsigned_abs() {
+            self.queue_move((face, dir));
+            self.await_moves();
         }
     }
 
     /// Wait for all moves in the queue to be performed
+    ///
+    /// Returns immediately if nothing has been queued since the last call.
     pub fn await_moves(&self) {
+        if !self.queued_since_await.swap(false, Ordering::Relaxed) {
+            return;
+        }
+
         let parker = Parker::new();
 
         self.motor_thread_handle
@@ -105,6 +343,121 @@ impl RobotHandle {
 
         parker.park();
     }
+
+    /// Swap a reloaded configuration into the running robot.
+    ///
+    /// Speeds, delays, currents, compensation, and step trim reach the motor
+    /// thread before the next move it performs. Structural settings that were
+    /// applied to hardware or the OS at startup (pins, UART addresses,
+    /// microstep resolution, priority, freewheel mode, thread cap) keep their
+    /// running values; the returned list names the ones that differed and
+    /// need a restart to change.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first numeric setting the robot cannot run
+    /// with, in which case nothing is applied.
+    pub fn reload_config(
+        &mut self,
+        new_config: RobotConfig,
+    ) -> Result<Vec<&'static str>, ConfigReloadError> {
+        validate_reload(&new_config)?;
+
+        let (merged, needs_restart) = merge_reload(&self.config, new_config);
+
+        self.motor_thread_handle
+            .send(MotorMessage::Reconfigure(Box::new(merged.clone())))
+            .unwrap();
+        self.config = merged;
+
+        Ok(needs_restart)
+    }
+}
+
+/// Parse a move like `U2'` into the face to turn and the direction
+fn parse_move(move_: &str) -> (Face, Dir) {
+    let mut move_ = move_;
+    let prime = if let Some(rest) = move_.strip_suffix('\'') {
+        move_ = rest;
+        true
+    } else {
+        false
+    };
+
+    // A half turn reaches the same state in either direction, so `U2`
+    // and `U2'` are both Double
+    let dir = if let Some(rest) = move_.strip_suffix('2') {
+        move_ = rest;
+        Dir::Double
+    } else if prime {
+        Dir::Prime
+    } else {
+        Dir::Normal
+    };
+
+    let face: Face = move_.parse().expect("invalid move: {move_}");
+
+    (face, dir)
+}
+
+/// Roughly how long the robot will take to physically perform `alg`,
+/// accounting for the motion profile, commutative move collapsing, backlash
+/// compensation, and the configured inter-move delays.
+///
+/// Queue latency and fractional step trim are not modeled, so treat the
+/// result as an estimate for pacing UIs rather than a guarantee.
+#[must_use]
+pub fn estimate_duration(alg: &Algorithm, config: &RobotConfig) -> Duration {
+    let (v_max, a_max) = motion_limits(config);
+    let microsteps = config.microstep_resolution.value();
+
+    // Collapse the move sequence the way the motor thread will
+    let mut fsm = CommutativeMoveFsm::new();
+    let mut instructions = Vec::new();
+    for move_ in alg.move_seq_iter() {
+        if let Some(instruction) = fsm.next(parse_move(move_)) {
+            instructions.push(instruction);
+        }
+    }
+    instructions.extend(fsm.flush());
+
+    // The time one motor spends stepping through `fullsteps`
+    let turn_seconds = |fullsteps: i32| {
+        let steps = fullsteps.unsigned_abs() * microsteps;
+        trapezoid_profile_inv(steps, steps, v_max, a_max)
+    };
+
+    // A move steps through the turn plus its compensation, then backs the
+    // compensation out; a double move runs both motors concurrently
+    let move_seconds = |(face, dir): (Face, Dir)| {
+        let steps = dir.qturns() * FULLSTEPS_PER_QUARTER.cast_signed();
+        let comp = config.compensation(face, dir);
+        (turn_seconds(steps + comp), turn_seconds(comp))
+    };
+
+    let mut seconds = 0.0;
+
+    for (i, instruction) in instructions.iter().enumerate() {
+        if i > 0 {
+            seconds += config.settle_delay;
+        }
+
+        seconds += match *instruction {
+            MoveInstruction::Single(move_) => {
+                let (turn, unwind) = move_seconds(move_);
+                turn + unwind
+            }
+            MoveInstruction::Double([move1, move2]) => {
+                let (turn1, unwind1) = move_seconds(move1);
+                let (turn2, unwind2) = move_seconds(move2);
+                turn1.max(turn2) + unwind1.max(unwind2)
+            }
+        };
+
+        seconds += config.wait_between_moves;
+    }
+
+    Duration::from_secs_f64(seconds)
 }
 
 /// Which UART port to use (BCM numbering context).
@@ -145,6 +498,35 @@ impl RobotConfig {
         let unsigned = for_motor.unwrap_or(self.compensation);
         unsigned.cast_signed() * sign
     }
+
+    /// The steps to turn for one move of `face`, including the face's
+    /// fractional step trim
+    fn trimmed_steps(&self, face: Face, dir: Dir, trim: &mut TrimAccumulator) -> i32 {
+        dir.qturns() * FULLSTEPS_PER_QUARTER.cast_signed()
+            + trim.advance(self.motors[face].step_trim, dir.qturns())
+    }
+}
+
+/// Applies a motor's `step_trim` a fraction of a step at a time.
+///
+/// `step_trim` is measured in steps per 100 quarter turns, so a single turn
+/// owes a fractional number of steps. The owed amount is accumulated in
+/// hundredths of a step and only whole steps are sent to the motor, keeping
+/// the remainder so that the fraction never drifts.
+#[derive(Default)]
+struct TrimAccumulator {
+    hundredths: i32,
+}
+
+impl TrimAccumulator {
+    /// Account for the trim owed by `qturns` quarter turns and return the
+    /// whole steps that are ready to be applied
+    fn advance(&mut self, step_trim: i8, qturns: i32) -> i32 {
+        self.hundredths += i32::from(step_trim) * qturns;
+        let steps = self.hundredths / 100;
+        self.hundredths -= steps * 100;
+        steps
+    }
 }
 
 impl Ticker {
@@ -167,6 +549,80 @@ impl Default for Ticker {
     }
 }
 
+/// Applies the configured settle delay between consecutive moves.
+///
+/// The timer starts when a move finishes, so time already spent idle (e.g.
+/// waiting for the next move to be queued) counts toward the delay and a
+/// settle delay never adds more of a gap than it needs to.
+struct SettleTimer {
+    delay: Duration,
+    ticker: Option<Ticker>,
+}
+
+impl SettleTimer {
+    fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            ticker: None,
+        }
+    }
+
+    /// Wait out the remainder of the settle delay. Does nothing before the
+    /// very first move or when the delay is zero.
+    fn wait_before_move(&mut self) {
+        if let Some(mut ticker) = self.ticker.take()
+            && !self.delay.is_zero()
+        {
+            ticker.wait(self.delay);
+        }
+    }
+
+    /// Start timing the gap after a move finishes
+    fn move_finished(&mut self) {
+        self.ticker = Some(Ticker::new());
+    }
+}
+
+/// Tracks which current profile the drivers are at and writes IHOLD_IRUN
+/// transitions between moves.
+///
+/// The drivers start at the run current (written by driver configuration),
+/// so the controller only has something to do after the robot goes idle.
+struct CurrentController {
+    at_idle_current: bool,
+}
+
+impl CurrentController {
+    fn new() -> Self {
+        Self {
+            at_idle_current: false,
+        }
+    }
+
+    /// Drop every driver to the idle hold current. Called when no move
+    /// arrived for the configured idle timeout.
+    fn went_idle(&mut self, uart0: &mut UartBus, uart4: &mut UartBus, robot_config: &RobotConfig) {
+        // With identical currents there is no transition to write
+        if self.at_idle_current || robot_config.currents.idle == robot_config.currents.run {
+            return;
+        }
+
+        write_currents(uart0, uart4, robot_config, robot_config.currents.idle);
+        self.at_idle_current = true;
+    }
+
+    /// Restore the run current if the drivers idled. Called before a move
+    /// starts stepping, so the transition never lands mid-move.
+    fn before_move(&mut self, uart0: &mut UartBus, uart4: &mut UartBus, robot_config: &RobotConfig) {
+        if !self.at_idle_current {
+            return;
+        }
+
+        write_currents(uart0, uart4, robot_config, robot_config.currents.run);
+        self.at_idle_current = false;
+    }
+}
+
 struct CommutativeMoveFsm {
     // stores the entire preceding commutative subsequence, which can always be
     // collapsed to up to two moves.
@@ -180,6 +636,18 @@ enum MoveInstruction {
     Double([(Face, Dir); 2]),
 }
 
+/// What [`move_instruction_iter`] tells the motor thread to do next
+#[derive(Debug, Clone)]
+enum MotorEvent {
+    Perform(MoveInstruction),
+    /// No move arrived for the configured idle timeout. Reported once per
+    /// idle period, so the motor thread can drop to the idle current without
+    /// rewriting it on every wakeup.
+    WentIdle,
+    /// A reloaded configuration to swap in before the next move
+    Reconfigure(Box<RobotConfig>),
+}
+
 impl CommutativeMoveFsm {
     fn new() -> Self {
         Self {
@@ -255,33 +723,88 @@ impl CommutativeMoveFsm {
     }
 }
 
-fn motor_thread(rx: mpsc::Receiver<MotorMessage>, robot_config: RobotConfig) {
-    set_prio(robot_config.priority);
-
-    let mut motors: [Motor; 6] = Face::ALL.map(|face| Motor::new(&robot_config, face));
+/// Waits for the motor thread to report that every driver is configured,
+/// allowing `timeout` per progress report.
+fn await_driver_configuration(
+    progress_rx: &mpsc::Receiver<InitProgress>,
+    timeout: Duration,
+) -> Result<(), RobotInitError> {
+    let mut configuring = None;
+
+    loop {
+        match progress_rx.recv_timeout(timeout) {
+            Ok(InitProgress::Configuring { face, uart_bus }) => {
+                configuring = Some((face, uart_bus));
+            }
+            Ok(InitProgress::Ready) => return Ok(()),
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(match configuring {
+                    Some((face, uart_bus)) => RobotInitError::DriverTimeout { face, uart_bus },
+                    None => RobotInitError::MotorThreadDied,
+                });
+            }
+            Err(RecvTimeoutError::Disconnected) => return Err(RobotInitError::MotorThreadDied),
+        }
+    }
+}
 
+/// Drains `rx` into move instructions to execute, collapsing commutative
+/// moves. `pending` is decremented by the number of queued moves an
+/// instruction accounts for when that instruction is yielded, including moves
+/// that cancelled out entirely.
+///
+/// When the queue stays empty for `idle_timeout`, a single
+/// [`MotorEvent::WentIdle`] is yielded before waiting indefinitely for the
+/// next move.
+///
+/// A reloaded configuration is yielded as [`MotorEvent::Reconfigure`] after
+/// its idle timeout replaces `idle_timeout` for every later countdown.
+fn move_instruction_iter(
+    rx: mpsc::Receiver<MotorMessage>,
+    pending: Arc<AtomicUsize>,
+    pause: Arc<PauseFlag>,
+    mut idle_timeout: Duration,
+) -> impl Iterator<Item = MotorEvent> {
     let mut fsm = CommutativeMoveFsm::new();
 
     // Unparkers from after the previously executed move
     let mut unparkers = Vec::<Unparker>::new();
 
-    let iter = from_fn(move || {
+    // How many queued moves the FSM's state was built from
+    let mut in_fsm = 0;
+
+    // Whether `WentIdle` was already yielded since the last queued move
+    let mut reported_idle = false;
+
+    from_fn(move || {
         const SHORT_TIMEOUT: Duration = Duration::from_millis(50);
         const NO_TIMEOUT: Duration = Duration::MAX;
 
+        pause.wait_if_paused();
+
         for unparker in unparkers.drain(..) {
             unparker.unpark();
         }
 
         let mut timeout = SHORT_TIMEOUT;
 
+        // Whether the current `recv_timeout` is waiting out `idle_timeout`
+        let mut counting_down = false;
+
         loop {
             match rx.recv_timeout(timeout) {
                 Ok(MotorMessage::QueueMove(move_)) => {
                     // If we get a move, we're ok with waiting at most `SHORT_TIMEOUT` amount of time for one that might commute
                     timeout = SHORT_TIMEOUT;
+                    counting_down = false;
+                    reported_idle = false;
+                    in_fsm += 1;
                     if let Some(instr) = fsm.next(move_) {
-                        return Some(instr);
+                        // Everything but the move that just entered the FSM is
+                        // about to be performed
+                        pending.fetch_sub(in_fsm - 1, Ordering::Relaxed);
+                        in_fsm = 1;
+                        return Some(MotorEvent::Perform(instr));
                     }
                 }
                 Ok(MotorMessage::PrevMovesDone(unparker)) => {
@@ -291,21 +814,91 @@ fn motor_thread(rx: mpsc::Receiver<MotorMessage>, robot_config: RobotConfig) {
                         unparkers.push(unparker);
                     }
                 }
+                Ok(MotorMessage::Reconfigure(config)) => {
+                    // The idle timeout is captured here rather than read by
+                    // the motor thread, so apply the new value before handing
+                    // the config over
+                    idle_timeout = Duration::from_secs_f64(config.currents.idle_timeout);
+                    return Some(MotorEvent::Reconfigure(config));
+                }
                 Err(RecvTimeoutError::Timeout) => {
                     // If we time out, then just send whatever's in the FSM
-                    if let Some(instr) = fsm.flush() {
-                        return Some(instr);
+                    let instr = fsm.flush();
+                    pending.fetch_sub(in_fsm, Ordering::Relaxed);
+                    in_fsm = 0;
+
+                    if let Some(instr) = instr {
+                        return Some(MotorEvent::Perform(instr));
+                    }
+
+                    // If there's nothing in the FSM, report idleness once
+                    // after `idle_timeout` and then just wait however long
+                    // for the next move
+                    if reported_idle {
+                        timeout = NO_TIMEOUT;
+                    } else if counting_down {
+                        reported_idle = true;
+                        return Some(MotorEvent::WentIdle);
+                    } else {
+                        counting_down = true;
+                        timeout = idle_timeout;
                     }
-                    // If there's nothing in the FSM, then just wait however long for the next move
-                    timeout = NO_TIMEOUT;
                 }
                 // Empty channel
                 Err(RecvTimeoutError::Disconnected) => return None,
             }
         }
-    });
+    })
+}
+
+fn motor_thread(
+    rx: mpsc::Receiver<MotorMessage>,
+    pending: Arc<AtomicUsize>,
+    pause: Arc<PauseFlag>,
+    progress_tx: &mpsc::Sender<InitProgress>,
+    mut robot_config: RobotConfig,
+) {
+    set_prio(robot_config.priority);
+
+    // The buses stay open so that current profile transitions can be written
+    // while the robot runs
+    let mut uart0 = UartBus::new(UartId::Uart0);
+    let mut uart4 = UartBus::new(UartId::Uart4);
+
+    configure_drivers(&mut uart0, &mut uart4, &robot_config, progress_tx);
+
+    let mut motors: [Motor; 6] = Face::ALL.map(|face| Motor::new(&robot_config, face));
+    let mut trims: [TrimAccumulator; 6] = std::array::from_fn(|_| TrimAccumulator::default());
+    let mut settle = SettleTimer::new(Duration::from_secs_f64(robot_config.settle_delay));
+    let mut currents = CurrentController::new();
+
+    let idle_timeout = Duration::from_secs_f64(robot_config.currents.idle_timeout);
+
+    for event in move_instruction_iter(rx, pending, pause, idle_timeout) {
+        let moves = match event {
+            MotorEvent::Perform(moves) => moves,
+            MotorEvent::WentIdle => {
+                currents.went_idle(&mut uart0, &mut uart4, &robot_config);
+                continue;
+            }
+            MotorEvent::Reconfigure(new_config) => {
+                robot_config = *new_config;
+                for motor in &mut motors {
+                    motor.update_motion_limits(&robot_config);
+                }
+                // Keep the gap already timed since the last move; only the
+                // length of the delay changes
+                settle.delay = Duration::from_secs_f64(robot_config.settle_delay);
+                info!(target: "move_seq", "Applied a reloaded configuration");
+                continue;
+            }
+        };
+
+        // No motor is stepping yet, so the transition can't land mid-move
+        currents.before_move(&mut uart0, &mut uart4, &robot_config);
+
+        settle.wait_before_move();
 
-    for moves in iter {
         info!(
             target: "move_seq",
             "Requested moves: {moves:?}",
@@ -315,7 +908,7 @@ fn motor_thread(rx: mpsc::Receiver<MotorMessage>, robot_config: RobotConfig) {
             MoveInstruction::Single((face, dir)) => {
                 let motor = &mut motors[face as usize];
 
-                let steps = dir.qturns() * FULLSTEPS_PER_QUARTER.cast_signed();
+                let steps = robot_config.trimmed_steps(face, dir, &mut trims[face as usize]);
                 let comp = robot_config.compensation(face, dir);
 
                 motor.turn(steps + comp);
@@ -326,8 +919,8 @@ fn motor_thread(rx: mpsc::Receiver<MotorMessage>, robot_config: RobotConfig) {
                     .get_disjoint_mut([face1 as usize, face2 as usize])
                     .unwrap();
 
-                let steps1 = dir1.qturns() * FULLSTEPS_PER_QUARTER.cast_signed();
-                let steps2 = dir2.qturns() * FULLSTEPS_PER_QUARTER.cast_signed();
+                let steps1 = robot_config.trimmed_steps(face1, dir1, &mut trims[face1 as usize]);
+                let steps2 = robot_config.trimmed_steps(face2, dir2, &mut trims[face2 as usize]);
                 let comp1 = robot_config.compensation(face1, dir1);
                 let comp2 = robot_config.compensation(face2, dir2);
 
@@ -341,6 +934,8 @@ fn motor_thread(rx: mpsc::Receiver<MotorMessage>, robot_config: RobotConfig) {
             "Completed moves: {moves:?}",
         );
 
+        settle.move_finished();
+
         let wait = Duration::from_secs_f64(robot_config.wait_between_moves);
         info!(
             target: "move_seq",
@@ -379,112 +974,138 @@ pub fn set_prio(prio: Priority) {
 }
 
 pub fn uart_init(robot_config: &RobotConfig) {
+    // Nobody is waiting on the progress reports here
+    let (progress_tx, _progress_rx) = mpsc::channel();
+
     let mut uart0 = UartBus::new(UartId::Uart0);
     let mut uart4 = UartBus::new(UartId::Uart4);
 
+    configure_drivers(&mut uart0, &mut uart4, robot_config, &progress_tx);
+}
+
+/// Configure every motor's driver over the given UART buses, reporting which
+/// face is being configured over `progress` before touching its driver.
+fn configure_drivers(
+    uart0: &mut UartBus,
+    uart4: &mut UartBus,
+    robot_config: &RobotConfig,
+    progress: &mpsc::Sender<InitProgress>,
+) {
     for face in Face::ALL {
         let config = &robot_config.motors[face];
+
+        // The receiver may have given up waiting; keep configuring anyway
+        let _ = progress.send(InitProgress::Configuring {
+            face,
+            uart_bus: config.uart_bus,
+        });
+
         let mut uart = match config.uart_bus {
-            UartId::Uart0 => &mut uart0,
-            UartId::Uart4 => &mut uart4,
+            UartId::Uart0 => &mut *uart0,
+            UartId::Uart4 => &mut *uart4,
         }
         .node(config.uart_address);
 
         debug!(target: "uart_init", "Initializing {face:?}: uart_bus={:?} node_address={:?}", config.uart_bus, config.uart_address);
 
-        // Set SENDDELAY without performing a read. We can't perform any reads yet *because* we
-        // haven't set SENDDELAY. We set NODECONF again later regardless, because this could
-        // fail without us knowing.
-        // TODO: there has to be a better way to integrate this into the API of `uart`
-        debug!(target: "uart_init", "Setting SENDDELAY");
-        uart.write_raw(
-            NodeConf::ADDRESS,
-            NodeConf::empty().with_senddelay(2).bits(),
-        );
+        configure_driver(&mut uart, robot_config);
 
-        //
-        // Configure GCONF
-        //
-        debug!(target: "uart_init", "Reading initial GCONF");
-        let initial_gconf = uart.gconf();
-        debug!(target: "uart_init", "Read initial GCONF: initial_value={initial_gconf:?}");
-        let new_gconf = initial_gconf
-            .union(GConf::MSTEP_REG_SELECT)
-            .union(GConf::PDN_DISABLE)
-            .union(GConf::INDEX_OTPW)
-            // qter robot turns the opposite direction
-            .union(GConf::SHAFT);
-        if initial_gconf == new_gconf {
-            debug!(target: "uart_init", "GCONF already configured");
-        } else {
-            debug!(
-                target: "uart_init",
-                "Writing GCONF: new_value={new_gconf:?}",
-            );
-            uart.set_gconf(new_gconf);
-        }
-
-        //
-        // Configure CHOPCONF
-        //
-        debug!(target: "uart_init", "Reading initial CHOPCONF");
-        let initial_chopconf = uart.chopconf();
-        debug!(target: "uart_init", "Read initial CHOPCONF: initial_value={initial_chopconf:?}");
-        let new_chopconf =
-            initial_chopconf.with_mres(robot_config.microstep_resolution.mres_value());
-        if new_chopconf == initial_chopconf {
-            debug!(target: "uart_init", "CHOPCONF already configured");
-        } else {
-            debug!(
-                target: "uart_init",
-                "Writing CHOPCONF: new_value={new_chopconf:?}",
-            );
-            uart.set_chopconf(new_chopconf);
-        }
-
-        //
-        // Configure PWMCONF.
-        //
-        debug!(target: "uart_init", "Reading initial PwmConf");
-        let initial_pwmconf = uart.pwmconf();
-        debug!(target: "uart_init", "Read initial PWMCONF: initial_value={initial_pwmconf:?}");
-        let new_pwmconf = initial_pwmconf
-            // Freewheel mode
-            .with_freewheel(if robot_config.float { 1 } else { 0 });
-        if new_pwmconf == initial_pwmconf {
-            debug!(target: "uart_init", "PWMCONF already configured");
-        } else {
-            debug!(
-                target: "uart_init",
-                "Writing PWMCONF: new_value={new_pwmconf:?}",
-            );
-            uart.set_pwmconf(new_pwmconf);
-        }
+        debug!(target: "uart_init", "Initialized{face:?}: uart_bus={:?} node_address={:?}", config.uart_bus, config.uart_address);
+    }
 
-        //
-        // Configure IHOLD_IRUN. Note that IHOLD_IRUN is write-only.
-        //
-        let ihold_irun = IholdIrun::empty()
-            .with_ihold(if robot_config.float { 0 } else { 31 })
-            // Set IRUN to 31
-            .with_irun(31)
-            // Set IHOLDDELAY to 1
-            .with_iholddelay(1);
+    let _ = progress.send(InitProgress::Ready);
+}
+
+fn configure_driver(uart: &mut UartNode<'_>, robot_config: &RobotConfig) {
+    // Set SENDDELAY without performing a read. We can't perform any reads yet *because* we
+    // haven't set SENDDELAY. We set NODECONF again later regardless, because this could
+    // fail without us knowing.
+    // TODO: there has to be a better way to integrate this into the API of `uart`
+    debug!(target: "uart_init", "Setting SENDDELAY");
+    uart.write_raw(
+        NodeConf::ADDRESS,
+        NodeConf::empty().with_senddelay(2).bits(),
+    );
+
+    //
+    // Configure GCONF
+    //
+    debug!(target: "uart_init", "Reading initial GCONF");
+    let initial_gconf = uart.gconf();
+    debug!(target: "uart_init", "Read initial GCONF: initial_value={initial_gconf:?}");
+    let new_gconf = initial_gconf
+        .union(GConf::MSTEP_REG_SELECT)
+        .union(GConf::PDN_DISABLE)
+        .union(GConf::INDEX_OTPW)
+        // qter robot turns the opposite direction
+        .union(GConf::SHAFT);
+    if initial_gconf == new_gconf {
+        debug!(target: "uart_init", "GCONF already configured");
+    } else {
         debug!(
             target: "uart_init",
-            "Writing IHOLD_IRUN: value={ihold_irun:?}",
+            "Writing GCONF: new_value={new_gconf:?}",
         );
-        uart.set_iholdirun(ihold_irun);
+        uart.set_gconf(new_gconf);
+    }
 
-        let tpowerdown = 2;
+    //
+    // Configure CHOPCONF
+    //
+    debug!(target: "uart_init", "Reading initial CHOPCONF");
+    let initial_chopconf = uart.chopconf();
+    debug!(target: "uart_init", "Read initial CHOPCONF: initial_value={initial_chopconf:?}");
+    let new_chopconf = initial_chopconf.with_mres(robot_config.microstep_resolution.mres_value());
+    if new_chopconf == initial_chopconf {
+        debug!(target: "uart_init", "CHOPCONF already configured");
+    } else {
         debug!(
             target: "uart_init",
-            "Writing TPOWERODNW: value={tpowerdown:?}",
+            "Writing CHOPCONF: new_value={new_chopconf:?}",
         );
-        uart.set_tpowerdown(tpowerdown);
+        uart.set_chopconf(new_chopconf);
+    }
 
-        debug!(target: "uart_init", "Initialized{face:?}: uart_bus={:?} node_address={:?}", config.uart_bus, config.uart_address);
+    //
+    // Configure PWMCONF.
+    //
+    debug!(target: "uart_init", "Reading initial PwmConf");
+    let initial_pwmconf = uart.pwmconf();
+    debug!(target: "uart_init", "Read initial PWMCONF: initial_value={initial_pwmconf:?}");
+    let new_pwmconf = initial_pwmconf
+        // Freewheel mode
+        .with_freewheel(if robot_config.float { 1 } else { 0 });
+    if new_pwmconf == initial_pwmconf {
+        debug!(target: "uart_init", "PWMCONF already configured");
+    } else {
+        debug!(
+            target: "uart_init",
+            "Writing PWMCONF: new_value={new_pwmconf:?}",
+        );
+        uart.set_pwmconf(new_pwmconf);
     }
+
+    //
+    // Configure IHOLD_IRUN. Note that IHOLD_IRUN is write-only.
+    //
+    let run_current = robot_config.currents.run;
+    let ihold_irun = IholdIrun::empty()
+        .with_ihold(if robot_config.float { 0 } else { run_current })
+        .with_irun(run_current)
+        // Set IHOLDDELAY to 1
+        .with_iholddelay(1);
+    debug!(
+        target: "uart_init",
+        "Writing IHOLD_IRUN: value={ihold_irun:?}",
+    );
+    uart.set_iholdirun(ihold_irun);
+
+    let tpowerdown = 2;
+    debug!(
+        target: "uart_init",
+        "Writing TPOWERODNW: value={tpowerdown:?}",
+    );
+    uart.set_tpowerdown(tpowerdown);
 }
 
 pub fn float(robot_config: &RobotConfig) {
@@ -511,8 +1132,140 @@ pub fn float(robot_config: &RobotConfig) {
     }
 }
 
+/// Write IHOLD_IRUN on every driver with both currents set to `current`.
+///
+/// Used for current profile transitions while the robot runs; only ever call
+/// this between moves, never while a motor is stepping.
+fn write_currents(
+    uart0: &mut UartBus,
+    uart4: &mut UartBus,
+    robot_config: &RobotConfig,
+    current: u8,
+) {
+    for face in Face::ALL {
+        let config = &robot_config.motors[face];
+        let mut uart = match config.uart_bus {
+            UartId::Uart0 => &mut *uart0,
+            UartId::Uart4 => &mut *uart4,
+        }
+        .node(config.uart_address);
+
+        let ihold_irun = IholdIrun::empty()
+            .with_ihold(if robot_config.float { 0 } else { current })
+            .with_irun(current)
+            .with_iholddelay(1);
+
+        debug!(target: "uart", "Writing IHOLD_IRUN for {face:?}: value={ihold_irun:?}");
+        uart.set_iholdirun(ihold_irun);
+    }
+}
+
+/// Put every driver at the configured recovery current, for retrying a move
+/// that stalled with extra torque.
+///
+/// There is no automatic stall detection yet, so this is an operator tool;
+/// nothing switches back to the run current afterwards other than re-running
+/// driver configuration (e.g. restarting the robot).
+pub fn apply_recovery_current(robot_config: &RobotConfig) {
+    let mut uart0 = UartBus::new(UartId::Uart0);
+    let mut uart4 = UartBus::new(UartId::Uart4);
+
+    write_currents(
+        &mut uart0,
+        &mut uart4,
+        robot_config,
+        robot_config.currents.recovery,
+    );
+}
+
 pub fn estop(robot_config: &RobotConfig) {}
 
+/// DRV_STATUS flags that indicate a wiring or driver problem.
+const FAULT_FLAGS: DrvStatus = DrvStatus::OT
+    .union(DrvStatus::S2GA)
+    .union(DrvStatus::S2GB)
+    .union(DrvStatus::S2VSA)
+    .union(DrvStatus::S2VSB)
+    .union(DrvStatus::OLA)
+    .union(DrvStatus::OLB);
+
+/// The health of a single motor as reported by [`self_test`]
+#[derive(Debug, Clone, Copy)]
+pub struct MotorHealth {
+    pub face: Face,
+    pub drv_status: DrvStatus,
+}
+
+impl MotorHealth {
+    /// Whether the motor's driver reported no fault flags
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        !self.drv_status.intersects(FAULT_FLAGS)
+    }
+}
+
+/// Read the DRV_STATUS register of every motor over the given UART buses.
+pub fn read_motor_statuses(
+    uart0: &mut UartBus,
+    uart4: &mut UartBus,
+    robot_config: &RobotConfig,
+) -> [MotorHealth; 6] {
+    Face::ALL.map(|face| {
+        let config = &robot_config.motors[face];
+        let mut uart = match config.uart_bus {
+            UartId::Uart0 => &mut *uart0,
+            UartId::Uart4 => &mut *uart4,
+        }
+        .node(config.uart_address);
+
+        let drv_status = uart.drvstatus();
+        debug!(target: "self_test", "Read DRV_STATUS for {face:?}: {drv_status:?}");
+
+        MotorHealth { face, drv_status }
+    })
+}
+
+/// Turn each face a small amount and back, then read each driver's status
+/// registers over UART. Useful for catching wiring/driver issues before
+/// attempting a solve. `uart_init` must have been called beforehand.
+pub fn self_test(robot_config: &RobotConfig) -> [MotorHealth; 6] {
+    // An eighth of a quarter turn; enough for the driver to notice a
+    // disconnected coil without disturbing the cube
+    let test_steps = (FULLSTEPS_PER_QUARTER / 8).cast_signed();
+
+    for face in Face::ALL {
+        let mut motor = Motor::new(robot_config, face);
+        info!(target: "self_test", "Wiggling {face:?}");
+        motor.turn(test_steps);
+        motor.turn(-test_steps);
+    }
+
+    let mut uart0 = UartBus::new(UartId::Uart0);
+    let mut uart4 = UartBus::new(UartId::Uart4);
+
+    read_motor_statuses(&mut uart0, &mut uart4, robot_config)
+}
+
+/// The drift measured during calibration, in steps per 100 quarter turns
+#[must_use]
+pub fn drift_per_100_turns(forward_drift: i32, backward_drift: i32, turns: u32) -> f64 {
+    f64::from(forward_drift + backward_drift) * 100.0 / (2.0 * f64::from(turns))
+}
+
+/// Compute a motor's `step_trim` from the drift measured during calibration.
+///
+/// `forward_drift` and `backward_drift` are how many steps each phase of
+/// `turns` quarter turns fell short, measured in the direction of travel.
+/// The result saturates at the range of the config field.
+// The value is clamped to the range of `i8` before the cast
+#[allow(clippy::cast_possible_truncation)]
+#[must_use]
+pub fn step_trim_from_drift(forward_drift: i32, backward_drift: i32, turns: u32) -> i8 {
+    drift_per_100_turns(forward_drift, backward_drift, turns)
+        .round()
+        .clamp(f64::from(i8::MIN), f64::from(i8::MAX)) as i8
+}
+
 #[derive(Debug, Clone, Copy)]
 enum Dir {
     Normal,
@@ -557,3 +1310,861 @@ impl Display for Dir {
         }
     }
 }
+
+/// A parseable configuration with mock pins and addresses, for tests that
+/// never talk to real hardware
+#[cfg(test)]
+pub(crate) fn mock_config() -> RobotConfig {
+    let mut config = String::from(
+        "revolutions_per_second = 1.0\n\
+         max_acceleration = 1.0\n\
+         microstep_resolution = 8\n\
+         priority = \"Default\"\n\
+         wait_between_moves = 0.0\n\
+         compensation = 0\n\
+         float = false\n",
+    );
+
+    for (i, face) in ["R", "U", "F", "L", "D", "B"].iter().enumerate() {
+        config.push_str(&format!(
+            "[motors.{face}]\n\
+             step_pin = {}\n\
+             dir_pin = {}\n\
+             uart_bus = \"Uart0\"\n\
+             uart_address = {}\n",
+            i * 2,
+            i * 2 + 1,
+            i % 4,
+        ));
+    }
+
+    toml::from_str(&config).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::uart::{
+        MASTER_ADDRESS, SYNC_BYTE, WRITE_BIT, crc,
+        regs::{self, DrvStatus},
+    };
+    use std::{
+        fs::File,
+        io::{Read, Write},
+        os::fd::AsRawFd,
+        path::PathBuf,
+    };
+
+    /// Open a pseudoterminal and return the master side along with the path
+    /// of the slave side, which `UartBus::with_path` can open like a serial
+    /// device.
+    fn open_pty() -> (File, PathBuf) {
+        let master = File::options()
+            .read(true)
+            .write(true)
+            .open("/dev/ptmx")
+            .unwrap();
+
+        let fd = master.as_raw_fd();
+        let mut unlock: libc::c_int = 0;
+        let mut pts_number: libc::c_uint = 0;
+
+        unsafe {
+            assert_eq!(libc::ioctl(fd, libc::TIOCSPTLCK, &raw mut unlock), 0);
+            assert_eq!(libc::ioctl(fd, libc::TIOCGPTN, &raw mut pts_number), 0);
+        }
+
+        (master, PathBuf::from(format!("/dev/pts/{pts_number}")))
+    }
+
+    /// Pretend to be the TMC2209s on a bus, replying to every DRV_STATUS read
+    /// request with the given value.
+    fn mock_drivers(mut master: File, drv_status: DrvStatus) {
+        loop {
+            let mut request = [0; 4];
+            if master.read_exact(&mut request).is_err() {
+                // The bus was dropped
+                return;
+            }
+
+            assert_eq!(request[0], SYNC_BYTE);
+            let register = request[2];
+
+            if register & WRITE_BIT == 0 && register == DrvStatus::ADDRESS {
+                let val_bytes = drv_status.bits().to_be_bytes();
+                let reply = crc::with_crc([
+                    SYNC_BYTE,
+                    MASTER_ADDRESS,
+                    register,
+                    val_bytes[0],
+                    val_bytes[1],
+                    val_bytes[2],
+                    val_bytes[3],
+                    0,
+                ]);
+                master.write_all(&reply).unwrap();
+            }
+        }
+    }
+
+    /// Pretend to be the TMC2209s on a bus, recording every register write
+    /// and maintaining IFCNT so that verified writes succeed.
+    fn mock_recording_drivers(mut master: File, writes: &mpsc::Sender<(u8, u32)>) {
+        let mut ifcnt: u8 = 0;
+
+        loop {
+            let mut header = [0; 3];
+            if master.read_exact(&mut header).is_err() {
+                // The bus was dropped
+                return;
+            }
+
+            assert_eq!(header[0], SYNC_BYTE);
+            let register = header[2];
+
+            if register & WRITE_BIT == 0 {
+                // A read request; the remaining byte is the crc
+                let mut crc_byte = [0; 1];
+                if master.read_exact(&mut crc_byte).is_err() {
+                    return;
+                }
+
+                if register == regs::IFCNT_ADDRESS {
+                    let val_bytes = u32::from(ifcnt).to_be_bytes();
+                    let reply = crc::with_crc([
+                        SYNC_BYTE,
+                        MASTER_ADDRESS,
+                        register,
+                        val_bytes[0],
+                        val_bytes[1],
+                        val_bytes[2],
+                        val_bytes[3],
+                        0,
+                    ]);
+                    master.write_all(&reply).unwrap();
+                }
+            } else {
+                // A write datagram; four value bytes and the crc remain
+                let mut rest = [0; 5];
+                if master.read_exact(&mut rest).is_err() {
+                    return;
+                }
+
+                ifcnt = ifcnt.wrapping_add(1);
+                let value = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]);
+                // The receiver may have given up; keep serving the bus anyway
+                let _ = writes.send((register & !WRITE_BIT, value));
+            }
+        }
+    }
+
+    /// Pretend to be a wiring fault: read requests forever without replying.
+    fn mock_unresponsive_drivers(mut master: File) {
+        loop {
+            let mut request = [0; 4];
+            if master.read_exact(&mut request).is_err() {
+                return;
+            }
+        }
+    }
+
+    #[test]
+    fn test_init_timeout_names_face() {
+        let robot_config = mock_config();
+
+        let (master0, slave0) = open_pty();
+        let (master4, slave4) = open_pty();
+
+        thread::spawn(move || mock_unresponsive_drivers(master0));
+        thread::spawn(move || mock_unresponsive_drivers(master4));
+
+        let (progress_tx, progress_rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut uart0 = UartBus::with_path(&slave0);
+            let mut uart4 = UartBus::with_path(&slave4);
+
+            configure_drivers(&mut uart0, &mut uart4, &robot_config, &progress_tx);
+        });
+
+        match await_driver_configuration(&progress_rx, Duration::from_millis(100)) {
+            Err(RobotInitError::DriverTimeout {
+                face: Face::R,
+                uart_bus: UartId::Uart0,
+            }) => {}
+            other => panic!("Expected a timeout for R on Uart0, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_await_driver_configuration_ready() {
+        let (progress_tx, progress_rx) = mpsc::channel();
+
+        for face in Face::ALL {
+            progress_tx
+                .send(InitProgress::Configuring {
+                    face,
+                    uart_bus: UartId::Uart0,
+                })
+                .unwrap();
+        }
+        progress_tx.send(InitProgress::Ready).unwrap();
+
+        assert!(await_driver_configuration(&progress_rx, Duration::from_millis(100)).is_ok());
+    }
+
+    #[test]
+    fn test_self_test_healthy_motors() {
+        let robot_config = mock_config();
+
+        let (master0, slave0) = open_pty();
+        let (master4, slave4) = open_pty();
+
+        thread::spawn(move || mock_drivers(master0, DrvStatus::empty()));
+        thread::spawn(move || mock_drivers(master4, DrvStatus::empty()));
+
+        let mut uart0 = UartBus::with_path(&slave0);
+        let mut uart4 = UartBus::with_path(&slave4);
+
+        let report = read_motor_statuses(&mut uart0, &mut uart4, &robot_config);
+
+        assert_eq!(report.len(), 6);
+        for (health, face) in report.iter().zip(Face::ALL) {
+            assert_eq!(health.face, face);
+            assert!(health.is_healthy(), "{face:?} should be healthy");
+        }
+    }
+
+    #[test]
+    fn test_pending_moves_accounting() {
+        let (tx, rx) = mpsc::channel();
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        // Queue moves the way `RobotHandle::queue_move` does. The two R moves
+        // collapse into one physical turn, but all three stay pending until
+        // they are handed off for execution.
+        for move_ in [
+            (Face::R, Dir::Normal),
+            (Face::R, Dir::Normal),
+            (Face::U, Dir::Normal),
+        ] {
+            pending.fetch_add(1, Ordering::Relaxed);
+            tx.send(MotorMessage::QueueMove(move_)).unwrap();
+        }
+
+        assert_eq!(pending.load(Ordering::Relaxed), 3);
+
+        // Stand in for the motor thread, consuming instructions without motors
+        let consumer = {
+            let pending = Arc::clone(&pending);
+            let pause = Arc::new(PauseFlag::default());
+            thread::spawn(move || move_instruction_iter(rx, pending, pause, Duration::MAX).count())
+        };
+
+        let parker = Parker::new();
+        tx.send(MotorMessage::PrevMovesDone(parker.unparker().clone()))
+            .unwrap();
+        parker.park();
+
+        assert_eq!(pending.load(Ordering::Relaxed), 0);
+
+        drop(tx);
+        assert_eq!(consumer.join().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_pause_holds_queued_moves_until_resume() {
+        let (tx, rx) = mpsc::channel();
+        let pending = Arc::new(AtomicUsize::new(0));
+        let pause = Arc::new(PauseFlag::default());
+
+        // Pause before anything is queued so no move starts
+        *pause.paused.lock().unwrap() = true;
+
+        for move_ in [
+            (Face::R, Dir::Normal),
+            (Face::U, Dir::Normal),
+            (Face::F, Dir::Prime),
+        ] {
+            pending.fetch_add(1, Ordering::Relaxed);
+            tx.send(MotorMessage::QueueMove(move_)).unwrap();
+        }
+
+        let (instr_tx, instr_rx) = mpsc::channel();
+        let consumer = {
+            let pending = Arc::clone(&pending);
+            let pause = Arc::clone(&pause);
+            thread::spawn(move || {
+                for event in move_instruction_iter(rx, pending, pause, Duration::MAX) {
+                    instr_tx.send(event).unwrap();
+                }
+            })
+        };
+
+        // While paused, nothing is executed and the queue stays intact
+        assert!(instr_rx.recv_timeout(Duration::from_millis(200)).is_err());
+        assert_eq!(pending.load(Ordering::Relaxed), 3);
+
+        // Resume the way `RobotHandle::resume` does
+        *pause.paused.lock().unwrap() = false;
+        pause.unpaused.notify_all();
+
+        for (face, qturns) in [(Face::R, 1), (Face::U, 1), (Face::F, -1)] {
+            match instr_rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+                MotorEvent::Perform(MoveInstruction::Single((got_face, dir))) => {
+                    assert_eq!(got_face, face);
+                    assert_eq!(dir.qturns(), qturns);
+                }
+                other => panic!("Expected a single move, got {other:?}"),
+            }
+        }
+
+        assert_eq!(pending.load(Ordering::Relaxed), 0);
+
+        drop(tx);
+        consumer.join().unwrap();
+    }
+
+    #[test]
+    fn test_identity_algorithm_queues_nothing() {
+        let (tx, rx) = mpsc::channel();
+
+        let mut handle = RobotHandle {
+            motor_thread_handle: tx,
+            config: mock_config(),
+            pending: Arc::new(AtomicUsize::new(0)),
+            pause: Arc::new(PauseFlag::default()),
+            queued_since_await: AtomicBool::new(false),
+        };
+
+        handle.queue_move_seq(&Algorithm::identity(Arc::clone(&crate::CUBE3)));
+
+        assert_eq!(handle.pending_moves(), 0);
+        assert!(matches!(rx.try_recv(), Err(mpsc::TryRecvError::Empty)));
+
+        // With no motor thread draining the channel, this would deadlock if
+        // it round-tripped
+        handle.await_moves();
+
+        // A real algorithm still queues its moves
+        handle.queue_move_seq(
+            &Algorithm::parse_from_string(Arc::clone(&crate::CUBE3), "R U2").unwrap(),
+        );
+        assert_eq!(handle.pending_moves(), 2);
+    }
+
+    /// Replay `alg` through the motor thread's logic on a virtual clock,
+    /// summing each half-step delay the way [`Motor::turn_many`] sleeps them.
+    fn virtual_timeline_seconds(alg: &Algorithm, config: &RobotConfig) -> f64 {
+        let (v_max, a_max) = motion_limits(config);
+
+        // One motor stepping through `fullsteps`, one delay at a time
+        let stepped_seconds = |fullsteps: i32| {
+            let steps = fullsteps.unsigned_abs() * config.microstep_resolution.value();
+            (0..steps)
+                .map(|i| {
+                    let t1 = trapezoid_profile_inv(i, steps, v_max, a_max);
+                    let t2 = trapezoid_profile_inv(i + 1, steps, v_max, a_max);
+                    t2 - t1
+                })
+                .sum::<f64>()
+        };
+
+        let mut fsm = CommutativeMoveFsm::new();
+        let mut instructions = Vec::new();
+        for move_ in alg.move_seq_iter() {
+            instructions.extend(fsm.next(parse_move(move_)));
+        }
+        instructions.extend(fsm.flush());
+
+        let mut seconds = 0.0;
+
+        for (i, instruction) in instructions.iter().enumerate() {
+            if i > 0 {
+                seconds += config.settle_delay;
+            }
+
+            seconds += match *instruction {
+                MoveInstruction::Single((face, dir)) => {
+                    let steps = dir.qturns() * FULLSTEPS_PER_QUARTER.cast_signed();
+                    let comp = config.compensation(face, dir);
+                    stepped_seconds(steps + comp) + stepped_seconds(comp)
+                }
+                MoveInstruction::Double([(face1, dir1), (face2, dir2)]) => {
+                    let steps1 = dir1.qturns() * FULLSTEPS_PER_QUARTER.cast_signed();
+                    let steps2 = dir2.qturns() * FULLSTEPS_PER_QUARTER.cast_signed();
+                    let comp1 = config.compensation(face1, dir1);
+                    let comp2 = config.compensation(face2, dir2);
+                    stepped_seconds(steps1 + comp1).max(stepped_seconds(steps2 + comp2))
+                        + stepped_seconds(comp1).max(stepped_seconds(comp2))
+                }
+            };
+
+            seconds += config.wait_between_moves;
+        }
+
+        seconds
+    }
+
+    #[test]
+    fn test_estimate_duration_matches_virtual_timeline() {
+        let mut config = mock_config();
+        config.settle_delay = 0.1;
+        config.wait_between_moves = 0.2;
+        config.compensation = 2;
+
+        for alg_str in [
+            "R",
+            "R U R' U'",
+            "R U2 F' D B2 L U'",
+            "R U R' U' R' F R2 U' R' U' R U R' F'",
+        ] {
+            let alg = Algorithm::parse_from_string(Arc::clone(&crate::CUBE3), alg_str).unwrap();
+
+            let estimate = estimate_duration(&alg, &config).as_secs_f64();
+            let timeline = virtual_timeline_seconds(&alg, &config);
+
+            assert!(
+                (estimate - timeline).abs() <= timeline * 0.15,
+                "{alg_str}: estimated {estimate}s against a {timeline}s timeline"
+            );
+        }
+    }
+
+    #[test]
+    fn test_estimate_duration_collapses_commutative_moves() {
+        let config = mock_config();
+        let estimate = |alg_str| {
+            estimate_duration(
+                &Algorithm::parse_from_string(Arc::clone(&crate::CUBE3), alg_str).unwrap(),
+                &config,
+            )
+        };
+
+        // Same-face moves merge before they reach the motors...
+        assert_eq!(estimate("R R"), estimate("R2"));
+        // ...or cancel out entirely
+        assert_eq!(estimate("R R'"), Duration::ZERO);
+        // Opposite faces turn concurrently
+        assert!(estimate("R L") < estimate("R U"));
+    }
+
+    #[test]
+    fn test_estimate_duration_includes_configured_delays() {
+        // Under the mock profile a quarter turn takes exactly one second
+        let mut config = mock_config();
+        config.settle_delay = 0.25;
+        config.wait_between_moves = 0.5;
+
+        let alg = Algorithm::parse_from_string(Arc::clone(&crate::CUBE3), "R U").unwrap();
+        let estimate = estimate_duration(&alg, &config).as_secs_f64();
+
+        // Two turns, a wait after each, and one settle in between
+        assert!((estimate - 3.25).abs() < 1e-9, "estimated {estimate}s");
+    }
+
+    #[test]
+    fn test_settle_delay_config() {
+        // Omitting settle_delay from the config means no settle delay
+        let mut config = mock_config();
+        assert!(config.settle_delay.abs() < f64::EPSILON);
+
+        // And it round-trips through the config format
+        config.settle_delay = 0.25;
+        let reparsed: RobotConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert!((reparsed.settle_delay - 0.25).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_settle_delay_applied_between_moves() {
+        let mut settle = SettleTimer::new(Duration::from_millis(50));
+
+        // No delay before the very first move
+        let start = Instant::now();
+        settle.wait_before_move();
+        assert!(start.elapsed() < Duration::from_millis(10));
+
+        // The full delay separates back-to-back moves
+        settle.move_finished();
+        let start = Instant::now();
+        settle.wait_before_move();
+        assert!(start.elapsed() >= Duration::from_millis(40));
+
+        // Time already spent idle since the move counts toward the delay
+        settle.move_finished();
+        thread::sleep(Duration::from_millis(50));
+        let start = Instant::now();
+        settle.wait_before_move();
+        assert!(start.elapsed() < Duration::from_millis(10));
+
+        // Zero preserves back-to-back behavior
+        let mut settle = SettleTimer::new(Duration::ZERO);
+        settle.move_finished();
+        let start = Instant::now();
+        settle.wait_before_move();
+        assert!(start.elapsed() < Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_step_trim_accumulates_without_drift() {
+        let mut acc = TrimAccumulator::default();
+
+        let mut total = 0;
+        for _ in 0..100 {
+            total += acc.advance(7, 1);
+        }
+        assert_eq!(total, 7);
+
+        for _ in 0..900 {
+            total += acc.advance(7, 1);
+        }
+        assert_eq!(total, 70);
+    }
+
+    #[test]
+    fn test_step_trim_sign_follows_direction() {
+        let mut acc = TrimAccumulator::default();
+        let mut total = 0;
+        for _ in 0..100 {
+            total += acc.advance(7, -1);
+        }
+        assert_eq!(total, -7);
+
+        let mut acc = TrimAccumulator::default();
+        let mut total = 0;
+        for _ in 0..50 {
+            total += acc.advance(-3, 2);
+        }
+        assert_eq!(total, -3);
+    }
+
+    #[test]
+    fn test_trimmed_steps_applies_trim() {
+        let mut config = mock_config();
+        config.motors[Face::R].step_trim = 50;
+
+        let mut acc = TrimAccumulator::default();
+        let quarter = FULLSTEPS_PER_QUARTER.cast_signed();
+
+        assert_eq!(
+            config.trimmed_steps(Face::R, Dir::Normal, &mut acc),
+            quarter
+        );
+        assert_eq!(
+            config.trimmed_steps(Face::R, Dir::Normal, &mut acc),
+            quarter + 1
+        );
+        assert_eq!(
+            config.trimmed_steps(Face::R, Dir::Double, &mut acc),
+            2 * quarter + 1
+        );
+        assert_eq!(
+            config.trimmed_steps(Face::R, Dir::Prime, &mut acc),
+            -quarter
+        );
+        assert_eq!(
+            config.trimmed_steps(Face::R, Dir::Prime, &mut acc),
+            -quarter - 1
+        );
+    }
+
+    #[test]
+    fn test_step_trim_from_drift() {
+        // Five steps short both ways over 100 turns is five steps of trim
+        assert_eq!(step_trim_from_drift(5, 5, 100), 5);
+        // 1.5 steps per 100 turns rounds up
+        assert_eq!(step_trim_from_drift(3, 0, 100), 2);
+        // The trim saturates at the range of the config field
+        assert_eq!(step_trim_from_drift(100_000, 100_000, 100), i8::MAX);
+        assert_eq!(step_trim_from_drift(-100_000, -100_000, 100), i8::MIN);
+    }
+
+    #[test]
+    fn test_current_profiles_default_to_full_current() {
+        // Configurations written before current profiles existed keep the old
+        // behavior of full current at all times
+        let config = mock_config();
+        assert_eq!(config.currents.run, 31);
+        assert_eq!(config.currents.idle, 31);
+        assert_eq!(config.currents.recovery, 31);
+
+        // And the profiles round-trip through the config format
+        let mut config = config;
+        config.currents.idle = 8;
+        let reparsed: RobotConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.currents.idle, 8);
+    }
+
+    /// Drain the IHOLD_IRUN writes for all six drivers off the recording mock
+    /// and assert they carry `expected` as both currents.
+    fn expect_current_writes(writes: &mpsc::Receiver<(u8, u32)>, expected: u8) {
+        for _ in 0..6 {
+            let (register, value) = writes.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(register, IholdIrun::ADDRESS);
+
+            let ihold_irun = IholdIrun::from_bits_retain(value);
+            assert_eq!(ihold_irun.ihold(), expected);
+            assert_eq!(ihold_irun.irun(), expected);
+        }
+    }
+
+    #[test]
+    fn test_idle_current_transitions_between_moves() {
+        let mut robot_config = mock_config();
+        robot_config.currents.run = 24;
+        robot_config.currents.idle = 6;
+
+        let (master0, slave0) = open_pty();
+        let (master4, slave4) = open_pty();
+
+        let (writes_tx, writes_rx) = mpsc::channel();
+        thread::spawn(move || mock_recording_drivers(master0, &writes_tx));
+        thread::spawn(move || mock_unresponsive_drivers(master4));
+
+        let mut uart0 = UartBus::with_path(&slave0);
+        let mut uart4 = UartBus::with_path(&slave4);
+
+        let mut currents = CurrentController::new();
+
+        // Going idle drops every driver to the idle current
+        currents.went_idle(&mut uart0, &mut uart4, &robot_config);
+        expect_current_writes(&writes_rx, 6);
+
+        // Going idle again writes nothing; the drivers are already there
+        currents.went_idle(&mut uart0, &mut uart4, &robot_config);
+        assert!(writes_rx.recv_timeout(Duration::from_millis(200)).is_err());
+
+        // The next move restores the run current before it starts stepping
+        currents.before_move(&mut uart0, &mut uart4, &robot_config);
+        expect_current_writes(&writes_rx, 24);
+
+        // Back-to-back moves don't rewrite the run current
+        currents.before_move(&mut uart0, &mut uart4, &robot_config);
+        assert!(writes_rx.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn test_equal_currents_disable_idle_transitions() {
+        let mut robot_config = mock_config();
+        robot_config.currents.run = 31;
+        robot_config.currents.idle = 31;
+
+        let (master0, slave0) = open_pty();
+        let (master4, slave4) = open_pty();
+
+        let (writes_tx, writes_rx) = mpsc::channel();
+        thread::spawn(move || mock_recording_drivers(master0, &writes_tx));
+        thread::spawn(move || mock_unresponsive_drivers(master4));
+
+        let mut uart0 = UartBus::with_path(&slave0);
+        let mut uart4 = UartBus::with_path(&slave4);
+
+        let mut currents = CurrentController::new();
+        currents.went_idle(&mut uart0, &mut uart4, &robot_config);
+        currents.before_move(&mut uart0, &mut uart4, &robot_config);
+
+        assert!(writes_rx.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn test_recovery_current_writes() {
+        let mut robot_config = mock_config();
+        robot_config.currents.recovery = 28;
+
+        let (master0, slave0) = open_pty();
+        let (master4, slave4) = open_pty();
+
+        let (writes_tx, writes_rx) = mpsc::channel();
+        thread::spawn(move || mock_recording_drivers(master0, &writes_tx));
+        thread::spawn(move || mock_unresponsive_drivers(master4));
+
+        let mut uart0 = UartBus::with_path(&slave0);
+        let mut uart4 = UartBus::with_path(&slave4);
+
+        write_currents(
+            &mut uart0,
+            &mut uart4,
+            &robot_config,
+            robot_config.currents.recovery,
+        );
+        expect_current_writes(&writes_rx, 28);
+    }
+
+    #[test]
+    fn test_went_idle_reported_once_after_timeout() {
+        let (tx, rx) = mpsc::channel();
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let consumer = {
+            let pending = Arc::clone(&pending);
+            let pause = Arc::new(PauseFlag::default());
+            thread::spawn(move || {
+                for event in
+                    move_instruction_iter(rx, pending, pause, Duration::from_millis(100))
+                {
+                    event_tx.send(event).unwrap();
+                }
+            })
+        };
+
+        pending.fetch_add(1, Ordering::Relaxed);
+        tx.send(MotorMessage::QueueMove((Face::R, Dir::Normal)))
+            .unwrap();
+
+        assert!(matches!(
+            event_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            MotorEvent::Perform(MoveInstruction::Single((Face::R, _)))
+        ));
+
+        // Idleness is reported once after the timeout passes without moves...
+        assert!(matches!(
+            event_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            MotorEvent::WentIdle
+        ));
+        // ...and not again while the queue stays empty
+        assert!(event_rx.recv_timeout(Duration::from_millis(300)).is_err());
+
+        // The next move wakes the iterator back up
+        pending.fetch_add(1, Ordering::Relaxed);
+        tx.send(MotorMessage::QueueMove((Face::U, Dir::Prime)))
+            .unwrap();
+        assert!(matches!(
+            event_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            MotorEvent::Perform(MoveInstruction::Single((Face::U, _)))
+        ));
+
+        drop(tx);
+        consumer.join().unwrap();
+    }
+
+    #[test]
+    fn test_reload_validation_rejects_bad_values() {
+        assert!(validate_reload(&mock_config()).is_ok());
+
+        let mut config = mock_config();
+        config.revolutions_per_second = 0.0;
+        assert_eq!(
+            validate_reload(&config).unwrap_err().field,
+            "revolutions_per_second"
+        );
+
+        let mut config = mock_config();
+        config.max_acceleration = f64::NAN;
+        assert_eq!(
+            validate_reload(&config).unwrap_err().field,
+            "max_acceleration"
+        );
+
+        let mut config = mock_config();
+        config.settle_delay = -0.1;
+        assert_eq!(validate_reload(&config).unwrap_err().field, "settle_delay");
+
+        let mut config = mock_config();
+        config.currents.run = 32;
+        assert_eq!(validate_reload(&config).unwrap_err().field, "currents.run");
+    }
+
+    #[test]
+    fn test_reload_keeps_structural_settings() {
+        let running = mock_config();
+
+        let mut new = mock_config();
+        new.revolutions_per_second = 2.5;
+        new.settle_delay = 0.3;
+        new.motors[Face::R].step_pin = 27;
+        new.priority = Priority::MaxNonRT;
+
+        let (merged, needs_restart) = merge_reload(&running, new);
+
+        // The speeds and delays come from the reloaded config...
+        assert!((merged.revolutions_per_second - 2.5).abs() < f64::EPSILON);
+        assert!((merged.settle_delay - 0.3).abs() < f64::EPSILON);
+
+        // ...while the settings applied to hardware at startup stay put
+        assert_eq!(
+            merged.motors[Face::R].step_pin,
+            running.motors[Face::R].step_pin
+        );
+        assert_eq!(merged.priority, Priority::Default);
+
+        assert_eq!(needs_restart, vec!["motor pins and addresses", "priority"]);
+    }
+
+    #[test]
+    fn test_reload_reaches_motor_thread() {
+        let (tx, rx) = mpsc::channel();
+        let pending = Arc::new(AtomicUsize::new(0));
+        let pause = Arc::new(PauseFlag::default());
+
+        let mut handle = RobotHandle {
+            motor_thread_handle: tx,
+            config: mock_config(),
+            pending: Arc::clone(&pending),
+            pause: Arc::clone(&pause),
+            queued_since_await: AtomicBool::new(false),
+        };
+
+        let mut new = mock_config();
+        new.revolutions_per_second = 3.0;
+        // Differs structurally, so the merged config keeps the old bus and
+        // the caller is told a restart is needed
+        new.motors[Face::U].uart_bus = UartId::Uart4;
+        // Short enough for the iterator to report idleness below
+        new.currents.idle_timeout = 0.05;
+
+        let needs_restart = handle.reload_config(new).unwrap();
+        assert_eq!(needs_restart, vec!["motor pins and addresses"]);
+        assert!((handle.config().revolutions_per_second - 3.0).abs() < f64::EPSILON);
+
+        // The motor thread sees the merged config through its event stream
+        let (event_tx, event_rx) = mpsc::channel();
+        let consumer = thread::spawn(move || {
+            for event in move_instruction_iter(rx, pending, pause, Duration::MAX) {
+                event_tx.send(event).unwrap();
+            }
+        });
+
+        let event = event_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        let MotorEvent::Reconfigure(config) = event else {
+            panic!("Expected a reconfigure, got {event:?}");
+        };
+        assert!((config.revolutions_per_second - 3.0).abs() < f64::EPSILON);
+        assert!(matches!(config.motors[Face::U].uart_bus, UartId::Uart0));
+
+        // The new speed raises the motion limits the motors will turn at...
+        let (v_max, _) = motion_limits(&config);
+        let expected =
+            3.0 * f64::from(FULLSTEPS_PER_REVOLUTION * config.microstep_resolution.value());
+        assert!((v_max - expected).abs() < 1e-9);
+
+        // ...and the reloaded idle timeout takes effect in the countdown
+        assert!(matches!(
+            event_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            MotorEvent::WentIdle
+        ));
+
+        drop(handle);
+        consumer.join().unwrap();
+    }
+
+    #[test]
+    fn test_self_test_faulted_motors() {
+        let robot_config = mock_config();
+
+        let (master0, slave0) = open_pty();
+        let (master4, slave4) = open_pty();
+
+        thread::spawn(move || mock_drivers(master0, DrvStatus::OLA));
+        thread::spawn(move || mock_drivers(master4, DrvStatus::OLA));
+
+        let mut uart0 = UartBus::with_path(&slave0);
+        let mut uart4 = UartBus::with_path(&slave4);
+
+        let report = read_motor_statuses(&mut uart0, &mut uart4, &robot_config);
+
+        for health in report {
+            assert!(!health.is_healthy(), "{:?} should be faulted", health.face);
+        }
+    }
+}
@@ -42,7 +42,7 @@ fn run_many<const N: usize>(mut iters: [impl Iterator<Item = Duration>; N]) {
 }
 
 // computes position -> time
-fn trapezoid_profile_inv(y: u32, s: u32, v_max: f64, a_max: f64) -> f64 {
+pub(crate) fn trapezoid_profile_inv(y: u32, s: u32, v_max: f64, a_max: f64) -> f64 {
     let yf = y as f64;
     let sf = s as f64;
     let thresh = v_max * v_max / a_max;
@@ -68,6 +68,16 @@ fn trapezoid_profile_inv(y: u32, s: u32, v_max: f64, a_max: f64) -> f64 {
     }
 }
 
+/// The peak velocity and acceleration, in microsteps per second (squared),
+/// that the motion profile runs at under the given configuration
+pub(crate) fn motion_limits(config: &RobotConfig) -> (f64, f64) {
+    let mult = (Motor::FULLSTEPS_PER_REVOLUTION * config.microstep_resolution.value()) as f64;
+    (
+        config.revolutions_per_second * mult,
+        config.max_acceleration * mult,
+    )
+}
+
 pub struct Motor {
     step: OutputPin,
     dir: OutputPin,
@@ -88,18 +98,27 @@ impl Motor {
             pin
         }
 
-        let microsteps = config.microstep_resolution;
-        let mult = (Self::FULLSTEPS_PER_REVOLUTION * microsteps.value()) as f64;
+        let (v_max, a_max) = motion_limits(config);
         let motor_config = &config.motors[face];
         Self {
             step: mk_output_pin(motor_config.step_pin),
             dir: mk_output_pin(motor_config.dir_pin),
-            microsteps,
-            v_max: config.revolutions_per_second * mult,
-            a_max: config.max_acceleration * mult,
+            microsteps: config.microstep_resolution,
+            v_max,
+            a_max,
         }
     }
 
+    /// Re-derive the motion profile's peak velocity and acceleration from a
+    /// reloaded configuration.
+    ///
+    /// Only the motion limits change; the GPIO pins and the driver's
+    /// microstep resolution were applied at startup and keep their running
+    /// values.
+    pub fn update_motion_limits(&mut self, config: &RobotConfig) {
+        (self.v_max, self.a_max) = motion_limits(config);
+    }
+
     pub fn turn(&mut self, steps: i32) {
         Self::turn_many([self], [steps]);
     }
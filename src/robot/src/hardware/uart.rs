@@ -1,4 +1,4 @@
-mod crc;
+pub(crate) mod crc;
 pub mod regs;
 
 use std::{ops::RangeTo, path::Path, time::Duration};
@@ -10,9 +10,9 @@ use regs::{ChopConf, DrvStatus, GConf, GStat, IholdIrun, NodeConf, PwmConf};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
-const WRITE_BIT: u8 = 1 << 7;
-const SYNC_BYTE: u8 = 0b_1010_0000_u8.reverse_bits();
-const MASTER_ADDRESS: u8 = 0xff;
+pub(crate) const WRITE_BIT: u8 = 1 << 7;
+pub(crate) const SYNC_BYTE: u8 = 0b_1010_0000_u8.reverse_bits();
+pub(crate) const MASTER_ADDRESS: u8 = 0xff;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UartId {
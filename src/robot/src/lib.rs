@@ -1,14 +1,24 @@
 #![feature(gen_blocks)]
 
-use std::sync::{Arc, LazyLock};
+use std::{
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
 
 use interpreter::puzzle_states::RobotLike;
 use qter_core::architectures::{Algorithm, Permutation, PermutationGroup, mk_puzzle_definition};
 
-use crate::{hardware::RobotHandle, rob_twophase::solve_rob_twophase};
+use crate::{
+    hardware::{ConfigReloadError, RobotHandle, config::RobotConfig},
+    scanner::{Scanner, reconcile_scan},
+    solve::CubeSolver,
+};
 
 pub mod hardware;
+pub mod repl;
 pub mod rob_twophase;
+pub mod scanner;
+pub mod solve;
 
 pub static CUBE3: LazyLock<Arc<PermutationGroup>> =
     LazyLock::new(|| Arc::clone(&mk_puzzle_definition("3x3").unwrap().perm_group));
@@ -16,6 +26,55 @@ pub static CUBE3: LazyLock<Arc<PermutationGroup>> =
 pub struct QterRobot {
     state: Permutation,
     handle: RobotHandle,
+    scanner: Option<Box<dyn Scanner>>,
+    solver: Box<dyn CubeSolver>,
+}
+
+impl QterRobot {
+    /// Reconcile the tracked state against a physical scan on every
+    /// `take_picture`, instead of trusting the tracked permutation alone.
+    pub fn set_scanner(&mut self, scanner: Box<dyn Scanner>) {
+        self.scanner = Some(scanner);
+    }
+
+    /// Record moves made to the physical cube by hand, adjusting the tracked
+    /// state without moving the robot
+    pub fn sync_moves(&mut self, alg: &Algorithm) {
+        self.state.compose_into(alg.permutation());
+    }
+
+    /// Reset the tracked state to solved without moving the robot
+    pub fn sync_solved(&mut self) {
+        self.state = CUBE3.identity();
+    }
+
+    /// Roughly how long the robot will take to physically perform `alg`; see
+    /// [`hardware::estimate_duration`]
+    #[must_use]
+    pub fn estimate_duration(&self, alg: &Algorithm) -> Duration {
+        self.handle.estimate_duration(alg)
+    }
+
+    /// Swap in a reloaded configuration; see [`RobotHandle::reload_config`].
+    ///
+    /// The solver is rebuilt as well, so a changed `solve_backend` takes
+    /// effect without a restart.
+    ///
+    /// # Errors
+    ///
+    /// See [`RobotHandle::reload_config`].
+    pub fn reload_config(
+        &mut self,
+        new_config: RobotConfig,
+    ) -> Result<Vec<&'static str>, ConfigReloadError> {
+        let needs_restart = self.handle.reload_config(new_config)?;
+        self.solver = self
+            .handle
+            .config()
+            .solve_backend
+            .solver(self.handle.config());
+        Ok(needs_restart)
+    }
 }
 
 impl RobotLike for QterRobot {
@@ -23,10 +82,14 @@ impl RobotLike for QterRobot {
 
     fn initialize(group: Arc<PermutationGroup>, handle: RobotHandle) -> Self {
         assert_eq!(group.definition().slice(), "3x3");
-        
+
+        let solver = handle.config().solve_backend.solver(handle.config());
+
         QterRobot {
             handle,
             state: CUBE3.identity(),
+            scanner: None,
+            solver,
         }
     }
 
@@ -38,11 +101,16 @@ impl RobotLike for QterRobot {
 
     fn take_picture(&mut self) -> &Permutation {
         self.handle.await_moves();
+        if let Some(scanner) = &mut self.scanner {
+            reconcile_scan(&mut self.state, scanner.scan());
+        }
         &self.state
     }
 
     fn solve(&mut self) {
-        let alg = solve_rob_twophase(self.take_picture().clone()).unwrap();
+        let state = self.take_picture().clone();
+        solve::verify_state(&state).unwrap();
+        let alg = self.solver.solve(&state).unwrap();
 
         self.compose_into(&alg);
     }
@@ -4,19 +4,20 @@
 use clap::{Parser, Subcommand};
 use env_logger::TimestampPrecision;
 use interpreter::puzzle_states::{RobotLike, run_robot_server};
-use log::{LevelFilter, warn};
+use log::{LevelFilter, error, warn};
 use qter_core::architectures::{Algorithm, mk_puzzle_definition};
 use robot::{
     CUBE3, QterRobot,
     hardware::{
         RobotHandle,
         config::{Face, Priority, RobotConfig},
-        set_prio,
+        drift_per_100_turns, set_prio, step_trim_from_drift,
     },
+    repl::{ReplCommand, parse_command, render_net},
     rob_twophase::solve_rob_twophase_string,
 };
 use std::{
-    io::BufReader,
+    io::{self, BufReader, Write},
     net::TcpListener,
     path::PathBuf,
     sync::Arc,
@@ -51,12 +52,30 @@ enum Commands {
         /// The face to control.
         face: Face,
     },
+    /// Measure a face's step drift over many quarter turns and store the
+    /// resulting step trim in the robot configuration file.
+    Calibrate {
+        /// The face to calibrate.
+        face: Face,
+        /// How many quarter turns to perform in each direction.
+        #[arg(long, default_value_t = 100)]
+        turns: u32,
+    },
     /// Stop holding position across all motors.
     Float,
+    /// Verify that all six motors respond by turning each face a small amount
+    /// and back and reading driver status registers over UART.
+    SelfTest,
+    /// Put every motor at the configured recovery current, for manually
+    /// retrying a move that stalled with extra torque.
+    Recover,
     /// Test latencies at the different options for priority level
     TestPrio {
         prio: Priority,
     },
+    /// Run an interactive prompt combining move execution, state display,
+    /// solving, and state synchronization
+    Repl,
     /// Host a server to allow the robot to be remote-controlled
     Server {
         port: u16,
@@ -66,6 +85,16 @@ enum Commands {
     },
 }
 
+fn init_or_exit(robot_config: RobotConfig) -> RobotHandle {
+    match RobotHandle::init(robot_config) {
+        Ok(handle) => handle,
+        Err(e) => {
+            error!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
@@ -85,9 +114,13 @@ fn main() {
     )
     .expect("Failed to parse robot configuration file");
 
+    if let Some(max_threads) = robot_config.max_threads {
+        qter_core::parallelism::set_max_threads(max_threads);
+    }
+
     match cli.command {
         Commands::MoveSeq { sequence } => {
-            let mut robot_handle = RobotHandle::init(robot_config);
+            let mut robot_handle = init_or_exit(robot_config);
             robot_handle.queue_move_seq(
                 &Algorithm::parse_from_string(Arc::clone(&CUBE3), &sequence)
                     .expect("The algorithm is invalid"),
@@ -95,12 +128,87 @@ fn main() {
             robot_handle.await_moves();
         }
         Commands::Motor { face } => {
-            let mut robot_handle = RobotHandle::init(robot_config);
+            let mut robot_handle = init_or_exit(robot_config);
             robot_handle.loop_face_turn(face);
         }
+        Commands::Calibrate { face, turns } => {
+            let mut robot_handle = init_or_exit(robot_config.clone());
+
+            let mut drifts = [0_i32; 2];
+
+            for (drift, qturns) in drifts
+                .iter_mut()
+                .zip([turns.cast_signed(), -turns.cast_signed()])
+            {
+                let direction = if qturns > 0 { "forward" } else { "backward" };
+                println!("Performing {turns} quarter turns {direction} on {face:?}...");
+                robot_handle.turn_face_quarters(face, qturns);
+
+                println!(
+                    "How many steps short is the face, measured in the direction of travel? (negative if it overshot)"
+                );
+                *drift = loop {
+                    let mut line = String::new();
+                    std::io::stdin().read_line(&mut line).unwrap();
+                    match line.trim().parse() {
+                        Ok(v) => break v,
+                        Err(_) => println!("Please enter a whole number of steps"),
+                    }
+                };
+
+                println!("Realign the face by hand, then press enter to continue");
+                let _ = std::io::stdin().read_line(&mut String::new());
+            }
+
+            let drift_per_100 = drift_per_100_turns(drifts[0], drifts[1], turns);
+            let step_trim = step_trim_from_drift(drifts[0], drifts[1], turns);
+
+            println!(
+                "Measured drift: {:+} steps forward, {:+} steps backward over {turns} turns each way",
+                drifts[0], drifts[1]
+            );
+            println!("Drift per 100 turns: {drift_per_100:+.1} steps");
+            println!("Step trim for {face:?}: {step_trim:+} steps per 100 quarter turns");
+
+            let mut new_config = robot_config;
+            new_config.motors[face].step_trim = step_trim;
+
+            std::fs::write(
+                &cli.robot_config,
+                toml::to_string(&new_config).expect("Failed to serialize the robot configuration"),
+            )
+            .expect("Failed to write the robot configuration file");
+
+            println!("Wrote the step trim to {}", cli.robot_config.display());
+        }
         Commands::Float => {
             robot::hardware::float(&robot_config);
         }
+        Commands::SelfTest => {
+            robot::hardware::uart_init(&robot_config);
+            let report = robot::hardware::self_test(&robot_config);
+
+            for health in &report {
+                if health.is_healthy() {
+                    println!("{:?}: healthy", health.face);
+                } else {
+                    println!("{:?}: FAULT ({:?})", health.face, health.drv_status);
+                }
+            }
+
+            if report.iter().all(robot::hardware::MotorHealth::is_healthy) {
+                println!("All motors healthy");
+            } else {
+                warn!("Some motors reported faults");
+            }
+        }
+        Commands::Recover => {
+            robot::hardware::apply_recovery_current(&robot_config);
+            println!(
+                "Motors set to the recovery current ({}); restart the robot to restore normal currents",
+                robot_config.currents.recovery
+            );
+        }
         Commands::TestPrio { prio } => {
             const SAMPLES: usize = 2048;
 
@@ -130,10 +238,79 @@ fn main() {
                 println!("Top 5 = {:?}", &latencies[SAMPLES - 5..SAMPLES]);
             }
         }
+        Commands::Repl => {
+            let handle = init_or_exit(robot_config.clone());
+            let mut robot = QterRobot::initialize(
+                Arc::clone(&mk_puzzle_definition("3x3").unwrap().perm_group),
+                handle,
+            );
+
+            loop {
+                print!("qter> ");
+                io::stdout().flush().unwrap();
+
+                let mut line = String::new();
+                if std::io::stdin().read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+
+                let command = match parse_command(&line) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        println!("{e}");
+                        continue;
+                    }
+                };
+
+                match command {
+                    ReplCommand::Move(alg) => {
+                        robot.compose_into(&alg);
+                        robot.take_picture();
+                    }
+                    ReplCommand::State => {
+                        let state = robot.take_picture();
+                        println!("{state}");
+                        print!("{}", render_net(state));
+                    }
+                    ReplCommand::Solve => robot.solve(),
+                    ReplCommand::SyncMoves(alg) => robot.sync_moves(&alg),
+                    ReplCommand::SyncSolved => robot.sync_solved(),
+                    ReplCommand::Reload => {
+                        let reloaded = std::fs::read_to_string(&cli.robot_config)
+                            .map_err(|e| e.to_string())
+                            .and_then(|contents| {
+                                toml::from_str::<RobotConfig>(&contents).map_err(|e| e.to_string())
+                            });
+
+                        match reloaded {
+                            Ok(new_config) => match robot.reload_config(new_config) {
+                                Ok(needs_restart) => {
+                                    if needs_restart.is_empty() {
+                                        println!("Reloaded {}", cli.robot_config.display());
+                                    } else {
+                                        println!(
+                                            "Reloaded {}; restart the robot to change {}",
+                                            cli.robot_config.display(),
+                                            needs_restart.join(", ")
+                                        );
+                                    }
+                                }
+                                Err(e) => println!("Not applied: {e}"),
+                            },
+                            Err(e) => {
+                                println!("Failed to reload {}: {e}", cli.robot_config.display());
+                            }
+                        }
+                    }
+                    ReplCommand::Estop => robot::hardware::estop(&robot_config),
+                    ReplCommand::Quit => break,
+                }
+            }
+        }
         Commands::Server { port } => {
             let listener = TcpListener::bind(format!("0.0.0.0:{port}")).unwrap();
 
-            let handle = RobotHandle::init(robot_config);
+            let handle = init_or_exit(robot_config);
             let mut robot = QterRobot::initialize(
                 Arc::clone(&mk_puzzle_definition("3x3").unwrap().perm_group),
                 handle,
@@ -150,7 +327,7 @@ fn main() {
         } => {
             let alg = solve_rob_twophase_string(&rob_twophase_string).unwrap();
 
-            let mut robot_handle = RobotHandle::init(robot_config);
+            let mut robot_handle = init_or_exit(robot_config);
             robot_handle.queue_move_seq(&alg);
             robot_handle.await_moves();
         }
@@ -0,0 +1,204 @@
+//! Command parsing and state rendering for the interactive robot REPL. The
+//! prompt loop itself lives in the binary; everything here is pure so it can
+//! be tested without hardware.
+
+use std::sync::Arc;
+
+use qter_core::architectures::{Algorithm, Permutation};
+
+use crate::CUBE3;
+
+/// A parsed REPL command
+#[derive(Debug)]
+pub enum ReplCommand {
+    /// `mv <moves>` — queue the moves and wait for them to finish
+    Move(Algorithm),
+    /// `state` — print the tracked permutation and a net diagram
+    State,
+    /// `solve` — solve the cube with `rob_twophase` and execute the solution
+    Solve,
+    /// `sync <moves>` — record moves made by hand without moving the robot
+    SyncMoves(Algorithm),
+    /// `sync solved` — reset the tracked state to solved without moving
+    SyncSolved,
+    /// `reload` — re-read the configuration file and apply the settings that
+    /// can change at runtime
+    Reload,
+    /// `estop` — stop the motors immediately
+    Estop,
+    /// `quit` — exit the REPL
+    Quit,
+}
+
+/// Parse a line of REPL input
+///
+/// # Errors
+///
+/// Returns a usage message if the line is not a valid command
+pub fn parse_command(line: &str) -> Result<ReplCommand, String> {
+    let line = line.trim();
+    let (command, rest) = match line.split_once(char::is_whitespace) {
+        Some((command, rest)) => (command, rest.trim()),
+        None => (line, ""),
+    };
+
+    match command {
+        "mv" => {
+            if rest.is_empty() {
+                return Err("Usage: mv <moves>".to_owned());
+            }
+
+            match Algorithm::parse_from_string(Arc::clone(&CUBE3), rest) {
+                Some(alg) => Ok(ReplCommand::Move(alg)),
+                None => Err(format!("Invalid move sequence: {rest}")),
+            }
+        }
+        "state" => Ok(ReplCommand::State),
+        "solve" => Ok(ReplCommand::Solve),
+        "sync" => {
+            if rest == "solved" {
+                return Ok(ReplCommand::SyncSolved);
+            }
+
+            if rest.is_empty() {
+                return Err("Usage: sync <moves> | sync solved".to_owned());
+            }
+
+            match Algorithm::parse_from_string(Arc::clone(&CUBE3), rest) {
+                Some(alg) => Ok(ReplCommand::SyncMoves(alg)),
+                None => Err(format!("Invalid move sequence: {rest}")),
+            }
+        }
+        "reload" => Ok(ReplCommand::Reload),
+        "estop" => Ok(ReplCommand::Estop),
+        "quit" | "exit" => Ok(ReplCommand::Quit),
+        _ => Err(format!(
+            "Unknown command {command:?}; expected mv, state, solve, sync, reload, estop, or quit"
+        )),
+    }
+}
+
+/// Render the cube state as an unfolded net diagram, with each sticker shown
+/// as the first letter of the color currently at that position
+#[must_use]
+pub fn render_net(state: &Permutation) -> String {
+    let solved = CUBE3
+        .facelet_colors()
+        .iter()
+        .map(|color| color.chars().next().unwrap())
+        .collect::<Vec<_>>();
+
+    let mut stickers = solved.clone();
+    for (from, &to) in state.mapping().iter().enumerate() {
+        stickers[to] = solved[from];
+    }
+
+    // Each face stores its eight movable stickers row-major with the fixed
+    // center omitted
+    let face_row = |face: usize, row: usize| -> [char; 3] {
+        let base = face * 8;
+        match row {
+            0 => [stickers[base], stickers[base + 1], stickers[base + 2]],
+            1 => [stickers[base + 3], solved[base], stickers[base + 4]],
+            _ => [stickers[base + 5], stickers[base + 6], stickers[base + 7]],
+        }
+    };
+
+    let mut out = String::new();
+
+    // Unfold as U on top, then the L F R B strip, then D on the bottom
+    for row in 0..3 {
+        let [a, b, c] = face_row(0, row);
+        out.push_str(&format!("      {a} {b} {c}\n"));
+    }
+
+    for row in 0..3 {
+        for (i, face) in [1, 2, 3, 4].into_iter().enumerate() {
+            let [a, b, c] = face_row(face, row);
+            if i > 0 {
+                out.push(' ');
+            }
+            out.push_str(&format!("{a} {b} {c}"));
+        }
+        out.push('\n');
+    }
+
+    for row in 0..3 {
+        let [a, b, c] = face_row(5, row);
+        out.push_str(&format!("      {a} {b} {c}\n"));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn commands_parse() {
+        let ReplCommand::Move(alg) = parse_command(" mv R U R' ").unwrap() else {
+            panic!("Expected a move command");
+        };
+        assert_eq!(alg.to_string(), "R U R'");
+
+        assert!(matches!(parse_command("state"), Ok(ReplCommand::State)));
+        assert!(matches!(parse_command("solve"), Ok(ReplCommand::Solve)));
+        assert!(matches!(
+            parse_command("sync solved"),
+            Ok(ReplCommand::SyncSolved)
+        ));
+        assert!(matches!(
+            parse_command("sync F2"),
+            Ok(ReplCommand::SyncMoves(_))
+        ));
+        assert!(matches!(parse_command("reload"), Ok(ReplCommand::Reload)));
+        assert!(matches!(parse_command("estop"), Ok(ReplCommand::Estop)));
+        assert!(matches!(parse_command("quit"), Ok(ReplCommand::Quit)));
+        assert!(matches!(parse_command("exit"), Ok(ReplCommand::Quit)));
+
+        assert!(parse_command("mv").is_err());
+        assert!(parse_command("mv Q3").is_err());
+        assert!(parse_command("sync").is_err());
+        assert!(parse_command("scramble").is_err());
+    }
+
+    #[test]
+    fn net_of_solved_state() {
+        assert_eq!(
+            render_net(&CUBE3.identity()),
+            "      W W W\n\
+             \x20     W W W\n\
+             \x20     W W W\n\
+             O O O G G G R R R B B B\n\
+             O O O G G G R R R B B B\n\
+             O O O G G G R R R B B B\n\
+             \x20     Y Y Y\n\
+             \x20     Y Y Y\n\
+             \x20     Y Y Y\n"
+        );
+    }
+
+    #[test]
+    fn net_after_a_move() {
+        let alg = Algorithm::parse_from_string(Arc::clone(&CUBE3), "F").unwrap();
+        let net = render_net(alg.permutation());
+
+        let lines = net.lines().collect_vec();
+
+        // F brings the left face's stickers onto the bottom row of U
+        assert_eq!(lines[2], "      O O O");
+        assert_eq!(lines[0], "      W W W");
+
+        // A permutation only rearranges stickers, so every color still
+        // appears nine times
+        for color in ['W', 'O', 'G', 'R', 'B', 'Y'] {
+            assert_eq!(
+                net.chars().filter(|&c| c == color).count(),
+                9,
+                "{color} is not on the net exactly nine times"
+            );
+        }
+    }
+}
@@ -4,14 +4,13 @@ use std::{
     io::{BufRead, BufReader, Error, Write},
     process::{ChildStdin, ChildStdout, Command, Stdio},
     sync::{Arc, LazyLock, Mutex},
-    thread::available_parallelism,
 };
 
 use internment::ArcIntern;
 use itertools::Itertools;
-use log::{trace, warn};
+use log::trace;
 use qter_core::{
-    I, Int,
+    I, Int, parallelism,
     architectures::{Algorithm, Permutation},
 };
 
@@ -87,16 +86,7 @@ pub fn solve_rob_twophase_string(rob_twophase_string: &str) -> Result<Algorithm,
         let child = Command::new("twophase")
             .current_dir(cache)
             .args(["-c", "-m", "30", "-t"])
-            .arg(match available_parallelism() {
-                Ok(v) => v.to_string(),
-                Err(e) => {
-                    warn!(
-                        "{} {e}",
-                        "Failed to get available parallelism; defaulting to 1:"
-                    );
-                    (1).to_string()
-                }
-            })
+            .arg(parallelism::max_threads().to_string())
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
@@ -0,0 +1,299 @@
+//! An abstraction over reading the physical state of the cube, used to
+//! reconcile the tracked permutation with reality.
+
+use std::collections::HashMap;
+
+use internment::ArcIntern;
+use log::warn;
+use qter_core::architectures::{Permutation, PermutationGroup};
+
+/// A device that can read the physical state of the cube, such as a camera.
+pub trait Scanner {
+    /// Read the physical cube and return its state as a permutation from the
+    /// solved state.
+    fn scan(&mut self) -> Permutation;
+}
+
+/// Why a color reading could not be turned into a permutation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanError {
+    /// The reading does not have one color per facelet
+    WrongFaceletCount { expected: usize, actual: usize },
+    /// An observed color has no calibration entry
+    UnknownColor(String),
+    /// The colors read at a piece's position match no piece of the puzzle
+    UnrecognizedPiece(Vec<String>),
+}
+
+impl core::fmt::Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanError::WrongFaceletCount { expected, actual } => write!(
+                f,
+                "Expected one color for each of the {expected} facelets but the reading has {actual}"
+            ),
+            ScanError::UnknownColor(color) => {
+                write!(f, "The observed color {color:?} is not in the calibration")
+            }
+            ScanError::UnrecognizedPiece(colors) => {
+                write!(
+                    f,
+                    "No piece of the puzzle wears the colors {}",
+                    colors.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+/// Maps the color labels a physical scanner reports to the facelet colors of
+/// the puzzle. The labels are whatever the camera's classifier emits — `"w"`,
+/// `"#ffffff"`, a cluster index — and never need to match the puzzle's own
+/// color names.
+#[derive(Debug, Clone, Default)]
+pub struct ColorCalibration {
+    colors: HashMap<String, ArcIntern<str>>,
+}
+
+impl ColorCalibration {
+    /// Build a calibration from pairs of observed label and facelet color
+    pub fn new(entries: impl IntoIterator<Item = (String, ArcIntern<str>)>) -> Self {
+        Self {
+            colors: entries.into_iter().collect(),
+        }
+    }
+
+    /// Look up the facelet color for an observed label
+    #[must_use]
+    pub fn facelet_color(&self, observed: &str) -> Option<&ArcIntern<str>> {
+        self.colors.get(observed)
+    }
+}
+
+/// Turn a per-facelet color reading into the permutation taking the solved
+/// state to the scanned state. The reading is indexed by facelet position in
+/// the same order as [`PermutationGroup::facelet_colors`].
+///
+/// Facelets that are moved by exactly the same generators always travel
+/// together as a piece, and on the 3x3 every piece wears a unique set of
+/// colors, so the reading determines where each piece sits and how it is
+/// twisted. A misread sticker either matches no piece, which is reported as
+/// an error here, or produces a state that no move sequence reaches, which
+/// [`crate::solve::verify_state`] rejects.
+///
+/// # Errors
+///
+/// Returns an error if the reading has the wrong number of facelets, uses a
+/// color missing from the calibration, or reads a combination of colors at
+/// some piece's position that no piece of the puzzle wears.
+pub fn permutation_from_reading(
+    group: &PermutationGroup,
+    calibration: &ColorCalibration,
+    reading: &[&str],
+) -> Result<Permutation, ScanError> {
+    let solved = group.facelet_colors();
+
+    if reading.len() != solved.len() {
+        return Err(ScanError::WrongFaceletCount {
+            expected: solved.len(),
+            actual: reading.len(),
+        });
+    }
+
+    let observed = reading
+        .iter()
+        .map(|&label| {
+            calibration
+                .facelet_color(label)
+                .cloned()
+                .ok_or_else(|| ScanError::UnknownColor(label.to_owned()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Group the facelets into pieces by which generators move them
+    let mut pieces: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+    for facelet in 0..solved.len() {
+        let mut signature = group
+            .generators()
+            .filter(|(_, generator)| generator.mapping()[facelet] != facelet)
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        signature.sort_unstable();
+        pieces.entry(signature).or_default().push(facelet);
+    }
+
+    let mut piece_list: Vec<(Vec<String>, Vec<usize>)> = pieces.into_iter().collect();
+    piece_list.sort_unstable();
+
+    let colors_at = |facelets: &[usize], colors: &[ArcIntern<str>]| -> Vec<String> {
+        let mut out = facelets
+            .iter()
+            .map(|&facelet| colors[facelet].to_string())
+            .collect::<Vec<_>>();
+        out.sort_unstable();
+        out
+    };
+
+    let mut mapping = vec![0; solved.len()];
+    let mut used = vec![false; piece_list.len()];
+
+    for (_, position_piece) in &piece_list {
+        let read_colors = colors_at(position_piece, &observed);
+
+        // Find the piece wearing exactly the colors read at this position
+        let found = piece_list.iter().enumerate().find(|(idx, (_, candidate))| {
+            !used[*idx] && colors_at(candidate, solved) == read_colors
+        });
+        let Some((idx, (_, source_piece))) = found else {
+            return Err(ScanError::UnrecognizedPiece(read_colors));
+        };
+        used[idx] = true;
+
+        // The color read at each position singles out the facelet of the
+        // source piece that moved there
+        let mut remaining = source_piece.clone();
+        for &position in position_piece {
+            let at = remaining
+                .iter()
+                .position(|&facelet| solved[facelet] == observed[position])
+                .expect("the color multisets match");
+            mapping[remaining.swap_remove(at)] = position;
+        }
+    }
+
+    Ok(Permutation::from_mapping(mapping))
+}
+
+/// Reconcile the tracked cube state with a physical scan. If they disagree,
+/// the scan is trusted because the physical cube may have been manually
+/// interfered with, and the discrepancy is logged. Returns whether a
+/// discrepancy was found.
+pub fn reconcile_scan(tracked: &mut Permutation, scanned: Permutation) -> bool {
+    if *tracked == scanned {
+        return false;
+    }
+
+    warn!(
+        "Physical scan disagrees with the tracked state; trusting the scan. Tracked: {tracked} — scanned: {scanned}"
+    );
+
+    *tracked = scanned;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CUBE3;
+    use qter_core::architectures::Algorithm;
+    use std::sync::Arc;
+
+    struct SimulatedScanner {
+        physical: Permutation,
+    }
+
+    impl Scanner for SimulatedScanner {
+        fn scan(&mut self) -> Permutation {
+            self.physical.clone()
+        }
+    }
+
+    #[test]
+    fn test_reconcile_scan_detects_discrepancy() {
+        let tracked_alg = Algorithm::parse_from_string(Arc::clone(&CUBE3), "R U R'").unwrap();
+        let mut tracked = tracked_alg.permutation().to_owned();
+
+        // The robot's picture matches reality
+        let mut scanner = SimulatedScanner {
+            physical: tracked.clone(),
+        };
+        assert!(!reconcile_scan(&mut tracked, scanner.scan()));
+        assert_eq!(&tracked, tracked_alg.permutation());
+
+        // Someone turned a face behind the robot's back
+        let interference = Algorithm::parse_from_string(Arc::clone(&CUBE3), "F2").unwrap();
+        scanner.physical.compose_into(interference.permutation());
+        assert!(reconcile_scan(&mut tracked, scanner.scan()));
+        assert_eq!(tracked, scanner.physical);
+    }
+
+    fn calibration() -> ColorCalibration {
+        ColorCalibration::new(
+            [
+                ("w", "White"),
+                ("o", "Orange"),
+                ("g", "Green"),
+                ("r", "Red"),
+                ("b", "Blue"),
+                ("y", "Yellow"),
+            ]
+            .map(|(label, color)| (label.to_owned(), ArcIntern::from(color))),
+        )
+    }
+
+    /// The color labels a perfect scanner would read off the given state
+    fn reading_of(state: &Permutation) -> Vec<&'static str> {
+        let solved = CUBE3.facelet_colors();
+        let mut reading = vec![""; solved.len()];
+        for (from, &to) in state.mapping().iter().enumerate() {
+            reading[to] = match &*solved[from] {
+                "White" => "w",
+                "Orange" => "o",
+                "Green" => "g",
+                "Red" => "r",
+                "Blue" => "b",
+                _ => "y",
+            };
+        }
+        reading
+    }
+
+    #[test]
+    fn test_solved_reading_is_identity() {
+        let reading = reading_of(&CUBE3.identity());
+        assert_eq!(
+            permutation_from_reading(&CUBE3, &calibration(), &reading).unwrap(),
+            CUBE3.identity()
+        );
+    }
+
+    #[test]
+    fn test_scrambled_reading_round_trips() {
+        let scramble = Algorithm::parse_from_string(Arc::clone(&CUBE3), "R U R' U'").unwrap();
+        let reading = reading_of(scramble.permutation());
+        assert_eq!(
+            &permutation_from_reading(&CUBE3, &calibration(), &reading).unwrap(),
+            scramble.permutation()
+        );
+    }
+
+    #[test]
+    fn test_bad_readings_are_rejected() {
+        let calibration = calibration();
+
+        assert_eq!(
+            permutation_from_reading(&CUBE3, &calibration, &["w"]),
+            Err(ScanError::WrongFaceletCount {
+                expected: 48,
+                actual: 1
+            })
+        );
+
+        let mut reading = reading_of(&CUBE3.identity());
+        reading[0] = "?";
+        assert_eq!(
+            permutation_from_reading(&CUBE3, &calibration, &reading),
+            Err(ScanError::UnknownColor("?".to_owned()))
+        );
+
+        // An orange sticker misread at a white position gives its corner two
+        // orange stickers, which no corner has
+        reading[0] = "o";
+        assert!(matches!(
+            permutation_from_reading(&CUBE3, &calibration, &reading),
+            Err(ScanError::UnrecognizedPiece(_))
+        ));
+    }
+}
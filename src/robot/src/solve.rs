@@ -0,0 +1,234 @@
+//! Pluggable solver backends for [`QterRobot::solve`](crate::QterRobot)
+
+use std::time::Duration;
+
+use qter_core::architectures::{Algorithm, Permutation};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CUBE3,
+    hardware::{config::RobotConfig, estimate_duration},
+    rob_twophase::solve_rob_twophase,
+};
+
+/// Why a solver backend could not produce a solution
+#[derive(Debug)]
+pub enum SolveError {
+    /// Talking to the external `rob_twophase` process failed
+    Io(std::io::Error),
+    /// No backend produced a candidate solution
+    NoCandidates,
+    /// The state to solve cannot be reached by any move sequence, which
+    /// indicates a scan error
+    UnreachableState,
+}
+
+impl std::fmt::Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Io(e) => write!(f, "The solver failed: {e}"),
+            SolveError::NoCandidates => f.write_str("No solver backend produced a solution"),
+            SolveError::UnreachableState => f.write_str(
+                "The cube state is not reachable by any move sequence; the scan is likely wrong",
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SolveError::Io(e) => Some(e),
+            SolveError::NoCandidates | SolveError::UnreachableState => None,
+        }
+    }
+}
+
+/// Verify that `state` is a valid cube state before handing it to a solver
+///
+/// A scan error can produce a permutation of the facelets that no move
+/// sequence reaches, like a single twisted corner; sending its "solution" to
+/// the robot would scramble the physical cube further.
+///
+/// # Errors
+///
+/// Returns [`SolveError::UnreachableState`] if no move sequence produces
+/// `state`.
+pub fn verify_state(state: &Permutation) -> Result<(), SolveError> {
+    if CUBE3.is_reachable(state) {
+        Ok(())
+    } else {
+        Err(SolveError::UnreachableState)
+    }
+}
+
+/// Which solver backend [`QterRobot::solve`](crate::QterRobot) uses
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum SolveBackend {
+    /// The external `rob_twophase` solver: fast, near move-optimal solutions
+    #[default]
+    RobTwophase,
+    /// Ask every available backend for a solution and perform whichever one
+    /// the robot finishes fastest according to
+    /// [`estimate_duration`](crate::hardware::estimate_duration)
+    MinimalRobotTime,
+}
+
+impl SolveBackend {
+    /// Build the solver this backend describes
+    ///
+    /// Only `rob_twophase` produces candidates today; the internal
+    /// `StateSolver` will join the candidate set once it exists.
+    #[must_use]
+    pub fn solver(self, config: &RobotConfig) -> Box<dyn CubeSolver> {
+        match self {
+            SolveBackend::RobTwophase => Box::new(RobTwophaseSolver),
+            SolveBackend::MinimalRobotTime => Box::new(MinimalRobotTimeSolver::new(
+                vec![Box::new(RobTwophaseSolver)],
+                config.clone(),
+            )),
+        }
+    }
+}
+
+/// A solver that can bring a cube state back to solved
+pub trait CubeSolver {
+    /// Produce a move sequence that solves `state`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend could not produce a solution.
+    fn solve(&self, state: &Permutation) -> Result<Algorithm, SolveError>;
+}
+
+/// The external `rob_twophase` solver
+pub struct RobTwophaseSolver;
+
+impl CubeSolver for RobTwophaseSolver {
+    fn solve(&self, state: &Permutation) -> Result<Algorithm, SolveError> {
+        solve_rob_twophase(state.clone()).map_err(SolveError::Io)
+    }
+}
+
+/// Asks every wrapped solver for a candidate solution and performs the one
+/// the robot finishes fastest
+///
+/// A shorter solution isn't necessarily faster for the robot: consecutive
+/// turns of opposite faces overlap while axis changes do not, so candidates
+/// are ranked by the duration estimator rather than their move count.
+pub struct MinimalRobotTimeSolver {
+    solvers: Vec<Box<dyn CubeSolver>>,
+    config: RobotConfig,
+}
+
+impl MinimalRobotTimeSolver {
+    #[must_use]
+    pub fn new(solvers: Vec<Box<dyn CubeSolver>>, config: RobotConfig) -> Self {
+        Self { solvers, config }
+    }
+}
+
+impl CubeSolver for MinimalRobotTimeSolver {
+    fn solve(&self, state: &Permutation) -> Result<Algorithm, SolveError> {
+        let mut best: Option<(Duration, Algorithm)> = None;
+        let mut last_error = None;
+
+        for solver in &self.solvers {
+            match solver.solve(state) {
+                Ok(candidate) => {
+                    let duration = estimate_duration(&candidate, &self.config);
+
+                    if best
+                        .as_ref()
+                        .is_none_or(|(best_duration, _)| duration < *best_duration)
+                    {
+                        best = Some((duration, candidate));
+                    }
+                }
+                // One backend failing is fine as long as another produces a
+                // candidate
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        match best {
+            Some((_, candidate)) => Ok(candidate),
+            None => Err(last_error.unwrap_or(SolveError::NoCandidates)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{CUBE3, hardware::mock_config};
+
+    /// A solver that always returns the same canned solution
+    struct StubSolver(&'static str);
+
+    impl CubeSolver for StubSolver {
+        fn solve(&self, _state: &Permutation) -> Result<Algorithm, SolveError> {
+            Ok(Algorithm::parse_from_string(Arc::clone(&CUBE3), self.0).unwrap())
+        }
+    }
+
+    struct FailingSolver;
+
+    impl CubeSolver for FailingSolver {
+        fn solve(&self, _state: &Permutation) -> Result<Algorithm, SolveError> {
+            Err(SolveError::NoCandidates)
+        }
+    }
+
+    #[test]
+    fn minimal_robot_time_picks_the_faster_candidate() {
+        let config = mock_config();
+
+        let slow = "U R U' R' U R U' R'";
+        let fast = "R2";
+
+        let solver = MinimalRobotTimeSolver::new(
+            vec![
+                Box::new(StubSolver(slow)),
+                Box::new(StubSolver(fast)),
+                Box::new(FailingSolver),
+            ],
+            config.clone(),
+        );
+
+        let solution = solver.solve(&CUBE3.identity()).unwrap();
+        let expected = Algorithm::parse_from_string(Arc::clone(&CUBE3), fast).unwrap();
+
+        assert_eq!(
+            solution.move_seq_iter().collect::<Vec<_>>(),
+            expected.move_seq_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unreachable_states_are_rejected_before_solving() {
+        // A single twisted corner permutes three facelets, but no move
+        // sequence produces it
+        assert!(matches!(
+            verify_state(&Permutation::from_cycles(vec![vec![10, 16, 5]])),
+            Err(SolveError::UnreachableState)
+        ));
+
+        assert!(verify_state(&CUBE3.identity()).is_ok());
+
+        let scrambled = Algorithm::parse_from_string(Arc::clone(&CUBE3), "R U R' U'").unwrap();
+        assert!(verify_state(scrambled.permutation()).is_ok());
+    }
+
+    #[test]
+    fn minimal_robot_time_propagates_failure() {
+        let solver = MinimalRobotTimeSolver::new(vec![Box::new(FailingSolver)], mock_config());
+
+        assert!(matches!(
+            solver.solve(&CUBE3.identity()),
+            Err(SolveError::NoCandidates)
+        ));
+    }
+}
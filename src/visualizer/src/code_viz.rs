@@ -14,8 +14,10 @@ impl Plugin for CodeViz {
     }
 }
 
-#[derive(Component)]
-struct Code;
+/// The raw program text currently shown, kept alongside the `Text` entity
+/// because the rendered text lives in `TextSpan` children.
+#[derive(Component, Default)]
+struct Code(String);
 
 #[derive(Component)]
 struct Panel;
@@ -23,6 +25,122 @@ struct Panel;
 #[derive(Component)]
 struct Highlight;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    /// An instruction word like `input` or `solved-goto`
+    Keyword,
+    /// A register name like `UFR` or `DL`
+    Register,
+    /// A move like `U`, `R'`, or `F2`
+    Move,
+    /// A line number or goto target
+    Number,
+    Plain,
+}
+
+fn is_face_char(c: char) -> bool {
+    matches!(c, 'U' | 'D' | 'F' | 'B' | 'L' | 'R')
+}
+
+fn classify_word(word: &str) -> TokenKind {
+    match word {
+        "input" | "goto" | "solved-goto" | "repeat" | "until" | "solved" | "halt" | "max-input"
+        | "counting-until" => return TokenKind::Keyword,
+        _ => {}
+    }
+
+    if !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()) {
+        return TokenKind::Number;
+    }
+
+    let mut chars = word.chars();
+
+    if chars.next().is_some_and(is_face_char) {
+        let rest = chars.as_str();
+
+        if rest.chars().all(is_face_char) {
+            // A bare face letter is a move; two or more are a register name
+            return if rest.is_empty() {
+                TokenKind::Move
+            } else {
+                TokenKind::Register
+            };
+        }
+
+        let rest = rest.trim_end_matches('\'');
+
+        if rest.chars().all(|c| c.is_ascii_digit()) {
+            return TokenKind::Move;
+        }
+    }
+
+    TokenKind::Plain
+}
+
+/// Split the program text into colorable tokens. The token texts concatenate
+/// back into the input exactly; whitespace is merged into the preceding token
+/// so that e.g. a move sequence becomes a single span.
+fn tokenize(code: &str) -> Vec<(TokenKind, &str)> {
+    let mut spans: Vec<(TokenKind, usize)> = vec![];
+    let mut spot = 0;
+
+    while spot < code.len() {
+        let rest = &code[spot..];
+
+        let (kind, len) = if rest.starts_with(char::is_whitespace) {
+            let len = rest
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len());
+            // Whitespace inherits the previous token's kind so runs merge
+            (
+                spans.last().map_or(TokenKind::Plain, |&(kind, _)| kind),
+                len,
+            )
+        } else if let Some(after_quote) = rest.strip_prefix('"') {
+            let len = after_quote
+                .find('"')
+                .map_or(rest.len(), |idx| idx + '"'.len_utf8() * 2);
+            (TokenKind::Plain, len)
+        } else {
+            let len = rest.find(|c: char| c.is_whitespace()).unwrap_or(rest.len());
+            (classify_word(&rest[..len]), len)
+        };
+
+        spot += len;
+
+        match spans.last_mut() {
+            Some(last) if last.0 == kind => last.1 = spot,
+            _ => spans.push((kind, spot)),
+        }
+    }
+
+    let mut start = 0;
+    spans
+        .into_iter()
+        .map(|(kind, end)| {
+            let text = &code[start..end];
+            start = end;
+            (kind, text)
+        })
+        .collect()
+}
+
+fn token_color(kind: TokenKind) -> Color {
+    match kind {
+        TokenKind::Keyword => Color::srgb_u8(255, 170, 80),
+        TokenKind::Register => Color::srgb_u8(120, 200, 255),
+        TokenKind::Move => Color::srgb_u8(150, 255, 150),
+        TokenKind::Number => Color::srgb_u8(220, 180, 255),
+        TokenKind::Plain => Color::WHITE,
+    }
+}
+
+/// The panel offset that centers the highlighted span in the view, clamped so
+/// the top of the program never scrolls below the top of the view.
+fn scroll_offset(start_spot: f32, end_spot: f32, view_height: f32) -> f32 {
+    (view_height / 2. - (start_spot + end_spot) / 2.).min(0.)
+}
+
 fn setup(mut commands: Commands, window: Single<&Window>) {
     let panel = commands
         .spawn((
@@ -63,22 +181,36 @@ fn setup(mut commands: Commands, window: Single<&Window>) {
             font_size: window.size().x / 66.,
             ..Default::default()
         },
-        Code,
+        Code(String::new()),
         ChildOf(panel),
     ));
 }
 
 fn started_program(
+    mut commands: Commands,
     mut began_programs: EventReader<BeganProgram>,
     mut panel: Single<&mut Node, (With<Panel>, Without<Highlight>)>,
-    mut code: Single<(&mut Text, &Code)>,
+    code: Single<(Entity, &mut Code, &TextFont)>,
     mut highlight: Single<(&mut Node, &Highlight)>,
 ) {
     let Some(program) = began_programs.read().last() else {
         return;
     };
 
-    *code.0 = Text(PROGRAMS.get(&program.0).unwrap().code.clone());
+    let (code_entity, mut code, font) = code.into_inner();
+
+    code.0 = PROGRAMS.get(&program.0).unwrap().code.clone();
+
+    let mut code_entity = commands.entity(code_entity);
+    code_entity.despawn_related::<Children>();
+
+    for (kind, text) in tokenize(&code.0) {
+        code_entity.with_child((
+            TextSpan::new(text),
+            font.clone(),
+            TextColor(token_color(kind)),
+        ));
+    }
 
     highlight.0.height = Val::ZERO;
     panel.top = Val::ZERO;
@@ -88,7 +220,7 @@ fn started_program(
 fn next_instruction(
     mut executing_instructions: EventReader<ExecutingInstruction>,
     mut panel: Single<&mut Node, (With<Panel>, Without<Highlight>)>,
-    code: Single<(&Text, &TextFont, &Code), Without<Highlight>>,
+    code: Single<(&Code, &TextFont), Without<Highlight>>,
     mut highlight: Single<(&mut Node, &Highlight)>,
     window: Single<&Window>,
 ) {
@@ -118,19 +250,87 @@ fn next_instruction(
     highlight.0.top = Val::Px(start_spot);
     highlight.0.height = Val::Px(size);
 
-    let offset = match panel.top {
-        Val::Px(px) => px,
-        Val::Auto => 0.,
-        _ => unreachable!(),
-    };
+    panel.top = Val::Px(scroll_offset(start_spot, end_spot, window.size().y));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_words() {
+        assert_eq!(classify_word("solved-goto"), TokenKind::Keyword);
+        assert_eq!(classify_word("repeat"), TokenKind::Keyword);
+        assert_eq!(classify_word("UFR"), TokenKind::Register);
+        assert_eq!(classify_word("DL"), TokenKind::Register);
+        assert_eq!(classify_word("U"), TokenKind::Move);
+        assert_eq!(classify_word("R'"), TokenKind::Move);
+        assert_eq!(classify_word("F2"), TokenKind::Move);
+        assert_eq!(classify_word("42"), TokenKind::Number);
+        assert_eq!(classify_word("|"), TokenKind::Plain);
+        assert_eq!(classify_word("Xyz"), TokenKind::Plain);
+    }
+
+    #[test]
+    fn test_tokenize_reassembles_input() {
+        let code = "2 | repeat until DFR solved\n\t\tU D\n3 | halt \"done until\" U'\n";
+
+        let tokens = tokenize(code);
+
+        assert_eq!(
+            tokens.iter().map(|&(_, text)| text).collect::<String>(),
+            code
+        );
+    }
+
+    #[test]
+    fn test_tokenize_kinds() {
+        let tokens = tokenize("1 | solved-goto UFR 3");
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Number, "1 "),
+                (TokenKind::Plain, "| "),
+                (TokenKind::Keyword, "solved-goto "),
+                (TokenKind::Register, "UFR "),
+                (TokenKind::Number, "3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_merges_move_sequences() {
+        let tokens = tokenize("U D L2 R'");
+
+        assert_eq!(tokens, vec![(TokenKind::Move, "U D L2 R'")]);
+    }
+
+    #[test]
+    fn test_tokenize_quoted_strings_are_plain() {
+        let tokens = tokenize("0 | input \"First number:\" U");
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Number, "0 "),
+                (TokenKind::Plain, "| "),
+                (TokenKind::Keyword, "input "),
+                (TokenKind::Plain, "\"First number:\" "),
+                (TokenKind::Move, "U"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scroll_offset_centers_when_below_view() {
+        let offset = scroll_offset(1000., 1050., 500.);
 
-    if start_spot + offset < 0. {
-        panel.top = Val::Px(-start_spot);
+        assert!((offset - (250. - 1025.)).abs() < f32::EPSILON);
     }
 
-    let max_spot = window.size().y * 9. / 10.;
-    println!("{end_spot} {offset} {max_spot}");
-    if end_spot + offset > max_spot {
-        panel.top = Val::Px(max_spot - end_spot);
+    #[test]
+    fn test_scroll_offset_clamps_at_top() {
+        assert!((scroll_offset(8., 30., 500.) - 0.).abs() < f32::EPSILON);
     }
 }
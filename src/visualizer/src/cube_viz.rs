@@ -2,8 +2,9 @@ use std::{collections::HashMap, sync::Arc};
 
 use bevy::prelude::*;
 use internment::ArcIntern;
+use puzzle_geometry::ksolve::PUZZLE_GEOMETRY_3X3;
 use qter_core::{
-    I, Int,
+    I, Int, U,
     architectures::Architecture,
     discrete_math::{chinese_remainder_theorem, decode, lcm_iter},
 };
@@ -13,8 +14,9 @@ use super::{
     interpreter_loop::CUBE3,
     interpreter_plugin::{
         BeganProgram, BeginHalt, CubeState, ExecutingInstruction, FinishedProgram, HaltCountUp,
-        SolvedGoto,
+        LoopProgress, SolvedGoto,
     },
+    puzzle_mesh::{alignment_to, label_size, point_in_polygon, polygon_mesh, projected_stickers},
 };
 
 pub struct CubeViz;
@@ -23,7 +25,7 @@ impl Plugin for CubeViz {
     fn build(&self, app: &mut bevy::app::App) {
         app.insert_resource(CurrentArch(None))
             .add_systems(Startup, setup)
-            .add_systems(Update, track_puzzles)
+            .add_systems(Update, (track_puzzles, sticker_tooltip))
             .add_systems(
                 Update,
                 (
@@ -33,6 +35,7 @@ impl Plugin for CubeViz {
                     solved_goto_visualizer,
                     start_halt,
                     halt_count,
+                    loop_progress,
                     finished_program,
                 )
                     .chain(),
@@ -57,6 +60,10 @@ struct Border;
 #[derive(Component)]
 struct Sticker;
 
+/// The corners of the sticker's polygon relative to its center, for hit testing
+#[derive(Component)]
+struct StickerShape(Vec<Vec2>);
+
 #[derive(Component)]
 struct PuzzleMeshes;
 
@@ -84,6 +91,9 @@ struct CycleValueText(usize, usize);
 #[derive(Component)]
 struct StickerLabel;
 
+#[derive(Component)]
+struct StickerTooltip;
+
 #[derive(Resource)]
 struct Colors {
     named: HashMap<ArcIntern<str>, Handle<ColorMaterial>>,
@@ -103,27 +113,34 @@ fn setup(
 
     commands.insert_resource(CurrentState(CUBE3.identity()));
 
-    let weird_dist = 1. / 3. * 1000.;
-
-    let scale = weird_dist / ((3_f32 / 4.).sqrt() * 2.);
-
-    let rhombus_matrix = Mat2::from_diagonal(Vec2::new(weird_dist, scale))
-        * Mat2::from_cols(Vec2::new(-1., 1.), Vec2::new(1., 1.));
-
-    let sticker = meshes.add(Rhombus::new(weird_dist * 2. * 0.9, 2. * scale * 0.9));
-    let border = meshes.add(Rhombus::new(weird_dist * 2. * 1.1, 2. * scale * 1.1));
+    let geometry = &*PUZZLE_GEOMETRY_3X3;
+    let alignment =
+        alignment_to(geometry, &CUBE3).expect("the 3x3 geometry is the hardcoded 3x3 puzzle");
+    let stickers = projected_stickers(geometry, &alignment);
+
+    let sticker_meshes = stickers
+        .iter()
+        .map(|sticker| {
+            (
+                meshes.add(polygon_mesh(&sticker.points, 0.9)),
+                meshes.add(polygon_mesh(&sticker.points, 1.1)),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let (min, max) = stickers.iter().fold(
+        (Vec2::INFINITY, Vec2::NEG_INFINITY),
+        |(min, max), sticker| {
+            sticker.points.iter().fold((min, max), |(min, max), &point| {
+                let corner = sticker.centroid + point;
+                (min.min(corner), max.max(corner))
+            })
+        },
+    );
+    let net_aspect_ratio = (max.x - min.x) / (max.y - min.y);
 
     let spots = [(false, false), (false, true), (true, false), (true, true)];
 
-    let indices = [
-        0, 1, 2, 3, 4, 5, 6, 7, // top
-        21, 19, 16, 22, 17, 23, 20, 18, // front
-        31, 30, 29, 28, 27, 26, 25, 24, // right
-        32, 33, 34, 35, 36, 37, 38, 39, // back
-        42, 44, 47, 41, 46, 40, 43, 45, // down
-        10, 12, 15, 9, 14, 8, 11, 13, // left
-    ];
-
     let mut colors = HashMap::new();
 
     colors.insert(
@@ -171,18 +188,9 @@ fn setup(
         }
     }
 
-    let grey = ArcIntern::from("Grey");
+    let grey = colors.get(&ArcIntern::from("Grey")).unwrap();
     let transparent = colors.get(&ArcIntern::from("Transparent")).unwrap();
 
-    let center_colors = [
-        ArcIntern::<str>::from("White"),
-        ArcIntern::from("Green"),
-        ArcIntern::from("Red"),
-        ArcIntern::from("Blue"),
-        ArcIntern::from("Yellow"),
-        ArcIntern::from("Orange"),
-    ];
-
     let panel = commands
         .spawn((
             Node {
@@ -266,7 +274,7 @@ fn setup(
         commands.spawn((
             Node {
                 display: Display::Grid,
-                aspect_ratio: Some((weird_dist * 3.) / (scale * 6.)),
+                aspect_ratio: Some(net_aspect_ratio),
                 margin: UiRect::all(Val::ZERO),
                 padding: UiRect::all(Val::ZERO),
                 grid_row: GridPlacement::start_span(if is_cycle_viz { 1 } else { 2 }, 1),
@@ -295,127 +303,91 @@ fn setup(
             ))
             .id();
 
-        let rotate = if is_right {
-            Mat4::from_scale(Vec3::new(-1., 1., 1.)) * Mat4::from_rotation_z((60_f32).to_radians())
-        } else {
-            Mat4::IDENTITY
-        };
+        for (sticker, (sticker_mesh, border_mesh)) in stickers.iter().zip(&sticker_meshes) {
+            if sticker.is_back != is_right {
+                continue;
+            }
 
-        let idx_to_add = if is_right { 3 } else { 0 };
+            let transform = Transform::from_translation(sticker.centroid.extend(0.));
 
-        let tri_translate = Mat4::from_translation(Vec3::new(0., scale * 3., 0.));
+            let Some(facelet_idx) = sticker.facelet_idx else {
+                // Stickers that no turn moves only ever show their own face color
+                commands.spawn((
+                    Mesh2d(sticker_mesh.clone()),
+                    MeshMaterial2d(face_color(&colors, &sticker.color).clone()),
+                    transform,
+                    ChildOf(puzzle_meshes),
+                ));
 
-        for (j, tri) in [
-            tri_translate,
-            Mat4::from_rotation_z((120_f32).to_radians()) * tri_translate,
-            Mat4::from_rotation_z((240_f32).to_radians()) * tri_translate,
-        ]
-        .into_iter()
-        .enumerate()
-        {
-            for (i, (x, y)) in [
-                (1., 1.),
-                (0., 1.),
-                (-1., 1.),
-                (1., 0.),
-                (-1., 0.),
-                (1., -1.),
-                (0., -1.),
-                (-1., -1.),
-                (0., 0.),
-            ]
-            .into_iter()
-            .enumerate()
-            {
-                let spot = rhombus_matrix * Vec2::new(x, y);
-                let transform =
-                    rotate * tri * Mat4::from_translation(Vec3::new(spot.x, spot.y, 0.));
-
-                let color = colors
-                    .get(if !is_cycle_viz || i == 8 {
-                        &center_colors[j + idx_to_add]
-                    } else {
-                        &grey
-                    })
-                    .unwrap()
-                    .clone();
-
-                if i == 8 {
-                    commands.spawn((
-                        Mesh2d(sticker.clone()),
-                        MeshMaterial2d(color),
-                        Transform::from_matrix(transform),
-                        ChildOf(puzzle_meshes),
-                    ));
-                } else {
-                    let facelet_idx = indices[(j + idx_to_add) * 8 + i];
-
-                    if is_cycle_viz {
-                        commands.spawn((
-                            Text2d::new(""),
-                            TextColor(Color::BLACK),
-                            TextFont {
-                                font_size: scale * 2. / 3.,
-                                ..Default::default()
-                            },
-                            Transform::from_matrix(transform)
-                                .with_rotation(Quat::IDENTITY)
-                                .with_scale(Vec3::new(1., 1., 1.)),
-                            FaceletIdx(facelet_idx),
-                            StickerLabel,
-                            ChildOf(puzzle_meshes),
-                        ));
-
-                        commands.spawn((
-                            Mesh2d(sticker.clone()),
-                            MeshMaterial2d(color),
-                            Transform::from_matrix(transform),
-                            FaceletIdx(facelet_idx),
-                            CycleViz,
-                            Sticker,
-                            ChildOf(puzzle_meshes),
-                        ));
-                    } else {
-                        commands.spawn((
-                            Mesh2d(border.clone()),
-                            MeshMaterial2d(transparent.clone()),
-                            Transform::from_matrix(
-                                Mat4::from_translation(Vec3::new(0., 0., -1.)) * transform,
-                            ),
-                            FaceletIdx(facelet_idx),
-                            StateViz,
-                            Border,
-                            ChildOf(puzzle_meshes),
-                        ));
-
-                        commands.spawn((
-                            Mesh2d(sticker.clone()),
-                            MeshMaterial2d(color),
-                            Transform::from_matrix(transform),
-                            FaceletIdx(facelet_idx),
-                            StateViz,
-                            Sticker,
-                            ChildOf(puzzle_meshes),
-                        ));
-
-                        // commands.spawn((
-                        //     Text2d::new(facelet_idx.to_string()),
-                        //     TextColor(Color::srgb_u8(0, 0, 0)),
-                        //     TextFont {
-                        //         font_size: scale * 2. / 3.,
-                        //         ..Default::default()
-                        //     },
-                        //     Transform::from_matrix(transform)
-                        //         .with_rotation(Quat::IDENTITY)
-                        //         .with_scale(Vec3::new(1., 1., 1.)),
-                        //     ChildOf(puzzle_meshes),
-                        // ));
-                    }
-                }
+                continue;
+            };
+
+            if is_cycle_viz {
+                commands.spawn((
+                    Text2d::new(""),
+                    TextColor(Color::BLACK),
+                    TextFont {
+                        font_size: label_size(&sticker.points),
+                        ..Default::default()
+                    },
+                    transform,
+                    FaceletIdx(facelet_idx),
+                    StickerLabel,
+                    ChildOf(puzzle_meshes),
+                ));
+
+                commands.spawn((
+                    Mesh2d(sticker_mesh.clone()),
+                    MeshMaterial2d(grey.clone()),
+                    transform,
+                    FaceletIdx(facelet_idx),
+                    CycleViz,
+                    Sticker,
+                    ChildOf(puzzle_meshes),
+                ));
+            } else {
+                commands.spawn((
+                    Mesh2d(border_mesh.clone()),
+                    MeshMaterial2d(transparent.clone()),
+                    transform.with_translation(sticker.centroid.extend(-1.)),
+                    FaceletIdx(facelet_idx),
+                    StateViz,
+                    Border,
+                    ChildOf(puzzle_meshes),
+                ));
+
+                commands.spawn((
+                    Mesh2d(sticker_mesh.clone()),
+                    MeshMaterial2d(face_color(&colors, &sticker.color).clone()),
+                    transform,
+                    FaceletIdx(facelet_idx),
+                    StateViz,
+                    Sticker,
+                    StickerShape(sticker.points.iter().map(|&point| point * 0.9).collect()),
+                    ChildOf(puzzle_meshes),
+                ));
             }
         }
     }
 
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Val::Px(4.)),
+            ..Default::default()
+        },
+        Text::new(""),
+        TextColor::WHITE,
+        TextFont {
+            font_size: window.size().x / 70.,
+            ..Default::default()
+        },
+        BackgroundColor(Color::srgba_u8(0, 0, 0, 200)),
+        Visibility::Hidden,
+        GlobalZIndex(1),
+        StickerTooltip,
+    ));
+
     commands.insert_resource(Colors {
         named: colors,
         cycles: cycle_colors,
@@ -450,6 +422,125 @@ fn track_puzzles(
     });
 }
 
+/// Geometries name their colors in lowercase while the named materials are
+/// capitalized, so look the face color up case-insensitively
+fn face_color<'a>(
+    colors: &'a HashMap<ArcIntern<str>, Handle<ColorMaterial>>,
+    name: &str,
+) -> &'a Handle<ColorMaterial> {
+    colors
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, handle)| handle)
+        .expect("every face color to have a named material")
+}
+
+/// Each face's eight facelets are numbered in reading order, putting the corners at the four spots on the diagonals
+fn piece_kind(facelet_idx: usize) -> &'static str {
+    if matches!(facelet_idx % 8, 0 | 2 | 5 | 7) {
+        "corner"
+    } else {
+        "edge"
+    }
+}
+
+/// A register whose signature facelets or algorithm support include a particular facelet
+#[derive(Debug, PartialEq, Eq)]
+struct FaceletMembership {
+    register: usize,
+    /// Whether the facelet is one of the register's signature facelets rather than merely moved by its algorithm
+    in_signature: bool,
+}
+
+fn facelet_memberships(arch: &Architecture, facelet_idx: usize) -> Vec<FaceletMembership> {
+    arch.registers()
+        .iter()
+        .enumerate()
+        .filter_map(|(register, reg)| {
+            let in_signature = reg.signature_facelets().0.contains(&facelet_idx);
+            let in_support = reg.algorithm().permutation().mapping()[facelet_idx] != facelet_idx;
+
+            (in_signature || in_support).then_some(FaceletMembership {
+                register,
+                in_signature,
+            })
+        })
+        .collect()
+}
+
+fn tooltip_text(facelet_idx: usize, color: &str, memberships: &[FaceletMembership]) -> String {
+    let mut text = format!("facelet {facelet_idx}\n{color} {}", piece_kind(facelet_idx));
+
+    text.push_str("\nregisters: ");
+
+    if memberships.is_empty() {
+        text.push_str("none");
+    } else {
+        for (i, membership) in memberships.iter().enumerate() {
+            if i != 0 {
+                text.push_str(", ");
+            }
+
+            text.push_str(NAMES[membership.register]);
+
+            if membership.in_signature {
+                text.push_str(" (signature)");
+            }
+        }
+    }
+
+    text
+}
+
+fn sticker_tooltip(
+    current_arch: Res<CurrentArch>,
+    current_state: Res<CurrentState>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    window: Single<&Window>,
+    stickers: Query<(&GlobalTransform, &StickerShape, &FaceletIdx), (With<StateViz>, With<Sticker>)>,
+    mut tooltip: Single<(&mut Node, &mut Text, &mut Visibility), With<StickerTooltip>>,
+) {
+    let hovered = window.cursor_position().and_then(|cursor| {
+        let world_spot = camera.0.viewport_to_world_2d(camera.1, cursor).ok()?;
+
+        stickers
+            .iter()
+            .find(|(transform, shape, _)| {
+                let local = transform
+                    .affine()
+                    .inverse()
+                    .transform_point3(world_spot.extend(0.));
+
+                point_in_polygon(local.xy(), &shape.0)
+            })
+            .map(|(_, _, facelet)| (cursor, facelet.0))
+    });
+
+    let Some((cursor, facelet_idx)) = hovered else {
+        *tooltip.2 = Visibility::Hidden;
+        return;
+    };
+
+    // The cube is displayed in the passive representation; see `state_visualizer`
+    let comes_from = current_state
+        .0
+        .mapping()
+        .iter()
+        .position(|&goes_to| goes_to == facelet_idx)
+        .unwrap();
+    let color = &CUBE3.facelet_colors()[comes_from];
+
+    let memberships = match &*current_arch {
+        CurrentArch(Some((arch, _))) => facelet_memberships(arch, facelet_idx),
+        CurrentArch(None) => Vec::new(),
+    };
+
+    tooltip.0.left = Val::Px(cursor.x + 12.);
+    tooltip.0.top = Val::Px(cursor.y + 12.);
+    *tooltip.1 = Text::new(tooltip_text(facelet_idx, color, &memberships));
+    *tooltip.2 = Visibility::Visible;
+}
+
 fn cycle_color(reg_idx: usize, cycle_idx: usize) -> Color {
     #[expect(clippy::cast_precision_loss)]
     Color::oklch(
@@ -864,6 +955,27 @@ fn halt_count(
     *solved_goto_statement.0 = Text(count.0.to_string());
 }
 
+fn loop_progress(
+    mut loop_progresses: EventReader<LoopProgress>,
+    mut solved_goto_statement: Single<(&mut Text, &mut TextColor, &SolvedGotoStatement)>,
+) {
+    let Some(progress) = loop_progresses.read().last() else {
+        return;
+    };
+
+    const BAR_WIDTH: usize = 20;
+
+    let filled = usize::try_from(progress.done * Int::<U>::from(BAR_WIDTH) / progress.max).unwrap();
+
+    let mut bar = String::with_capacity(BAR_WIDTH);
+    for i in 0..BAR_WIDTH {
+        bar.push(if i < filled { '█' } else { '░' });
+    }
+
+    *solved_goto_statement.0 = Text(format!("[{bar}] {}/{}", progress.done, progress.max));
+    *solved_goto_statement.1 = TextColor::WHITE;
+}
+
 fn finished_program(
     // colors: Res<Colors>,
     // mut commands: Commands,
@@ -897,3 +1009,65 @@ fn finished_program(
     //         *text = Text2d::new("");
     //     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piece_kind() {
+        // The top row of the U face reads corner, edge, corner
+        assert_eq!(piece_kind(0), "corner");
+        assert_eq!(piece_kind(1), "edge");
+        assert_eq!(piece_kind(2), "corner");
+        // The first facelet of the F face is its top left corner
+        assert_eq!(piece_kind(16), "corner");
+        assert_eq!(piece_kind(20), "edge");
+    }
+
+    #[test]
+    fn test_facelet_memberships() {
+        let arch =
+            Architecture::new(Arc::clone(&CUBE3), &[vec!["U"], vec!["D'"]]).unwrap();
+
+        // The top left U sticker is moved by the U register only
+        let memberships = facelet_memberships(&arch, 0);
+        assert!(memberships.iter().any(|membership| membership.register == 0));
+        assert!(memberships.iter().all(|membership| membership.register == 0));
+
+        // The left F sticker sits in neither register's support
+        assert!(facelet_memberships(&arch, 19).is_empty());
+
+        // A signature facelet is reported as such
+        let sig_facelet = arch.registers()[1].signature_facelets().0[0];
+        assert!(
+            facelet_memberships(&arch, sig_facelet)
+                .iter()
+                .any(|membership| membership.register == 1 && membership.in_signature)
+        );
+    }
+
+    #[test]
+    fn test_tooltip_text() {
+        let text = tooltip_text(
+            7,
+            "White",
+            &[
+                FaceletMembership {
+                    register: 0,
+                    in_signature: true,
+                },
+                FaceletMembership {
+                    register: 1,
+                    in_signature: false,
+                },
+            ],
+        );
+        assert_eq!(text, "facelet 7\nWhite corner\nregisters: A (signature), B");
+
+        assert_eq!(
+            tooltip_text(20, "Green", &[]),
+            "facelet 20\nGreen edge\nregisters: none"
+        );
+    }
+}
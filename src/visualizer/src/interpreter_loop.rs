@@ -38,7 +38,12 @@ struct TrackedRobotState;
 
 impl TrackedRobotState {
     /// This WILL NOT TAKE THE INVERSE OF `generator` which is necessary for `print` but not for `repeat until`
-    fn halt_quiet(&mut self, facelets: &[usize], generator: &Algorithm) -> Option<Int<U>> {
+    fn halt_quiet(
+        &mut self,
+        facelets: &[usize],
+        generator: &Algorithm,
+        progress: &mut dyn FnMut(Int<U>),
+    ) -> Option<Int<U>> {
         let mut sum = Int::<U>::zero();
 
         let chromatic_orders = generator.chromatic_orders_by_facelets();
@@ -55,6 +60,7 @@ impl TrackedRobotState {
             }
 
             self.compose_into(generator);
+            progress(sum);
         }
 
         Some(sum)
@@ -114,7 +120,7 @@ impl PuzzleState for TrackedRobotState {
         let mut generator = generator.to_owned();
         generator.exponentiate(-Int::<U>::one());
 
-        let c = self.halt_quiet(facelets, &generator)?;
+        let c = self.halt_quiet(facelets, &generator, &mut |_| ())?;
 
         let mut exponentiated = generator.clone();
         exponentiated.exponentiate(c.into());
@@ -177,7 +183,28 @@ impl PuzzleState for TrackedRobotState {
 
     fn repeat_until(&mut self, facelets: &[usize], generator: &Algorithm) -> Option<()> {
         // repeat_until has the same behavior as halt
-        self.halt_quiet(facelets, generator).map(|_| ())
+        self.halt_quiet(facelets, generator, &mut |_| ())
+            .map(|_| ())
+    }
+
+    fn repeat_until_with_progress(
+        &mut self,
+        facelets: &[usize],
+        generator: &Algorithm,
+        progress: &mut dyn FnMut(Int<U>),
+    ) -> Option<()> {
+        let chromatic_orders = generator.chromatic_orders_by_facelets();
+        let max = lcm_iter(facelets.iter().map(|&i| chromatic_orders[i]));
+
+        self.halt_quiet(facelets, generator, &mut |done| {
+            robot_handle()
+                .event_tx
+                .send(InterpretationEvent::LoopProgress { done, max })
+                .unwrap();
+
+            progress(done);
+        })
+        .map(|_| ())
     }
 
     fn solve(&mut self) {
@@ -279,10 +306,33 @@ pub fn interpreter_loop<R: RobotLike + Send + 'static>(
                     .send(InterpretationEvent::ExecutingInstruction { which_one: instr })
                     .unwrap();
 
+                let program = interpreter.program_arc();
+
                 match interpreter.step() {
+                    A::Added(by_puzzle_type) => {
+                        if let qter_core::ByPuzzleType::Puzzle((idx, alg, _)) = by_puzzle_type {
+                            for info in program.registers_for_puzzle(idx) {
+                                let Some(amt) = info.amount_added_by(alg) else {
+                                    continue;
+                                };
+
+                                if amt.is_zero() {
+                                    continue;
+                                }
+
+                                robot_handle()
+                                    .event_tx
+                                    .send(InterpretationEvent::Message(format!(
+                                        "{} += {amt}",
+                                        info.name
+                                    )))
+                                    .unwrap();
+                            }
+                        }
+                    }
                     A::Goto { instruction_idx: _ }
-                    | A::Added(_)
                     | A::Solved(_)
+                    | A::RandomAdded { .. }
                     | A::RepeatedUntil {
                         puzzle_idx: _,
                         facelets: _,
@@ -319,9 +369,9 @@ pub fn interpreter_loop<R: RobotLike + Send + 'static>(
                             })
                             .unwrap(),
                     },
-                    A::SucceededSolvedGoto(by_puzzle_type) => match by_puzzle_type {
-                        qter_core::ByPuzzleType::Theoretical(_) => unreachable!(),
-                        qter_core::ByPuzzleType::Puzzle((_, _, facelets)) => robot_handle()
+                    A::SucceededSolvedGoto(succeeded) => match succeeded.facelets {
+                        None => unreachable!(),
+                        Some(facelets) => robot_handle()
                             .event_tx
                             .send(InterpretationEvent::SolvedGoto {
                                 facelets: facelets.clone(),
@@ -376,7 +426,7 @@ pub fn interpreter_loop<R: RobotLike + Send + 'static>(
                     if let Err(msg) = interpreter.give_input(int) {
                         robot_handle()
                             .event_tx
-                            .send(InterpretationEvent::Message(msg))
+                            .send(InterpretationEvent::Message(msg.to_string()))
                             .unwrap();
                     } else {
                         let mut handle = robot_handle();
@@ -37,6 +37,12 @@ pub struct BeginHalt {
 #[derive(Event)]
 pub struct HaltCountUp(pub Int<U>);
 
+#[derive(Event)]
+pub struct LoopProgress {
+    pub done: Int<U>,
+    pub max: Int<U>,
+}
+
 #[derive(Event)]
 pub struct CubeState(pub Permutation);
 
@@ -66,6 +72,7 @@ pub enum InterpretationEvent {
     GaveInput,
     BeginHalt { facelets: Facelets },
     HaltCountUp(Int<U>),
+    LoopProgress { done: Int<U>, max: Int<U> },
     CubeState(Permutation),
     SolvedGoto { facelets: Facelets },
     ExecutingInstruction { which_one: usize },
@@ -109,6 +116,7 @@ impl Plugin for InterpreterPlugin {
             .add_event::<GaveInput>()
             .add_event::<BeginHalt>()
             .add_event::<HaltCountUp>()
+            .add_event::<LoopProgress>()
             .add_event::<CubeState>()
             .add_event::<SolvedGoto>()
             .add_event::<ExecutingInstruction>()
@@ -136,6 +144,7 @@ fn read_events(
     mut gave_inputs: EventWriter<GaveInput>,
     mut begin_halts: EventWriter<BeginHalt>,
     mut halt_count_ups: EventWriter<HaltCountUp>,
+    mut loop_progresses: EventWriter<LoopProgress>,
     mut cube_states: EventWriter<CubeState>,
     mut solved_gotos: EventWriter<SolvedGoto>,
     mut executed_instructions: EventWriter<ExecutingInstruction>,
@@ -161,6 +170,9 @@ fn read_events(
             InterpretationEvent::HaltCountUp(int) => {
                 halt_count_ups.write(HaltCountUp(int));
             }
+            InterpretationEvent::LoopProgress { done, max } => {
+                loop_progresses.write(LoopProgress { done, max });
+            }
             InterpretationEvent::CubeState(permutation) => {
                 cube_states.write(CubeState(permutation));
             }
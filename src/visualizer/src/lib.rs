@@ -4,7 +4,7 @@ use std::{
 
 use bevy::prelude::*;
 use bevy_simple_text_input::TextInputPlugin;
-use compiler::compile;
+use compiler::{ImportError, compile};
 use cube_viz::CubeViz;
 use internment::{ArcIntern, Intern};
 use interpreter_loop::{CUBE3, CUBE3_DEF};
@@ -21,6 +21,7 @@ mod cube_viz;
 mod interpreter_loop;
 mod interpreter_plugin;
 mod io_viz;
+mod puzzle_mesh;
 
 struct ProgramInfo {
     program: Arc<Program>,
@@ -29,17 +30,19 @@ struct ProgramInfo {
     code: String,
 }
 
-fn load_file(name: &str) -> Result<ArcIntern<str>, String> {
+fn load_file(name: &str) -> Result<ArcIntern<str>, ImportError> {
     let path = PathBuf::from(name);
 
     if path.ancestors().count() > 1 {
         // Easier not to implement relative paths and stuff
-        return Err("Imported files must be in the same path".to_owned());
+        return Err(ImportError::Unsupported(
+            "Imported files must be in the same path".to_owned(),
+        ));
     }
 
     match fs::read_to_string(path) {
         Ok(s) => Ok(ArcIntern::from(s)),
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(ImportError::Io(e.to_string())),
     }
 }
 
@@ -53,6 +56,7 @@ static PROGRAMS: LazyLock<HashMap<Intern<str>, ProgramInfo>> = LazyLock::new(||
                 compile(
                     &File::from(include_str!("../../compiler/tests/simple/simple.qat")),
                     load_file,
+                    false,
                 )
                 .unwrap(),
             ),
@@ -81,6 +85,7 @@ static PROGRAMS: LazyLock<HashMap<Intern<str>, ProgramInfo>> = LazyLock::new(||
                         "../../compiler/tests/average/average_transform.qat"
                     )),
                     load_file,
+                    false,
                 )
                 .unwrap(),
             ),
@@ -145,6 +150,7 @@ static PROGRAMS: LazyLock<HashMap<Intern<str>, ProgramInfo>> = LazyLock::new(||
                         "../../compiler/tests/fib/fib_transform.qat"
                     )),
                     load_file,
+                    false,
                 )
                 .unwrap(),
             ),
@@ -210,6 +216,7 @@ static PROGRAMS: LazyLock<HashMap<Intern<str>, ProgramInfo>> = LazyLock::new(||
                         "../../compiler/tests/multiply/multiply_transform.qat"
                     )),
                     load_file,
+                    false,
                 )
                 .unwrap(),
             ),
@@ -0,0 +1,378 @@
+//! Builds sticker meshes for a puzzle directly from its [`PuzzleGeometry`]
+//! rather than from a hardcoded table, so any geometry-defined puzzle can be
+//! displayed. The stickers are orthographically projected along the cube
+//! diagonal that the hand-built 3x3 net was drawn from, with the stickers
+//! facing away from the camera drawn see-through in a second, mirrored view.
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
+use internment::ArcIntern;
+use itertools::Itertools;
+use puzzle_geometry::PuzzleGeometry;
+use qter_core::architectures::PermutationGroup;
+
+/// Half the width that `track_puzzles` sizes a puzzle's UI spot against
+const PUZZLE_EXTENT: f32 = 1000.;
+
+/// A sticker polygon projected into the plane of the screen
+pub struct ProjectedSticker {
+    /// The facelet the sticker displays, in the numbering of the group the
+    /// geometry was aligned to; `None` for stickers that no turn moves, like
+    /// the centers of a 3x3
+    pub facelet_idx: Option<usize>,
+    /// The color name of the face the sticker belongs to
+    pub color: ArcIntern<str>,
+    /// Whether the sticker faces away from the camera and belongs in the
+    /// mirrored view
+    pub is_back: bool,
+    /// The corners of the polygon relative to `centroid`, counterclockwise
+    pub points: Vec<Vec2>,
+    /// The center of the polygon
+    pub centroid: Vec2,
+}
+
+/// Map each facelet of `geometry` onto the facelet of `target` that denotes
+/// the same sticker, so that the meshes built from the geometry can carry the
+/// facelet numbering the rest of the visualizer uses. Returns `None` when the
+/// two are not the same puzzle.
+pub fn alignment_to(geometry: &PuzzleGeometry, target: &PermutationGroup) -> Option<Vec<usize>> {
+    let labeling = target.is_isomorphic_labeling(&geometry.permutation_group())?;
+
+    let mut alignment = vec![0; labeling.len()];
+    for (target_facelet, &geometry_facelet) in labeling.iter().enumerate() {
+        alignment[geometry_facelet] = target_facelet;
+    }
+
+    Some(alignment)
+}
+
+/// Project every sticker of the geometry onto the viewing plane, scaled so
+/// the front view spans the ±[`PUZZLE_EXTENT`] width that `track_puzzles`
+/// sizes against.
+///
+/// `alignment` maps the geometry's facelet numbering onto the numbering that
+/// `FaceletIdx` carries, as found by [`alignment_to`].
+pub fn projected_stickers(geometry: &PuzzleGeometry, alignment: &[usize]) -> Vec<ProjectedSticker> {
+    // The same diagonal view the hand-built 3x3 net used: `U` projects
+    // straight up, `F` to the lower left, and `R` to the lower right
+    let view = Vec3::splat(1.).normalize();
+    let right = Vec3::new(1., 0., -1.).normalize();
+    let up = view.cross(right);
+
+    let fixed = geometry.fixed_sticker_indices();
+    let mut non_fixed_so_far = 0;
+
+    let mut stickers = Vec::new();
+
+    for (i, (face, _)) in geometry.stickers().iter().enumerate() {
+        let points_3d = face
+            .points
+            .iter()
+            .map(|point| {
+                #[expect(clippy::cast_possible_truncation)]
+                let [x, y, z] = point.approx_coordinates().map(|coordinate| coordinate as f32);
+                Vec3::new(x, y, z)
+            })
+            .collect_vec();
+
+        // The polyhedron is centered on the origin, so the plane normal
+        // pointing away from the sticker's own centroid is the outward one
+        #[expect(clippy::cast_precision_loss)]
+        let centroid_3d = points_3d.iter().fold(Vec3::ZERO, |acc, &point| acc + point)
+            / points_3d.len() as f32;
+        let mut normal = (points_3d[1] - points_3d[0]).cross(points_3d[2] - points_3d[0]);
+        if normal.dot(centroid_3d) < 0. {
+            normal = -normal;
+        }
+
+        let is_back = normal.dot(view) < 0.;
+
+        let mut points = points_3d
+            .iter()
+            .map(|&point| Vec2::new(point.dot(right), point.dot(up)))
+            .collect_vec();
+
+        // The see-through projection of a back-facing sticker winds backwards
+        if double_signed_area(&points) < 0. {
+            points.reverse();
+        }
+
+        #[expect(clippy::cast_precision_loss)]
+        let centroid = points.iter().fold(Vec2::ZERO, |acc, &point| acc + point)
+            / points.len() as f32;
+        for point in &mut points {
+            *point -= centroid;
+        }
+
+        let facelet_idx = if fixed.contains(&i) {
+            None
+        } else {
+            let idx = alignment[non_fixed_so_far];
+            non_fixed_so_far += 1;
+            Some(idx)
+        };
+
+        stickers.push(ProjectedSticker {
+            facelet_idx,
+            color: ArcIntern::clone(&face.color),
+            is_back,
+            points,
+            centroid,
+        });
+    }
+
+    let max_width = stickers
+        .iter()
+        .flat_map(|sticker| {
+            sticker
+                .points
+                .iter()
+                .map(|&point| (sticker.centroid + point).x.abs())
+        })
+        .fold(0_f32, f32::max);
+
+    let factor = PUZZLE_EXTENT / max_width;
+
+    for sticker in &mut stickers {
+        sticker.centroid *= factor;
+        for point in &mut sticker.points {
+            *point *= factor;
+        }
+    }
+
+    stickers
+}
+
+/// Twice the signed area of the polygon; positive when the corners wind
+/// counterclockwise
+fn double_signed_area(points: &[Vec2]) -> f32 {
+    points
+        .iter()
+        .circular_tuple_windows()
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum()
+}
+
+/// A mesh of the polygon scaled by `scale` about its center and fanned into
+/// triangles, which is valid because stickers are convex: they are made by
+/// cutting the convex faces of a convex polyhedron with planes
+pub fn polygon_mesh(points: &[Vec2], scale: f32) -> Mesh {
+    let positions = points
+        .iter()
+        .map(|point| [point.x * scale, point.y * scale, 0.])
+        .collect_vec();
+    let normals = vec![[0., 0., 1.]; points.len()];
+
+    let min = points.iter().fold(Vec2::INFINITY, |acc, &point| acc.min(point));
+    let max = points.iter().fold(Vec2::NEG_INFINITY, |acc, &point| acc.max(point));
+    let uvs = points
+        .iter()
+        .map(|&point| {
+            let uv = (point - min) / (max - min);
+            [uv.x, 1. - uv.y]
+        })
+        .collect_vec();
+
+    let indices = (1..points.len() - 1)
+        .flat_map(|i| {
+            [0, i, i + 1].map(|corner| u32::try_from(corner).unwrap())
+        })
+        .collect_vec();
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Whether the point lies inside the convex, counterclockwise polygon
+pub fn point_in_polygon(point: Vec2, points: &[Vec2]) -> bool {
+    points
+        .iter()
+        .circular_tuple_windows()
+        .all(|(a, b)| (*b - *a).perp_dot(point - *a) >= 0.)
+}
+
+/// A font size that fits a one or two character label inside the polygon,
+/// whose corners are given relative to its center
+pub fn label_size(points: &[Vec2]) -> f32 {
+    points
+        .iter()
+        .map(Vec2::length)
+        .fold(f32::INFINITY, f32::min)
+        * 2.
+        / 3.
+}
+
+#[cfg(test)]
+mod tests {
+    use puzzle_geometry::ksolve::{PUZZLE_GEOMETRY_3X3, PUZZLE_GEOMETRY_PYRAMINX};
+    use qter_core::{I, Int, architectures::Permutation};
+
+    use crate::interpreter_loop::CUBE3;
+
+    use super::*;
+
+    #[test]
+    fn test_point_in_polygon() {
+        let square = [
+            Vec2::new(-1., -1.),
+            Vec2::new(1., -1.),
+            Vec2::new(1., 1.),
+            Vec2::new(-1., 1.),
+        ];
+
+        assert!(point_in_polygon(Vec2::ZERO, &square));
+        assert!(point_in_polygon(Vec2::new(0.99, 0.99), &square));
+        assert!(!point_in_polygon(Vec2::new(1.5, 0.), &square));
+        assert!(!point_in_polygon(Vec2::new(0., -1.01), &square));
+    }
+
+    #[test]
+    fn polygons_triangulate_into_fans() {
+        let pentagon = [
+            Vec2::new(0., 1.),
+            Vec2::new(-1., 0.3),
+            Vec2::new(-0.6, -0.8),
+            Vec2::new(0.6, -0.8),
+            Vec2::new(1., 0.3),
+        ];
+
+        let mesh = polygon_mesh(&pentagon, 0.9);
+        assert_eq!(mesh.count_vertices(), 5);
+        assert_eq!(mesh.indices().unwrap().len(), 9);
+    }
+
+    /// The table the 3x3 net used to be built from, along with the transforms
+    /// that placed each sticker, to pin down that the geometry-driven net puts
+    /// every facelet in the same spot
+    #[test]
+    fn three_by_three_matches_the_hand_built_net() {
+        let indices = [
+            0, 1, 2, 3, 4, 5, 6, 7, // top
+            21, 19, 16, 22, 17, 23, 20, 18, // front
+            31, 30, 29, 28, 27, 26, 25, 24, // right
+            32, 33, 34, 35, 36, 37, 38, 39, // back
+            42, 44, 47, 41, 46, 40, 43, 45, // down
+            10, 12, 15, 9, 14, 8, 11, 13, // left
+        ];
+
+        let spots = [
+            (1., 1.),
+            (0., 1.),
+            (-1., 1.),
+            (1., 0.),
+            (-1., 0.),
+            (1., -1.),
+            (0., -1.),
+            (-1., -1.),
+        ];
+
+        let weird_dist = 1. / 3. * 1000.;
+        let scale = weird_dist / ((3_f32 / 4.).sqrt() * 2.);
+
+        let rhombus_matrix = Mat2::from_diagonal(Vec2::new(weird_dist, scale))
+            * Mat2::from_cols(Vec2::new(-1., 1.), Vec2::new(1., 1.));
+
+        let tri_translate = Mat4::from_translation(Vec3::new(0., scale * 3., 0.));
+
+        let mut expected = Vec::new();
+
+        for (is_right, idx_to_add) in [(false, 0), (true, 3)] {
+            let rotate = if is_right {
+                Mat4::from_scale(Vec3::new(-1., 1., 1.))
+                    * Mat4::from_rotation_z((60_f32).to_radians())
+            } else {
+                Mat4::IDENTITY
+            };
+
+            for (j, angle) in [0_f32, 120., 240.].into_iter().enumerate() {
+                let tri = Mat4::from_rotation_z(angle.to_radians()) * tri_translate;
+
+                for (i, (x, y)) in spots.into_iter().enumerate() {
+                    let spot = rhombus_matrix * Vec2::new(x, y);
+                    let transform =
+                        rotate * tri * Mat4::from_translation(Vec3::new(spot.x, spot.y, 0.));
+
+                    expected.push((
+                        indices[(j + idx_to_add) * 8 + i],
+                        transform.transform_point3(Vec3::ZERO).xy(),
+                        is_right,
+                    ));
+                }
+            }
+        }
+
+        let alignment = alignment_to(&PUZZLE_GEOMETRY_3X3, &CUBE3).unwrap();
+        let stickers = projected_stickers(&PUZZLE_GEOMETRY_3X3, &alignment);
+
+        for (facelet, position, is_right) in expected {
+            let sticker = stickers
+                .iter()
+                .find(|sticker| sticker.facelet_idx == Some(facelet))
+                .unwrap();
+
+            assert_eq!(sticker.is_back, is_right, "facelet {facelet}");
+            assert!(
+                (sticker.centroid - position).length() < 0.5,
+                "facelet {facelet} moved from {position} to {}",
+                sticker.centroid
+            );
+        }
+    }
+
+    #[test]
+    fn pyraminx_renders_from_its_geometry() {
+        let group = PUZZLE_GEOMETRY_PYRAMINX.permutation_group();
+
+        let alignment = alignment_to(&PUZZLE_GEOMETRY_PYRAMINX, &group).unwrap();
+        let stickers = projected_stickers(&PUZZLE_GEOMETRY_PYRAMINX, &alignment);
+
+        // Every sticker of the pyraminx moves, so every sticker shows a facelet
+        assert_eq!(stickers.len(), 36);
+        assert!(stickers.iter().all(|sticker| sticker.facelet_idx.is_some()));
+
+        // One face of the tetrahedron faces the camera and three face away
+        assert_eq!(
+            stickers
+                .iter()
+                .filter(|sticker| !sticker.is_back)
+                .count(),
+            9
+        );
+
+        // A trivial program: perform `A`, then undo it. The colors come from
+        // the same passive-representation recoloring that `state_visualizer`
+        // does.
+        let colors_of = |state: &Permutation| {
+            let mut inverse = state.clone();
+            inverse.exponentiate(-Int::<I>::one());
+
+            stickers
+                .iter()
+                .filter_map(|sticker| sticker.facelet_idx)
+                .map(|facelet| ArcIntern::clone(&group.facelet_colors()[inverse.mapping()[facelet]]))
+                .collect::<Vec<_>>()
+        };
+
+        let mut state = group.identity();
+        let solved = colors_of(&state);
+
+        state.compose_into(group.get_generator("A").unwrap());
+        assert_ne!(colors_of(&state), solved);
+
+        let mut undo = group.get_generator("A").unwrap().clone();
+        undo.exponentiate(-Int::<I>::one());
+        state.compose_into(&undo);
+        assert_eq!(colors_of(&state), solved);
+    }
+}